@@ -0,0 +1,279 @@
+// メモの添付ファイル（データディレクトリに内容のハッシュをファイル名として保存する）
+
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::hash::content_hash;
+
+/// Markdownで添付ファイルを参照するときのスキーム（`![名前](attachment:<ハッシュ>.png)`）
+pub const ATTACHMENT_SCHEME: &str = "attachment:";
+/// 追加直後の添付ファイルは、まだメモに保存されていない可能性があるため削除しない
+pub const GC_GRACE_HOURS: i64 = 24;
+
+/// 添付ファイル
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Attachment {
+    /// 内容のSHA-256（16進表記）
+    pub hash: String,
+    /// 追加したときのファイル名
+    pub file_name: String,
+    /// 小文字の拡張子（無い場合は空）
+    pub extension: String,
+    pub mime_type: String,
+    pub size: u64,
+    pub created_at: DateTime<Utc>,
+}
+
+impl Attachment {
+    /// 添付ファイルのディレクトリ内のファイル名
+    pub fn stored_name(&self) -> String {
+        if self.extension.is_empty() {
+            self.hash.clone()
+        } else {
+            format!("{}.{}", self.hash, self.extension)
+        }
+    }
+
+    /// Markdownでの参照（`attachment:<ハッシュ>.<拡張子>`）
+    pub fn reference(&self) -> String {
+        format!("{}{}", ATTACHMENT_SCHEME, self.stored_name())
+    }
+
+    /// 本文に貼り付けるMarkdown（画像は `![]()`、それ以外はリンク）
+    pub fn markdown(&self) -> String {
+        let label = self.file_name.replace(['[', ']'], "");
+        if self.mime_type.starts_with("image/") {
+            format!("![{}]({})", label, self.reference())
+        } else {
+            format!("[{}]({})", label, self.reference())
+        }
+    }
+}
+
+/// 拡張子からMIMEタイプを推定する
+pub fn mime_type_for(extension: &str) -> &'static str {
+    match extension {
+        "png" => "image/png",
+        "jpg" | "jpeg" => "image/jpeg",
+        "gif" => "image/gif",
+        "webp" => "image/webp",
+        "svg" => "image/svg+xml",
+        "bmp" => "image/bmp",
+        "pdf" => "application/pdf",
+        "txt" => "text/plain",
+        "md" => "text/markdown",
+        "csv" => "text/csv",
+        "json" => "application/json",
+        "zip" => "application/zip",
+        _ => "application/octet-stream",
+    }
+}
+
+/// MIMEタイプから拡張子を推定する（ファイル名に拡張子が無い貼り付け画像用）
+fn extension_for(mime_type: &str) -> Option<&'static str> {
+    match mime_type {
+        "image/png" => Some("png"),
+        "image/jpeg" => Some("jpg"),
+        "image/gif" => Some("gif"),
+        "image/webp" => Some("webp"),
+        "image/svg+xml" => Some("svg"),
+        "image/bmp" => Some("bmp"),
+        "application/pdf" => Some("pdf"),
+        "text/plain" => Some("txt"),
+        _ => None,
+    }
+}
+
+/// 英数字のみの拡張子（パスとして安全なもの）
+fn extension_of(file_name: &str) -> Option<String> {
+    let (_, extension) = file_name.rsplit_once('.')?;
+    let extension = extension.to_lowercase();
+    let valid = !extension.is_empty()
+        && extension.len() <= 10
+        && extension.chars().all(|c| c.is_ascii_alphanumeric());
+    valid.then_some(extension)
+}
+
+/// 添付ファイルを追加する（同じ内容のファイルが既にあればそれを返す）
+pub fn add_bytes(
+    attachments: &mut Vec<Attachment>,
+    dir: &Path,
+    bytes: &[u8],
+    file_name: &str,
+    mime_type: Option<&str>,
+) -> Result<Attachment, String> {
+    if bytes.is_empty() {
+        return Err("Attachment is empty".to_string());
+    }
+    let hash = content_hash(bytes);
+    if let Some(existing) = attachments.iter().find(|a| a.hash == hash) {
+        if dir.join(existing.stored_name()).is_file() {
+            return Ok(existing.clone());
+        }
+    }
+
+    let mime_type = mime_type
+        .map(|m| m.trim().to_lowercase())
+        .filter(|m| !m.is_empty());
+    let extension = extension_of(file_name)
+        .or_else(|| {
+            mime_type
+                .as_deref()
+                .and_then(extension_for)
+                .map(str::to_string)
+        })
+        .unwrap_or_default();
+    let file_name = match file_name.trim() {
+        "" => format!("attachment.{}", extension)
+            .trim_end_matches('.')
+            .to_string(),
+        name => name.to_string(),
+    };
+    let attachment = Attachment {
+        mime_type: mime_type.unwrap_or_else(|| mime_type_for(&extension).to_string()),
+        hash,
+        file_name,
+        extension,
+        size: bytes.len() as u64,
+        created_at: Utc::now(),
+    };
+
+    fs::create_dir_all(dir).map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+    let path = dir.join(attachment.stored_name());
+    fs::write(&path, bytes).map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+
+    attachments.retain(|a| a.hash != attachment.hash);
+    attachments.push(attachment.clone());
+    Ok(attachment)
+}
+
+/// ファイルを添付ファイルとして取り込む（元のファイルはそのまま）
+pub fn add_file(
+    attachments: &mut Vec<Attachment>,
+    dir: &Path,
+    source: &Path,
+) -> Result<Attachment, String> {
+    let bytes =
+        fs::read(source).map_err(|e| format!("Failed to read {}: {}", source.display(), e))?;
+    let file_name = source
+        .file_name()
+        .map(|n| n.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    add_bytes(attachments, dir, &bytes, &file_name, None)
+}
+
+/// 本文中の参照のハッシュ（出現順）
+fn reference_hashes(content: &str) -> impl Iterator<Item = String> + '_ {
+    content
+        .match_indices(ATTACHMENT_SCHEME)
+        .filter_map(|(i, _)| {
+            let rest = &content[i + ATTACHMENT_SCHEME.len()..];
+            let hash: String = rest.chars().take_while(char::is_ascii_hexdigit).collect();
+            (hash.len() == 64).then(|| hash.to_lowercase())
+        })
+}
+
+/// 本文で参照している添付ファイルのハッシュ
+pub fn referenced_hashes(content: &str) -> HashSet<String> {
+    reference_hashes(content).collect()
+}
+
+/// 本文で参照している添付ファイル（最初に参照した順）
+pub fn attachments_in(attachments: &[Attachment], content: &str) -> Vec<Attachment> {
+    let mut seen = HashSet::new();
+    reference_hashes(content)
+        .filter(|hash| seen.insert(hash.clone()))
+        .filter_map(|hash| attachments.iter().find(|a| a.hash == hash).cloned())
+        .collect()
+}
+
+/// 不要な添付ファイルの削除結果
+#[derive(Debug, Clone, Default, Serialize, PartialEq)]
+pub struct AttachmentGcReport {
+    /// 削除した添付ファイルのハッシュ
+    pub removed: Vec<String>,
+    pub freed_bytes: u64,
+}
+
+/// どの本文からも参照されていない添付ファイルを削除する
+///
+/// `now` から [`GC_GRACE_HOURS`] 以内に追加したものは残す。
+/// 一覧に無いファイル（一覧の保存前に終了した場合など）も同じ条件で削除する。
+pub fn collect_garbage<'a>(
+    attachments: &mut Vec<Attachment>,
+    dir: &Path,
+    contents: impl IntoIterator<Item = &'a str>,
+    now: DateTime<Utc>,
+) -> AttachmentGcReport {
+    let referenced: HashSet<String> = contents.into_iter().flat_map(referenced_hashes).collect();
+    let cutoff = now - Duration::hours(GC_GRACE_HOURS);
+    let mut report = AttachmentGcReport::default();
+
+    attachments.retain(|attachment| {
+        if referenced.contains(&attachment.hash) || attachment.created_at > cutoff {
+            return true;
+        }
+        let path = dir.join(attachment.stored_name());
+        if fs::remove_file(&path).is_ok() {
+            report.freed_bytes += attachment.size;
+        }
+        report.removed.push(attachment.hash.clone());
+        false
+    });
+
+    let known: HashSet<String> = attachments.iter().map(Attachment::stored_name).collect();
+    for entry in fs::read_dir(dir).into_iter().flatten().flatten() {
+        let name = entry.file_name().to_string_lossy().into_owned();
+        let hash = name.split('.').next().unwrap_or_default().to_string();
+        let Ok(metadata) = entry.metadata() else {
+            continue;
+        };
+        let modified = metadata.modified().ok().map(DateTime::<Utc>::from);
+        let stale = modified.is_some_and(|m| m <= cutoff);
+        if metadata.is_file()
+            && stale
+            && hash.len() == 64
+            && !known.contains(&name)
+            && !referenced.contains(&hash)
+            && fs::remove_file(entry.path()).is_ok()
+        {
+            report.freed_bytes += metadata.len();
+            report.removed.push(hash);
+        }
+    }
+    report
+}
+
+pub fn get_attachments_dir() -> PathBuf {
+    let app_data = dirs::data_local_dir().unwrap();
+    app_data.join("com.noruno.platform").join("attachments")
+}
+
+pub fn get_attachments_file_path() -> PathBuf {
+    let app_data = dirs::data_local_dir().unwrap();
+    app_data
+        .join("com.noruno.platform")
+        .join("attachments.json")
+}
+
+pub fn load_attachments() -> Result<Vec<Attachment>, String> {
+    let path = get_attachments_file_path();
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+
+    let contents =
+        fs::read_to_string(&path).map_err(|e| format!("Failed to read attachments file: {}", e))?;
+
+    serde_json::from_str(&contents).map_err(|e| format!("Failed to parse attachments: {}", e))
+}
+
+pub fn save_attachments(attachments: &Vec<Attachment>) -> Result<(), String> {
+    let path = get_attachments_file_path();
+    let json = serde_json::to_string_pretty(attachments).map_err(|e| e.to_string())?;
+    fs::write(&path, json).map_err(|e| format!("Failed to save attachments: {}", e))?;
+    Ok(())
+}
@@ -0,0 +1,212 @@
+// 書籍の表紙画像（データディレクトリに保存し、アセットプロトコルで表示する）
+
+use chrono::{DateTime, Utc};
+use image::{DynamicImage, ImageFormat};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use crate::hash::content_hash;
+use crate::reading_memo::ReadingBook;
+
+/// サムネイルの最大サイズ（縦横比は保つ）
+pub const THUMBNAIL_WIDTH: u32 = 200;
+pub const THUMBNAIL_HEIGHT: u32 = 300;
+/// 受け付ける画像の最大サイズ
+pub const MAX_COVER_BYTES: u64 = 10 * 1024 * 1024;
+const DOWNLOAD_TIMEOUT: Duration = Duration::from_secs(15);
+
+/// 保存済みの表紙画像
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct BookCover {
+    /// 元画像の内容のSHA-256（16進表記）
+    pub hash: String,
+    /// 元画像の拡張子
+    pub extension: String,
+    pub width: u32,
+    pub height: u32,
+    /// URLからダウンロードした場合のURL
+    pub source_url: Option<String>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl BookCover {
+    /// 表紙画像のディレクトリ内の元画像のファイル名
+    pub fn file_name(&self) -> String {
+        format!("{}.{}", self.hash, self.extension)
+    }
+
+    /// 表紙画像のディレクトリ内のサムネイルのファイル名
+    pub fn thumbnail_name(&self) -> String {
+        format!("{}_thumb.jpg", self.hash)
+    }
+
+    /// 元画像とサムネイルが両方ともディスクにあるか
+    pub fn is_stored(&self, dir: &Path) -> bool {
+        dir.join(self.file_name()).is_file() && dir.join(self.thumbnail_name()).is_file()
+    }
+}
+
+/// 表紙画像の取得元
+#[derive(Debug, Clone, Deserialize, PartialEq)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum CoverSource {
+    /// ローカルの画像ファイル
+    File { path: String },
+    /// 画像のバイト列（貼り付け・ドロップした画像など）
+    Bytes { bytes: Vec<u8> },
+    /// ダウンロードする画像のURL
+    Url { url: String },
+}
+
+/// http(s) のURLか
+pub fn is_remote_url(url: &str) -> bool {
+    let lower = url.trim().to_lowercase();
+    lower.starts_with("http://") || lower.starts_with("https://")
+}
+
+/// 画像の形式を判定する（WebViewで表示できる形式のみ）
+fn cover_format(bytes: &[u8]) -> Result<ImageFormat, String> {
+    let format = image::guess_format(bytes).map_err(|_| "Cover is not an image".to_string())?;
+    match format {
+        ImageFormat::Png
+        | ImageFormat::Jpeg
+        | ImageFormat::Gif
+        | ImageFormat::WebP
+        | ImageFormat::Bmp => Ok(format),
+        other => Err(format!("Unsupported cover image format: {:?}", other)),
+    }
+}
+
+/// 画像を保存し、サムネイルを作る（同じ画像が保存済みならそのまま使う）
+pub fn store_cover(
+    dir: &Path,
+    bytes: &[u8],
+    source_url: Option<String>,
+    now: DateTime<Utc>,
+) -> Result<BookCover, String> {
+    if bytes.len() as u64 > MAX_COVER_BYTES {
+        return Err(format!(
+            "Cover image is larger than {} MB",
+            MAX_COVER_BYTES / 1024 / 1024
+        ));
+    }
+    let format = cover_format(bytes)?;
+    let image = image::load_from_memory_with_format(bytes, format)
+        .map_err(|e| format!("Failed to decode cover image: {}", e))?;
+
+    let cover = BookCover {
+        hash: content_hash(bytes),
+        extension: format.extensions_str()[0].to_string(),
+        width: image.width(),
+        height: image.height(),
+        source_url,
+        updated_at: now,
+    };
+    if cover.is_stored(dir) {
+        return Ok(cover);
+    }
+
+    fs::create_dir_all(dir).map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+    let path = dir.join(cover.file_name());
+    fs::write(&path, bytes).map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+
+    // JPEG has no alpha channel, so flatten before encoding
+    let thumbnail = image.thumbnail(THUMBNAIL_WIDTH, THUMBNAIL_HEIGHT);
+    let path = dir.join(cover.thumbnail_name());
+    DynamicImage::ImageRgb8(thumbnail.to_rgb8())
+        .save_with_format(&path, ImageFormat::Jpeg)
+        .map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+    Ok(cover)
+}
+
+/// 画像ファイルを読み込む
+pub fn read_cover_file(path: &Path) -> Result<Vec<u8>, String> {
+    let metadata =
+        fs::metadata(path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    if metadata.len() > MAX_COVER_BYTES {
+        return Err(format!(
+            "Cover image is larger than {} MB",
+            MAX_COVER_BYTES / 1024 / 1024
+        ));
+    }
+    fs::read(path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))
+}
+
+/// URLから画像をダウンロードする
+pub fn download_cover(url: &str) -> Result<Vec<u8>, String> {
+    if !is_remote_url(url) {
+        return Err(format!("Not an http(s) URL: {}", url));
+    }
+    let agent = ureq::AgentBuilder::new().timeout(DOWNLOAD_TIMEOUT).build();
+    let response = agent
+        .get(url.trim())
+        .call()
+        .map_err(|e| format!("Failed to download {}: {}", url, e))?;
+
+    let mut bytes = Vec::new();
+    response
+        .into_reader()
+        .take(MAX_COVER_BYTES + 1)
+        .read_to_end(&mut bytes)
+        .map_err(|e| format!("Failed to download {}: {}", url, e))?;
+    if bytes.len() as u64 > MAX_COVER_BYTES {
+        return Err(format!(
+            "Cover image is larger than {} MB",
+            MAX_COVER_BYTES / 1024 / 1024
+        ));
+    }
+    Ok(bytes)
+}
+
+/// URLから画像をダウンロードして保存する
+pub fn fetch_cover(dir: &Path, url: &str) -> Result<BookCover, String> {
+    let bytes = download_cover(url)?;
+    store_cover(dir, &bytes, Some(url.to_string()), Utc::now())
+}
+
+/// 既に同じURLからダウンロードした表紙画像（どの書籍のものでもよい）
+pub fn cached_cover_for_url(books: &[ReadingBook], dir: &Path, url: &str) -> Option<BookCover> {
+    books
+        .iter()
+        .filter_map(|b| b.cover.as_ref())
+        .find(|c| c.source_url.as_deref() == Some(url) && c.is_stored(dir))
+        .cloned()
+}
+
+/// 表紙画像をダウンロードしていない（またはURLが変わった）書籍のIDとURL
+pub fn uncached_cover_urls(books: &[ReadingBook]) -> Vec<(String, String)> {
+    books
+        .iter()
+        .filter_map(|book| {
+            let url = book
+                .cover_image_url
+                .as_deref()
+                .filter(|u| is_remote_url(u))?;
+            let cached = book
+                .cover
+                .as_ref()
+                .is_some_and(|c| c.source_url.as_deref() == Some(url));
+            (!cached).then(|| (book.id.clone(), url.to_string()))
+        })
+        .collect()
+}
+
+/// 外した表紙画像を、他の書籍で使っていなければ削除する
+pub fn remove_cover_if_unused(dir: &Path, books: &[ReadingBook], cover: &BookCover) {
+    let used = books
+        .iter()
+        .filter_map(|b| b.cover.as_ref())
+        .any(|c| c.hash == cover.hash);
+    if !used {
+        let _ = fs::remove_file(dir.join(cover.file_name()));
+        let _ = fs::remove_file(dir.join(cover.thumbnail_name()));
+    }
+}
+
+pub fn get_covers_dir() -> PathBuf {
+    let app_data = dirs::data_local_dir().unwrap();
+    app_data.join("com.noruno.platform").join("covers")
+}
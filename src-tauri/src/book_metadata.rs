@@ -0,0 +1,149 @@
+// ISBNから書誌情報を取得する（取得先は `BookMetadataProvider` で差し替えられる）
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::time::Duration;
+
+use crate::reading_memo::{ReadingBook, ReadingBookPatch};
+
+const LOOKUP_TIMEOUT: Duration = Duration::from_secs(15);
+
+/// 取得した書誌情報（取得先に無い項目は `None`）
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct BookMetadata {
+    pub title: Option<String>,
+    pub author: Option<String>,
+    pub publisher: Option<String>,
+    pub published_year: Option<i32>,
+    pub total_pages: Option<u32>,
+    pub cover_url: Option<String>,
+}
+
+/// 書誌情報の取得先（`AppState::book_metadata` に保持し、別スレッドから呼ぶ）
+pub trait BookMetadataProvider: Send + Sync {
+    /// 正規化済みのISBNで検索する（見つからない場合は `Ok(None)`）
+    fn lookup_isbn(&self, isbn: &str) -> Result<Option<BookMetadata>, String>;
+}
+
+/// Open Library（https://openlibrary.org）の Books API
+pub struct OpenLibraryProvider {
+    base_url: String,
+}
+
+impl Default for OpenLibraryProvider {
+    fn default() -> Self {
+        Self::with_base_url("https://openlibrary.org")
+    }
+}
+
+impl OpenLibraryProvider {
+    /// 接続先を指定する（テスト用のサーバーなど）
+    pub fn with_base_url(base_url: &str) -> Self {
+        Self {
+            base_url: base_url.trim_end_matches('/').to_string(),
+        }
+    }
+}
+
+impl BookMetadataProvider for OpenLibraryProvider {
+    fn lookup_isbn(&self, isbn: &str) -> Result<Option<BookMetadata>, String> {
+        let key = format!("ISBN:{}", isbn);
+        let url = format!(
+            "{}/api/books?bibkeys={}&format=json&jscmd=data",
+            self.base_url, key
+        );
+        let agent = ureq::AgentBuilder::new().timeout(LOOKUP_TIMEOUT).build();
+        let body: Value = agent
+            .get(&url)
+            .call()
+            .map_err(|e| format!("Failed to look up ISBN {}: {}", isbn, e))?
+            .into_json()
+            .map_err(|e| format!("Failed to parse Open Library response: {}", e))?;
+
+        Ok(body.get(&key).map(parse_open_library))
+    }
+}
+
+/// Open Library の `jscmd=data` 形式の書籍情報を読む
+fn parse_open_library(data: &Value) -> BookMetadata {
+    let text = |value: Option<&Value>| {
+        value
+            .and_then(Value::as_str)
+            .map(str::trim)
+            .filter(|s| !s.is_empty())
+            .map(str::to_string)
+    };
+    let names = |field: &str| {
+        let names: Vec<String> = data
+            .get(field)
+            .and_then(Value::as_array)
+            .into_iter()
+            .flatten()
+            .filter_map(|v| text(v.get("name")))
+            .collect();
+        (!names.is_empty()).then(|| names.join(", "))
+    };
+    let cover = data.get("cover");
+
+    BookMetadata {
+        title: text(data.get("title")),
+        author: names("authors"),
+        publisher: names("publishers"),
+        published_year: data
+            .get("publish_date")
+            .and_then(Value::as_str)
+            .and_then(parse_year),
+        total_pages: data
+            .get("number_of_pages")
+            .and_then(Value::as_u64)
+            .and_then(|n| u32::try_from(n).ok())
+            .filter(|n| *n > 0),
+        cover_url: ["large", "medium", "small"]
+            .iter()
+            .find_map(|size| text(cover.and_then(|c| c.get(*size)))),
+    }
+}
+
+/// 出版日の表記（"2008", "March 2008", "2008-03-01" など）から年を取り出す
+pub fn parse_year(date: &str) -> Option<i32> {
+    let bytes = date.as_bytes();
+    (0..bytes.len().saturating_sub(3)).find_map(|i| {
+        let candidate = &bytes[i..i + 4];
+        let before = i.checked_sub(1).map(|j| bytes[j]);
+        let after = bytes.get(i + 4).copied();
+        let isolated = !before.is_some_and(|b| b.is_ascii_digit())
+            && !after.is_some_and(|b| b.is_ascii_digit());
+        (isolated && candidate.iter().all(u8::is_ascii_digit))
+            .then(|| std::str::from_utf8(candidate).ok()?.parse().ok())
+            .flatten()
+    })
+}
+
+/// 書誌情報から書籍の部分更新を作る（`overwrite` でなければ空の項目だけ埋める）
+///
+/// 表紙画像のURLは呼び出し側で扱う。
+pub fn metadata_patch(
+    book: &ReadingBook,
+    metadata: &BookMetadata,
+    overwrite: bool,
+) -> ReadingBookPatch {
+    fn fill<T: Clone>(
+        current: &Option<T>,
+        value: &Option<T>,
+        overwrite: bool,
+    ) -> Option<Option<T>> {
+        (value.is_some() && (overwrite || current.is_none())).then(|| value.clone())
+    }
+
+    ReadingBookPatch {
+        title: metadata
+            .title
+            .clone()
+            .filter(|_| overwrite || book.title.trim().is_empty()),
+        author: fill(&book.author, &metadata.author, overwrite),
+        publisher: fill(&book.publisher, &metadata.publisher, overwrite),
+        published_year: fill(&book.published_year, &metadata.published_year, overwrite),
+        total_pages: fill(&book.total_pages, &metadata.total_pages, overwrite),
+        ..ReadingBookPatch::default()
+    }
+}
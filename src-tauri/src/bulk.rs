@@ -0,0 +1,238 @@
+// 複数タスクの一括更新と取り消し履歴
+
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+use std::collections::{HashMap, HashSet};
+
+use crate::dependency::{incomplete_blockers, remove_dependency_references};
+use crate::entity_links::EntityLink;
+use crate::group::{resolve_group_id, TaskGroup};
+use crate::ordering::ensure_sort_keys;
+use crate::patch::present;
+use crate::task::{normalize_tags, Task};
+use crate::task_date::validate_task_dates;
+use crate::workflow::{normalize_state, set_completed};
+
+/// 保持する取り消し記録の上限
+pub const UNDO_LIMIT: usize = 20;
+
+/// 一括更新の内容（指定された項目だけを変更する）
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct BulkTaskPatch {
+    #[serde(default)]
+    pub completed: Option<bool>,
+    /// 削除する（他の項目は無視される）
+    #[serde(default)]
+    pub delete: bool,
+    /// 移動先のグループ（`null` または空文字列はグループ無し）
+    #[serde(default, deserialize_with = "present")]
+    pub group_id: Option<Option<String>>,
+    /// 期限をずらす日数（期限の無いタスクは対象外）
+    #[serde(default)]
+    pub shift_due_days: Option<i64>,
+    #[serde(default)]
+    pub add_tags: Vec<String>,
+}
+
+/// 取り消し用に保存する変更前の状態
+#[derive(Debug, Clone)]
+pub struct UndoEntry {
+    pub label: String,
+    /// 変更・削除されたタスクの変更前の内容
+    pub previous: Vec<Task>,
+    /// 削除されたタスクのID
+    pub deleted: Vec<i32>,
+    /// 変更したタスクに設定した `updated_at`（その後に変更されたかの判定に使う）
+    pub applied_at: DateTime<Utc>,
+    /// 削除したタスクと一緒に取り除いたリンク（取り消し時に戻す）
+    pub links: Vec<EntityLink>,
+}
+
+impl UndoEntry {
+    /// 変更された（削除されていない）タスクのID
+    pub fn updated_ids(&self) -> Vec<i32> {
+        self.previous
+            .iter()
+            .map(|t| t.id)
+            .filter(|id| !self.deleted.contains(id))
+            .collect()
+    }
+}
+
+/// タスクの変更の取り消し履歴（新しいものが末尾）
+#[derive(Debug, Default)]
+pub struct UndoHistory {
+    entries: Vec<UndoEntry>,
+}
+
+impl UndoHistory {
+    pub fn push(&mut self, entry: UndoEntry) {
+        self.entries.push(entry);
+        if self.entries.len() > UNDO_LIMIT {
+            self.entries.remove(0);
+        }
+    }
+
+    pub fn pop(&mut self) -> Option<UndoEntry> {
+        self.entries.pop()
+    }
+}
+
+/// 複数のタスクに同じ変更を適用する
+///
+/// すべてのタスクを検証してから反映するため、エラーの場合は何も変更しない。
+/// 完了にする場合、同時に完了にするタスクは依存先として未完了でも構わない。
+/// 成功した場合は取り消し用の記録を返す。
+pub fn bulk_update(
+    tasks: &mut Vec<Task>,
+    groups: &[TaskGroup],
+    ids: &[i32],
+    patch: &BulkTaskPatch,
+    now: DateTime<Utc>,
+) -> Result<UndoEntry, String> {
+    if ids.is_empty() {
+        return Err("No tasks selected".to_string());
+    }
+    if let Some(unknown) = ids.iter().find(|id| !tasks.iter().any(|t| t.id == **id)) {
+        return Err(format!("Task {} not found", unknown));
+    }
+    let group_id = match &patch.group_id {
+        Some(group_id) => Some(resolve_group_id(groups, group_id.clone())?),
+        None => None,
+    };
+    let tags = normalize_tags(patch.add_tags.clone());
+
+    let mut updated = tasks.clone();
+    if patch.delete {
+        updated.retain(|t| !ids.contains(&t.id));
+        for id in ids {
+            remove_dependency_references(&mut updated, *id);
+        }
+    } else {
+        for task in updated.iter_mut().filter(|t| ids.contains(&t.id)) {
+            if let Some(group_id) = &group_id {
+                task.group_id = group_id.clone();
+                normalize_state(task, groups);
+            }
+            if let (Some(days), Some(due)) = (patch.shift_due_days, task.due_date) {
+                let shifted = due.shift_days(days).ok_or_else(|| {
+                    format!(
+                        "Task {}: Due date cannot be shifted by {} days",
+                        task.id, days
+                    )
+                })?;
+                validate_task_dates(task.start_date.as_ref(), Some(&shifted))
+                    .map_err(|e| format!("Task {}: {}", task.id, e))?;
+                task.due_date = Some(shifted);
+                task.notified = false;
+            }
+            if !tags.is_empty() {
+                let mut all = task.tags.clone();
+                all.extend(tags.iter().cloned());
+                task.tags = normalize_tags(all);
+            }
+        }
+
+        if let Some(completed) = patch.completed {
+            if completed {
+                for task in updated
+                    .iter()
+                    .filter(|t| ids.contains(&t.id) && !t.completed)
+                {
+                    let blockers: Vec<String> = incomplete_blockers(&updated, task)
+                        .into_iter()
+                        .filter(|id| !ids.contains(id))
+                        .map(|id| id.to_string())
+                        .collect();
+                    if !blockers.is_empty() {
+                        return Err(format!(
+                            "Task {} is blocked by incomplete tasks: {}",
+                            task.id,
+                            blockers.join(", ")
+                        ));
+                    }
+                }
+            }
+            for task in updated.iter_mut().filter(|t| ids.contains(&t.id)) {
+                if task.completed != completed {
+                    set_completed(task, groups, completed, now);
+                }
+            }
+        }
+    }
+
+    let original: HashMap<i32, &Task> = tasks.iter().map(|t| (t.id, t)).collect();
+    let mut changed = HashSet::new();
+    for task in updated.iter_mut() {
+        if original.get(&task.id) != Some(&&*task) {
+            task.updated_at = Some(now);
+            changed.insert(task.id);
+        }
+    }
+    let previous = tasks
+        .iter()
+        .filter(|t| changed.contains(&t.id) || (patch.delete && ids.contains(&t.id)))
+        .cloned()
+        .collect();
+    *tasks = updated;
+    Ok(UndoEntry {
+        label: format!("Bulk update of {} tasks", ids.len()),
+        previous,
+        deleted: if patch.delete {
+            ids.to_vec()
+        } else {
+            Vec::new()
+        },
+        applied_at: now,
+        links: Vec::new(),
+    })
+}
+
+/// 取り消し記録の内容に戻す
+///
+/// 記録されたタスクだけを変更前の内容に戻し（削除されたタスクは元の位置に復元する）、
+/// その後に行われた他のタスクへの変更はそのまま残す。
+/// 記録されたタスクがその後に変更・削除されていた場合は、変更を失わないよう何もせずエラーにする。
+/// 復元するタスクのグループがその後に削除されていた場合はグループ無しにする。
+/// 取り除いたリンク（`entry.links`）は戻さないため、呼び出し側で戻す。
+/// 戻したタスクのIDを返す。
+pub fn undo(
+    tasks: &mut Vec<Task>,
+    groups: &[TaskGroup],
+    entry: UndoEntry,
+) -> Result<Vec<i32>, String> {
+    let changed: Vec<String> = entry
+        .previous
+        .iter()
+        .filter(|previous| {
+            let current = tasks.iter().find(|t| t.id == previous.id);
+            if entry.deleted.contains(&previous.id) {
+                current.is_some()
+            } else {
+                current.is_none_or(|t| t.updated_at != Some(entry.applied_at))
+            }
+        })
+        .map(|t| t.id.to_string())
+        .collect();
+    if !changed.is_empty() {
+        return Err(format!(
+            "Cannot undo because tasks changed afterwards: {}",
+            changed.join(", ")
+        ));
+    }
+
+    let restored = entry.previous.iter().map(|t| t.id).collect();
+    for mut previous in entry.previous {
+        if let Some(group_id) = previous.group_id.as_deref() {
+            if !groups.iter().any(|g| g.id == group_id) {
+                previous.group_id = None;
+            }
+        }
+        match tasks.iter_mut().find(|t| t.id == previous.id) {
+            Some(task) => *task = previous,
+            None => tasks.push(previous),
+        }
+    }
+    ensure_sort_keys(tasks);
+    Ok(restored)
+}
@@ -1,6 +1,8 @@
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 
+use crate::patch::present;
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CalendarEvent {
     pub id: String,
@@ -16,6 +18,58 @@ pub struct CalendarEvent {
     pub updated_at: DateTime<Utc>,
 }
 
+/// 予定の部分更新（指定された項目だけを変更する）
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct CalendarEventPatch {
+    #[serde(default)]
+    pub title: Option<String>,
+    #[serde(default)]
+    pub description: Option<String>,
+    #[serde(default)]
+    pub start_datetime: Option<String>,
+    #[serde(default, deserialize_with = "present")]
+    pub end_datetime: Option<Option<String>>,
+    #[serde(default)]
+    pub all_day: Option<bool>,
+    #[serde(default, deserialize_with = "present")]
+    pub color: Option<Option<String>>,
+    #[serde(default, deserialize_with = "present")]
+    pub recurrence_rule: Option<Option<String>>,
+    #[serde(default, deserialize_with = "present")]
+    pub reminder_minutes: Option<Option<i32>>,
+}
+
+impl CalendarEvent {
+    /// 部分更新を適用する
+    pub fn apply_patch(&mut self, patch: CalendarEventPatch, now: DateTime<Utc>) {
+        if let Some(title) = patch.title {
+            self.title = title;
+        }
+        if let Some(description) = patch.description {
+            self.description = description;
+        }
+        if let Some(start_datetime) = patch.start_datetime {
+            self.start_datetime = start_datetime;
+        }
+        if let Some(end_datetime) = patch.end_datetime {
+            self.end_datetime = end_datetime;
+        }
+        if let Some(all_day) = patch.all_day {
+            self.all_day = all_day;
+        }
+        if let Some(color) = patch.color {
+            self.color = color;
+        }
+        if let Some(recurrence_rule) = patch.recurrence_rule {
+            self.recurrence_rule = recurrence_rule;
+        }
+        if let Some(reminder_minutes) = patch.reminder_minutes {
+            self.reminder_minutes = reminder_minutes;
+        }
+        self.updated_at = now;
+    }
+}
+
 use std::fs;
 use std::path::PathBuf;
 
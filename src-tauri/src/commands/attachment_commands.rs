@@ -0,0 +1,115 @@
+// メモの添付ファイル関連のTauriコマンド
+
+use chrono::Utc;
+use serde::Serialize;
+use std::path::PathBuf;
+use tauri::State;
+
+use crate::attachments::{self, Attachment, AttachmentGcReport};
+use crate::AppState;
+
+/// 追加した添付ファイルと、本文に貼り付けるMarkdown
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct AddedAttachment {
+    pub attachment: Attachment,
+    pub markdown: String,
+}
+
+impl From<Attachment> for AddedAttachment {
+    fn from(attachment: Attachment) -> Self {
+        AddedAttachment {
+            markdown: attachment.markdown(),
+            attachment,
+        }
+    }
+}
+
+/// 添付ファイルのディレクトリ（フロントエンドで `convertFileSrc` に渡すパスの基点）
+#[tauri::command]
+pub fn get_attachments_dir() -> String {
+    attachments::get_attachments_dir().display().to_string()
+}
+
+/// ファイルを添付ファイルとして取り込む
+#[tauri::command]
+pub async fn add_attachment_from_path(
+    state: State<'_, AppState>,
+    path: String,
+) -> Result<AddedAttachment, String> {
+    let attachment = {
+        let mut attachments = state.attachments.lock().unwrap();
+        let attachment = attachments::add_file(
+            &mut attachments,
+            &attachments::get_attachments_dir(),
+            &PathBuf::from(path),
+        )?;
+        attachments::save_attachments(&attachments)?;
+        attachment
+    };
+    Ok(attachment.into())
+}
+
+/// 貼り付けた画像などのバイト列を添付ファイルにする
+///
+/// `file_name` に拡張子が無い場合は `mime_type` から決める。
+#[tauri::command]
+pub async fn add_attachment_from_bytes(
+    state: State<'_, AppState>,
+    bytes: Vec<u8>,
+    file_name: String,
+    mime_type: Option<String>,
+) -> Result<AddedAttachment, String> {
+    let attachment = {
+        let mut attachments = state.attachments.lock().unwrap();
+        let attachment = attachments::add_bytes(
+            &mut attachments,
+            &attachments::get_attachments_dir(),
+            &bytes,
+            &file_name,
+            mime_type.as_deref(),
+        )?;
+        attachments::save_attachments(&attachments)?;
+        attachment
+    };
+    Ok(attachment.into())
+}
+
+/// メモの本文で参照している添付ファイル
+#[tauri::command]
+pub fn get_memo_attachments(
+    state: State<AppState>,
+    memo_id: String,
+) -> Result<Vec<Attachment>, String> {
+    let memos = state.memos.lock().unwrap();
+    let attachments = state.attachments.lock().unwrap();
+    let memo = memos
+        .iter()
+        .find(|m| m.id == memo_id)
+        .ok_or_else(|| format!("Memo {} not found", memo_id))?;
+    Ok(attachments::attachments_in(&attachments, &memo.content))
+}
+
+/// メモ・テンプレートのどこからも参照されていない添付ファイルを削除する
+#[tauri::command]
+pub async fn collect_unused_attachments(
+    state: State<'_, AppState>,
+) -> Result<AttachmentGcReport, String> {
+    let store = state.memo_templates.lock().unwrap();
+    let memos = state.memos.lock().unwrap();
+    let mut attachments = state.attachments.lock().unwrap();
+
+    let contents = memos
+        .iter()
+        .map(|m| m.content.as_str())
+        .chain(store.templates.iter().map(|t| t.content.as_str()));
+    let report = attachments::collect_garbage(
+        &mut attachments,
+        &attachments::get_attachments_dir(),
+        contents,
+        Utc::now(),
+    );
+    if !report.removed.is_empty() {
+        attachments::save_attachments(&attachments)?;
+    }
+    Ok(report)
+}
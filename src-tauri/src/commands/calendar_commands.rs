@@ -1,6 +1,8 @@
-use crate::calendar::{save_calendar_events, CalendarEvent};
+use crate::calendar::{save_calendar_events, CalendarEvent, CalendarEventPatch};
+use crate::entity_links::{EntityKind, EntityRef};
+use crate::patch::check_updated_at;
 use crate::AppState;
-use tauri::State;
+use tauri::{AppHandle, State};
 
 #[tauri::command]
 pub async fn get_calendar_events(state: State<'_, AppState>) -> Result<Vec<CalendarEvent>, String> {
@@ -19,7 +21,7 @@ pub async fn create_calendar_event(
     color: Option<String>,
     recurrence_rule: Option<String>,
     reminder_minutes: Option<i32>,
-) -> Result<Vec<CalendarEvent>, String> {
+) -> Result<CalendarEvent, String> {
     let new_event = CalendarEvent {
         id: uuid::Uuid::new_v4().to_string(),
         title,
@@ -34,60 +36,56 @@ pub async fn create_calendar_event(
         updated_at: chrono::Utc::now(),
     };
 
-    let events = {
+    {
         let mut events = state.calendar_events.lock().unwrap();
-        events.push(new_event);
+        events.push(new_event.clone());
         save_calendar_events(&events)?;
-        events.clone()
-    };
+    }
 
-    Ok(events)
+    Ok(new_event)
 }
 
+/// 予定を部分更新する
+///
+/// `expected_updated_at` は読み込んだ時点の `updated_at`（省略時は確認しない）。
+/// 他で更新されていた場合は `Conflict:` で始まるエラーを返す。
 #[tauri::command]
 pub async fn update_calendar_event(
     state: State<'_, AppState>,
     id: String,
-    title: String,
-    description: String,
-    start_datetime: String,
-    end_datetime: Option<String>,
-    all_day: bool,
-    color: Option<String>,
-    recurrence_rule: Option<String>,
-    reminder_minutes: Option<i32>,
-) -> Result<Vec<CalendarEvent>, String> {
-    let events = {
+    patch: CalendarEventPatch,
+    expected_updated_at: Option<chrono::DateTime<chrono::Utc>>,
+) -> Result<CalendarEvent, String> {
+    let event = {
         let mut events = state.calendar_events.lock().unwrap();
-        if let Some(event) = events.iter_mut().find(|e| e.id == id) {
-            event.title = title;
-            event.description = description;
-            event.start_datetime = start_datetime;
-            event.end_datetime = end_datetime;
-            event.all_day = all_day;
-            event.color = color;
-            event.recurrence_rule = recurrence_rule;
-            event.reminder_minutes = reminder_minutes;
-            event.updated_at = chrono::Utc::now();
-            save_calendar_events(&events)?;
-        }
-        events.clone()
+        let event = events
+            .iter_mut()
+            .find(|e| e.id == id)
+            .ok_or_else(|| format!("Event {} not found", id))?;
+        check_updated_at("Event", &id, Some(event.updated_at), expected_updated_at)?;
+        event.apply_patch(patch, chrono::Utc::now());
+        let event = event.clone();
+        save_calendar_events(&events)?;
+        event
     };
 
-    Ok(events)
+    Ok(event)
 }
 
 #[tauri::command]
 pub async fn delete_calendar_event(
     state: State<'_, AppState>,
+    app: AppHandle,
     id: String,
-) -> Result<Vec<CalendarEvent>, String> {
-    let events = {
+) -> Result<String, String> {
+    {
         let mut events = state.calendar_events.lock().unwrap();
         events.retain(|e| e.id != id);
         save_calendar_events(&events)?;
-        events.clone()
-    };
+    }
+
+    let removed = [EntityRef::new(EntityKind::CalendarEvent, &id)];
+    super::link_commands::remove_links_to(&state, &app, &removed)?;
 
-    Ok(events)
+    Ok(id)
 }
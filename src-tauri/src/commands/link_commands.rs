@@ -0,0 +1,289 @@
+// エンティティ間のリンク関連のTauriコマンド
+
+use chrono::Utc;
+use tauri::{AppHandle, State};
+
+use crate::entity_links::{self, EntityKind, EntityLink, EntityRef, EntitySources, LinkedEntity};
+use crate::events::{self, ChangeEvent};
+use crate::group;
+use crate::memo::Memo;
+use crate::ordering;
+use crate::task::Task;
+use crate::AppState;
+
+/// 削除されたエンティティへのリンクを取り除く（削除系のコマンドから呼ぶ）
+pub(crate) fn remove_links_to(
+    state: &AppState,
+    app: &AppHandle,
+    removed: &[EntityRef],
+) -> Result<(), String> {
+    let removed_links = take_links_to(state, removed)?;
+    events::emit(
+        app,
+        removed_links
+            .into_iter()
+            .map(|link| ChangeEvent::EntityLinkRemoved(link.id)),
+    );
+    Ok(())
+}
+
+/// 削除されたエンティティへのリンクを取り除いて保存し、取り除いたリンクを返す（イベントは呼び出し側で送る）
+pub(crate) fn take_links_to(
+    state: &AppState,
+    removed: &[EntityRef],
+) -> Result<Vec<EntityLink>, String> {
+    if removed.is_empty() {
+        return Ok(Vec::new());
+    }
+    let mut links = state.entity_links.lock().unwrap();
+    let removed_links = entity_links::remove_links_to(&mut links, removed);
+    if !removed_links.is_empty() {
+        entity_links::save_entity_links(&links)?;
+    }
+    Ok(removed_links)
+}
+
+/// 取り除いたリンクを戻す（取り消し系のコマンドから呼ぶ）
+pub(crate) fn restore_links(
+    state: &AppState,
+    app: &AppHandle,
+    removed: Vec<EntityLink>,
+) -> Result<(), String> {
+    if removed.is_empty() {
+        return Ok(());
+    }
+    let restored = {
+        let tasks = state.tasks.lock().unwrap();
+        let memos = state.memos.lock().unwrap();
+        let books = state.reading_books.lock().unwrap();
+        let calendar_events = state.calendar_events.lock().unwrap();
+        let mut links = state.entity_links.lock().unwrap();
+
+        let sources = EntitySources {
+            tasks: &tasks,
+            memos: &memos,
+            books: &books,
+            events: &calendar_events,
+        };
+        let restored = entity_links::restore_links(&mut links, &sources, removed);
+        if !restored.is_empty() {
+            entity_links::save_entity_links(&links)?;
+        }
+        restored
+    };
+
+    events::emit(app, restored.into_iter().map(ChangeEvent::EntityLinkAdded));
+    Ok(())
+}
+
+/// 2つのエンティティをリンクする（既にリンクされている場合は既存のリンクを返す）
+#[tauri::command]
+pub async fn add_entity_link(
+    state: State<'_, AppState>,
+    app: AppHandle,
+    source: EntityRef,
+    target: EntityRef,
+    label: Option<String>,
+) -> Result<EntityLink, String> {
+    let (link, added) = {
+        let tasks = state.tasks.lock().unwrap();
+        let memos = state.memos.lock().unwrap();
+        let books = state.reading_books.lock().unwrap();
+        let calendar_events = state.calendar_events.lock().unwrap();
+        let mut links = state.entity_links.lock().unwrap();
+
+        let sources = EntitySources {
+            tasks: &tasks,
+            memos: &memos,
+            books: &books,
+            events: &calendar_events,
+        };
+        let (link, added) = entity_links::add_link(&mut links, &sources, source, target, label)?;
+        if added {
+            entity_links::save_entity_links(&links)?;
+        }
+        (link, added)
+    };
+
+    if added {
+        events::emit(&app, [ChangeEvent::EntityLinkAdded(link.clone())]);
+    }
+    Ok(link)
+}
+
+#[tauri::command]
+pub async fn remove_entity_link(
+    state: State<'_, AppState>,
+    app: AppHandle,
+    id: String,
+) -> Result<String, String> {
+    {
+        let mut links = state.entity_links.lock().unwrap();
+        let before = links.len();
+        links.retain(|l| l.id != id);
+        if links.len() == before {
+            return Err(format!("Link {} not found", id));
+        }
+
+        entity_links::save_entity_links(&links)?;
+    }
+
+    events::emit(&app, [ChangeEvent::EntityLinkRemoved(id.clone())]);
+    Ok(id)
+}
+
+/// `entity` にリンクされているエンティティ
+#[tauri::command]
+pub fn get_entity_links(state: State<AppState>, entity: EntityRef) -> Vec<LinkedEntity> {
+    let tasks = state.tasks.lock().unwrap();
+    let memos = state.memos.lock().unwrap();
+    let books = state.reading_books.lock().unwrap();
+    let calendar_events = state.calendar_events.lock().unwrap();
+    let links = state.entity_links.lock().unwrap();
+
+    let sources = EntitySources {
+        tasks: &tasks,
+        memos: &memos,
+        books: &books,
+        events: &calendar_events,
+    };
+    entity_links::linked_entities(&links, &sources, &entity)
+}
+
+/// 読書メモからメモを作成し、読書メモとリンクする
+#[tauri::command]
+pub async fn create_memo_from_reading_note(
+    state: State<'_, AppState>,
+    app: AppHandle,
+    book_id: String,
+    note_id: String,
+    folder_id: Option<String>,
+) -> Result<Memo, String> {
+    let (title, content, tags) = {
+        let books = state.reading_books.lock().unwrap();
+        let (book, note) = entity_links::find_note(&books, &note_id)
+            .filter(|(book, _)| book.id == book_id)
+            .ok_or_else(|| format!("Note {} not found in book {}", note_id, book_id))?;
+        let (title, content) = entity_links::memo_from_note(book, note);
+        (title, content, book.tags.clone())
+    };
+
+    let (memo, link) = {
+        let mut memos = state.memos.lock().unwrap();
+        let memo = Memo::new(title, content, folder_id, tags);
+        memos.push(memo.clone());
+
+        crate::memo::save_memos(&memos)?;
+        state.memo_index.lock().unwrap().upsert(&memo);
+        state.memo_links.lock().unwrap().update(&memo);
+
+        let mut links = state.entity_links.lock().unwrap();
+        let link = EntityLink::new(
+            EntityRef::new(EntityKind::Memo, &memo.id),
+            EntityRef::new(EntityKind::ReadingNote, &note_id),
+            None,
+        );
+        links.push(link.clone());
+        entity_links::save_entity_links(&links)?;
+
+        (memo, link)
+    };
+
+    events::emit(
+        &app,
+        [
+            ChangeEvent::MemoCreated(memo.clone()),
+            ChangeEvent::EntityLinkAdded(link),
+        ],
+    );
+    Ok(memo)
+}
+
+/// メモの1行（0始まりの行番号）からタスクを作成し、メモとリンクする
+///
+/// リストの記号やチェックボックスは説明文から除く。元の行はリンクの説明として残す。
+#[tauri::command]
+pub async fn create_task_from_memo_line(
+    state: State<'_, AppState>,
+    app: AppHandle,
+    memo_id: String,
+    line: usize,
+    group_id: Option<String>,
+) -> Result<Task, String> {
+    let original = {
+        let memos = state.memos.lock().unwrap();
+        let memo = memos
+            .iter()
+            .find(|m| m.id == memo_id)
+            .ok_or_else(|| format!("Memo {} not found", memo_id))?;
+        memo.content
+            .lines()
+            .nth(line)
+            .map(|l| l.trim().to_string())
+            .ok_or_else(|| format!("Memo {} has no line {}", memo_id, line))?
+    };
+    let description = entity_links::task_text_from_line(&original);
+    if description.is_empty() {
+        return Err(format!("Line {} of memo {} is empty", line, memo_id));
+    }
+
+    let task = {
+        let groups = state.groups.lock().unwrap();
+        let mut tasks = state.tasks.lock().unwrap();
+        let mut next_id = state.next_id.lock().unwrap();
+
+        let group_id = group::resolve_group_id(&groups, group_id)?;
+        let task = Task {
+            id: *next_id,
+            description,
+            start_date: None,
+            due_date: None,
+            group_id,
+            details: String::new(),
+            completed: false,
+            notified: false,
+            notification_minutes: None,
+            subtasks: Vec::new(),
+            dependencies: None,
+            priority: None,
+            estimated_minutes: None,
+            time_entries: Vec::new(),
+            tags: Vec::new(),
+            state: None,
+            state_history: Vec::new(),
+            sort_key: ordering::key_after_last(tasks.as_slice()),
+            rollup_subtasks: false,
+            updated_at: Some(Utc::now()),
+        };
+
+        *next_id += 1;
+        tasks.push(task.clone());
+
+        // Save to JSON file
+        let data_file = state.data_file.lock().unwrap();
+        crate::task::save_tasks(&tasks, &data_file)?;
+
+        task
+    };
+
+    let link = {
+        let mut links = state.entity_links.lock().unwrap();
+        let link = EntityLink::new(
+            EntityRef::task(task.id),
+            EntityRef::new(EntityKind::Memo, &memo_id),
+            Some(original),
+        );
+        links.push(link.clone());
+        entity_links::save_entity_links(&links)?;
+        link
+    };
+
+    events::emit(
+        &app,
+        [
+            ChangeEvent::TaskCreated(task.clone()),
+            ChangeEvent::EntityLinkAdded(link),
+        ],
+    );
+    Ok(task)
+}
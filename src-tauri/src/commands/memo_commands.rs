@@ -1,18 +1,30 @@
 // メモ・フォルダ関連のTauriコマンド
 
-use tauri::State;
+use chrono::Utc;
+use tauri::{AppHandle, State};
 
+use crate::entity_links::{EntityKind, EntityRef};
+use crate::events::{self, ChangeEvent};
+use crate::folder_tree::{self, DeleteFolderOptions, FolderTree};
+use crate::listing::{self, ListOptions, MemoSummary, Page, Projected};
 use crate::memo::{Folder, Memo};
+use crate::memo_links::{self, Backlink, BrokenLink};
+use crate::memo_search::{MemoSearchHit, MemoSearchQuery, DEFAULT_SEARCH_LIMIT};
+use crate::memo_sync::SyncMessage;
 use crate::AppState;
 
 // ========================================
 // メモ関連コマンド
 // ========================================
 
+/// メモの一覧（`options.summary` の場合は本文の冒頭のみ）
 #[tauri::command]
-pub fn get_memos(state: State<AppState>) -> Vec<Memo> {
+pub fn get_memos(
+    state: State<AppState>,
+    options: Option<ListOptions>,
+) -> Result<Page<Projected<Memo, MemoSummary>>, String> {
     let memos = state.memos.lock().unwrap();
-    memos.clone()
+    listing::list(&memos, &options.unwrap_or_default())
 }
 
 #[tauri::command]
@@ -24,76 +36,144 @@ pub fn get_memo(state: State<AppState>, id: String) -> Option<Memo> {
 #[tauri::command]
 pub async fn create_memo(
     state: State<'_, AppState>,
+    app: AppHandle,
     title: String,
     content: String,
     folder_id: Option<String>,
     tags: Vec<String>,
-) -> Result<Vec<Memo>, String> {
-    let memos = {
+) -> Result<Memo, String> {
+    let memo = {
         let mut memos = state.memos.lock().unwrap();
         let memo = Memo::new(title, content, folder_id, tags);
-        memos.push(memo);
+        memos.push(memo.clone());
 
         crate::memo::save_memos(&memos)?;
+        state.memo_index.lock().unwrap().upsert(&memo);
+        state.memo_links.lock().unwrap().update(&memo);
 
-        memos.clone()
+        memo
     };
 
-    Ok(memos)
+    events::emit(&app, [ChangeEvent::MemoCreated(memo.clone())]);
+    Ok(memo)
 }
 
+/// メモを更新する
+///
+/// タイトルが変わった場合は、他のメモの `[[旧タイトル]]` も新しいタイトルに書き換える
+/// （書き換えたメモは `memo-updated` イベントで通知する）。
 #[tauri::command]
 pub async fn update_memo(
     state: State<'_, AppState>,
+    app: AppHandle,
     id: String,
     title: String,
     content: String,
     folder_id: Option<String>,
     tags: Vec<String>,
-) -> Result<Vec<Memo>, String> {
-    let memos = {
+) -> Result<Memo, String> {
+    let (memo, renamed) = {
         let mut memos = state.memos.lock().unwrap();
-        if let Some(memo) = memos.iter_mut().find(|m| m.id == id) {
-            memo.update(title, content, folder_id, tags);
+        let mut memo_index = state.memo_index.lock().unwrap();
+        let mut links = state.memo_links.lock().unwrap();
+
+        let old_title = memos
+            .iter()
+            .find(|m| m.id == id)
+            .map(|m| m.title.clone())
+            .ok_or_else(|| format!("Memo {} not found", id))?;
+        let title_owner = memo_links::resolve(&memos, &old_title).map(|m| m.id.clone());
+
+        let memo = memos.iter_mut().find(|m| m.id == id).unwrap();
+        memo.update(title, content, folder_id, tags);
+        links.update(memo);
+
+        let changed = memo_links::rename_references(
+            &mut memos,
+            &mut links,
+            &id,
+            title_owner.as_deref(),
+            &old_title,
+        );
+        let memo = memos.iter().find(|m| m.id == id).unwrap().clone();
+        let renamed: Vec<Memo> = memos
+            .iter()
+            .filter(|m| m.id != id && changed.contains(&m.id))
+            .cloned()
+            .collect();
 
-            crate::memo::save_memos(&memos)?;
+        crate::memo::save_memos(&memos)?;
+        memo_index.upsert(&memo);
+        for other in &renamed {
+            memo_index.upsert(other);
         }
 
-        memos.clone()
+        (memo, renamed)
     };
 
-    Ok(memos)
+    let updated = std::iter::once(memo.clone()).chain(renamed);
+    events::emit(&app, updated.map(ChangeEvent::MemoUpdated));
+    Ok(memo)
 }
 
 #[tauri::command]
-pub async fn delete_memo(state: State<'_, AppState>, id: String) -> Result<Vec<Memo>, String> {
-    let memos = {
+pub async fn delete_memo(
+    state: State<'_, AppState>,
+    app: AppHandle,
+    id: String,
+) -> Result<String, String> {
+    {
         let mut memos = state.memos.lock().unwrap();
         memos.retain(|m| m.id != id);
 
         crate::memo::save_memos(&memos)?;
+        state.memo_index.lock().unwrap().remove(&id);
+        state.memo_links.lock().unwrap().remove(&id);
+    }
+
+    let removed = [EntityRef::new(EntityKind::Memo, &id)];
+    super::link_commands::remove_links_to(&state, &app, &removed)?;
+    events::emit(&app, [ChangeEvent::MemoDeleted(id.clone())]);
+    Ok(id)
+}
 
-        memos.clone()
-    };
+/// メモの全文検索（関連度順）
+///
+/// `"..."` でフレーズ、`語*` で前方一致、`tag:名前` / `folder:名前` で絞り込む。
+#[tauri::command]
+pub fn search_memos(
+    state: State<AppState>,
+    query: String,
+    limit: Option<usize>,
+) -> Vec<MemoSearchHit> {
+    let folders = state.folders.lock().unwrap();
+    let memos = state.memos.lock().unwrap();
+    let index = state.memo_index.lock().unwrap();
+    index.search(
+        &memos,
+        &folders,
+        &MemoSearchQuery::parse(&query),
+        limit.unwrap_or(DEFAULT_SEARCH_LIMIT),
+    )
+}
 
-    Ok(memos)
+/// `id` のメモへリンクしているメモ
+#[tauri::command]
+pub fn get_backlinks(state: State<AppState>, id: String) -> Result<Vec<Backlink>, String> {
+    let memos = state.memos.lock().unwrap();
+    if !memos.iter().any(|m| m.id == id) {
+        return Err(format!("Memo {} not found", id));
+    }
+    let links = state.memo_links.lock().unwrap();
+    Ok(links.backlinks(&memos, &id))
 }
 
+/// リンク先が存在しないリンク（`memo_id` を省略した場合はすべてのメモ）
 #[tauri::command]
-pub fn search_memos(state: State<AppState>, query: String) -> Vec<Memo> {
+pub fn get_broken_links(state: State<AppState>, memo_id: Option<String>) -> Vec<BrokenLink> {
     let memos = state.memos.lock().unwrap();
-    let query_lower = query.to_lowercase();
-    memos
-        .iter()
-        .filter(|m| {
-            m.title.to_lowercase().contains(&query_lower)
-                || m.content.to_lowercase().contains(&query_lower)
-                || m.tags
-                    .iter()
-                    .any(|t| t.to_lowercase().contains(&query_lower))
-        })
-        .cloned()
-        .collect()
+    let links = state.memo_links.lock().unwrap();
+    links.broken_links(&memos, memo_id.as_deref())
 }
 
 #[tauri::command]
@@ -115,6 +195,14 @@ pub fn get_folders(state: State<AppState>) -> Vec<Folder> {
     folders.clone()
 }
 
+/// フォルダの階層（メモ数付き）
+#[tauri::command]
+pub fn get_folder_tree(state: State<AppState>) -> FolderTree {
+    let folders = state.folders.lock().unwrap();
+    let memos = state.memos.lock().unwrap();
+    folder_tree::build_tree(&folders, &memos)
+}
+
 #[tauri::command]
 pub async fn create_folder(
     state: State<'_, AppState>,
@@ -123,6 +211,7 @@ pub async fn create_folder(
 ) -> Result<Vec<Folder>, String> {
     let folders = {
         let mut folders = state.folders.lock().unwrap();
+        folder_tree::validate_parent(&folders, parent_id.as_deref())?;
         let folder = Folder::new(name, parent_id);
         folders.push(folder);
 
@@ -151,35 +240,71 @@ pub async fn update_folder(
         folders.clone()
     };
 
+    // Rename the synced directory
+    super::sync_commands::request_memo_sync(&state, SyncMessage::Rescan);
+    Ok(folders)
+}
+
+/// フォルダを別の親（`None` は最上位）の下に移動する
+#[tauri::command]
+pub async fn move_folder(
+    state: State<'_, AppState>,
+    id: String,
+    parent_id: Option<String>,
+) -> Result<Vec<Folder>, String> {
+    let folders = {
+        let mut folders = state.folders.lock().unwrap();
+        folder_tree::move_folder(&mut folders, &id, parent_id)?;
+
+        crate::memo::save_folders(&folders)?;
+
+        folders.clone()
+    };
+
+    super::sync_commands::request_memo_sync(&state, SyncMessage::Rescan);
     Ok(folders)
 }
 
+/// フォルダを削除する
+///
+/// 省略時は子フォルダを親に付け替え、フォルダ内のメモはフォルダなしにする。
 #[tauri::command]
-pub async fn delete_folder(state: State<'_, AppState>, id: String) -> Result<Vec<Folder>, String> {
-    let (folders, _updated_memos) = {
+pub async fn delete_folder(
+    state: State<'_, AppState>,
+    app: AppHandle,
+    id: String,
+    options: Option<DeleteFolderOptions>,
+) -> Result<Vec<Folder>, String> {
+    let (folders, updated_memos) = {
         let mut folders = state.folders.lock().unwrap();
         let mut memos = state.memos.lock().unwrap();
 
-        // Remove folder
-        folders.retain(|f| f.id != id);
-
-        // Remove folder_id from memos in this folder
-        let mut memos_modified = false;
-        for memo in memos.iter_mut() {
-            if memo.folder_id.as_ref() == Some(&id) {
-                memo.folder_id = None;
-                memos_modified = true;
-            }
-        }
+        let moved = folder_tree::delete_folder(
+            &mut folders,
+            &mut memos,
+            &id,
+            &options.unwrap_or_default(),
+            Utc::now(),
+        )?;
+        let updated_memos: Vec<Memo> = memos
+            .iter()
+            .filter(|m| moved.contains(&m.id))
+            .cloned()
+            .collect();
 
         crate::memo::save_folders(&folders)?;
 
-        if memos_modified {
+        if !updated_memos.is_empty() {
             crate::memo::save_memos(&memos)?;
         }
 
-        (folders.clone(), Vec::<Memo>::new())
+        (folders.clone(), updated_memos)
     };
 
+    events::emit(
+        &app,
+        updated_memos.into_iter().map(ChangeEvent::MemoUpdated),
+    );
+    super::sync_commands::request_memo_sync(&state, SyncMessage::Rescan);
     Ok(folders)
 }
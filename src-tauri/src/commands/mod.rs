@@ -1,12 +1,24 @@
 // Tauriコマンドモジュール
 
+pub mod attachment_commands;
 pub mod calendar_commands;
+pub mod link_commands;
 pub mod memo_commands;
 pub mod reading_commands;
+pub mod search_commands;
+pub mod sync_commands;
 pub mod task_commands;
+pub mod template_commands;
+pub mod vault_commands;
 
 // すべてのコマンドを再エクスポート
+pub use attachment_commands::*;
 pub use calendar_commands::*;
+pub use link_commands::*;
 pub use memo_commands::*;
 pub use reading_commands::*;
+pub use search_commands::*;
+pub use sync_commands::*;
 pub use task_commands::*;
+pub use template_commands::*;
+pub use vault_commands::*;
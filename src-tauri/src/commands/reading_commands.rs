@@ -1,104 +1,465 @@
 // 読書記録関連のTauriコマンド
 
 use chrono::{DateTime, Utc};
-use tauri::State;
-
-use crate::reading_memo::{ReadingBook, ReadingNote, ReadingSession, ReadingStatus};
+use serde::Serialize;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use tauri::{AppHandle, State};
+
+use crate::book_cover::{self, BookCover, CoverSource};
+use crate::book_metadata::{self, BookMetadataProvider};
+use crate::entity_links::{EntityKind, EntityRef};
+use crate::events::{self, BookItemChange, ChangeEvent};
+use crate::isbn;
+use crate::listing::{self, ListOptions, Page, Projected, ReadingBookSummary};
+use crate::patch::check_updated_at;
+use crate::reading_memo::{ReadingBook, ReadingBookPatch, ReadingNote, ReadingSession};
 use crate::AppState;
 
 // ========================================
 // 読書書籍関連コマンド
 // ========================================
 
+/// IDで書籍を探す
+fn find_book_mut<'a>(
+    books: &'a mut [ReadingBook],
+    id: &str,
+) -> Result<&'a mut ReadingBook, String> {
+    books
+        .iter_mut()
+        .find(|b| b.id == id)
+        .ok_or_else(|| format!("Book {} not found", id))
+}
+
+/// 書籍の一覧（`options.summary` の場合は読書メモ・読書セッションを含まない）
+#[tauri::command]
+pub fn get_reading_books(
+    state: State<AppState>,
+    options: Option<ListOptions>,
+) -> Result<Page<Projected<ReadingBook, ReadingBookSummary>>, String> {
+    let books = state.reading_books.lock().unwrap();
+    listing::list(&books, &options.unwrap_or_default())
+}
+
 #[tauri::command]
-pub fn get_reading_books(state: State<AppState>) -> Vec<ReadingBook> {
+pub fn get_reading_book(state: State<AppState>, id: String) -> Result<ReadingBook, String> {
     let books = state.reading_books.lock().unwrap();
-    books.clone()
+    books
+        .iter()
+        .find(|b| b.id == id)
+        .cloned()
+        .ok_or_else(|| format!("Book {} not found", id))
 }
 
 #[tauri::command]
 pub async fn create_reading_book(
     state: State<'_, AppState>,
+    app: AppHandle,
     title: String,
-) -> Result<Vec<ReadingBook>, String> {
-    let books = {
+) -> Result<ReadingBook, String> {
+    let book = {
         let mut books = state.reading_books.lock().unwrap();
         let book = ReadingBook::new(title);
-        books.push(book);
+        books.push(book.clone());
 
         crate::reading_memo::save_reading_books(&books)?;
 
-        books.clone()
+        book
     };
 
-    Ok(books)
+    events::emit(&app, [ChangeEvent::BookCreated(book.clone())]);
+    Ok(book)
 }
 
+/// 書籍情報を部分更新する
+///
+/// `expected_updated_at` は読み込んだ時点の `updated_at`（省略時は確認しない）。
+/// 他で更新されていた場合は `Conflict:` で始まるエラーを返す。
+/// ISBNは正規化して保存し、チェックディジットが不正な場合はエラーにする。
 #[tauri::command]
 pub async fn update_reading_book(
     state: State<'_, AppState>,
+    app: AppHandle,
     id: String,
-    title: String,
-    author: Option<String>,
-    isbn: Option<String>,
-    publisher: Option<String>,
-    published_year: Option<i32>,
-    cover_image_url: Option<String>,
-    genres: Vec<String>,
-    status: ReadingStatus,
-    start_date: Option<DateTime<Utc>>,
-    finish_date: Option<DateTime<Utc>>,
-    total_pages: Option<u32>,
-    current_page: Option<u32>,
-    rating: Option<u8>,
-    summary: String,
-    tags: Vec<String>,
-) -> Result<Vec<ReadingBook>, String> {
-    let books = {
+    mut patch: ReadingBookPatch,
+    expected_updated_at: Option<DateTime<Utc>>,
+) -> Result<ReadingBook, String> {
+    if let Some(value) = patch.isbn.take() {
+        patch.isbn = Some(match value.filter(|v| !v.trim().is_empty()) {
+            Some(value) => Some(isbn::normalize_isbn(&value)?),
+            None => None,
+        });
+    }
+
+    let book = {
         let mut books = state.reading_books.lock().unwrap();
-        if let Some(book) = books.iter_mut().find(|b| b.id == id) {
-            book.update(
-                title,
-                author,
-                isbn,
-                publisher,
-                published_year,
-                cover_image_url,
-                genres,
-                status,
-                start_date,
-                finish_date,
-                total_pages,
-                current_page,
-                rating,
-                summary,
-                tags,
-            );
+        let book = find_book_mut(&mut books, &id)?;
+        check_updated_at("Book", &id, Some(book.updated_at), expected_updated_at)?;
+        book.apply_patch(patch, Utc::now());
+        let book = book.clone();
 
-            crate::reading_memo::save_reading_books(&books)?;
-        }
+        crate::reading_memo::save_reading_books(&books)?;
 
-        books.clone()
+        book
     };
 
-    Ok(books)
+    events::emit(&app, [ChangeEvent::BookUpdated(book.clone())]);
+    Ok(book)
 }
 
 #[tauri::command]
 pub async fn delete_reading_book(
     state: State<'_, AppState>,
+    app: AppHandle,
     id: String,
-) -> Result<Vec<ReadingBook>, String> {
-    let books = {
+) -> Result<String, String> {
+    let removed = {
         let mut books = state.reading_books.lock().unwrap();
+        // Links to the book's notes go away with the book
+        let mut removed = vec![EntityRef::new(EntityKind::Book, &id)];
+        let mut cover = None;
+        if let Some(book) = books.iter().find(|b| b.id == id) {
+            removed.extend(
+                book.notes
+                    .iter()
+                    .map(|n| EntityRef::new(EntityKind::ReadingNote, &n.id)),
+            );
+            cover = book.cover.clone();
+        }
         books.retain(|b| b.id != id);
 
         crate::reading_memo::save_reading_books(&books)?;
+        if let Some(cover) = cover {
+            book_cover::remove_cover_if_unused(&book_cover::get_covers_dir(), &books, &cover);
+        }
+        removed
+    };
+
+    super::link_commands::remove_links_to(&state, &app, &removed)?;
+    events::emit(&app, [ChangeEvent::BookDeleted(id.clone())]);
+    Ok(id)
+}
+
+// ========================================
+// 表紙画像関連コマンド
+// ========================================
+
+/// 表紙画像をダウンロードした結果
+#[derive(Debug, Clone, Default, Serialize, PartialEq)]
+pub struct BookCoverCacheReport {
+    /// 表紙画像を保存した書籍のID
+    pub cached: Vec<String>,
+    /// ダウンロードできなかった書籍のIDとエラー
+    pub failed: Vec<(String, String)>,
+}
+
+/// 保存した表紙画像を書籍に設定する（使われなくなった前の画像は削除する）
+fn apply_cover(
+    state: &AppState,
+    book_id: &str,
+    cover: Option<BookCover>,
+    cover_image_url: Option<String>,
+) -> Result<ReadingBook, String> {
+    let mut books = state.reading_books.lock().unwrap();
+    let book = find_book_mut(&mut books, book_id)?;
+    let previous = std::mem::replace(&mut book.cover, cover);
+    book.cover_image_url = cover_image_url;
+    book.updated_at = Utc::now();
+    let book = book.clone();
+
+    crate::reading_memo::save_reading_books(&books)?;
+    if let Some(previous) = previous {
+        book_cover::remove_cover_if_unused(&book_cover::get_covers_dir(), &books, &previous);
+    }
+    Ok(book)
+}
 
-        books.clone()
+/// 表紙画像の保存処理（ダウンロード・画像の変換）をロックを持たずに別スレッドで行う
+async fn run_cover_task<F>(task: F) -> Result<BookCover, String>
+where
+    F: FnOnce() -> Result<BookCover, String> + Send + 'static,
+{
+    tauri::async_runtime::spawn_blocking(task)
+        .await
+        .map_err(|e| format!("Failed to store cover image: {}", e))?
+}
+
+/// URLの画像をダウンロードして保存する（同じURLを保存済みならダウンロードしない）
+async fn fetch_cover(state: &AppState, url: &str) -> Result<BookCover, String> {
+    let dir = book_cover::get_covers_dir();
+    let cached = {
+        let books = state.reading_books.lock().unwrap();
+        book_cover::cached_cover_for_url(&books, &dir, url)
+    };
+    match cached {
+        Some(cover) => Ok(cover),
+        None => {
+            let url = url.to_string();
+            run_cover_task(move || book_cover::fetch_cover(&dir, &url)).await
+        }
+    }
+}
+
+/// 同時にダウンロードする表紙画像の数
+const CONCURRENT_COVER_DOWNLOADS: usize = 4;
+
+/// 複数のURLの画像を並行してダウンロードし、保存する（同じURLは1回だけ）
+///
+/// 結果はURLごとに、最初に現れた順で返す。
+pub(crate) async fn download_covers(
+    dir: PathBuf,
+    urls: Vec<String>,
+) -> Vec<(String, Result<BookCover, String>)> {
+    let mut unique: Vec<String> = Vec::new();
+    for url in urls {
+        if !unique.contains(&url) {
+            unique.push(url);
+        }
+    }
+
+    let mut results = Vec::new();
+    for batch in unique.chunks(CONCURRENT_COVER_DOWNLOADS) {
+        // Spawn the whole batch before awaiting so the downloads overlap
+        let tasks: Vec<_> = batch
+            .iter()
+            .map(|url| {
+                let (dir, url) = (dir.clone(), url.clone());
+                tauri::async_runtime::spawn_blocking(move || book_cover::fetch_cover(&dir, &url))
+            })
+            .collect();
+        for (url, task) in batch.iter().zip(tasks) {
+            let result = task
+                .await
+                .map_err(|e| format!("Failed to store cover image: {}", e))
+                .and_then(|r| r);
+            results.push((url.clone(), result));
+        }
+    }
+    results
+}
+
+/// 表紙画像のディレクトリ（フロントエンドで `convertFileSrc` に渡すパスの基点）
+#[tauri::command]
+pub fn get_book_covers_dir() -> String {
+    book_cover::get_covers_dir().display().to_string()
+}
+
+/// 表紙画像を設定する（画像はデータディレクトリに保存し、サムネイルも作る）
+///
+/// URLの場合は `cover_image_url` にも設定する。
+/// ファイル・バイト列の場合は `cover_image_url` を消す（ダウンロードで上書きしないため）。
+#[tauri::command]
+pub async fn set_book_cover(
+    state: State<'_, AppState>,
+    app: AppHandle,
+    book_id: String,
+    source: CoverSource,
+) -> Result<ReadingBook, String> {
+    {
+        let books = state.reading_books.lock().unwrap();
+        if !books.iter().any(|b| b.id == book_id) {
+            return Err(format!("Book {} not found", book_id));
+        }
+    }
+
+    let dir = book_cover::get_covers_dir();
+    let (cover, cover_image_url) = match source {
+        CoverSource::File { path } => {
+            let cover = run_cover_task(move || {
+                let bytes = book_cover::read_cover_file(&PathBuf::from(path))?;
+                book_cover::store_cover(&dir, &bytes, None, Utc::now())
+            })
+            .await?;
+            (cover, None)
+        }
+        CoverSource::Bytes { bytes } => {
+            let cover =
+                run_cover_task(move || book_cover::store_cover(&dir, &bytes, None, Utc::now()))
+                    .await?;
+            (cover, None)
+        }
+        CoverSource::Url { url } => {
+            let url = url.trim().to_string();
+            (fetch_cover(&state, &url).await?, Some(url))
+        }
     };
 
-    Ok(books)
+    let book = apply_cover(&state, &book_id, Some(cover), cover_image_url)?;
+    events::emit(&app, [ChangeEvent::BookUpdated(book.clone())]);
+    Ok(book)
+}
+
+/// 表紙画像を外す（`cover_image_url` も消す）
+#[tauri::command]
+pub async fn remove_book_cover(
+    state: State<'_, AppState>,
+    app: AppHandle,
+    book_id: String,
+) -> Result<ReadingBook, String> {
+    let book = apply_cover(&state, &book_id, None, None)?;
+    events::emit(&app, [ChangeEvent::BookUpdated(book.clone())]);
+    Ok(book)
+}
+
+/// `cover_image_url` がhttp(s)で、まだ保存していない表紙画像をダウンロードする
+///
+/// オフラインの間は失敗するだけなので、ネットワークにつながったときに再度呼べばよい。
+#[tauri::command]
+pub async fn cache_book_covers(
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<BookCoverCacheReport, String> {
+    let dir = book_cover::get_covers_dir();
+    let (pending, cached) = {
+        let books = state.reading_books.lock().unwrap();
+        let pending = book_cover::uncached_cover_urls(&books);
+        let cached: Vec<(String, BookCover)> = pending
+            .iter()
+            .filter_map(|(_, url)| {
+                let cover = book_cover::cached_cover_for_url(&books, &dir, url)?;
+                Some((url.clone(), cover))
+            })
+            .collect();
+        (pending, cached)
+    };
+
+    let urls = pending
+        .iter()
+        .map(|(_, url)| url.clone())
+        .filter(|url| !cached.iter().any(|(u, _)| u == url))
+        .collect();
+    let mut covers: Vec<(String, Result<BookCover, String>)> = cached
+        .into_iter()
+        .map(|(url, cover)| (url, Ok(cover)))
+        .collect();
+    covers.extend(download_covers(dir.clone(), urls).await);
+
+    let mut report = BookCoverCacheReport::default();
+    let mut changes = Vec::new();
+    let mut books = state.reading_books.lock().unwrap();
+    let mut replaced = Vec::new();
+    let now = Utc::now();
+    for (book_id, url) in pending {
+        let cover = match covers.iter().find(|(u, _)| *u == url).map(|(_, r)| r) {
+            Some(Ok(cover)) => cover.clone(),
+            Some(Err(e)) => {
+                report.failed.push((book_id, e.clone()));
+                continue;
+            }
+            None => continue,
+        };
+        // Skip books that were removed or whose URL changed while downloading
+        let Some(book) = books
+            .iter_mut()
+            .find(|b| b.id == book_id && b.cover_image_url.as_deref() == Some(url.as_str()))
+        else {
+            continue;
+        };
+        replaced.extend(book.cover.replace(cover));
+        book.updated_at = now;
+        changes.push(ChangeEvent::BookUpdated(book.clone()));
+        report.cached.push(book_id);
+    }
+    if !changes.is_empty() {
+        crate::reading_memo::save_reading_books(&books)?;
+        for previous in &replaced {
+            book_cover::remove_cover_if_unused(&dir, &books, previous);
+        }
+    }
+    drop(books);
+
+    events::emit(&app, changes);
+    Ok(report)
+}
+
+// ========================================
+// 書誌情報関連コマンド
+// ========================================
+
+/// ISBNを検証し、ハイフン等を除いた形にする
+#[tauri::command]
+pub fn validate_isbn(isbn: String) -> Result<String, String> {
+    isbn::normalize_isbn(&isbn)
+}
+
+/// ISBNで書誌情報を検索し、`books` の書籍に反映する（`lookup_book_by_isbn` の本体）
+///
+/// 検索は `provider` を使い、ロックを持たずに別スレッドで行う。保存は呼び出し側で行う。
+/// 反映後の書籍と、新たに設定した表紙画像のURLを返す。
+pub(crate) async fn lookup_book_metadata(
+    books: &Mutex<Vec<ReadingBook>>,
+    provider: Arc<dyn BookMetadataProvider>,
+    book_id: &str,
+    isbn: Option<String>,
+    overwrite: bool,
+) -> Result<(ReadingBook, Option<String>), String> {
+    let isbn = {
+        let books = books.lock().unwrap();
+        let book = books
+            .iter()
+            .find(|b| b.id == book_id)
+            .ok_or_else(|| format!("Book {} not found", book_id))?;
+        isbn.or_else(|| book.isbn.clone())
+            .ok_or_else(|| format!("Book {} has no ISBN", book_id))?
+    };
+    let isbn = isbn::normalize_isbn(&isbn)?;
+
+    let query = isbn::to_isbn13(&isbn);
+    let metadata = tauri::async_runtime::spawn_blocking(move || provider.lookup_isbn(&query))
+        .await
+        .map_err(|e| format!("ISBN lookup failed: {}", e))??
+        .ok_or_else(|| format!("No book found for ISBN {}", isbn))?;
+
+    let mut books = books.lock().unwrap();
+    let book = find_book_mut(&mut books, book_id)?;
+    let mut patch = book_metadata::metadata_patch(book, &metadata, overwrite);
+    patch.isbn = Some(Some(isbn));
+    let cover_url = metadata
+        .cover_url
+        .clone()
+        .filter(|_| overwrite || (book.cover.is_none() && book.cover_image_url.is_none()));
+    if let Some(url) = &cover_url {
+        patch.cover_image_url = Some(Some(url.clone()));
+    }
+    book.apply_patch(patch, Utc::now());
+    Ok((book.clone(), cover_url))
+}
+
+/// ISBNで書誌情報を検索し、著者・出版社・出版年・ページ数・表紙画像を書籍に反映する
+///
+/// `isbn` を省略した場合は書籍のISBNを使う。`overwrite` でなければ空の項目だけ埋める。
+/// 表紙画像のダウンロードに失敗しても `cover_image_url` は設定し、
+/// 後で `cache_book_covers` で保存できるようにする。
+#[tauri::command]
+pub async fn lookup_book_by_isbn(
+    state: State<'_, AppState>,
+    app: AppHandle,
+    book_id: String,
+    isbn: Option<String>,
+    overwrite: Option<bool>,
+) -> Result<ReadingBook, String> {
+    let (mut book, cover_url) = lookup_book_metadata(
+        &state.reading_books,
+        state.book_metadata.clone(),
+        &book_id,
+        isbn,
+        overwrite.unwrap_or(false),
+    )
+    .await?;
+    {
+        let books = state.reading_books.lock().unwrap();
+        crate::reading_memo::save_reading_books(&books)?;
+    }
+
+    // A failed download leaves the URL for cache_book_covers to retry
+    if let Some(url) = cover_url {
+        if let Ok(cover) = fetch_cover(&state, &url).await {
+            book = apply_cover(&state, &book_id, Some(cover), Some(url))?;
+        }
+    }
+
+    events::emit(&app, [ChangeEvent::BookUpdated(book.clone())]);
+    Ok(book)
 }
 
 // ========================================
@@ -108,74 +469,98 @@ pub async fn delete_reading_book(
 #[tauri::command]
 pub async fn add_reading_note(
     state: State<'_, AppState>,
+    app: AppHandle,
     book_id: String,
     page_number: Option<u32>,
     quote: Option<String>,
     comment: String,
-) -> Result<Vec<ReadingBook>, String> {
-    let books = {
+) -> Result<BookItemChange<ReadingNote>, String> {
+    let change = {
         let mut books = state.reading_books.lock().unwrap();
-        if let Some(book) = books.iter_mut().find(|b| b.id == book_id) {
-            let note = ReadingNote::new(page_number, quote, comment);
-            book.notes.push(note);
-            book.updated_at = Utc::now();
+        let book = find_book_mut(&mut books, &book_id)?;
+        let note = ReadingNote::new(page_number, quote, comment);
+        book.notes.push(note.clone());
+        book.updated_at = Utc::now();
+        let book_updated_at = book.updated_at;
 
-            crate::reading_memo::save_reading_books(&books)?;
-        }
+        crate::reading_memo::save_reading_books(&books)?;
 
-        books.clone()
+        BookItemChange {
+            book_id,
+            book_updated_at,
+            item: note,
+        }
     };
 
-    Ok(books)
+    events::emit(&app, [ChangeEvent::BookNoteAdded(change.clone())]);
+    Ok(change)
 }
 
 #[tauri::command]
 pub async fn update_reading_note(
     state: State<'_, AppState>,
+    app: AppHandle,
     book_id: String,
     note_id: String,
     page_number: Option<u32>,
     quote: Option<String>,
     comment: String,
-) -> Result<Vec<ReadingBook>, String> {
-    let books = {
+) -> Result<BookItemChange<ReadingNote>, String> {
+    let change = {
         let mut books = state.reading_books.lock().unwrap();
-        if let Some(book) = books.iter_mut().find(|b| b.id == book_id) {
-            if let Some(note) = book.notes.iter_mut().find(|n| n.id == note_id) {
-                note.page_number = page_number;
-                note.quote = quote;
-                note.comment = comment;
-                book.updated_at = Utc::now();
-
-                crate::reading_memo::save_reading_books(&books)?;
-            }
-        }
+        let book = find_book_mut(&mut books, &book_id)?;
+        let note = book
+            .notes
+            .iter_mut()
+            .find(|n| n.id == note_id)
+            .ok_or_else(|| format!("Note {} not found", note_id))?;
+        note.page_number = page_number;
+        note.quote = quote;
+        note.comment = comment;
+        let note = note.clone();
+        book.updated_at = Utc::now();
+        let book_updated_at = book.updated_at;
+
+        crate::reading_memo::save_reading_books(&books)?;
 
-        books.clone()
+        BookItemChange {
+            book_id,
+            book_updated_at,
+            item: note,
+        }
     };
 
-    Ok(books)
+    events::emit(&app, [ChangeEvent::BookNoteUpdated(change.clone())]);
+    Ok(change)
 }
 
 #[tauri::command]
 pub async fn delete_reading_note(
     state: State<'_, AppState>,
+    app: AppHandle,
     book_id: String,
     note_id: String,
-) -> Result<Vec<ReadingBook>, String> {
-    let books = {
+) -> Result<BookItemChange<String>, String> {
+    let change = {
         let mut books = state.reading_books.lock().unwrap();
-        if let Some(book) = books.iter_mut().find(|b| b.id == book_id) {
-            book.notes.retain(|n| n.id != note_id);
-            book.updated_at = Utc::now();
+        let book = find_book_mut(&mut books, &book_id)?;
+        book.notes.retain(|n| n.id != note_id);
+        book.updated_at = Utc::now();
+        let book_updated_at = book.updated_at;
 
-            crate::reading_memo::save_reading_books(&books)?;
-        }
+        crate::reading_memo::save_reading_books(&books)?;
 
-        books.clone()
+        BookItemChange {
+            book_id,
+            book_updated_at,
+            item: note_id,
+        }
     };
 
-    Ok(books)
+    let removed = [EntityRef::new(EntityKind::ReadingNote, &change.item)];
+    super::link_commands::remove_links_to(&state, &app, &removed)?;
+    events::emit(&app, [ChangeEvent::BookNoteDeleted(change.clone())]);
+    Ok(change)
 }
 
 // ========================================
@@ -185,6 +570,7 @@ pub async fn delete_reading_note(
 #[tauri::command]
 pub async fn add_reading_session(
     state: State<'_, AppState>,
+    app: AppHandle,
     book_id: String,
     session_date: DateTime<Utc>,
     start_page: Option<u32>,
@@ -192,33 +578,39 @@ pub async fn add_reading_session(
     pages_read: u32,
     duration_minutes: Option<u32>,
     memo: Option<String>,
-) -> Result<Vec<ReadingBook>, String> {
-    let books = {
+) -> Result<BookItemChange<ReadingSession>, String> {
+    let change = {
         let mut books = state.reading_books.lock().unwrap();
-        if let Some(book) = books.iter_mut().find(|b| b.id == book_id) {
-            let session = ReadingSession::new(
-                session_date,
-                start_page,
-                end_page,
-                pages_read,
-                duration_minutes,
-                memo,
-            );
-            book.reading_sessions.push(session);
-            book.updated_at = Utc::now();
+        let book = find_book_mut(&mut books, &book_id)?;
+        let session = ReadingSession::new(
+            session_date,
+            start_page,
+            end_page,
+            pages_read,
+            duration_minutes,
+            memo,
+        );
+        book.reading_sessions.push(session.clone());
+        book.updated_at = Utc::now();
+        let book_updated_at = book.updated_at;
 
-            crate::reading_memo::save_reading_books(&books)?;
-        }
+        crate::reading_memo::save_reading_books(&books)?;
 
-        books.clone()
+        BookItemChange {
+            book_id,
+            book_updated_at,
+            item: session,
+        }
     };
 
-    Ok(books)
+    events::emit(&app, [ChangeEvent::BookSessionAdded(change.clone())]);
+    Ok(change)
 }
 
 #[tauri::command]
 pub async fn update_reading_session(
     state: State<'_, AppState>,
+    app: AppHandle,
     book_id: String,
     session_id: String,
     session_date: DateTime<Utc>,
@@ -227,50 +619,61 @@ pub async fn update_reading_session(
     pages_read: u32,
     duration_minutes: Option<u32>,
     memo: Option<String>,
-) -> Result<Vec<ReadingBook>, String> {
-    let books = {
+) -> Result<BookItemChange<ReadingSession>, String> {
+    let change = {
         let mut books = state.reading_books.lock().unwrap();
-        if let Some(book) = books.iter_mut().find(|b| b.id == book_id) {
-            if let Some(session) = book
-                .reading_sessions
-                .iter_mut()
-                .find(|s| s.id == session_id)
-            {
-                session.session_date = session_date;
-                session.start_page = start_page;
-                session.end_page = end_page;
-                session.pages_read = pages_read;
-                session.duration_minutes = duration_minutes;
-                session.memo = memo;
-                book.updated_at = Utc::now();
-
-                crate::reading_memo::save_reading_books(&books)?;
-            }
-        }
+        let book = find_book_mut(&mut books, &book_id)?;
+        let session = book
+            .reading_sessions
+            .iter_mut()
+            .find(|s| s.id == session_id)
+            .ok_or_else(|| format!("Session {} not found", session_id))?;
+        session.session_date = session_date;
+        session.start_page = start_page;
+        session.end_page = end_page;
+        session.pages_read = pages_read;
+        session.duration_minutes = duration_minutes;
+        session.memo = memo;
+        let session = session.clone();
+        book.updated_at = Utc::now();
+        let book_updated_at = book.updated_at;
 
-        books.clone()
+        crate::reading_memo::save_reading_books(&books)?;
+
+        BookItemChange {
+            book_id,
+            book_updated_at,
+            item: session,
+        }
     };
 
-    Ok(books)
+    events::emit(&app, [ChangeEvent::BookSessionUpdated(change.clone())]);
+    Ok(change)
 }
 
 #[tauri::command]
 pub async fn delete_reading_session(
     state: State<'_, AppState>,
+    app: AppHandle,
     book_id: String,
     session_id: String,
-) -> Result<Vec<ReadingBook>, String> {
-    let books = {
+) -> Result<BookItemChange<String>, String> {
+    let change = {
         let mut books = state.reading_books.lock().unwrap();
-        if let Some(book) = books.iter_mut().find(|b| b.id == book_id) {
-            book.reading_sessions.retain(|s| s.id != session_id);
-            book.updated_at = Utc::now();
+        let book = find_book_mut(&mut books, &book_id)?;
+        book.reading_sessions.retain(|s| s.id != session_id);
+        book.updated_at = Utc::now();
+        let book_updated_at = book.updated_at;
 
-            crate::reading_memo::save_reading_books(&books)?;
-        }
+        crate::reading_memo::save_reading_books(&books)?;
 
-        books.clone()
+        BookItemChange {
+            book_id,
+            book_updated_at,
+            item: session_id,
+        }
     };
 
-    Ok(books)
+    events::emit(&app, [ChangeEvent::BookSessionDeleted(change.clone())]);
+    Ok(change)
 }
@@ -0,0 +1,35 @@
+// 横断検索のTauriコマンド
+
+use tauri::State;
+
+use crate::global_search::{self, GlobalSearchHit, SearchSources, DEFAULT_GLOBAL_SEARCH_LIMIT};
+use crate::AppState;
+
+/// タスク・サブタスク・メモ・書籍・読書メモ・予定をまとめて検索する（コマンドパレット用）
+#[tauri::command]
+pub fn global_search(
+    state: State<AppState>,
+    query: String,
+    limit: Option<usize>,
+) -> Vec<GlobalSearchHit> {
+    let tasks = state.tasks.lock().unwrap();
+    let folders = state.folders.lock().unwrap();
+    let memos = state.memos.lock().unwrap();
+    let memo_index = state.memo_index.lock().unwrap();
+    let books = state.reading_books.lock().unwrap();
+    let events = state.calendar_events.lock().unwrap();
+
+    let sources = SearchSources {
+        tasks: &tasks,
+        memos: &memos,
+        memo_index: &memo_index,
+        folders: &folders,
+        books: &books,
+        events: &events,
+    };
+    global_search::search(
+        &sources,
+        &query,
+        limit.unwrap_or(DEFAULT_GLOBAL_SEARCH_LIMIT),
+    )
+}
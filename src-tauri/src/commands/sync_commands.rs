@@ -0,0 +1,321 @@
+// メモとディスク上のディレクトリの同期関連のTauriコマンド
+
+use notify::{RecursiveMode, Watcher};
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver};
+use std::thread;
+use std::time::Duration;
+use tauri::{AppHandle, Listener, Manager, State};
+
+use crate::entity_links::{EntityKind, EntityRef};
+use crate::events::{self, ChangeEvent};
+use crate::memo::Memo;
+use crate::memo_sync::{self, MemoSync, SyncChanges, SyncConflict, SyncMessage, SyncSide};
+use crate::AppState;
+
+/// エディタの保存は複数回の書き込みになるため、まとめて処理するまでの待ち時間
+const SYNC_DEBOUNCE: Duration = Duration::from_millis(300);
+
+/// 同期の状態
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct MemoSyncStatus {
+    pub enabled: bool,
+    pub directory: Option<String>,
+    /// ディレクトリを監視しているか
+    pub watching: bool,
+    pub conflicts: Vec<SyncConflict>,
+    pub last_error: Option<String>,
+}
+
+fn status(sync: &MemoSync) -> MemoSyncStatus {
+    MemoSyncStatus {
+        enabled: sync.store.settings.enabled,
+        directory: sync.store.settings.directory.clone(),
+        watching: sync.watcher.is_some(),
+        conflicts: sync.store.conflicts.clone(),
+        last_error: sync.last_error.clone(),
+    }
+}
+
+/// 同期中であればワーカーに依頼を送る
+pub(crate) fn request_memo_sync(state: &AppState, message: SyncMessage) {
+    let sync = state.memo_sync.lock().unwrap();
+    if let Some(sender) = &sync.sender {
+        let _ = sender.send(message);
+    }
+}
+
+/// 起動時の処理（メモの変更イベントの購読と、有効な場合は同期の開始）
+pub(crate) fn init_memo_sync(app: &AppHandle) {
+    for (name, deleted) in [
+        ("memo-created", false),
+        ("memo-updated", false),
+        ("memo-deleted", true),
+    ] {
+        let handle = app.clone();
+        app.listen_any(name, move |event| {
+            let payload: Value = serde_json::from_str(event.payload()).unwrap_or_default();
+            let Some(id) = payload.get("id").and_then(Value::as_str) else {
+                return;
+            };
+            let message = if deleted {
+                SyncMessage::MemoDeleted(id.to_string())
+            } else {
+                SyncMessage::MemoChanged(id.to_string())
+            };
+            request_memo_sync(&handle.state::<AppState>(), message);
+        });
+    }
+
+    let state = app.state::<AppState>();
+    let mut sync = state.memo_sync.lock().unwrap();
+    if let Some(directory) = sync.store.settings.directory.clone() {
+        if sync.store.settings.enabled {
+            if let Err(e) = start_watching(app, &mut sync, PathBuf::from(directory)) {
+                sync.last_error = Some(e);
+            }
+        }
+    }
+}
+
+fn start_watching(app: &AppHandle, sync: &mut MemoSync, root: PathBuf) -> Result<(), String> {
+    std::fs::create_dir_all(&root)
+        .map_err(|e| format!("Failed to create {}: {}", root.display(), e))?;
+
+    let (sender, receiver) = mpsc::channel();
+    let watcher_sender = sender.clone();
+    let mut watcher = notify::recommended_watcher(move |result: notify::Result<notify::Event>| {
+        if let Ok(event) = result {
+            if !event.kind.is_access() {
+                let _ = watcher_sender.send(SyncMessage::Paths(event.paths));
+            }
+        }
+    })
+    .map_err(|e| format!("Failed to watch {}: {}", root.display(), e))?;
+    watcher
+        .watch(&root, RecursiveMode::Recursive)
+        .map_err(|e| format!("Failed to watch {}: {}", root.display(), e))?;
+
+    let _ = sender.send(SyncMessage::Rescan);
+    let handle = app.clone();
+    thread::spawn(move || run_worker(handle, receiver));
+
+    sync.watcher = Some(watcher);
+    sync.sender = Some(sender);
+    sync.last_error = None;
+    Ok(())
+}
+
+/// 監視を止める（送信側がすべて破棄されるとワーカーも終わる）
+fn stop_watching(sync: &mut MemoSync) {
+    sync.watcher = None;
+    sync.sender = None;
+}
+
+fn run_worker(app: AppHandle, receiver: Receiver<SyncMessage>) {
+    while let Ok(message) = receiver.recv() {
+        thread::sleep(SYNC_DEBOUNCE);
+        let mut messages = vec![message];
+        messages.extend(receiver.try_iter());
+
+        let result = process(&app, messages);
+        let state = app.state::<AppState>();
+        state.memo_sync.lock().unwrap().last_error = result.err();
+    }
+}
+
+/// 依頼をまとめて処理し、メモの変更を保存・通知する
+fn process(app: &AppHandle, messages: Vec<SyncMessage>) -> Result<(), String> {
+    let state = app.state::<AppState>();
+    let (changed, deleted, result) = {
+        let mut folders = state.folders.lock().unwrap();
+        let mut memos = state.memos.lock().unwrap();
+        let mut memo_index = state.memo_index.lock().unwrap();
+        let mut memo_links = state.memo_links.lock().unwrap();
+        let mut sync = state.memo_sync.lock().unwrap();
+        let Some(root) = sync.store.settings.directory.clone().map(PathBuf::from) else {
+            return Ok(());
+        };
+
+        let store = &mut sync.store;
+        let mut changes = SyncChanges::default();
+        let mut result = Ok(());
+        for message in messages {
+            let step = match message {
+                SyncMessage::Paths(paths) => {
+                    apply_paths(store, &root, &paths, &mut memos, &mut folders, &mut changes)
+                }
+                SyncMessage::MemoChanged(id) => match memos.iter().find(|m| m.id == id) {
+                    Some(memo) => memo_sync::write_memo(store, &root, memo, &folders).map(|_| ()),
+                    None => Ok(()),
+                },
+                SyncMessage::MemoDeleted(id) => {
+                    memo_sync::remove_memo_file(store, &root, &id);
+                    Ok(())
+                }
+                SyncMessage::Rescan => {
+                    memo_sync::reconcile(store, &root, &mut memos, &mut folders, &mut changes)
+                }
+                SyncMessage::Resolve(id, keep) => memo_sync::resolve_conflict(
+                    store,
+                    &root,
+                    &id,
+                    keep,
+                    &mut memos,
+                    &mut folders,
+                    &mut changes,
+                ),
+            };
+            // Keep going so one unreadable file does not block the rest
+            if let Err(e) = step {
+                result = Err(e);
+            }
+        }
+
+        if changes.folders_changed {
+            crate::memo::save_folders(&folders)?;
+        }
+        let changed: Vec<(Memo, bool)> = memos
+            .iter()
+            .filter_map(|m| {
+                if changes.created.contains(&m.id) {
+                    Some((m.clone(), true))
+                } else if changes.updated.contains(&m.id) {
+                    Some((m.clone(), false))
+                } else {
+                    None
+                }
+            })
+            .collect();
+        if !changed.is_empty() || !changes.deleted.is_empty() {
+            crate::memo::save_memos(&memos)?;
+        }
+        for (memo, _) in &changed {
+            memo_index.upsert(memo);
+            memo_links.update(memo);
+        }
+        for id in &changes.deleted {
+            memo_index.remove(id);
+            memo_links.remove(id);
+        }
+        memo_sync::save_memo_sync(store)?;
+
+        (changed, changes.deleted, result)
+    };
+
+    let removed: Vec<EntityRef> = deleted
+        .iter()
+        .map(|id| EntityRef::new(EntityKind::Memo, id))
+        .collect();
+    super::link_commands::remove_links_to(&state, app, &removed)?;
+    events::emit(
+        app,
+        changed
+            .into_iter()
+            .map(|(memo, created)| {
+                if created {
+                    ChangeEvent::MemoCreated(memo)
+                } else {
+                    ChangeEvent::MemoUpdated(memo)
+                }
+            })
+            .chain(deleted.into_iter().map(ChangeEvent::MemoDeleted)),
+    );
+    result
+}
+
+/// ディスク上で変更されたパスを反映する（リネームで消えたパスより新しいパスを先に処理する）
+fn apply_paths(
+    store: &mut memo_sync::MemoSyncStore,
+    root: &Path,
+    paths: &[PathBuf],
+    memos: &mut Vec<Memo>,
+    folders: &mut Vec<crate::memo::Folder>,
+    changes: &mut SyncChanges,
+) -> Result<(), String> {
+    let mut seen = HashSet::new();
+    let mut relatives: Vec<(bool, String)> = paths
+        .iter()
+        .filter_map(|path| {
+            let relative = path.strip_prefix(root).ok()?;
+            if !memo_sync::is_sync_target(relative) {
+                return None;
+            }
+            let relative = memo_sync::to_relative(relative);
+            seen.insert(relative.clone())
+                .then(|| (!path.is_file(), relative))
+        })
+        .collect();
+    relatives.sort_by_key(|(missing, _)| *missing);
+
+    for (_, relative) in relatives {
+        memo_sync::apply_file(store, root, &relative, memos, folders, changes)?;
+    }
+    Ok(())
+}
+
+#[tauri::command]
+pub fn get_memo_sync_status(state: State<AppState>) -> MemoSyncStatus {
+    let sync = state.memo_sync.lock().unwrap();
+    status(&sync)
+}
+
+/// `directory` との同期を始める（アプリの次回起動時も続ける）
+///
+/// 最初にディレクトリ全体を照合し、以降はファイルの変更を監視してメモに反映する。
+/// アプリでのメモの変更は `.md` ファイルに書き出す。
+#[tauri::command]
+pub async fn start_memo_sync(
+    state: State<'_, AppState>,
+    app: AppHandle,
+    directory: String,
+) -> Result<MemoSyncStatus, String> {
+    let directory = directory.trim().to_string();
+    if directory.is_empty() {
+        return Err("Sync directory must not be empty".to_string());
+    }
+
+    let mut sync = state.memo_sync.lock().unwrap();
+    stop_watching(&mut sync);
+    // Paths recorded for another directory no longer apply
+    if sync.store.settings.directory.as_deref() != Some(directory.as_str()) {
+        sync.store.entries.clear();
+        sync.store.conflicts.clear();
+    }
+    sync.store.settings.directory = Some(directory.clone());
+    sync.store.settings.enabled = true;
+    memo_sync::save_memo_sync(&sync.store)?;
+
+    start_watching(&app, &mut sync, PathBuf::from(directory))?;
+    Ok(status(&sync))
+}
+
+/// 同期を止める（ディレクトリのファイルはそのまま残す）
+#[tauri::command]
+pub async fn stop_memo_sync(state: State<'_, AppState>) -> Result<MemoSyncStatus, String> {
+    let mut sync = state.memo_sync.lock().unwrap();
+    stop_watching(&mut sync);
+    sync.store.settings.enabled = false;
+    memo_sync::save_memo_sync(&sync.store)?;
+    Ok(status(&sync))
+}
+
+/// 競合を解決する（`keep` が `app` ならメモで、`file` ならファイルの内容で上書きする）
+#[tauri::command]
+pub async fn resolve_memo_sync_conflict(
+    state: State<'_, AppState>,
+    app: AppHandle,
+    memo_id: String,
+    keep: SyncSide,
+) -> Result<MemoSyncStatus, String> {
+    if !state.memo_sync.lock().unwrap().store.has_conflict(&memo_id) {
+        return Err(format!("No sync conflict for memo {}", memo_id));
+    }
+    process(&app, vec![SyncMessage::Resolve(memo_id, keep)])?;
+
+    let sync = state.memo_sync.lock().unwrap();
+    Ok(status(&sync))
+}
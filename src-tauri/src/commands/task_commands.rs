@@ -1,142 +1,700 @@
 // タスク関連のTauriコマンド
 
-use chrono::{Local, NaiveDate, NaiveDateTime, TimeZone};
-use tauri::State;
-
+use chrono::{DateTime, Local, Utc};
+use tauri::{AppHandle, State};
+
+use crate::bulk::{self, BulkTaskPatch};
+use crate::dependency;
+use crate::events::{self, ChangeEvent, TaskChanges};
+use crate::group::{self, TaskGroup};
+use crate::listing::{self, ListOptions, Page, Projected, TaskSummary};
 use crate::mail::send_email;
+use crate::ordering;
+use crate::quick_add::parse_quick_add;
+use crate::schedule::{self, ProjectSchedule};
 use crate::settings::MailSettings;
-use crate::task::{Subtask, Task};
+use crate::subtask;
+use crate::task::{
+    apply_task_patch, normalize_tags, touch_task, touch_tasks, Subtask, Task, TaskPatch,
+    TaskPriority,
+};
+use crate::task_date::{validate_task_dates, TaskDate};
+use crate::task_query::{self, SavedView, TaskQuery};
+use crate::time_tracking::{self, GroupTimeReport, TimeEntry};
+use crate::workflow::{self, Board, WorkflowState};
 use crate::AppState;
 
 // ========================================
 // タスク関連コマンド
 // ========================================
 
+/// 変更後のタスクを返す（コマンドの戻り値・イベント用）
+fn find_task(tasks: &[Task], id: i32) -> Result<Task, String> {
+    tasks
+        .iter()
+        .find(|t| t.id == id)
+        .cloned()
+        .ok_or_else(|| format!("Task {} not found", id))
+}
+
+/// タスクの一覧（`options` を省略した場合は保存順ですべて）
+#[tauri::command]
+pub fn get_tasks(
+    state: State<AppState>,
+    options: Option<ListOptions>,
+) -> Result<Page<Projected<Task, TaskSummary>>, String> {
+    let tasks = state.tasks.lock().unwrap();
+    listing::list(&tasks, &options.unwrap_or_default())
+}
+
 #[tauri::command]
-pub fn get_tasks(state: State<AppState>) -> Vec<Task> {
+pub fn get_task(state: State<AppState>, id: i32) -> Result<Task, String> {
     let tasks = state.tasks.lock().unwrap();
-    tasks.clone()
+    find_task(&tasks, id)
 }
 
 #[tauri::command]
 pub async fn add_task(
     state: State<'_, AppState>,
+    app: AppHandle,
     description: String,
     start_date: Option<String>,
-    due_date: String,
-    group: String,
+    due_date: Option<String>,
+    group_id: Option<String>,
     details: String,
     notification_minutes: Option<i32>,
     dependencies: Option<Vec<i32>>,
-) -> Result<Vec<Task>, String> {
-    let tasks = {
+    priority: Option<TaskPriority>,
+    estimated_minutes: Option<u32>,
+    tags: Option<Vec<String>>,
+) -> Result<Task, String> {
+    let start_date = TaskDate::parse_optional(start_date.as_deref())?;
+    let due_date = TaskDate::parse_optional(due_date.as_deref())?;
+    validate_task_dates(start_date.as_ref(), due_date.as_ref())?;
+
+    let task = {
+        let groups = state.groups.lock().unwrap();
         let mut tasks = state.tasks.lock().unwrap();
         let mut next_id = state.next_id.lock().unwrap();
 
+        let group_id = group::resolve_group_id(&groups, group_id)?;
+        let dependencies = dependency::validate_dependencies(&tasks, *next_id, dependencies)?;
+
         let task = Task {
             id: *next_id,
             description,
             start_date,
             due_date,
-            group,
+            group_id,
             details,
             completed: false,
             notified: false,
             notification_minutes,
             subtasks: Vec::new(),
             dependencies,
+            priority,
+            estimated_minutes,
+            time_entries: Vec::new(),
+            tags: normalize_tags(tags.unwrap_or_default()),
+            state: None,
+            state_history: Vec::new(),
+            sort_key: ordering::key_after_last(tasks.as_slice()),
+            rollup_subtasks: false,
+            updated_at: Some(Utc::now()),
         };
 
         *next_id += 1;
-        tasks.push(task);
+        tasks.push(task.clone());
 
         // Save to JSON file
         let data_file = state.data_file.lock().unwrap();
         crate::task::save_tasks(&tasks, &data_file)?;
 
-        tasks.clone()
+        task
     };
 
-    Ok(tasks)
+    events::emit(&app, [ChangeEvent::TaskCreated(task.clone())]);
+    Ok(task)
 }
 
+/// 自然言語の入力からタスクを追加する
+///
+/// 例: `"Submit report tomorrow 17:00 #Work !30m depends:12"`
+#[tauri::command]
+pub async fn quick_add_task(
+    state: State<'_, AppState>,
+    app: AppHandle,
+    text: String,
+) -> Result<Task, String> {
+    let parsed = parse_quick_add(&text, Local::now().naive_local())?;
+
+    let task = {
+        let mut groups = state.groups.lock().unwrap();
+        let mut tasks = state.tasks.lock().unwrap();
+        let mut next_id = state.next_id.lock().unwrap();
+
+        let dependencies =
+            dependency::validate_dependencies(&tasks, *next_id, parsed.dependencies)?;
+
+        // Reuse an existing group regardless of case, otherwise create it
+        let group_id = match parsed.group {
+            Some(name) => {
+                let created = group::find_by_name(&groups, &name).is_none();
+                let id = group::ensure_group(&mut groups, &name);
+                if created {
+                    let groups_file = state.groups_file.lock().unwrap();
+                    group::save_groups(&groups, &groups_file)?;
+                }
+                Some(id)
+            }
+            None => None,
+        };
+
+        let task = Task {
+            id: *next_id,
+            description: parsed.description,
+            start_date: None,
+            due_date: parsed.due_date,
+            group_id,
+            details: String::new(),
+            completed: false,
+            notified: false,
+            notification_minutes: parsed.notification_minutes,
+            subtasks: Vec::new(),
+            dependencies,
+            priority: None,
+            estimated_minutes: None,
+            time_entries: Vec::new(),
+            tags: Vec::new(),
+            state: None,
+            state_history: Vec::new(),
+            sort_key: ordering::key_after_last(tasks.as_slice()),
+            rollup_subtasks: false,
+            updated_at: Some(Utc::now()),
+        };
+
+        *next_id += 1;
+        tasks.push(task.clone());
+
+        // Save to JSON file
+        let data_file = state.data_file.lock().unwrap();
+        crate::task::save_tasks(&tasks, &data_file)?;
+
+        task
+    };
+
+    events::emit(&app, [ChangeEvent::TaskCreated(task.clone())]);
+    Ok(task)
+}
+
+/// タスクを部分更新する
+///
+/// `expected_updated_at` は読み込んだ時点のタスクの `updated_at`（省略時は確認しない）。
+/// 他で更新されていた場合は `Conflict:` で始まるエラーを返す。
 #[tauri::command]
 pub async fn update_task(
     state: State<'_, AppState>,
+    app: AppHandle,
     id: i32,
-    description: String,
-    start_date: Option<String>,
-    due_date: String,
-    group: String,
-    details: String,
-    notification_minutes: Option<i32>,
-    dependencies: Option<Vec<i32>>,
-) -> Result<Vec<Task>, String> {
-    let tasks = {
+    patch: TaskPatch,
+    expected_updated_at: Option<DateTime<Utc>>,
+) -> Result<Task, String> {
+    let task = {
+        let groups = state.groups.lock().unwrap();
         let mut tasks = state.tasks.lock().unwrap();
 
-        if let Some(task) = tasks.iter_mut().find(|t| t.id == id) {
-            // Reset notified flag if due_date or notification settings change
-            let due_date_changed = task.due_date != due_date;
-            let notification_changed = task.notification_minutes != notification_minutes;
-
-            task.description = description;
-            task.start_date = start_date;
-            task.due_date = due_date;
-            task.group = group;
-            task.details = details;
-            task.notification_minutes = notification_minutes;
-            task.dependencies = dependencies;
-
-            // Reset notified flag if relevant fields changed
-            if due_date_changed || notification_changed {
-                task.notified = false;
-            }
-        }
+        apply_task_patch(
+            &mut tasks,
+            &groups,
+            id,
+            patch,
+            expected_updated_at,
+            Utc::now(),
+        )?;
 
         // Save to JSON file
         let data_file = state.data_file.lock().unwrap();
         crate::task::save_tasks(&tasks, &data_file)?;
 
-        tasks.clone()
+        find_task(&tasks, id)?
     };
 
-    Ok(tasks)
+    events::emit(&app, [ChangeEvent::TaskUpdated(task.clone())]);
+    Ok(task)
 }
 
 #[tauri::command]
-pub async fn delete_task(state: State<'_, AppState>, id: i32) -> Result<Vec<Task>, String> {
-    let tasks = {
+pub async fn delete_task(
+    state: State<'_, AppState>,
+    app: AppHandle,
+    id: i32,
+) -> Result<TaskChanges, String> {
+    let changes = {
         let mut tasks = state.tasks.lock().unwrap();
+        let count = tasks.len();
         tasks.retain(|t| t.id != id);
+        let deleted = if tasks.len() < count {
+            vec![id]
+        } else {
+            Vec::new()
+        };
+        let updated = dependency::remove_dependency_references(&mut tasks, id);
+        touch_tasks(&mut tasks, &updated, Utc::now());
 
         // Save to JSON file
         let data_file = state.data_file.lock().unwrap();
         crate::task::save_tasks(&tasks, &data_file)?;
 
-        tasks.clone()
+        TaskChanges::from_ids(&tasks, &[], &updated, &deleted)
     };
 
-    Ok(tasks)
+    super::link_commands::remove_links_to(&state, &app, &changes.deleted_refs())?;
+    events::emit(&app, changes.events());
+    Ok(changes)
 }
 
+/// タスクを並び替える
+///
+/// `after_id` の直後（`None` は先頭）に移動する。書き換わるのは移動したタスクのキーだけ。
 #[tauri::command]
-pub async fn complete_task(state: State<'_, AppState>, id: i32) -> Result<Vec<Task>, String> {
-    let tasks = {
+pub async fn reorder_tasks(
+    state: State<'_, AppState>,
+    app: AppHandle,
+    task_id: i32,
+    after_id: Option<i32>,
+) -> Result<Task, String> {
+    let task = {
         let mut tasks = state.tasks.lock().unwrap();
 
+        ordering::move_task(&mut tasks, task_id, after_id)?;
+
+        // Save to JSON file
+        let data_file = state.data_file.lock().unwrap();
+        crate::task::save_tasks(&tasks, &data_file)?;
+
+        find_task(&tasks, task_id)?
+    };
+
+    events::emit(&app, [ChangeEvent::TaskUpdated(task.clone())]);
+    Ok(task)
+}
+
+#[tauri::command]
+pub async fn complete_task(
+    state: State<'_, AppState>,
+    app: AppHandle,
+    id: i32,
+) -> Result<Task, String> {
+    let task = {
+        let groups = state.groups.lock().unwrap();
+        let mut tasks = state.tasks.lock().unwrap();
+
+        // A task cannot be completed while any of its blockers is still open
+        let task = find_task(&tasks, id)?;
+        if !task.completed {
+            let blockers = dependency::incomplete_blockers(&tasks, &task);
+            if !blockers.is_empty() {
+                let ids = blockers
+                    .iter()
+                    .map(|id| id.to_string())
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                return Err(format!("Task is blocked by incomplete tasks: {}", ids));
+            }
+        }
+
+        // Completion is recorded as a move to/from the group's terminal state
         if let Some(task) = tasks.iter_mut().find(|t| t.id == id) {
-            task.completed = !task.completed;
+            let completed = !task.completed;
+            workflow::set_completed(task, &groups, completed, Utc::now());
         }
+        touch_task(&mut tasks, id, Utc::now());
+
+        // Save to JSON file
+        let data_file = state.data_file.lock().unwrap();
+        crate::task::save_tasks(&tasks, &data_file)?;
+
+        find_task(&tasks, id)?
+    };
+
+    events::emit(&app, [ChangeEvent::TaskUpdated(task.clone())]);
+    Ok(task)
+}
+
+/// 複数のタスクをまとめて更新する（1回の保存で反映し、まとめて取り消せる）
+#[tauri::command]
+pub async fn bulk_update_tasks(
+    state: State<'_, AppState>,
+    app: AppHandle,
+    ids: Vec<i32>,
+    patch: BulkTaskPatch,
+) -> Result<TaskChanges, String> {
+    let (changes, removed_links) = {
+        let groups = state.groups.lock().unwrap();
+        let mut tasks = state.tasks.lock().unwrap();
+        let mut history = state.task_history.lock().unwrap();
+
+        let mut entry = bulk::bulk_update(&mut tasks, &groups, &ids, &patch, Utc::now())?;
+        let changes = TaskChanges::from_ids(&tasks, &[], &entry.updated_ids(), &entry.deleted);
+
+        // Save to JSON file
+        let data_file = state.data_file.lock().unwrap();
+        crate::task::save_tasks(&tasks, &data_file)?;
+
+        // Links to deleted tasks are kept with the entry so that undo restores them
+        entry.links = super::link_commands::take_links_to(&state, &changes.deleted_refs())?;
+        let removed_links: Vec<String> = entry.links.iter().map(|l| l.id.clone()).collect();
+        history.push(entry);
+
+        (changes, removed_links)
+    };
+
+    events::emit(
+        &app,
+        removed_links
+            .into_iter()
+            .map(ChangeEvent::EntityLinkRemoved),
+    );
+    events::emit(&app, changes.events());
+    Ok(changes)
+}
+
+/// 直前の一括更新を取り消す
+#[tauri::command]
+pub async fn undo_task_change(
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<TaskChanges, String> {
+    let (changes, links) = {
+        let groups = state.groups.lock().unwrap();
+        let mut tasks = state.tasks.lock().unwrap();
+        let mut history = state.task_history.lock().unwrap();
+
+        // A stale entry is dropped rather than kept for another attempt
+        let mut entry = history.pop().ok_or_else(|| "Nothing to undo".to_string())?;
+        let created = entry.deleted.clone();
+        let links = std::mem::take(&mut entry.links);
+        let restored = bulk::undo(&mut tasks, &groups, entry)?;
 
         // Save to JSON file
         let data_file = state.data_file.lock().unwrap();
         crate::task::save_tasks(&tasks, &data_file)?;
 
-        tasks.clone()
+        (
+            TaskChanges::from_ids(&tasks, &created, &restored, &[]),
+            links,
+        )
     };
 
-    Ok(tasks)
+    events::emit(&app, changes.events());
+    super::link_commands::restore_links(&state, &app, links)?;
+    Ok(changes)
+}
+
+// ========================================
+// タグ・クエリ関連コマンド
+// ========================================
+
+#[tauri::command]
+pub fn get_task_tags(state: State<AppState>) -> Vec<String> {
+    let tasks = state.tasks.lock().unwrap();
+    let mut tags: Vec<String> = tasks.iter().flat_map(|t| t.tags.clone()).collect();
+    tags.sort();
+    tags.dedup();
+    tags
+}
+
+/// クエリ言語でタスクを絞り込む
+///
+/// 例: `"group:Work tag:urgent due<7d !completed has:subtasks"`
+#[tauri::command]
+pub fn query_tasks(state: State<AppState>, query: String) -> Result<Vec<Task>, String> {
+    let query = TaskQuery::parse(&query)?;
+    let groups = state.groups.lock().unwrap();
+    let tasks = state.tasks.lock().unwrap();
+    Ok(query.filter(&tasks, &groups, &Local::now()))
+}
+
+#[tauri::command]
+pub fn get_saved_views(state: State<AppState>) -> Vec<SavedView> {
+    let views = state.saved_views.lock().unwrap();
+    views.clone()
+}
+
+#[tauri::command]
+pub async fn create_saved_view(
+    state: State<'_, AppState>,
+    name: String,
+    query: String,
+) -> Result<Vec<SavedView>, String> {
+    task_query::validate_saved_view(&name, &query)?;
+
+    let views = {
+        let mut views = state.saved_views.lock().unwrap();
+        views.push(SavedView::new(name.trim().to_string(), query));
+
+        // Save to JSON file
+        let views_file = state.saved_views_file.lock().unwrap();
+        task_query::save_saved_views(&views, &views_file)?;
+
+        views.clone()
+    };
+
+    Ok(views)
+}
+
+#[tauri::command]
+pub async fn update_saved_view(
+    state: State<'_, AppState>,
+    id: String,
+    name: String,
+    query: String,
+) -> Result<Vec<SavedView>, String> {
+    task_query::validate_saved_view(&name, &query)?;
+
+    let views = {
+        let mut views = state.saved_views.lock().unwrap();
+        if let Some(view) = views.iter_mut().find(|v| v.id == id) {
+            view.name = name.trim().to_string();
+            view.query = query;
+
+            // Save to JSON file
+            let views_file = state.saved_views_file.lock().unwrap();
+            task_query::save_saved_views(&views, &views_file)?;
+        }
+
+        views.clone()
+    };
+
+    Ok(views)
+}
+
+#[tauri::command]
+pub async fn delete_saved_view(
+    state: State<'_, AppState>,
+    id: String,
+) -> Result<Vec<SavedView>, String> {
+    let views = {
+        let mut views = state.saved_views.lock().unwrap();
+        views.retain(|v| v.id != id);
+
+        // Save to JSON file
+        let views_file = state.saved_views_file.lock().unwrap();
+        task_query::save_saved_views(&views, &views_file)?;
+
+        views.clone()
+    };
+
+    Ok(views)
+}
+
+// ========================================
+// ワークフロー（カンバン）関連コマンド
+// ========================================
+
+#[tauri::command]
+pub async fn move_task_state(
+    state: State<'_, AppState>,
+    app: AppHandle,
+    task_id: i32,
+    state_id: String,
+) -> Result<Task, String> {
+    let task = {
+        let groups = state.groups.lock().unwrap();
+        let mut tasks = state.tasks.lock().unwrap();
+
+        workflow::move_task_state(&mut tasks, &groups, task_id, &state_id, Utc::now())?;
+        touch_task(&mut tasks, task_id, Utc::now());
+
+        // Save to JSON file
+        let data_file = state.data_file.lock().unwrap();
+        crate::task::save_tasks(&tasks, &data_file)?;
+
+        find_task(&tasks, task_id)?
+    };
+
+    events::emit(&app, [ChangeEvent::TaskUpdated(task.clone())]);
+    Ok(task)
+}
+
+/// グループのボード（`group_id` が `None` の場合はグループ無しのタスク）
+#[tauri::command]
+pub fn get_board(state: State<AppState>, group_id: Option<String>) -> Result<Board, String> {
+    let groups = state.groups.lock().unwrap();
+    let tasks = state.tasks.lock().unwrap();
+    let group_id = group::resolve_group_id(&groups, group_id)?;
+    Ok(workflow::board(&tasks, &groups, group_id.as_deref()))
+}
+
+/// グループのワークフローを設定する（空の場合は親グループまたは既定に戻す）
+#[tauri::command]
+pub async fn set_group_workflow(
+    state: State<'_, AppState>,
+    app: AppHandle,
+    group_id: String,
+    states: Vec<WorkflowState>,
+) -> Result<Vec<TaskGroup>, String> {
+    if !states.is_empty() {
+        workflow::validate_workflow(&states)?;
+    }
+
+    let (groups, changes) = {
+        let mut groups = state.groups.lock().unwrap();
+        let mut tasks = state.tasks.lock().unwrap();
+
+        let target = groups
+            .iter_mut()
+            .find(|g| g.id == group_id)
+            .ok_or_else(|| format!("Group {} not found", group_id))?;
+        target.workflow = states;
+
+        // Tasks in states that no longer exist fall back to the default column
+        let updated = workflow::normalize_states(&mut tasks, &groups, Utc::now());
+
+        // Save groups
+        let groups_file = state.groups_file.lock().unwrap();
+        group::save_groups(&groups, &groups_file)?;
+
+        // Save tasks if modified
+        if !updated.is_empty() {
+            let data_file = state.data_file.lock().unwrap();
+            crate::task::save_tasks(&tasks, &data_file)?;
+        }
+
+        (
+            groups.clone(),
+            TaskChanges::from_ids(&tasks, &[], &updated, &[]),
+        )
+    };
+
+    events::emit(&app, changes.events());
+    Ok(groups)
+}
+
+// ========================================
+// 依存関係関連コマンド
+// ========================================
+
+#[tauri::command]
+pub fn get_blocked_tasks(state: State<AppState>) -> Vec<Task> {
+    let tasks = state.tasks.lock().unwrap();
+    dependency::blocked_tasks(&tasks)
+}
+
+#[tauri::command]
+pub fn get_ready_tasks(state: State<AppState>) -> Vec<Task> {
+    let tasks = state.tasks.lock().unwrap();
+    dependency::ready_tasks(&tasks)
+}
+
+#[tauri::command]
+pub fn get_task_topological_order(state: State<AppState>) -> Vec<i32> {
+    let tasks = state.tasks.lock().unwrap();
+    dependency::topological_order(&tasks)
+}
+
+// ========================================
+// スケジュール関連コマンド
+// ========================================
+
+#[tauri::command]
+pub fn get_task_schedule(state: State<AppState>) -> ProjectSchedule {
+    let tasks = state.tasks.lock().unwrap();
+    schedule::compute_schedule(&tasks)
+}
+
+#[tauri::command]
+pub async fn auto_schedule_tasks(
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<TaskChanges, String> {
+    let changes = {
+        let mut tasks = state.tasks.lock().unwrap();
+
+        let moved = schedule::auto_schedule(&mut tasks);
+        touch_tasks(&mut tasks, &moved, Utc::now());
+
+        // Save to JSON file
+        if !moved.is_empty() {
+            let data_file = state.data_file.lock().unwrap();
+            crate::task::save_tasks(&tasks, &data_file)?;
+        }
+
+        TaskChanges::from_ids(&tasks, &[], &moved, &[])
+    };
+
+    events::emit(&app, changes.events());
+    Ok(changes)
+}
+
+// ========================================
+// 作業時間記録関連コマンド
+// ========================================
+
+#[tauri::command]
+pub async fn start_task_timer(
+    state: State<'_, AppState>,
+    app: AppHandle,
+    task_id: i32,
+) -> Result<TaskChanges, String> {
+    let changes = {
+        let mut tasks = state.tasks.lock().unwrap();
+
+        let now = Utc::now();
+        let mut updated = time_tracking::start_timer(&mut tasks, task_id, now)?;
+        updated.push(task_id);
+        touch_tasks(&mut tasks, &updated, now);
+
+        // Save to JSON file
+        let data_file = state.data_file.lock().unwrap();
+        crate::task::save_tasks(&tasks, &data_file)?;
+
+        TaskChanges::from_ids(&tasks, &[], &updated, &[])
+    };
+
+    events::emit(&app, changes.events());
+    Ok(changes)
+}
+
+#[tauri::command]
+pub async fn stop_task_timer(
+    state: State<'_, AppState>,
+    app: AppHandle,
+    task_id: i32,
+    memo: Option<String>,
+) -> Result<Task, String> {
+    let task = {
+        let mut tasks = state.tasks.lock().unwrap();
+
+        time_tracking::stop_timer(&mut tasks, task_id, memo, Utc::now())?;
+        touch_task(&mut tasks, task_id, Utc::now());
+
+        // Save to JSON file
+        let data_file = state.data_file.lock().unwrap();
+        crate::task::save_tasks(&tasks, &data_file)?;
+
+        find_task(&tasks, task_id)?
+    };
+
+    events::emit(&app, [ChangeEvent::TaskUpdated(task.clone())]);
+    Ok(task)
+}
+
+#[tauri::command]
+pub fn get_time_entries(state: State<AppState>, task_id: i32) -> Result<Vec<TimeEntry>, String> {
+    let tasks = state.tasks.lock().unwrap();
+    tasks
+        .iter()
+        .find(|t| t.id == task_id)
+        .map(|t| t.time_entries.clone())
+        .ok_or_else(|| format!("Task {} not found", task_id))
+}
+
+#[tauri::command]
+pub fn get_time_report(state: State<AppState>) -> Vec<GroupTimeReport> {
+    let groups = state.groups.lock().unwrap();
+    let tasks = state.tasks.lock().unwrap();
+    time_tracking::time_report(&tasks, &groups, Utc::now())
 }
 
 // ========================================
@@ -144,22 +702,40 @@ pub async fn complete_task(state: State<'_, AppState>, id: i32) -> Result<Vec<Ta
 // ========================================
 
 #[tauri::command]
-pub fn get_groups(state: State<AppState>) -> Vec<String> {
+pub fn get_groups(state: State<AppState>) -> Vec<TaskGroup> {
     let groups = state.groups.lock().unwrap();
     groups.clone()
 }
 
 #[tauri::command]
-pub async fn create_group(state: State<'_, AppState>, name: String) -> Result<Vec<String>, String> {
+pub async fn create_group(
+    state: State<'_, AppState>,
+    name: String,
+    parent_id: Option<String>,
+    color: Option<String>,
+    icon: Option<String>,
+    notification_minutes: Option<i32>,
+) -> Result<Vec<TaskGroup>, String> {
     let groups = {
         let mut groups = state.groups.lock().unwrap();
-        if !groups.contains(&name) && !name.trim().is_empty() {
-            groups.push(name);
 
-            // Save to JSON file
-            let groups_file = state.groups_file.lock().unwrap();
-            crate::task::save_groups(&groups, &groups_file)?;
-        }
+        group::validate_parent(&groups, None, parent_id.as_deref())?;
+        group::validate_group_name(&groups, &name, parent_id.as_deref(), None)?;
+
+        let mut new_group = TaskGroup::new(
+            name.trim().to_string(),
+            parent_id,
+            group::next_sort_order(&groups),
+        );
+        new_group.color = color;
+        new_group.icon = icon;
+        new_group.notification_minutes = notification_minutes;
+        groups.push(new_group);
+
+        // Save to JSON file
+        let groups_file = state.groups_file.lock().unwrap();
+        group::save_groups(&groups, &groups_file)?;
+
         groups.clone()
     };
 
@@ -167,34 +743,36 @@ pub async fn create_group(state: State<'_, AppState>, name: String) -> Result<Ve
 }
 
 #[tauri::command]
-pub async fn delete_group(state: State<'_, AppState>, name: String) -> Result<Vec<String>, String> {
-    let (groups, _updated_tasks) = {
+pub async fn update_group(
+    state: State<'_, AppState>,
+    id: String,
+    name: String,
+    parent_id: Option<String>,
+    color: Option<String>,
+    icon: Option<String>,
+    notification_minutes: Option<i32>,
+) -> Result<Vec<TaskGroup>, String> {
+    let groups = {
         let mut groups = state.groups.lock().unwrap();
-        let mut tasks = state.tasks.lock().unwrap();
 
-        if let Some(pos) = groups.iter().position(|g| *g == name) {
-            groups.remove(pos);
+        group::validate_parent(&groups, Some(&id), parent_id.as_deref())?;
+        group::validate_group_name(&groups, &name, parent_id.as_deref(), Some(&id))?;
 
-            // Update tasks with this group
-            let mut tasks_modified = false;
-            for task in tasks.iter_mut() {
-                if task.group == name {
-                    task.group = String::new();
-                    tasks_modified = true;
-                }
-            }
+        let target = groups
+            .iter_mut()
+            .find(|g| g.id == id)
+            .ok_or_else(|| format!("Group {} not found", id))?;
+        target.name = name.trim().to_string();
+        target.parent_id = parent_id;
+        target.color = color;
+        target.icon = icon;
+        target.notification_minutes = notification_minutes;
 
-            // Save groups
-            let groups_file = state.groups_file.lock().unwrap();
-            crate::task::save_groups(&groups, &groups_file)?;
+        // Save to JSON file
+        let groups_file = state.groups_file.lock().unwrap();
+        group::save_groups(&groups, &groups_file)?;
 
-            // Save tasks if modified
-            if tasks_modified {
-                let data_file = state.data_file.lock().unwrap();
-                crate::task::save_tasks(&tasks, &data_file)?;
-            }
-        }
-        (groups.clone(), Vec::<Task>::new())
+        groups.clone()
     };
 
     Ok(groups)
@@ -203,49 +781,112 @@ pub async fn delete_group(state: State<'_, AppState>, name: String) -> Result<Ve
 #[tauri::command]
 pub async fn rename_group(
     state: State<'_, AppState>,
-    old_name: String,
+    id: String,
     new_name: String,
-) -> Result<(Vec<String>, Vec<Task>), String> {
-    let (groups_clone, tasks_clone) = {
+) -> Result<Vec<TaskGroup>, String> {
+    let groups = {
         let mut groups = state.groups.lock().unwrap();
-        let mut tasks = state.tasks.lock().unwrap();
 
-        // Basic validation
-        let new_trim = new_name.trim();
-        if new_trim.is_empty() {
-            return Err("New group name cannot be empty".to_string());
-        }
-        if groups.contains(&new_trim.to_string()) {
-            return Err("A group with the new name already exists".to_string());
+        let parent_id = groups
+            .iter()
+            .find(|g| g.id == id)
+            .ok_or_else(|| format!("Group {} not found", id))?
+            .parent_id
+            .clone();
+        group::validate_group_name(&groups, &new_name, parent_id.as_deref(), Some(&id))?;
+
+        if let Some(target) = groups.iter_mut().find(|g| g.id == id) {
+            target.name = new_name.trim().to_string();
         }
 
-        if let Some(pos) = groups.iter().position(|g| *g == old_name) {
-            groups[pos] = new_trim.to_string();
+        // Save to JSON file
+        let groups_file = state.groups_file.lock().unwrap();
+        group::save_groups(&groups, &groups_file)?;
 
-            // Update tasks that referenced the old group
-            let mut tasks_modified = false;
-            for task in tasks.iter_mut() {
-                if task.group == old_name {
-                    task.group = new_trim.to_string();
-                    tasks_modified = true;
-                }
-            }
+        groups.clone()
+    };
 
-            // Save groups
-            let groups_file = state.groups_file.lock().unwrap();
-            crate::task::save_groups(&groups, &groups_file)?;
+    Ok(groups)
+}
 
-            // Save tasks if modified
-            if tasks_modified {
-                let data_file = state.data_file.lock().unwrap();
-                crate::task::save_tasks(&tasks, &data_file)?;
-            }
+#[tauri::command]
+pub async fn delete_group(
+    state: State<'_, AppState>,
+    app: AppHandle,
+    id: String,
+) -> Result<Vec<TaskGroup>, String> {
+    let (groups, changes) = {
+        let mut groups = state.groups.lock().unwrap();
+        let mut tasks = state.tasks.lock().unwrap();
+
+        let now = Utc::now();
+        let mut updated = group::remove_group(&mut groups, &mut tasks, &id)?;
+        touch_tasks(&mut tasks, &updated, now);
+        updated.extend(workflow::normalize_states(&mut tasks, &groups, now));
+
+        // Save groups
+        let groups_file = state.groups_file.lock().unwrap();
+        group::save_groups(&groups, &groups_file)?;
+
+        // Save tasks if modified
+        if !updated.is_empty() {
+            let data_file = state.data_file.lock().unwrap();
+            crate::task::save_tasks(&tasks, &data_file)?;
         }
 
-        (groups.clone(), tasks.clone())
+        (
+            groups.clone(),
+            TaskChanges::from_ids(&tasks, &[], &updated, &[]),
+        )
+    };
+
+    events::emit(&app, changes.events());
+    Ok(groups)
+}
+
+#[tauri::command]
+pub async fn set_group_archived(
+    state: State<'_, AppState>,
+    id: String,
+    archived: bool,
+) -> Result<Vec<TaskGroup>, String> {
+    let groups = {
+        let mut groups = state.groups.lock().unwrap();
+
+        let target = groups
+            .iter_mut()
+            .find(|g| g.id == id)
+            .ok_or_else(|| format!("Group {} not found", id))?;
+        target.archived = archived;
+
+        // Save to JSON file
+        let groups_file = state.groups_file.lock().unwrap();
+        group::save_groups(&groups, &groups_file)?;
+
+        groups.clone()
     };
 
-    Ok((groups_clone, tasks_clone))
+    Ok(groups)
+}
+
+#[tauri::command]
+pub async fn reorder_groups(
+    state: State<'_, AppState>,
+    ids: Vec<String>,
+) -> Result<Vec<TaskGroup>, String> {
+    let groups = {
+        let mut groups = state.groups.lock().unwrap();
+
+        group::reorder_groups(&mut groups, &ids)?;
+
+        // Save to JSON file
+        let groups_file = state.groups_file.lock().unwrap();
+        group::save_groups(&groups, &groups_file)?;
+
+        groups.clone()
+    };
+
+    Ok(groups)
 }
 
 // ========================================
@@ -293,7 +934,10 @@ pub async fn send_test_email(state: State<'_, AppState>) -> Result<String, Strin
 // ========================================
 
 #[tauri::command]
-pub async fn check_notifications(state: State<'_, AppState>) -> Result<String, String> {
+pub async fn check_notifications(
+    state: State<'_, AppState>,
+    app: AppHandle,
+) -> Result<String, String> {
     let settings = {
         let s = state.mail_settings.lock().unwrap();
         s.clone()
@@ -312,7 +956,9 @@ pub async fn check_notifications(state: State<'_, AppState>) -> Result<String, S
         settings.notification_minutes
     ));
 
-    let _tasks_to_update: Vec<Task> = {
+    let groups = state.groups.lock().unwrap().clone();
+
+    let tasks_to_update: Vec<Task> = {
         let mut tasks = state.tasks.lock().unwrap();
         let mut changed = false;
         let mut updated = Vec::new();
@@ -320,8 +966,7 @@ pub async fn check_notifications(state: State<'_, AppState>) -> Result<String, S
         debug_info.push(format!("Total tasks: {}", tasks.len()));
 
         for task in tasks.iter_mut() {
-            let task_threshold = task
-                .notification_minutes
+            let task_threshold = group::effective_notification_minutes(task, &groups)
                 .unwrap_or(settings.notification_minutes);
 
             if task.completed {
@@ -337,38 +982,19 @@ pub async fn check_notifications(state: State<'_, AppState>) -> Result<String, S
                 continue;
             }
 
-            // Try parsing as DateTime first, then fall back to Date
-            let due_time_result = NaiveDateTime::parse_from_str(&task.due_date, "%Y-%m-%d %H:%M");
-            let minutes_until_due = if let Ok(due_dt) = due_time_result {
-                let due_time = Local.from_local_datetime(&due_dt).unwrap();
-                let duration = due_time.signed_duration_since(now);
-                duration.num_minutes()
-            } else {
-                // Fall back to date-only parsing
-                let date_str = task
-                    .due_date
-                    .split_whitespace()
-                    .next()
-                    .unwrap_or(&task.due_date);
-                if let Ok(due_date) = NaiveDate::parse_from_str(date_str, "%Y-%m-%d") {
-                    let due_midnight = Local
-                        .from_local_datetime(&due_date.and_hms_opt(0, 0, 0).unwrap())
-                        .unwrap();
-                    let duration = due_midnight.signed_duration_since(now);
-                    duration.num_minutes()
-                } else {
-                    debug_info.push(format!(
-                        "Task '{}': ERROR parsing date '{}': invalid format",
-                        task.description, task.due_date
-                    ));
-                    continue;
-                }
+            let Some(due_date) = task.due_date.as_ref() else {
+                debug_info.push(format!(
+                    "Task '{}': SKIPPED (no due date)",
+                    task.description
+                ));
+                continue;
             };
+            let minutes_until_due = due_date.minutes_until(&now);
 
             debug_info.push(format!(
                 "Task '{}': due_date={}, minutes_until_due={}, threshold={}, will_notify={}",
                 task.description,
-                task.due_date,
+                due_date,
                 minutes_until_due,
                 task_threshold,
                 minutes_until_due <= task_threshold as i64 && minutes_until_due >= 0
@@ -390,13 +1016,20 @@ pub async fn check_notifications(state: State<'_, AppState>) -> Result<String, S
         }
         updated
     };
+    events::emit(
+        &app,
+        tasks_to_update.into_iter().map(ChangeEvent::TaskUpdated),
+    );
 
     let count = tasks_to_notify.len();
     for task in &tasks_to_notify {
         let subject = format!("[Todo App] Task Due: {}", task.description);
         let body = format!(
             "Your task '{}' is due on {}.\n\nDetails: {}\nGroup: {}",
-            task.description, task.due_date, task.details, task.group
+            task.description,
+            task.due_date.map(|d| d.to_string()).unwrap_or_default(),
+            task.details,
+            group::group_name(&groups, task.group_id.as_deref())
         );
 
         match send_email(&settings, &settings.email, &subject, &body) {
@@ -421,103 +1054,318 @@ pub async fn check_notifications(state: State<'_, AppState>) -> Result<String, S
 // サブタスク関連コマンド
 // ========================================
 
+/// サブタスクを追加する（`parent_subtask_id` を指定した場合はそのサブタスクの子）
 #[tauri::command]
 pub async fn add_subtask(
     state: State<'_, AppState>,
+    app: AppHandle,
     task_id: i32,
     description: String,
-) -> Result<Vec<Task>, String> {
-    let tasks = {
+    parent_subtask_id: Option<i32>,
+) -> Result<Task, String> {
+    let task = {
+        let groups = state.groups.lock().unwrap();
         let mut tasks = state.tasks.lock().unwrap();
 
-        if let Some(task) = tasks.iter_mut().find(|t| t.id == task_id) {
-            let subtask_id = task.next_subtask_id();
-            let subtask = Subtask::new(subtask_id, description);
-            task.subtasks.push(subtask);
+        let task = tasks
+            .iter_mut()
+            .find(|t| t.id == task_id)
+            .ok_or_else(|| format!("Task {} not found", task_id))?;
+        let subtask_id = task.next_subtask_id();
+        let siblings = subtask::children_mut(&mut task.subtasks, parent_subtask_id)?;
+        let mut new_subtask = Subtask::new(subtask_id, description);
+        new_subtask.sort_key = ordering::key_after_last(siblings);
+        siblings.push(new_subtask);
+        subtask::roll_up(&mut tasks, &groups, task_id, Utc::now());
+        touch_task(&mut tasks, task_id, Utc::now());
 
-            // Save to JSON file
-            let data_file = state.data_file.lock().unwrap();
-            crate::task::save_tasks(&tasks, &data_file)?;
-        }
+        // Save to JSON file
+        let data_file = state.data_file.lock().unwrap();
+        crate::task::save_tasks(&tasks, &data_file)?;
 
-        tasks.clone()
+        find_task(&tasks, task_id)?
     };
 
-    Ok(tasks)
+    events::emit(&app, [ChangeEvent::TaskUpdated(task.clone())]);
+    Ok(task)
 }
 
 #[tauri::command]
 pub async fn update_subtask(
     state: State<'_, AppState>,
+    app: AppHandle,
     task_id: i32,
     subtask_id: i32,
     description: String,
     completed: bool,
-) -> Result<Vec<Task>, String> {
-    let tasks = {
+) -> Result<Task, String> {
+    let task = {
+        let groups = state.groups.lock().unwrap();
         let mut tasks = state.tasks.lock().unwrap();
 
-        if let Some(task) = tasks.iter_mut().find(|t| t.id == task_id) {
-            if let Some(subtask) = task.subtasks.iter_mut().find(|s| s.id == subtask_id) {
-                subtask.description = description;
-                subtask.completed = completed;
+        let task = tasks
+            .iter_mut()
+            .find(|t| t.id == task_id)
+            .ok_or_else(|| format!("Task {} not found", task_id))?;
+        let subtask = subtask::find_mut(&mut task.subtasks, subtask_id)
+            .ok_or_else(|| format!("Subtask {} not found", subtask_id))?;
+        subtask.description = description;
+        subtask.completed = completed;
+        subtask::roll_up(&mut tasks, &groups, task_id, Utc::now());
+        touch_task(&mut tasks, task_id, Utc::now());
 
-                // Save to JSON file
-                let data_file = state.data_file.lock().unwrap();
-                crate::task::save_tasks(&tasks, &data_file)?;
-            }
-        }
+        // Save to JSON file
+        let data_file = state.data_file.lock().unwrap();
+        crate::task::save_tasks(&tasks, &data_file)?;
+
+        find_task(&tasks, task_id)?
+    };
+
+    events::emit(&app, [ChangeEvent::TaskUpdated(task.clone())]);
+    Ok(task)
+}
+
+/// サブタスクの期日とメモを更新する
+#[tauri::command]
+pub async fn update_subtask_details(
+    state: State<'_, AppState>,
+    app: AppHandle,
+    task_id: i32,
+    subtask_id: i32,
+    due_date: Option<String>,
+    notes: String,
+) -> Result<Task, String> {
+    let due_date = TaskDate::parse_optional(due_date.as_deref())?;
+
+    let task = {
+        let mut tasks = state.tasks.lock().unwrap();
+
+        let task = tasks
+            .iter_mut()
+            .find(|t| t.id == task_id)
+            .ok_or_else(|| format!("Task {} not found", task_id))?;
+        let subtask = subtask::find_mut(&mut task.subtasks, subtask_id)
+            .ok_or_else(|| format!("Subtask {} not found", subtask_id))?;
+        subtask.due_date = due_date;
+        subtask.notes = notes;
+        touch_task(&mut tasks, task_id, Utc::now());
+
+        // Save to JSON file
+        let data_file = state.data_file.lock().unwrap();
+        crate::task::save_tasks(&tasks, &data_file)?;
 
-        tasks.clone()
+        find_task(&tasks, task_id)?
     };
 
-    Ok(tasks)
+    events::emit(&app, [ChangeEvent::TaskUpdated(task.clone())]);
+    Ok(task)
 }
 
+/// サブタスクを削除する（子のサブタスクも削除される）
 #[tauri::command]
 pub async fn delete_subtask(
     state: State<'_, AppState>,
+    app: AppHandle,
     task_id: i32,
     subtask_id: i32,
-) -> Result<Vec<Task>, String> {
-    let tasks = {
+) -> Result<Task, String> {
+    let task = {
+        let groups = state.groups.lock().unwrap();
         let mut tasks = state.tasks.lock().unwrap();
 
-        if let Some(task) = tasks.iter_mut().find(|t| t.id == task_id) {
-            task.subtasks.retain(|s| s.id != subtask_id);
+        let task = tasks
+            .iter_mut()
+            .find(|t| t.id == task_id)
+            .ok_or_else(|| format!("Task {} not found", task_id))?;
+        subtask::take(&mut task.subtasks, subtask_id)
+            .ok_or_else(|| format!("Subtask {} not found", subtask_id))?;
+        subtask::roll_up(&mut tasks, &groups, task_id, Utc::now());
+        touch_task(&mut tasks, task_id, Utc::now());
 
-            // Save to JSON file
-            let data_file = state.data_file.lock().unwrap();
-            crate::task::save_tasks(&tasks, &data_file)?;
-        }
+        // Save to JSON file
+        let data_file = state.data_file.lock().unwrap();
+        crate::task::save_tasks(&tasks, &data_file)?;
 
-        tasks.clone()
+        find_task(&tasks, task_id)?
     };
 
-    Ok(tasks)
+    events::emit(&app, [ChangeEvent::TaskUpdated(task.clone())]);
+    Ok(task)
 }
 
 #[tauri::command]
 pub async fn toggle_subtask(
     state: State<'_, AppState>,
+    app: AppHandle,
     task_id: i32,
     subtask_id: i32,
-) -> Result<Vec<Task>, String> {
-    let tasks = {
+) -> Result<Task, String> {
+    let task = {
+        let groups = state.groups.lock().unwrap();
         let mut tasks = state.tasks.lock().unwrap();
 
-        if let Some(task) = tasks.iter_mut().find(|t| t.id == task_id) {
-            if let Some(subtask) = task.subtasks.iter_mut().find(|s| s.id == subtask_id) {
-                subtask.completed = !subtask.completed;
+        let task = tasks
+            .iter_mut()
+            .find(|t| t.id == task_id)
+            .ok_or_else(|| format!("Task {} not found", task_id))?;
+        let subtask = subtask::find_mut(&mut task.subtasks, subtask_id)
+            .ok_or_else(|| format!("Subtask {} not found", subtask_id))?;
+        subtask.completed = !subtask.completed;
+        subtask::roll_up(&mut tasks, &groups, task_id, Utc::now());
+        touch_task(&mut tasks, task_id, Utc::now());
 
-                // Save to JSON file
-                let data_file = state.data_file.lock().unwrap();
-                crate::task::save_tasks(&tasks, &data_file)?;
-            }
-        }
+        // Save to JSON file
+        let data_file = state.data_file.lock().unwrap();
+        crate::task::save_tasks(&tasks, &data_file)?;
+
+        find_task(&tasks, task_id)?
+    };
+
+    events::emit(&app, [ChangeEvent::TaskUpdated(task.clone())]);
+    Ok(task)
+}
+
+/// サブタスクを並び替える（別のタスクや別の親サブタスクへの移動も可能）
+///
+/// `target_parent_subtask_id` の子（`None` はタスク直下）として、
+/// `after_subtask_id` の直後（`None` は先頭）に移動する。
+#[tauri::command]
+pub async fn reorder_subtasks(
+    state: State<'_, AppState>,
+    app: AppHandle,
+    task_id: i32,
+    subtask_id: i32,
+    target_task_id: i32,
+    target_parent_subtask_id: Option<i32>,
+    after_subtask_id: Option<i32>,
+) -> Result<TaskChanges, String> {
+    let changes = {
+        let groups = state.groups.lock().unwrap();
+        let mut tasks = state.tasks.lock().unwrap();
+
+        subtask::move_subtask(
+            &mut tasks,
+            task_id,
+            subtask_id,
+            target_task_id,
+            target_parent_subtask_id,
+            after_subtask_id,
+        )?;
+        let now = Utc::now();
+        subtask::roll_up(&mut tasks, &groups, task_id, now);
+        subtask::roll_up(&mut tasks, &groups, target_task_id, now);
+        let updated = [task_id, target_task_id];
+        touch_tasks(&mut tasks, &updated, now);
+
+        // Save to JSON file
+        let data_file = state.data_file.lock().unwrap();
+        crate::task::save_tasks(&tasks, &data_file)?;
+
+        TaskChanges::from_ids(&tasks, &[], &updated, &[])
+    };
+
+    events::emit(&app, changes.events());
+    Ok(changes)
+}
+
+/// サブタスクをタスクに昇格させる
+#[tauri::command]
+pub async fn promote_subtask(
+    state: State<'_, AppState>,
+    app: AppHandle,
+    task_id: i32,
+    subtask_id: i32,
+) -> Result<TaskChanges, String> {
+    let changes = {
+        let groups = state.groups.lock().unwrap();
+        let mut tasks = state.tasks.lock().unwrap();
+        let mut next_id = state.next_id.lock().unwrap();
+
+        let new_id = *next_id;
+        subtask::promote(&mut tasks, task_id, subtask_id, new_id)?;
+        let now = Utc::now();
+        touch_tasks(&mut tasks, &[new_id, task_id], now);
+        *next_id += 1;
+        subtask::roll_up(&mut tasks, &groups, task_id, now);
+
+        // Save to JSON file
+        let data_file = state.data_file.lock().unwrap();
+        crate::task::save_tasks(&tasks, &data_file)?;
+
+        TaskChanges::from_ids(&tasks, &[new_id], &[task_id], &[])
+    };
+
+    events::emit(&app, changes.events());
+    Ok(changes)
+}
+
+/// タスクを別のタスク（またはそのサブタスク）のサブタスクに降格させる
+///
+/// サブタスクに引き継げない情報を持つタスクは、`force` を指定しない限りエラーにする。
+#[tauri::command]
+pub async fn demote_task(
+    state: State<'_, AppState>,
+    app: AppHandle,
+    task_id: i32,
+    parent_task_id: i32,
+    parent_subtask_id: Option<i32>,
+    force: Option<bool>,
+) -> Result<TaskChanges, String> {
+    let changes = {
+        let groups = state.groups.lock().unwrap();
+        let mut tasks = state.tasks.lock().unwrap();
+
+        let mut updated = subtask::demote(
+            &mut tasks,
+            task_id,
+            parent_task_id,
+            parent_subtask_id,
+            force.unwrap_or(false),
+        )?;
+        updated.push(parent_task_id);
+        let now = Utc::now();
+        subtask::roll_up(&mut tasks, &groups, parent_task_id, now);
+        touch_tasks(&mut tasks, &updated, now);
+
+        // Save to JSON file
+        let data_file = state.data_file.lock().unwrap();
+        crate::task::save_tasks(&tasks, &data_file)?;
+
+        TaskChanges::from_ids(&tasks, &[], &updated, &[task_id])
+    };
+
+    super::link_commands::remove_links_to(&state, &app, &changes.deleted_refs())?;
+    events::emit(&app, changes.events());
+    Ok(changes)
+}
+
+/// サブタスクの完了状態を親に集約するかを設定する
+#[tauri::command]
+pub async fn set_subtask_rollup(
+    state: State<'_, AppState>,
+    app: AppHandle,
+    task_id: i32,
+    enabled: bool,
+) -> Result<Task, String> {
+    let task = {
+        let groups = state.groups.lock().unwrap();
+        let mut tasks = state.tasks.lock().unwrap();
+
+        let task = tasks
+            .iter_mut()
+            .find(|t| t.id == task_id)
+            .ok_or_else(|| format!("Task {} not found", task_id))?;
+        task.rollup_subtasks = enabled;
+        subtask::roll_up(&mut tasks, &groups, task_id, Utc::now());
+        touch_task(&mut tasks, task_id, Utc::now());
+
+        // Save to JSON file
+        let data_file = state.data_file.lock().unwrap();
+        crate::task::save_tasks(&tasks, &data_file)?;
 
-        tasks.clone()
+        find_task(&tasks, task_id)?
     };
 
-    Ok(tasks)
+    events::emit(&app, [ChangeEvent::TaskUpdated(task.clone())]);
+    Ok(task)
 }
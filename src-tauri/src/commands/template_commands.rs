@@ -0,0 +1,203 @@
+// メモのテンプレート・デイリーノート関連のTauriコマンド
+
+use chrono::{Local, NaiveDate, Utc};
+use tauri::{AppHandle, State};
+
+use crate::events::{self, ChangeEvent};
+use crate::folder_tree;
+use crate::memo::Memo;
+use crate::memo_template::{self, DailyNoteSettings, MemoTemplate};
+use crate::AppState;
+
+// ========================================
+// テンプレート関連コマンド
+// ========================================
+
+#[tauri::command]
+pub fn get_memo_templates(state: State<AppState>) -> Vec<MemoTemplate> {
+    let store = state.memo_templates.lock().unwrap();
+    store.templates.clone()
+}
+
+#[tauri::command]
+pub async fn create_memo_template(
+    state: State<'_, AppState>,
+    name: String,
+    title: String,
+    content: String,
+    folder_id: Option<String>,
+    tags: Vec<String>,
+) -> Result<MemoTemplate, String> {
+    if name.trim().is_empty() {
+        return Err("Template name must not be empty".to_string());
+    }
+
+    let mut store = state.memo_templates.lock().unwrap();
+    {
+        let folders = state.folders.lock().unwrap();
+        folder_tree::validate_parent(&folders, folder_id.as_deref())?;
+    }
+    let template = MemoTemplate::new(name, title, content, folder_id, tags);
+    store.templates.push(template.clone());
+
+    memo_template::save_memo_templates(&store)?;
+
+    Ok(template)
+}
+
+#[tauri::command]
+pub async fn update_memo_template(
+    state: State<'_, AppState>,
+    id: String,
+    name: String,
+    title: String,
+    content: String,
+    folder_id: Option<String>,
+    tags: Vec<String>,
+) -> Result<MemoTemplate, String> {
+    if name.trim().is_empty() {
+        return Err("Template name must not be empty".to_string());
+    }
+
+    let mut store = state.memo_templates.lock().unwrap();
+    {
+        let folders = state.folders.lock().unwrap();
+        folder_tree::validate_parent(&folders, folder_id.as_deref())?;
+    }
+    let template = store
+        .templates
+        .iter_mut()
+        .find(|t| t.id == id)
+        .ok_or_else(|| format!("Template {} not found", id))?;
+    template.name = name;
+    template.title = title;
+    template.content = content;
+    template.folder_id = folder_id;
+    template.tags = tags;
+    template.updated_at = Utc::now();
+    let template = template.clone();
+
+    memo_template::save_memo_templates(&store)?;
+
+    Ok(template)
+}
+
+/// テンプレートを削除する（デイリーノートで使っている場合は設定から外す）
+#[tauri::command]
+pub async fn delete_memo_template(
+    state: State<'_, AppState>,
+    id: String,
+) -> Result<String, String> {
+    let mut store = state.memo_templates.lock().unwrap();
+    let before = store.templates.len();
+    store.templates.retain(|t| t.id != id);
+    if store.templates.len() == before {
+        return Err(format!("Template {} not found", id));
+    }
+    if store.daily_note.template_id.as_deref() == Some(id.as_str()) {
+        store.daily_note.template_id = None;
+    }
+
+    memo_template::save_memo_templates(&store)?;
+
+    Ok(id)
+}
+
+/// テンプレートからメモを作成する
+///
+/// `title` を省略した場合はテンプレートのタイトルを展開して使う。
+/// `folder_id` を省略した場合はテンプレートのフォルダに作成する。
+#[tauri::command]
+pub async fn create_memo_from_template(
+    state: State<'_, AppState>,
+    app: AppHandle,
+    template_id: String,
+    title: Option<String>,
+    folder_id: Option<String>,
+) -> Result<Memo, String> {
+    let memo = {
+        let store = state.memo_templates.lock().unwrap();
+        let folders = state.folders.lock().unwrap();
+        let mut memos = state.memos.lock().unwrap();
+
+        let template = store.find(&template_id)?;
+        let now = Local::now().naive_local();
+        let memo = memo_template::instantiate(template, title, folder_id, now.date(), now.time());
+        folder_tree::validate_parent(&folders, memo.folder_id.as_deref())?;
+        memos.push(memo.clone());
+
+        crate::memo::save_memos(&memos)?;
+        state.memo_index.lock().unwrap().upsert(&memo);
+        state.memo_links.lock().unwrap().update(&memo);
+
+        memo
+    };
+
+    events::emit(&app, [ChangeEvent::MemoCreated(memo.clone())]);
+    Ok(memo)
+}
+
+// ========================================
+// デイリーノート関連コマンド
+// ========================================
+
+#[tauri::command]
+pub fn get_daily_note_settings(state: State<AppState>) -> DailyNoteSettings {
+    let store = state.memo_templates.lock().unwrap();
+    store.daily_note.clone()
+}
+
+#[tauri::command]
+pub async fn update_daily_note_settings(
+    state: State<'_, AppState>,
+    settings: DailyNoteSettings,
+) -> Result<DailyNoteSettings, String> {
+    let mut store = state.memo_templates.lock().unwrap();
+    {
+        let folders = state.folders.lock().unwrap();
+        memo_template::validate_daily_note_settings(&settings, &folders, &store.templates)?;
+    }
+    store.daily_note = settings.clone();
+
+    memo_template::save_memo_templates(&store)?;
+
+    Ok(settings)
+}
+
+/// 日付（`YYYY-MM-DD`、省略時は今日）のデイリーノートを返す（無ければ作成する）
+#[tauri::command]
+pub async fn get_or_create_daily_note(
+    state: State<'_, AppState>,
+    app: AppHandle,
+    date: Option<String>,
+) -> Result<Memo, String> {
+    let now = Local::now().naive_local();
+    let date = match date {
+        Some(date) => NaiveDate::parse_from_str(date.trim(), "%Y-%m-%d")
+            .map_err(|_| format!("Invalid date '{}'", date))?,
+        None => now.date(),
+    };
+
+    let memo = {
+        let store = state.memo_templates.lock().unwrap();
+        let folders = state.folders.lock().unwrap();
+        let mut memos = state.memos.lock().unwrap();
+
+        if let Some(memo) = memo_template::find_daily_note(&memos, &store.daily_note, date) {
+            return Ok(memo.clone());
+        }
+
+        folder_tree::validate_parent(&folders, store.daily_note.folder_id.as_deref())?;
+        let memo = memo_template::new_daily_note(&store, date, now.time())?;
+        memos.push(memo.clone());
+
+        crate::memo::save_memos(&memos)?;
+        state.memo_index.lock().unwrap().upsert(&memo);
+        state.memo_links.lock().unwrap().update(&memo);
+
+        memo
+    };
+
+    events::emit(&app, [ChangeEvent::MemoCreated(memo.clone())]);
+    Ok(memo)
+}
@@ -0,0 +1,73 @@
+// メモのMarkdownファイルへの書き出し・読み込み関連のTauriコマンド
+
+use std::path::PathBuf;
+use tauri::{AppHandle, State};
+
+use crate::events::{self, ChangeEvent};
+use crate::memo::Memo;
+use crate::memo_vault::{self, VaultExportReport, VaultImportReport};
+use crate::AppState;
+
+/// すべてのメモを `path` 以下に `.md` ファイル（前置き付き）として書き出す
+#[tauri::command]
+pub async fn export_memos_to_directory(
+    state: State<'_, AppState>,
+    path: String,
+) -> Result<VaultExportReport, String> {
+    let (folders, memos) = {
+        let folders = state.folders.lock().unwrap();
+        let memos = state.memos.lock().unwrap();
+        (folders.clone(), memos.clone())
+    };
+
+    memo_vault::export_vault(&memos, &folders, &PathBuf::from(path))
+}
+
+/// `path` 以下の `.md` ファイルをメモとして読み込む
+///
+/// 前置きの `id` が既存のメモと一致するファイルはそのメモを更新する。
+#[tauri::command]
+pub async fn import_memos_from_directory(
+    state: State<'_, AppState>,
+    app: AppHandle,
+    path: String,
+) -> Result<VaultImportReport, String> {
+    let (report, changes) = {
+        let mut folders = state.folders.lock().unwrap();
+        let mut memos = state.memos.lock().unwrap();
+
+        let report = memo_vault::import_vault(&mut memos, &mut folders, &PathBuf::from(path))?;
+        if !report.created_folders.is_empty() {
+            crate::memo::save_folders(&folders)?;
+        }
+
+        let changed = |ids: &[String]| -> Vec<Memo> {
+            memos
+                .iter()
+                .filter(|m| ids.contains(&m.id))
+                .cloned()
+                .collect()
+        };
+        let created = changed(&report.created);
+        let updated = changed(&report.updated);
+        if !created.is_empty() || !updated.is_empty() {
+            crate::memo::save_memos(&memos)?;
+            let mut memo_index = state.memo_index.lock().unwrap();
+            let mut memo_links = state.memo_links.lock().unwrap();
+            for memo in created.iter().chain(&updated) {
+                memo_index.upsert(memo);
+                memo_links.update(memo);
+            }
+        }
+
+        let changes: Vec<ChangeEvent> = created
+            .into_iter()
+            .map(ChangeEvent::MemoCreated)
+            .chain(updated.into_iter().map(ChangeEvent::MemoUpdated))
+            .collect();
+        (report, changes)
+    };
+
+    events::emit(&app, changes);
+    Ok(report)
+}
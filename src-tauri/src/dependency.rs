@@ -0,0 +1,199 @@
+// タスク間の依存関係（検証・クエリ）
+
+use std::collections::{HashMap, HashSet, VecDeque};
+
+use crate::task::Task;
+
+/// 依存関係を検証し、重複を取り除いたリストを返す
+///
+/// `task_id` が `dependencies` を持つと仮定したとき、存在しないIDへの参照、
+/// 自分自身への依存、循環依存があればエラーにする。
+pub fn validate_dependencies(
+    tasks: &[Task],
+    task_id: i32,
+    dependencies: Option<Vec<i32>>,
+) -> Result<Option<Vec<i32>>, String> {
+    let Some(dependencies) = dependencies else {
+        return Ok(None);
+    };
+
+    let mut unique = Vec::new();
+    for id in dependencies {
+        if id == task_id {
+            return Err("A task cannot depend on itself".to_string());
+        }
+        if !tasks.iter().any(|t| t.id == id) {
+            return Err(format!("Dependency task {} does not exist", id));
+        }
+        if !unique.contains(&id) {
+            unique.push(id);
+        }
+    }
+
+    if let Some(cycle) = find_cycle(tasks, task_id, &unique) {
+        let path = cycle
+            .iter()
+            .map(|id| id.to_string())
+            .collect::<Vec<_>>()
+            .join(" -> ");
+        return Err(format!("Dependency cycle detected: {}", path));
+    }
+
+    Ok(Some(unique))
+}
+
+/// `task_id` の依存を `dependencies` に置き換えたときの循環を探す
+///
+/// 見つかった場合は `task_id` から始まり `task_id` で終わるパスを返す。
+fn find_cycle(tasks: &[Task], task_id: i32, dependencies: &[i32]) -> Option<Vec<i32>> {
+    let graph = dependency_map(tasks);
+
+    // BFS で task_id に戻ってくる経路を探す
+    let mut parent: HashMap<i32, i32> = HashMap::new();
+    let mut visited: HashSet<i32> = HashSet::new();
+    let mut queue: VecDeque<i32> = VecDeque::new();
+    for &dep in dependencies {
+        if visited.insert(dep) {
+            parent.insert(dep, task_id);
+            queue.push_back(dep);
+        }
+    }
+
+    while let Some(current) = queue.pop_front() {
+        for &next in graph.get(&current).map(Vec::as_slice).unwrap_or_default() {
+            if next == task_id {
+                let mut path = vec![task_id, current];
+                let mut node = current;
+                while let Some(&p) = parent.get(&node) {
+                    if p == task_id {
+                        break;
+                    }
+                    path.push(p);
+                    node = p;
+                }
+                path[1..].reverse();
+                path.push(task_id);
+                return Some(path);
+            }
+            if visited.insert(next) {
+                parent.insert(next, current);
+                queue.push_back(next);
+            }
+        }
+    }
+
+    None
+}
+
+/// 削除されたタスクへの参照を取り除く（参照を取り除いたタスクのIDを返す）
+pub fn remove_dependency_references(tasks: &mut [Task], deleted_id: i32) -> Vec<i32> {
+    let mut changed = Vec::new();
+    for task in tasks.iter_mut() {
+        if let Some(dependencies) = task.dependencies.as_mut() {
+            let before = dependencies.len();
+            dependencies.retain(|&id| id != deleted_id);
+            if dependencies.len() != before {
+                changed.push(task.id);
+                if dependencies.is_empty() {
+                    task.dependencies = None;
+                }
+            }
+        }
+    }
+    changed
+}
+
+/// 未完了の依存先タスクのID
+pub fn incomplete_blockers(tasks: &[Task], task: &Task) -> Vec<i32> {
+    task.dependencies
+        .iter()
+        .flatten()
+        .filter(|&&id| tasks.iter().any(|t| t.id == id && !t.completed))
+        .copied()
+        .collect()
+}
+
+/// 未完了の依存先があるため着手できないタスク
+pub fn blocked_tasks(tasks: &[Task]) -> Vec<Task> {
+    tasks
+        .iter()
+        .filter(|t| !t.completed && !incomplete_blockers(tasks, t).is_empty())
+        .cloned()
+        .collect()
+}
+
+/// 依存先がすべて完了しており着手できる未完了タスク
+pub fn ready_tasks(tasks: &[Task]) -> Vec<Task> {
+    tasks
+        .iter()
+        .filter(|t| !t.completed && incomplete_blockers(tasks, t).is_empty())
+        .cloned()
+        .collect()
+}
+
+/// 依存先が先に来る順序のタスクID（ガントチャート用）
+///
+/// 依存関係の無いタスク同士は元の並び順を保つ。既存データに循環が
+/// 残っている場合、循環に含まれるタスクは末尾に元の順序で並べる。
+pub fn topological_order(tasks: &[Task]) -> Vec<i32> {
+    let graph = dependency_map(tasks);
+    let position: HashMap<i32, usize> = tasks.iter().enumerate().map(|(i, t)| (t.id, i)).collect();
+
+    let mut remaining: HashMap<i32, usize> = HashMap::new();
+    let mut dependents: HashMap<i32, Vec<i32>> = HashMap::new();
+    for task in tasks {
+        let deps = &graph[&task.id];
+        remaining.insert(task.id, deps.len());
+        for &dep in deps {
+            dependents.entry(dep).or_default().push(task.id);
+        }
+    }
+
+    let mut order = Vec::with_capacity(tasks.len());
+    let mut ready: Vec<i32> = tasks
+        .iter()
+        .filter(|t| remaining[&t.id] == 0)
+        .map(|t| t.id)
+        .collect();
+
+    while !ready.is_empty() {
+        // Always take the earliest task in the original order
+        ready.sort_by_key(|id| std::cmp::Reverse(position[id]));
+        let id = ready.pop().unwrap();
+        order.push(id);
+        for &dependent in dependents.get(&id).map(Vec::as_slice).unwrap_or_default() {
+            let count = remaining.get_mut(&dependent).unwrap();
+            *count -= 1;
+            if *count == 0 {
+                ready.push(dependent);
+            }
+        }
+    }
+
+    for task in tasks {
+        if !order.contains(&task.id) {
+            order.push(task.id);
+        }
+    }
+
+    order
+}
+
+/// タスクID → 存在する依存先IDの一覧
+fn dependency_map(tasks: &[Task]) -> HashMap<i32, Vec<i32>> {
+    let ids: HashSet<i32> = tasks.iter().map(|t| t.id).collect();
+    tasks
+        .iter()
+        .map(|t| {
+            let mut seen = HashSet::new();
+            let deps: Vec<i32> = t
+                .dependencies
+                .iter()
+                .flatten()
+                .filter(|&&id| ids.contains(&id) && id != t.id && seen.insert(id))
+                .copied()
+                .collect();
+            (t.id, deps)
+        })
+        .collect()
+}
@@ -0,0 +1,310 @@
+// タスク・メモ・書籍・読書メモ・予定の間のリンク
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::PathBuf;
+use uuid::Uuid;
+
+use crate::calendar::CalendarEvent;
+use crate::memo::Memo;
+use crate::reading_memo::{ReadingBook, ReadingNote};
+use crate::task::Task;
+
+/// リンクできるエンティティの種類
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum EntityKind {
+    Task,
+    Memo,
+    Book,
+    ReadingNote,
+    CalendarEvent,
+}
+
+/// エンティティへの参照（タスクのIDも文字列で持つ）
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash)]
+pub struct EntityRef {
+    pub kind: EntityKind,
+    pub id: String,
+}
+
+impl EntityRef {
+    pub fn new(kind: EntityKind, id: impl ToString) -> Self {
+        Self {
+            kind,
+            id: id.to_string(),
+        }
+    }
+
+    pub fn task(id: i32) -> Self {
+        Self::new(EntityKind::Task, id)
+    }
+}
+
+/// 2つのエンティティ間のリンク（向きは作成時の記録のみで、一覧ではどちらからも辿れる）
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct EntityLink {
+    pub id: String,
+    pub source: EntityRef,
+    pub target: EntityRef,
+    /// リンクの説明（「議事録」「元の行」など）
+    #[serde(default)]
+    pub label: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl EntityLink {
+    pub fn new(source: EntityRef, target: EntityRef, label: Option<String>) -> Self {
+        Self {
+            id: Uuid::new_v4().to_string(),
+            source,
+            target,
+            label: label.filter(|l| !l.trim().is_empty()),
+            created_at: Utc::now(),
+        }
+    }
+
+    /// `entity` から見たリンク先（`entity` がどちらの端でもなければ `None`）
+    pub fn other_end(&self, entity: &EntityRef) -> Option<&EntityRef> {
+        if self.source == *entity {
+            Some(&self.target)
+        } else if self.target == *entity {
+            Some(&self.source)
+        } else {
+            None
+        }
+    }
+
+    fn connects(&self, a: &EntityRef, b: &EntityRef) -> bool {
+        self.other_end(a) == Some(b)
+    }
+}
+
+/// リンク先の表示用の情報
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct LinkedEntity {
+    pub link_id: String,
+    pub entity: EntityRef,
+    pub title: String,
+    pub label: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// リンク先の解決に使うデータ
+pub struct EntitySources<'a> {
+    pub tasks: &'a [Task],
+    pub memos: &'a [Memo],
+    pub books: &'a [ReadingBook],
+    pub events: &'a [CalendarEvent],
+}
+
+impl EntitySources<'_> {
+    /// エンティティの表示名（存在しなければ `None`）
+    pub fn title(&self, entity: &EntityRef) -> Option<String> {
+        match entity.kind {
+            EntityKind::Task => self
+                .tasks
+                .iter()
+                .find(|t| t.id.to_string() == entity.id)
+                .map(|t| t.description.clone()),
+            EntityKind::Memo => self
+                .memos
+                .iter()
+                .find(|m| m.id == entity.id)
+                .map(|m| m.title.clone()),
+            EntityKind::Book => self
+                .books
+                .iter()
+                .find(|b| b.id == entity.id)
+                .map(|b| b.title.clone()),
+            EntityKind::ReadingNote => {
+                find_note(self.books, &entity.id).map(|(book, note)| match note.page_number {
+                    Some(page) => format!("{} (p. {})", book.title, page),
+                    None => book.title.clone(),
+                })
+            }
+            EntityKind::CalendarEvent => self
+                .events
+                .iter()
+                .find(|e| e.id == entity.id)
+                .map(|e| e.title.clone()),
+        }
+    }
+}
+
+/// 読書メモとそれを含む書籍
+pub fn find_note<'a>(
+    books: &'a [ReadingBook],
+    note_id: &str,
+) -> Option<(&'a ReadingBook, &'a ReadingNote)> {
+    books.iter().find_map(|book| {
+        book.notes
+            .iter()
+            .find(|n| n.id == note_id)
+            .map(|note| (book, note))
+    })
+}
+
+/// リンクを追加する（同じ組み合わせのリンクが既にあればそれを返す）
+///
+/// 戻り値の `bool` は新しく追加したかどうか。
+pub fn add_link(
+    links: &mut Vec<EntityLink>,
+    sources: &EntitySources,
+    source: EntityRef,
+    target: EntityRef,
+    label: Option<String>,
+) -> Result<(EntityLink, bool), String> {
+    if source == target {
+        return Err("Cannot link an entity to itself".to_string());
+    }
+    for entity in [&source, &target] {
+        if sources.title(entity).is_none() {
+            return Err(format!("{:?} {} not found", entity.kind, entity.id));
+        }
+    }
+    if let Some(existing) = links.iter().find(|l| l.connects(&source, &target)) {
+        return Ok((existing.clone(), false));
+    }
+
+    let link = EntityLink::new(source, target, label);
+    links.push(link.clone());
+    Ok((link, true))
+}
+
+/// `entity` のリンク先（作成順）
+///
+/// 解決できないリンク先（削除済み）は含めない。
+pub fn linked_entities(
+    links: &[EntityLink],
+    sources: &EntitySources,
+    entity: &EntityRef,
+) -> Vec<LinkedEntity> {
+    links
+        .iter()
+        .filter_map(|link| {
+            let other = link.other_end(entity)?;
+            Some(LinkedEntity {
+                link_id: link.id.clone(),
+                entity: other.clone(),
+                title: sources.title(other)?,
+                label: link.label.clone(),
+                created_at: link.created_at,
+            })
+        })
+        .collect()
+}
+
+/// 削除されたエンティティへのリンクを取り除き、取り除いたリンクを返す
+pub fn remove_links_to(links: &mut Vec<EntityLink>, removed: &[EntityRef]) -> Vec<EntityLink> {
+    let mut dangling = Vec::new();
+    links.retain(|link| {
+        if removed.contains(&link.source) || removed.contains(&link.target) {
+            dangling.push(link.clone());
+            false
+        } else {
+            true
+        }
+    });
+    dangling
+}
+
+/// 取り除いたリンクを戻し、戻したリンクを返す
+///
+/// どちらかの端がその後に削除されたリンクや、同じ組み合わせのリンクが既にあるものは戻さない。
+pub fn restore_links(
+    links: &mut Vec<EntityLink>,
+    sources: &EntitySources,
+    removed: Vec<EntityLink>,
+) -> Vec<EntityLink> {
+    let mut restored = Vec::new();
+    for link in removed {
+        let resolvable =
+            sources.title(&link.source).is_some() && sources.title(&link.target).is_some();
+        if resolvable && !links.iter().any(|l| l.connects(&link.source, &link.target)) {
+            links.push(link.clone());
+            restored.push(link);
+        }
+    }
+    restored
+}
+
+/// メモの1行からタスクの説明文を取り出す（リストの記号・チェックボックス・見出し記号を除く）
+pub fn task_text_from_line(line: &str) -> String {
+    let mut text = line.trim();
+    text = text.trim_start_matches('#').trim_start();
+    for marker in ["- ", "* ", "+ "] {
+        if let Some(rest) = text.strip_prefix(marker) {
+            text = rest.trim_start();
+            break;
+        }
+    }
+    // Ordered list markers such as "1. " or "2) "
+    let digits = text.chars().take_while(char::is_ascii_digit).count();
+    if digits > 0 {
+        let rest = &text[digits..];
+        if let Some(rest) = rest.strip_prefix(". ").or_else(|| rest.strip_prefix(") ")) {
+            text = rest.trim_start();
+        }
+    }
+    for checkbox in ["[ ] ", "[x] ", "[X] "] {
+        if let Some(rest) = text.strip_prefix(checkbox) {
+            text = rest.trim_start();
+            break;
+        }
+    }
+    text.to_string()
+}
+
+/// 読書メモから作るメモのタイトルと本文（引用・感想・出典）
+pub fn memo_from_note(book: &ReadingBook, note: &ReadingNote) -> (String, String) {
+    let title = match note.page_number {
+        Some(page) => format!("{} p.{}", book.title, page),
+        None => book.title.clone(),
+    };
+
+    let mut sections = Vec::new();
+    if let Some(quote) = note.quote.as_deref().filter(|q| !q.trim().is_empty()) {
+        let quoted: Vec<String> = quote.lines().map(|line| format!("> {}", line)).collect();
+        sections.push(quoted.join("\n"));
+    }
+    if !note.comment.trim().is_empty() {
+        sections.push(note.comment.trim().to_string());
+    }
+    let source = match (&book.author, note.page_number) {
+        (Some(author), Some(page)) => format!("{} / {} p.{}", book.title, author, page),
+        (Some(author), None) => format!("{} / {}", book.title, author),
+        (None, Some(page)) => format!("{} p.{}", book.title, page),
+        (None, None) => book.title.clone(),
+    };
+    sections.push(format!("Source: {}", source));
+
+    (title, sections.join("\n\n"))
+}
+
+pub fn get_entity_links_file_path() -> PathBuf {
+    let app_data = dirs::data_local_dir().unwrap();
+    app_data
+        .join("com.noruno.platform")
+        .join("entity_links.json")
+}
+
+pub fn load_entity_links() -> Result<Vec<EntityLink>, String> {
+    let path = get_entity_links_file_path();
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+
+    let contents = fs::read_to_string(&path)
+        .map_err(|e| format!("Failed to read entity_links file: {}", e))?;
+
+    serde_json::from_str(&contents).map_err(|e| format!("Failed to parse entity_links: {}", e))
+}
+
+pub fn save_entity_links(links: &Vec<EntityLink>) -> Result<(), String> {
+    let path = get_entity_links_file_path();
+    let json = serde_json::to_string_pretty(links).map_err(|e| e.to_string())?;
+    fs::write(&path, json).map_err(|e| format!("Failed to save entity_links: {}", e))?;
+    Ok(())
+}
@@ -0,0 +1,152 @@
+// 変更通知イベント（フロントエンドと他のウィンドウの同期用）
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use serde_json::{json, Value};
+use tauri::{AppHandle, Emitter};
+
+use crate::entity_links::{EntityLink, EntityRef};
+use crate::memo::Memo;
+use crate::reading_memo::{ReadingBook, ReadingNote, ReadingSession};
+use crate::task::Task;
+
+/// 書籍の読書メモ・読書セッションの変更内容
+///
+/// 書籍の `updated_at` も変わるため、部分更新の競合検出用に変更後の値を含める。
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct BookItemChange<T> {
+    pub book_id: String,
+    pub book_updated_at: DateTime<Utc>,
+    pub item: T,
+}
+
+/// 複数のタスクを変更するコマンドの結果
+#[derive(Debug, Clone, Default, Serialize, PartialEq)]
+pub struct TaskChanges {
+    pub created: Vec<Task>,
+    pub updated: Vec<Task>,
+    pub deleted: Vec<i32>,
+}
+
+impl TaskChanges {
+    /// 変更後のタスク一覧から、作成・更新したIDのタスクを取り出す
+    ///
+    /// タスクは一覧の順に並べる。作成したタスクは更新には含めず、一覧に無いIDは無視する。
+    pub fn from_ids(tasks: &[Task], created: &[i32], updated: &[i32], deleted: &[i32]) -> Self {
+        let pick = |matches: &dyn Fn(i32) -> bool| -> Vec<Task> {
+            tasks.iter().filter(|t| matches(t.id)).cloned().collect()
+        };
+        let mut deleted = deleted.to_vec();
+        deleted.sort_unstable();
+        deleted.dedup();
+        TaskChanges {
+            created: pick(&|id| created.contains(&id)),
+            updated: pick(&|id| updated.contains(&id) && !created.contains(&id)),
+            deleted,
+        }
+    }
+
+    /// 削除されたタスク（リンクを取り除く対象）
+    pub fn deleted_refs(&self) -> Vec<EntityRef> {
+        self.deleted.iter().map(|&id| EntityRef::task(id)).collect()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.created.is_empty() && self.updated.is_empty() && self.deleted.is_empty()
+    }
+
+    pub fn events(&self) -> Vec<ChangeEvent> {
+        let created = self.created.iter().cloned().map(ChangeEvent::TaskCreated);
+        let updated = self.updated.iter().cloned().map(ChangeEvent::TaskUpdated);
+        let deleted = self.deleted.iter().copied().map(ChangeEvent::TaskDeleted);
+        created.chain(updated).chain(deleted).collect()
+    }
+}
+
+/// 変更通知イベント
+///
+/// 削除のイベントは `{ "id": ... }` だけを送る。
+#[derive(Debug, Clone, PartialEq)]
+pub enum ChangeEvent {
+    TaskCreated(Task),
+    TaskUpdated(Task),
+    TaskDeleted(i32),
+    MemoCreated(Memo),
+    MemoUpdated(Memo),
+    MemoDeleted(String),
+    BookCreated(ReadingBook),
+    BookUpdated(ReadingBook),
+    BookDeleted(String),
+    BookNoteAdded(BookItemChange<ReadingNote>),
+    BookNoteUpdated(BookItemChange<ReadingNote>),
+    BookNoteDeleted(BookItemChange<String>),
+    BookSessionAdded(BookItemChange<ReadingSession>),
+    BookSessionUpdated(BookItemChange<ReadingSession>),
+    BookSessionDeleted(BookItemChange<String>),
+    EntityLinkAdded(EntityLink),
+    EntityLinkRemoved(String),
+}
+
+impl ChangeEvent {
+    /// フロントエンドで `listen` するイベント名
+    pub fn name(&self) -> &'static str {
+        match self {
+            ChangeEvent::TaskCreated(_) => "task-created",
+            ChangeEvent::TaskUpdated(_) => "task-updated",
+            ChangeEvent::TaskDeleted(_) => "task-deleted",
+            ChangeEvent::MemoCreated(_) => "memo-created",
+            ChangeEvent::MemoUpdated(_) => "memo-updated",
+            ChangeEvent::MemoDeleted(_) => "memo-deleted",
+            ChangeEvent::BookCreated(_) => "book-created",
+            ChangeEvent::BookUpdated(_) => "book-updated",
+            ChangeEvent::BookDeleted(_) => "book-deleted",
+            ChangeEvent::BookNoteAdded(_) => "book-note-added",
+            ChangeEvent::BookNoteUpdated(_) => "book-note-updated",
+            ChangeEvent::BookNoteDeleted(_) => "book-note-deleted",
+            ChangeEvent::BookSessionAdded(_) => "book-session-added",
+            ChangeEvent::BookSessionUpdated(_) => "book-session-updated",
+            ChangeEvent::BookSessionDeleted(_) => "book-session-deleted",
+            ChangeEvent::EntityLinkAdded(_) => "entity-link-added",
+            ChangeEvent::EntityLinkRemoved(_) => "entity-link-removed",
+        }
+    }
+
+    /// イベントの内容（変更されたエンティティのみ）
+    pub fn payload(&self) -> Value {
+        let value = match self {
+            ChangeEvent::TaskCreated(task) | ChangeEvent::TaskUpdated(task) => {
+                serde_json::to_value(task)
+            }
+            ChangeEvent::TaskDeleted(id) => Ok(json!({ "id": id })),
+            ChangeEvent::MemoCreated(memo) | ChangeEvent::MemoUpdated(memo) => {
+                serde_json::to_value(memo)
+            }
+            ChangeEvent::MemoDeleted(id) => Ok(json!({ "id": id })),
+            ChangeEvent::BookCreated(book) | ChangeEvent::BookUpdated(book) => {
+                serde_json::to_value(book)
+            }
+            ChangeEvent::BookDeleted(id) => Ok(json!({ "id": id })),
+            ChangeEvent::BookNoteAdded(change) | ChangeEvent::BookNoteUpdated(change) => {
+                serde_json::to_value(change)
+            }
+            ChangeEvent::BookSessionAdded(change) | ChangeEvent::BookSessionUpdated(change) => {
+                serde_json::to_value(change)
+            }
+            ChangeEvent::BookNoteDeleted(change) | ChangeEvent::BookSessionDeleted(change) => {
+                serde_json::to_value(change)
+            }
+            ChangeEvent::EntityLinkAdded(link) => serde_json::to_value(link),
+            ChangeEvent::EntityLinkRemoved(id) => Ok(json!({ "id": id })),
+        };
+        value.unwrap_or(Value::Null)
+    }
+}
+
+/// イベントを全ウィンドウに送る
+///
+/// 送信の失敗（ウィンドウが閉じられている等）は変更そのものの失敗ではないため無視する。
+pub fn emit(app: &AppHandle, events: impl IntoIterator<Item = ChangeEvent>) {
+    for event in events {
+        let _ = app.emit(event.name(), event.payload());
+    }
+}
@@ -0,0 +1,198 @@
+// メモのフォルダ階層（親の検証・循環の防止・移動・削除・ツリー表示）
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+
+use crate::memo::{Folder, Memo};
+
+/// 親フォルダが存在することを確認する（`None` は最上位）
+pub fn validate_parent(folders: &[Folder], parent_id: Option<&str>) -> Result<(), String> {
+    match parent_id {
+        Some(parent_id) if !folders.iter().any(|f| f.id == parent_id) => {
+            Err(format!("Parent folder {} not found", parent_id))
+        }
+        _ => Ok(()),
+    }
+}
+
+/// `id` の子孫フォルダのID（`id` 自身は含まない、親が先）
+pub fn descendants(folders: &[Folder], id: &str) -> Vec<String> {
+    let mut result: Vec<String> = Vec::new();
+    let mut visited: HashSet<&str> = HashSet::from([id]);
+    let mut index = 0;
+    let mut current = id.to_string();
+    loop {
+        for folder in folders {
+            if folder.parent_id.as_deref() == Some(current.as_str())
+                && visited.insert(folder.id.as_str())
+            {
+                result.push(folder.id.clone());
+            }
+        }
+        match result.get(index) {
+            Some(next) => current = next.clone(),
+            None => return result,
+        }
+        index += 1;
+    }
+}
+
+/// フォルダを別の親（`None` は最上位）の下に移動する
+///
+/// 自分自身や子孫の下には移動できない。
+pub fn move_folder(
+    folders: &mut [Folder],
+    id: &str,
+    parent_id: Option<String>,
+) -> Result<(), String> {
+    if !folders.iter().any(|f| f.id == id) {
+        return Err(format!("Folder {} not found", id));
+    }
+    validate_parent(folders, parent_id.as_deref())?;
+    if let Some(parent_id) = &parent_id {
+        if parent_id == id || descendants(folders, id).contains(parent_id) {
+            return Err("Cannot move a folder into itself or one of its subfolders".to_string());
+        }
+    }
+
+    if let Some(folder) = folders.iter_mut().find(|f| f.id == id) {
+        folder.parent_id = parent_id;
+    }
+    Ok(())
+}
+
+/// フォルダ削除の方法
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct DeleteFolderOptions {
+    /// 子フォルダもまとめて削除する（省略時は子フォルダを削除するフォルダの親に付け替える）
+    #[serde(default)]
+    pub recursive: bool,
+    /// 削除するフォルダのメモを親フォルダに移す（省略時はフォルダなしにする）
+    #[serde(default)]
+    pub move_memos_to_parent: bool,
+}
+
+/// フォルダを削除し、移動したメモのIDを返す
+///
+/// メモ自体は削除しない。移動したメモは `updated_at` を `now` にする
+/// （同期先のファイルを書き直させるため）。
+pub fn delete_folder(
+    folders: &mut Vec<Folder>,
+    memos: &mut [Memo],
+    id: &str,
+    options: &DeleteFolderOptions,
+    now: DateTime<Utc>,
+) -> Result<Vec<String>, String> {
+    let parent_id = folders
+        .iter()
+        .find(|f| f.id == id)
+        .map(|f| f.parent_id.clone())
+        .ok_or_else(|| format!("Folder {} not found", id))?;
+
+    let mut removed = vec![id.to_string()];
+    if options.recursive {
+        removed.extend(descendants(folders, id));
+    } else {
+        for folder in folders.iter_mut() {
+            if folder.parent_id.as_deref() == Some(id) {
+                folder.parent_id = parent_id.clone();
+            }
+        }
+    }
+    folders.retain(|f| !removed.contains(&f.id));
+
+    let destination = if options.move_memos_to_parent {
+        parent_id
+    } else {
+        None
+    };
+    let mut moved = Vec::new();
+    for memo in memos.iter_mut() {
+        if memo.folder_id.as_ref().is_some_and(|f| removed.contains(f)) {
+            memo.folder_id = destination.clone();
+            memo.updated_at = now;
+            moved.push(memo.id.clone());
+        }
+    }
+    Ok(moved)
+}
+
+/// フォルダツリーの節
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct FolderNode {
+    pub id: String,
+    pub name: String,
+    pub parent_id: Option<String>,
+    /// このフォルダ直下のメモ数
+    pub memo_count: usize,
+    /// 子孫のフォルダを含むメモ数
+    pub total_memo_count: usize,
+    pub children: Vec<FolderNode>,
+}
+
+/// フォルダツリー全体
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct FolderTree {
+    pub roots: Vec<FolderNode>,
+    /// フォルダに入っていないメモ数
+    pub unfiled_memo_count: usize,
+}
+
+/// 入れ子のフォルダツリーを作る（フォルダの並びは保存順）
+///
+/// 親が存在しないフォルダや、既存データの循環に含まれるフォルダは最上位に置く。
+pub fn build_tree(folders: &[Folder], memos: &[Memo]) -> FolderTree {
+    let exists = |id: &str| folders.iter().any(|f| f.id == id);
+    let mut visited: HashSet<String> = HashSet::new();
+    let mut roots: Vec<FolderNode> = Vec::new();
+
+    for folder in folders {
+        let is_root = folder.parent_id.as_deref().is_none_or(|p| !exists(p));
+        if is_root {
+            roots.push(build_node(folder, folders, memos, &mut visited));
+        }
+    }
+    for folder in folders {
+        if !visited.contains(&folder.id) {
+            roots.push(build_node(folder, folders, memos, &mut visited));
+        }
+    }
+
+    FolderTree {
+        roots,
+        unfiled_memo_count: memos
+            .iter()
+            .filter(|m| m.folder_id.as_deref().is_none_or(|f| !exists(f)))
+            .count(),
+    }
+}
+
+fn build_node(
+    folder: &Folder,
+    folders: &[Folder],
+    memos: &[Memo],
+    visited: &mut HashSet<String>,
+) -> FolderNode {
+    visited.insert(folder.id.clone());
+    let mut children = Vec::new();
+    for child in folders {
+        if child.parent_id.as_deref() == Some(folder.id.as_str()) && !visited.contains(&child.id) {
+            children.push(build_node(child, folders, memos, visited));
+        }
+    }
+
+    let memo_count = memos
+        .iter()
+        .filter(|m| m.folder_id.as_deref() == Some(folder.id.as_str()))
+        .count();
+    let total_memo_count = memo_count + children.iter().map(|c| c.total_memo_count).sum::<usize>();
+    FolderNode {
+        id: folder.id.clone(),
+        name: folder.name.clone(),
+        parent_id: folder.parent_id.clone(),
+        memo_count,
+        total_memo_count,
+        children,
+    }
+}
@@ -0,0 +1,270 @@
+// タスク・メモ・読書記録・予定を横断する検索（コマンドパレット用）
+
+use serde::Serialize;
+
+use crate::calendar::CalendarEvent;
+use crate::memo::{Folder, Memo};
+use crate::memo_search::{self, MemoIndex, MemoSearchQuery, Snippet};
+use crate::reading_memo::ReadingBook;
+use crate::task::Task;
+
+/// 件数を指定しない場合の検索結果の上限
+pub const DEFAULT_GLOBAL_SEARCH_LIMIT: usize = 20;
+
+/// フィールドの重み（見出し、タグ・著者・引用、本文）
+const TITLE_WEIGHT: f64 = 2.0;
+const SECONDARY_WEIGHT: f64 = 1.5;
+const BODY_WEIGHT: f64 = 1.0;
+
+/// 検索結果の対象（種類と、開くためのID）
+#[derive(Debug, Clone, Serialize, PartialEq)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum SearchTarget {
+    Task { task_id: i32 },
+    Subtask { task_id: i32, subtask_id: i32 },
+    Memo { memo_id: String },
+    Book { book_id: String },
+    ReadingNote { book_id: String, note_id: String },
+    CalendarEvent { event_id: String },
+}
+
+impl SearchTarget {
+    /// 同じスコアの場合の並び順
+    fn rank(&self) -> u8 {
+        match self {
+            SearchTarget::Task { .. } => 0,
+            SearchTarget::Subtask { .. } => 1,
+            SearchTarget::Memo { .. } => 2,
+            SearchTarget::Book { .. } => 3,
+            SearchTarget::ReadingNote { .. } => 4,
+            SearchTarget::CalendarEvent { .. } => 5,
+        }
+    }
+}
+
+/// 横断検索の結果
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct GlobalSearchHit {
+    #[serde(flatten)]
+    pub target: SearchTarget,
+    pub title: String,
+    pub title_highlights: Vec<[usize; 2]>,
+    /// 補足（サブタスクの親タスク、書籍・読書メモの著者、予定の開始日時）
+    pub context: Option<String>,
+    pub snippet: Option<Snippet>,
+    pub score: f64,
+}
+
+/// 検索の対象となるデータ
+pub struct SearchSources<'a> {
+    pub tasks: &'a [Task],
+    pub memos: &'a [Memo],
+    pub memo_index: &'a MemoIndex,
+    pub folders: &'a [Folder],
+    pub books: &'a [ReadingBook],
+    pub events: &'a [CalendarEvent],
+}
+
+fn tags_match(query: &MemoSearchQuery, tags: &[String]) -> bool {
+    query
+        .tags
+        .iter()
+        .all(|tag| tags.iter().any(|t| t.eq_ignore_ascii_case(tag)))
+}
+
+/// 本文のうち検索語を含む最初のフィールドから抜粋する（空のフィールドは除く）
+fn snippet_of(query: &MemoSearchQuery, bodies: &[&str]) -> Option<Snippet> {
+    let bodies: Vec<&str> = bodies
+        .iter()
+        .copied()
+        .filter(|body| !body.trim().is_empty())
+        .collect();
+    let matched = bodies
+        .iter()
+        .find(|body| !memo_search::highlights(body, query).is_empty())
+        .or(bodies.first())?;
+    Some(memo_search::snippet(matched, query))
+}
+
+fn hit(
+    query: &MemoSearchQuery,
+    target: SearchTarget,
+    title: &str,
+    context: Option<String>,
+    bodies: &[&str],
+    score: f64,
+) -> GlobalSearchHit {
+    GlobalSearchHit {
+        target,
+        title: title.to_string(),
+        title_highlights: memo_search::highlights(title, query),
+        context,
+        snippet: snippet_of(query, bodies),
+        score,
+    }
+}
+
+/// すべての種類を検索して関連度の高い順に返す
+///
+/// クエリの構文はメモの検索と同じ。`tag:` はタグを持つもの（タスク・サブタスクは親の
+/// タスク、読書メモは書籍のタグ）で絞り込み、`folder:` を指定した場合はメモのみを返す。
+pub fn search(sources: &SearchSources, query: &str, limit: usize) -> Vec<GlobalSearchHit> {
+    let query = MemoSearchQuery::parse(query);
+    if query.clauses.is_empty() {
+        return Vec::new();
+    }
+    let memos_only = !query.folders.is_empty();
+    let mut hits = Vec::new();
+
+    if !memos_only {
+        for task in sources.tasks {
+            if !tags_match(&query, &task.tags) {
+                continue;
+            }
+            let tags = task.tags.join("\n");
+            let fields = [
+                (task.description.as_str(), TITLE_WEIGHT),
+                (task.details.as_str(), BODY_WEIGHT),
+                (tags.as_str(), SECONDARY_WEIGHT),
+            ];
+            if let Some(score) = memo_search::score_fields(&query, &fields) {
+                hits.push(hit(
+                    &query,
+                    SearchTarget::Task { task_id: task.id },
+                    &task.description,
+                    None,
+                    &[&task.details],
+                    score,
+                ));
+            }
+
+            for subtask in crate::subtask::flatten(&task.subtasks) {
+                let fields = [
+                    (subtask.description.as_str(), TITLE_WEIGHT),
+                    (subtask.notes.as_str(), BODY_WEIGHT),
+                ];
+                if let Some(score) = memo_search::score_fields(&query, &fields) {
+                    hits.push(hit(
+                        &query,
+                        SearchTarget::Subtask {
+                            task_id: task.id,
+                            subtask_id: subtask.id,
+                        },
+                        &subtask.description,
+                        Some(task.description.clone()),
+                        &[&subtask.notes],
+                        score,
+                    ));
+                }
+            }
+        }
+    }
+
+    // The index narrows the candidates; scoring uses the same formula as the other kinds
+    for (memo, _) in sources
+        .memo_index
+        .ranked(sources.memos, sources.folders, &query)
+    {
+        let tags = memo.tags.join("\n");
+        let fields = [
+            (memo.title.as_str(), TITLE_WEIGHT),
+            (memo.content.as_str(), BODY_WEIGHT),
+            (tags.as_str(), SECONDARY_WEIGHT),
+        ];
+        if let Some(score) = memo_search::score_fields(&query, &fields) {
+            hits.push(hit(
+                &query,
+                SearchTarget::Memo {
+                    memo_id: memo.id.clone(),
+                },
+                &memo.title,
+                None,
+                &[&memo.content],
+                score,
+            ));
+        }
+    }
+
+    if !memos_only {
+        for book in sources.books {
+            if !tags_match(&query, &book.tags) {
+                continue;
+            }
+            let author = book.author.clone().unwrap_or_default();
+            let tags = book.tags.join("\n");
+            let fields = [
+                (book.title.as_str(), TITLE_WEIGHT),
+                (author.as_str(), SECONDARY_WEIGHT),
+                (book.summary.as_str(), BODY_WEIGHT),
+                (tags.as_str(), SECONDARY_WEIGHT),
+            ];
+            if let Some(score) = memo_search::score_fields(&query, &fields) {
+                hits.push(hit(
+                    &query,
+                    SearchTarget::Book {
+                        book_id: book.id.clone(),
+                    },
+                    &book.title,
+                    book.author.clone(),
+                    &[&book.summary],
+                    score,
+                ));
+            }
+
+            for note in &book.notes {
+                let quote = note.quote.clone().unwrap_or_default();
+                let fields = [
+                    (quote.as_str(), SECONDARY_WEIGHT),
+                    (note.comment.as_str(), BODY_WEIGHT),
+                ];
+                if let Some(score) = memo_search::score_fields(&query, &fields) {
+                    let title = match note.page_number {
+                        Some(page) => format!("{} (p. {})", book.title, page),
+                        None => book.title.clone(),
+                    };
+                    hits.push(hit(
+                        &query,
+                        SearchTarget::ReadingNote {
+                            book_id: book.id.clone(),
+                            note_id: note.id.clone(),
+                        },
+                        &title,
+                        book.author.clone(),
+                        &[&quote, &note.comment],
+                        score,
+                    ));
+                }
+            }
+        }
+
+        // Calendar events have no tags
+        if query.tags.is_empty() {
+            for event in sources.events {
+                let fields = [
+                    (event.title.as_str(), TITLE_WEIGHT),
+                    (event.description.as_str(), BODY_WEIGHT),
+                ];
+                if let Some(score) = memo_search::score_fields(&query, &fields) {
+                    hits.push(hit(
+                        &query,
+                        SearchTarget::CalendarEvent {
+                            event_id: event.id.clone(),
+                        },
+                        &event.title,
+                        Some(event.start_datetime.clone()),
+                        &[&event.description],
+                        score,
+                    ));
+                }
+            }
+        }
+    }
+
+    hits.sort_by(|a, b| {
+        b.score
+            .total_cmp(&a.score)
+            .then_with(|| a.target.rank().cmp(&b.target.rank()))
+    });
+    hits.truncate(limit);
+    hits
+}
@@ -0,0 +1,298 @@
+// タスクグループ（メタデータ・並び順・アーカイブ・入れ子）とファイルI/O処理
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::fs;
+use std::io::Write;
+use std::path::PathBuf;
+use uuid::Uuid;
+
+use crate::task::Task;
+use crate::workflow::WorkflowState;
+
+/// タスクグループ構造体
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct TaskGroup {
+    pub id: String,
+    pub name: String,
+    #[serde(default)]
+    pub color: Option<String>,
+    #[serde(default)]
+    pub icon: Option<String>,
+    #[serde(default)]
+    pub sort_order: i32,
+    /// グループ内タスクの通知タイミングの既定値（分）
+    #[serde(default)]
+    pub notification_minutes: Option<i32>,
+    #[serde(default)]
+    pub archived: bool,
+    /// 親グループ（トップレベルは `None`）
+    #[serde(default)]
+    pub parent_id: Option<String>,
+    /// カンバンの状態一覧（空の場合は親グループまたは既定のワークフロー）
+    #[serde(default)]
+    pub workflow: Vec<WorkflowState>,
+}
+
+impl TaskGroup {
+    pub fn new(name: String, parent_id: Option<String>, sort_order: i32) -> Self {
+        Self {
+            id: Uuid::new_v4().to_string(),
+            name,
+            color: None,
+            icon: None,
+            sort_order,
+            notification_minutes: None,
+            archived: false,
+            parent_id,
+            workflow: Vec::new(),
+        }
+    }
+}
+
+/// 末尾に追加する際の並び順
+pub fn next_sort_order(groups: &[TaskGroup]) -> i32 {
+    groups
+        .iter()
+        .map(|g| g.sort_order)
+        .max()
+        .map_or(0, |max| max + 1)
+}
+
+/// 名前（大文字小文字を区別しない）でグループを探す
+pub fn find_by_name<'a>(groups: &'a [TaskGroup], name: &str) -> Option<&'a TaskGroup> {
+    let name = name.trim().to_lowercase();
+    groups.iter().find(|g| g.name.to_lowercase() == name)
+}
+
+/// 名前に一致するグループのIDを返す。無ければトップレベルに作成する
+pub fn ensure_group(groups: &mut Vec<TaskGroup>, name: &str) -> String {
+    if let Some(group) = find_by_name(groups, name) {
+        return group.id.clone();
+    }
+    let group = TaskGroup::new(name.trim().to_string(), None, next_sort_order(groups));
+    let id = group.id.clone();
+    groups.push(group);
+    id
+}
+
+/// グループIDの存在を確認し、空文字列は `None` として扱う（コマンド引数用）
+pub fn resolve_group_id(
+    groups: &[TaskGroup],
+    group_id: Option<String>,
+) -> Result<Option<String>, String> {
+    match group_id.filter(|id| !id.is_empty()) {
+        Some(id) if !groups.iter().any(|g| g.id == id) => {
+            Err(format!("Group {} does not exist", id))
+        }
+        other => Ok(other),
+    }
+}
+
+/// グループ名を取得する（グループ無し・不明なIDは空文字列）
+pub fn group_name(groups: &[TaskGroup], group_id: Option<&str>) -> String {
+    group_id
+        .and_then(|id| groups.iter().find(|g| g.id == id))
+        .map(|g| g.name.clone())
+        .unwrap_or_default()
+}
+
+/// 自身と親をたどったグループ（自身が先頭）
+pub fn ancestors<'a>(groups: &'a [TaskGroup], group_id: &str) -> Vec<&'a TaskGroup> {
+    let mut chain = Vec::new();
+    let mut current = groups.iter().find(|g| g.id == group_id);
+    while let Some(group) = current {
+        // 壊れたデータで親子関係が循環していても止まるようにする
+        if chain.iter().any(|g: &&TaskGroup| g.id == group.id) {
+            break;
+        }
+        chain.push(group);
+        current = group
+            .parent_id
+            .as_deref()
+            .and_then(|parent| groups.iter().find(|g| g.id == parent));
+    }
+    chain
+}
+
+/// 自身とすべての子孫グループのID
+pub fn descendant_ids(groups: &[TaskGroup], group_id: &str) -> Vec<String> {
+    let mut ids = vec![group_id.to_string()];
+    let mut index = 0;
+    while index < ids.len() {
+        for group in groups {
+            if group.parent_id.as_deref() == Some(ids[index].as_str()) && !ids.contains(&group.id) {
+                ids.push(group.id.clone());
+            }
+        }
+        index += 1;
+    }
+    ids
+}
+
+/// タスクに適用される通知タイミング
+///
+/// タスク自身の設定、所属グループ、親グループの順に探す。
+pub fn effective_notification_minutes(task: &Task, groups: &[TaskGroup]) -> Option<i32> {
+    task.notification_minutes.or_else(|| {
+        ancestors(groups, task.group_id.as_deref()?)
+            .iter()
+            .find_map(|g| g.notification_minutes)
+    })
+}
+
+/// 名前を検証する（空、または同じ親の下での重複はエラー）
+pub fn validate_group_name(
+    groups: &[TaskGroup],
+    name: &str,
+    parent_id: Option<&str>,
+    exclude_id: Option<&str>,
+) -> Result<(), String> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err("Group name cannot be empty".to_string());
+    }
+    let duplicate = groups.iter().any(|g| {
+        Some(g.id.as_str()) != exclude_id
+            && g.parent_id.as_deref() == parent_id
+            && g.name.to_lowercase() == name.to_lowercase()
+    });
+    if duplicate {
+        return Err("A group with the same name already exists".to_string());
+    }
+    Ok(())
+}
+
+/// 親グループを検証する（存在しない親、自身や子孫を親にする指定はエラー）
+pub fn validate_parent(
+    groups: &[TaskGroup],
+    group_id: Option<&str>,
+    parent_id: Option<&str>,
+) -> Result<(), String> {
+    let Some(parent_id) = parent_id else {
+        return Ok(());
+    };
+    if !groups.iter().any(|g| g.id == parent_id) {
+        return Err(format!("Parent group {} does not exist", parent_id));
+    }
+    if let Some(group_id) = group_id {
+        if descendant_ids(groups, group_id)
+            .iter()
+            .any(|id| id == parent_id)
+        {
+            return Err("A group cannot be moved into itself or its sub-groups".to_string());
+        }
+    }
+    Ok(())
+}
+
+/// グループを削除する
+///
+/// 子グループは削除したグループの親へ移動し、所属していたタスクはグループ無しにする。
+/// グループ無しにしたタスクのIDを返す。
+pub fn remove_group(
+    groups: &mut Vec<TaskGroup>,
+    tasks: &mut [Task],
+    group_id: &str,
+) -> Result<Vec<i32>, String> {
+    let pos = groups
+        .iter()
+        .position(|g| g.id == group_id)
+        .ok_or_else(|| format!("Group {} not found", group_id))?;
+    let removed = groups.remove(pos);
+
+    for group in groups.iter_mut() {
+        if group.parent_id.as_deref() == Some(group_id) {
+            group.parent_id = removed.parent_id.clone();
+        }
+    }
+
+    let mut moved = Vec::new();
+    for task in tasks.iter_mut() {
+        if task.group_id.as_deref() == Some(group_id) {
+            task.group_id = None;
+            moved.push(task.id);
+        }
+    }
+    Ok(moved)
+}
+
+/// 指定したIDの順に並び順を振り直す（指定されなかったグループは後ろに元の順で並ぶ）
+pub fn reorder_groups(groups: &mut [TaskGroup], ids: &[String]) -> Result<(), String> {
+    if let Some(unknown) = ids.iter().find(|id| !groups.iter().any(|g| &g.id == *id)) {
+        return Err(format!("Group {} not found", unknown));
+    }
+
+    groups.sort_by_key(|g| {
+        (
+            ids.iter().position(|id| *id == g.id).unwrap_or(ids.len()),
+            g.sort_order,
+        )
+    });
+    for (index, group) in groups.iter_mut().enumerate() {
+        group.sort_order = index as i32;
+    }
+    Ok(())
+}
+
+/// 旧形式（グループ名の文字列）の参照をグループIDに移行
+///
+/// 見つからない名前のグループは作成する。
+pub fn migrate_task_group(value: &mut Value, groups: &mut Vec<TaskGroup>) {
+    let Some(obj) = value.as_object_mut() else {
+        return;
+    };
+    let Some(Value::String(name)) = obj.remove("group") else {
+        return;
+    };
+    if obj.get("group_id").is_some_and(|v| !v.is_null()) {
+        return;
+    }
+
+    let group_id = if name.trim().is_empty() {
+        Value::Null
+    } else {
+        Value::String(ensure_group(groups, &name))
+    };
+    obj.insert("group_id".to_string(), group_id);
+}
+
+/// グループデータをファイルに保存
+pub fn save_groups(groups: &Vec<TaskGroup>, file_path: &PathBuf) -> Result<(), String> {
+    let json = serde_json::to_string_pretty(groups).map_err(|e| e.to_string())?;
+    let mut file = fs::File::create(file_path).map_err(|e| e.to_string())?;
+    file.write_all(json.as_bytes()).map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+/// グループデータをファイルから読み込み
+///
+/// 旧形式（グループ名の配列）は保存されていた順にグループへ変換する。
+/// ファイルが無い場合は空、読み込めないグループがある場合はエラー（ファイルには触れない）。
+pub fn load_groups(file_path: &PathBuf) -> Result<Vec<TaskGroup>, String> {
+    if !file_path.exists() {
+        return Ok(Vec::new());
+    }
+    let content = fs::read_to_string(file_path)
+        .map_err(|e| format!("Failed to read {}: {}", file_path.display(), e))?;
+    let values = serde_json::from_str::<Vec<Value>>(&content)
+        .map_err(|e| format!("Failed to parse {}: {}", file_path.display(), e))?;
+
+    let mut groups: Vec<TaskGroup> = Vec::new();
+    for (i, value) in values.into_iter().enumerate() {
+        match value {
+            Value::String(name) => {
+                if !name.trim().is_empty() && find_by_name(&groups, &name).is_none() {
+                    groups.push(TaskGroup::new(name, None, next_sort_order(&groups)));
+                }
+            }
+            value => {
+                let group = serde_json::from_value(value)
+                    .map_err(|e| format!("Failed to load group #{}: {}", i + 1, e))?;
+                groups.push(group);
+            }
+        }
+    }
+    groups.sort_by_key(|g| g.sort_order);
+    Ok(groups)
+}
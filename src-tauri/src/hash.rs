@@ -0,0 +1,11 @@
+// ファイル・本文の内容ハッシュ
+
+use sha2::{Digest, Sha256};
+
+/// 内容のハッシュ（SHA-256の16進表記）
+pub fn content_hash(bytes: &[u8]) -> String {
+    Sha256::digest(bytes)
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect()
+}
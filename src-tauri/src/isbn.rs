@@ -0,0 +1,79 @@
+// ISBN（ISBN-10 / ISBN-13）の正規化とチェックディジットの検証
+
+/// ハイフン・空白を除き、チェックディジットを検証したISBN
+///
+/// 先頭の `ISBN` / `ISBN-13:` などは無視し、ISBN-10の末尾の `x` は大文字にする。
+/// 桁数・チェックディジットが不正な場合はエラー。
+pub fn normalize_isbn(input: &str) -> Result<String, String> {
+    let mut rest = input.trim();
+    if rest
+        .get(..4)
+        .is_some_and(|p| p.eq_ignore_ascii_case("isbn"))
+    {
+        rest = rest[4..].trim_start_matches(['-', ' ']);
+        rest = rest
+            .strip_prefix("10:")
+            .or_else(|| rest.strip_prefix("13:"))
+            .unwrap_or(rest);
+        rest = rest.trim_start_matches(':');
+    }
+    let isbn: String = rest
+        .chars()
+        .filter(|c| !matches!(c, '-' | ' ' | '\u{2010}'..='\u{2015}'))
+        .map(|c| c.to_ascii_uppercase())
+        .collect();
+
+    let valid = match isbn.len() {
+        10 => is_valid_isbn10(&isbn),
+        13 => is_valid_isbn13(&isbn),
+        _ => return Err(format!("ISBN must have 10 or 13 digits: {}", input.trim())),
+    };
+    if valid {
+        Ok(isbn)
+    } else {
+        Err(format!("Invalid ISBN check digit: {}", input.trim()))
+    }
+}
+
+/// 正規化済みのISBN-10か（末尾は `X` も可）
+fn is_valid_isbn10(isbn: &str) -> bool {
+    let mut sum = 0;
+    for (i, c) in isbn.chars().enumerate() {
+        let value = match c {
+            '0'..='9' => c as u32 - '0' as u32,
+            'X' if i == 9 => 10,
+            _ => return false,
+        };
+        sum += value * (10 - i as u32);
+    }
+    sum % 11 == 0
+}
+
+/// 正規化済みのISBN-13か（978/979で始まるもの）
+fn is_valid_isbn13(isbn: &str) -> bool {
+    if !isbn.chars().all(|c| c.is_ascii_digit())
+        || !(isbn.starts_with("978") || isbn.starts_with("979"))
+    {
+        return false;
+    }
+    weighted_sum(isbn).is_multiple_of(10)
+}
+
+/// ISBN-13のチェックディジット計算用の重み付き和（重みは1, 3の繰り返し）
+fn weighted_sum(digits: &str) -> u32 {
+    digits
+        .bytes()
+        .enumerate()
+        .map(|(i, b)| (b - b'0') as u32 * if i % 2 == 0 { 1 } else { 3 })
+        .sum()
+}
+
+/// 正規化済みのISBNをISBN-13にする（ISBN-13はそのまま）
+pub fn to_isbn13(isbn: &str) -> String {
+    if isbn.len() != 10 {
+        return isbn.to_string();
+    }
+    let body = format!("978{}", &isbn[..9]);
+    let check = (10 - weighted_sum(&body) % 10) % 10;
+    format!("{}{}", body, check)
+}
@@ -6,85 +6,188 @@ use std::io::Write;
 use std::panic;
 use std::path::PathBuf;
 
-use std::sync::Mutex;
+use std::sync::{Arc, Mutex};
 
-use chrono::{Local, NaiveDate, NaiveDateTime, TimeZone};
 use tauri::Manager;
 
 // モジュール宣言
+mod attachments;
+mod book_cover;
+mod book_metadata;
+mod bulk;
 mod calendar;
 mod commands;
+mod dependency;
+mod entity_links;
+mod events;
+mod folder_tree;
+mod global_search;
+mod group;
+mod hash;
+mod isbn;
+mod listing;
 mod mail;
 mod memo;
+mod memo_links;
+mod memo_search;
+mod memo_sync;
+mod memo_template;
+mod memo_vault;
 mod notification;
+mod ordering;
+mod patch;
+mod quick_add;
 mod reading_memo;
+mod schedule;
 mod settings;
+mod subtask;
 mod task;
+mod task_date;
+mod task_query;
 #[cfg(test)]
 pub mod tests;
+mod time_tracking;
+mod workflow;
 
 // 再エクスポート
+use attachments::Attachment;
+use book_metadata::{BookMetadataProvider, OpenLibraryProvider};
+use bulk::UndoHistory;
 use calendar::CalendarEvent;
+use entity_links::EntityLink;
+use group::TaskGroup;
 use mail::send_email;
 use memo::{Folder, Memo};
+use memo_links::LinkIndex;
+use memo_search::MemoIndex;
+use memo_sync::MemoSync;
+use memo_template::MemoTemplateStore;
 use reading_memo::ReadingBook;
 use settings::MailSettings;
 use task::Task;
+use task_query::SavedView;
 
 // コマンドの使用
 use commands::{
-    // 読書記録関連
+    // Calendar,
+    // タスク関連,
+    // メモ関連,
+    // 読書記録関連,
+    add_attachment_from_bytes,
+    add_attachment_from_path,
+    add_entity_link,
     add_reading_note,
     add_reading_session,
-    // タスク関連
     add_subtask,
     add_task,
+    auto_schedule_tasks,
+    bulk_update_tasks,
+    cache_book_covers,
     check_notifications,
+    collect_unused_attachments,
     complete_task,
     create_calendar_event,
-    // メモ関連
     create_folder,
     create_group,
-    rename_group,
     create_memo,
+    create_memo_from_reading_note,
+    create_memo_from_template,
+    create_memo_template,
     create_reading_book,
+    create_saved_view,
+    create_task_from_memo_line,
     delete_calendar_event,
     delete_folder,
     delete_group,
     delete_memo,
+    delete_memo_template,
     delete_reading_book,
     delete_reading_note,
     delete_reading_session,
+    delete_saved_view,
     delete_subtask,
     delete_task,
+    demote_task,
+    export_memos_to_directory,
     get_all_tags,
-    // Calendar
+    get_attachments_dir,
+    get_backlinks,
+    get_blocked_tasks,
+    get_board,
+    get_book_covers_dir,
+    get_broken_links,
     get_calendar_events,
+    get_daily_note_settings,
+    get_entity_links,
+    get_folder_tree,
     get_folders,
     get_groups,
     get_mail_settings,
     get_memo,
+    get_memo_attachments,
+    get_memo_sync_status,
+    get_memo_templates,
     get_memos,
+    get_or_create_daily_note,
+    get_reading_book,
     get_reading_books,
+    get_ready_tasks,
+    get_saved_views,
+    get_task,
+    get_task_schedule,
+    get_task_tags,
+    get_task_topological_order,
     get_tasks,
+    get_time_entries,
+    get_time_report,
+    global_search,
+    import_memos_from_directory,
+    lookup_book_by_isbn,
+    move_folder,
+    move_task_state,
+    promote_subtask,
+    query_tasks,
+    quick_add_task,
+    remove_book_cover,
+    remove_entity_link,
+    rename_group,
+    reorder_groups,
+    reorder_subtasks,
+    reorder_tasks,
+    resolve_memo_sync_conflict,
     save_mail_settings,
     search_memos,
     send_test_email,
+    set_book_cover,
+    set_group_archived,
+    set_group_workflow,
+    set_subtask_rollup,
+    start_memo_sync,
+    start_task_timer,
+    stop_memo_sync,
+    stop_task_timer,
     toggle_subtask,
+    undo_task_change,
     update_calendar_event,
+    update_daily_note_settings,
     update_folder,
+    update_group,
     update_memo,
+    update_memo_template,
     update_reading_book,
     update_reading_note,
     update_reading_session,
+    update_saved_view,
     update_subtask,
+    update_subtask_details,
     update_task,
+    validate_isbn,
 };
 
 /// アプリ内の状態を一括で管理している構造体
 pub struct AppState {
     pub tasks: Mutex<Vec<Task>>,
-    pub groups: Mutex<Vec<String>>,
+    pub groups: Mutex<Vec<TaskGroup>>,
     pub next_id: Mutex<i32>,
     pub data_file: Mutex<PathBuf>,
     pub groups_file: Mutex<PathBuf>,
@@ -92,8 +195,45 @@ pub struct AppState {
     pub mail_settings_file: Mutex<PathBuf>,
     pub memos: Mutex<Vec<Memo>>,
     pub folders: Mutex<Vec<Folder>>,
+    pub memo_index: Mutex<MemoIndex>,
+    pub memo_links: Mutex<LinkIndex>,
+    pub memo_templates: Mutex<MemoTemplateStore>,
+    pub memo_sync: Mutex<MemoSync>,
+    pub attachments: Mutex<Vec<Attachment>>,
     pub reading_books: Mutex<Vec<ReadingBook>>,
+    /// ISBNから書誌情報を取得する先
+    pub book_metadata: Arc<dyn BookMetadataProvider>,
     pub calendar_events: Mutex<Vec<CalendarEvent>>,
+    pub entity_links: Mutex<Vec<EntityLink>>,
+    pub saved_views: Mutex<Vec<SavedView>>,
+    pub saved_views_file: Mutex<PathBuf>,
+    pub task_history: Mutex<UndoHistory>,
+}
+
+/// 起動時に読み込めなかったデータファイルを退避し、警告を表示する
+///
+/// 以降の保存で上書きされても元の内容が残るよう、`<ファイル名>.unreadable` にコピーする。
+fn report_load_error(file_path: &PathBuf, error: &str) {
+    let mut name = file_path.file_name().unwrap_or_default().to_os_string();
+    name.push(".unreadable");
+    let copy = file_path.with_file_name(name);
+    let copied = fs::copy(file_path, &copy).is_ok();
+
+    let kept = if copied {
+        format!("A copy of the file was saved to: {}", copy.display())
+    } else {
+        "The file could not be copied; back it up before making changes.".to_string()
+    };
+    MessageDialog::new()
+        .set_title("Failed to load data")
+        .set_description(&format!(
+            "{} could not be loaded and was skipped.\n\nError: {}\n\n{}",
+            file_path.display(),
+            error,
+            kept
+        ))
+        .set_level(rfd::MessageLevel::Warning)
+        .show();
 }
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
@@ -153,15 +293,38 @@ pub fn run() {
             let data_file = app_data_dir.join("tasks.json");
             let groups_file = app_data_dir.join("groups.json");
             let mail_settings_file = app_data_dir.join("settings.json");
+            let saved_views_file = app_data_dir.join("views.json");
 
             // Load data from JSON files
-            let tasks = task::load_tasks(&data_file);
-            let groups = task::load_groups(&groups_file);
+            let mut groups = group::load_groups(&groups_file).unwrap_or_else(|e| {
+                report_load_error(&groups_file, &e);
+                Vec::new()
+            });
+            let tasks = task::load_tasks(&data_file, &mut groups).unwrap_or_else(|e| {
+                report_load_error(&data_file, &e);
+                Vec::new()
+            });
+            // Rewrite legacy dates and group names in the new format, keeping a backup
+            let _ =
+                task::save_if_migrated(&data_file, &tasks, || task::save_tasks(&tasks, &data_file));
+            let _ = task::save_if_migrated(&groups_file, &groups, || {
+                group::save_groups(&groups, &groups_file)
+            });
             let mail_settings = settings::load_settings(&mail_settings_file);
             let memos = memo::load_memos().unwrap_or_default();
             let folders = memo::load_folders().unwrap_or_default();
+            let memo_index = MemoIndex::build(&memos);
+            let memo_links = LinkIndex::build(&memos);
+            let memo_templates = memo_template::load_memo_templates().unwrap_or_default();
+            let memo_sync = MemoSync {
+                store: memo_sync::load_memo_sync().unwrap_or_default(),
+                ..MemoSync::default()
+            };
+            let attachments = attachments::load_attachments().unwrap_or_default();
             let reading_books = reading_memo::load_reading_books().unwrap_or_default();
             let calendar_events = calendar::load_calendar_events().unwrap_or_default();
+            let entity_links = entity_links::load_entity_links().unwrap_or_default();
+            let saved_views = task_query::load_saved_views(&saved_views_file);
 
             let max_id = tasks.iter().map(|t| t.id).max().unwrap_or(0);
 
@@ -175,10 +338,23 @@ pub fn run() {
                 mail_settings_file: Mutex::new(mail_settings_file),
                 memos: Mutex::new(memos),
                 folders: Mutex::new(folders),
+                memo_index: Mutex::new(memo_index),
+                memo_links: Mutex::new(memo_links),
+                memo_templates: Mutex::new(memo_templates),
+                memo_sync: Mutex::new(memo_sync),
+                attachments: Mutex::new(attachments),
                 reading_books: Mutex::new(reading_books),
+                book_metadata: Arc::new(OpenLibraryProvider::default()),
                 calendar_events: Mutex::new(calendar_events),
+                entity_links: Mutex::new(entity_links),
+                saved_views: Mutex::new(saved_views),
+                saved_views_file: Mutex::new(saved_views_file),
+                task_history: Mutex::new(UndoHistory::default()),
             });
 
+            // Two-way sync of memos with a directory on disk
+            commands::init_memo_sync(app.handle());
+
             // Background task for notifications
             let app_handle = app.handle().clone();
             tauri::async_runtime::spawn(async move {
@@ -197,8 +373,8 @@ pub fn run() {
                         continue;
                     }
 
+                    let groups = state.groups.lock().unwrap().clone();
                     let mut tasks_to_notify = Vec::new();
-                    let now = Local::now();
 
                     let tasks_to_update: Vec<Task> = {
                         let mut tasks = state.tasks.lock().unwrap();
@@ -209,37 +385,14 @@ pub fn run() {
                                 continue;
                             }
 
-                            // Try parsing as DateTime first, then fall back to Date
-                            let due_time_result =
-                                NaiveDateTime::parse_from_str(&task.due_date, "%Y-%m-%d %H:%M");
-                            let minutes_until_due = if let Ok(due_dt) = due_time_result {
-                                let due_time = Local.from_local_datetime(&due_dt).unwrap();
-                                let duration = due_time.signed_duration_since(now);
-                                duration.num_minutes()
-                            } else {
-                                // Fall back to date-only parsing
-                                let date_str = task
-                                    .due_date
-                                    .split_whitespace()
-                                    .next()
-                                    .unwrap_or(&task.due_date);
-                                if let Ok(due_date) =
-                                    NaiveDate::parse_from_str(date_str, "%Y-%m-%d")
-                                {
-                                    let due_midnight = Local
-                                        .from_local_datetime(
-                                            &due_date.and_hms_opt(0, 0, 0).unwrap(),
-                                        )
-                                        .unwrap();
-                                    let duration = due_midnight.signed_duration_since(now);
-                                    duration.num_minutes()
-                                } else {
-                                    continue;
-                                }
+                            // Tasks without a due date are never notified
+                            let Some(due_date) = task.due_date.as_ref() else {
+                                continue;
                             };
+                            let minutes_until_due =
+                                notification::calculate_minutes_until_due(due_date);
 
-                            let threshold = task
-                                .notification_minutes
+                            let threshold = group::effective_notification_minutes(task, &groups)
                                 .unwrap_or(settings.notification_minutes);
 
                             if minutes_until_due <= threshold as i64 && minutes_until_due >= 0 {
@@ -257,12 +410,21 @@ pub fn run() {
                         let data_file = state.data_file.lock().unwrap();
                         let _ = crate::task::save_tasks(&tasks, &data_file);
                     }
+                    events::emit(
+                        &app_handle,
+                        tasks_to_update
+                            .into_iter()
+                            .map(events::ChangeEvent::TaskUpdated),
+                    );
 
                     for task in tasks_to_notify {
                         let subject = format!("[Todo App] Task Due: {}", task.description);
                         let body = format!(
                             "Your task '{}' is due on {}.\n\nDetails: {}\nGroup: {}",
-                            task.description, task.due_date, task.details, task.group
+                            task.description,
+                            task.due_date.map(|d| d.to_string()).unwrap_or_default(),
+                            task.details,
+                            group::group_name(&groups, task.group_id.as_deref())
                         );
 
                         let _ = send_email(&settings, &settings.email, &subject, &body);
@@ -290,14 +452,40 @@ pub fn run() {
         })
         .invoke_handler(tauri::generate_handler![
             get_tasks,
+            get_task,
             get_groups,
             create_group,
+            update_group,
             rename_group,
             delete_group,
+            set_group_archived,
+            reorder_groups,
             add_task,
+            quick_add_task,
             update_task,
             delete_task,
+            reorder_tasks,
             complete_task,
+            bulk_update_tasks,
+            undo_task_change,
+            move_task_state,
+            get_board,
+            set_group_workflow,
+            get_blocked_tasks,
+            get_ready_tasks,
+            get_task_topological_order,
+            get_task_schedule,
+            auto_schedule_tasks,
+            start_task_timer,
+            stop_task_timer,
+            get_time_entries,
+            get_time_report,
+            get_task_tags,
+            query_tasks,
+            get_saved_views,
+            create_saved_view,
+            update_saved_view,
+            delete_saved_view,
             get_mail_settings,
             save_mail_settings,
             send_test_email,
@@ -309,14 +497,50 @@ pub fn run() {
             delete_memo,
             search_memos,
             get_all_tags,
+            get_backlinks,
+            get_broken_links,
+            get_memo_templates,
+            create_memo_template,
+            update_memo_template,
+            delete_memo_template,
+            create_memo_from_template,
+            get_daily_note_settings,
+            update_daily_note_settings,
+            get_or_create_daily_note,
+            export_memos_to_directory,
+            import_memos_from_directory,
+            get_memo_sync_status,
+            start_memo_sync,
+            stop_memo_sync,
+            resolve_memo_sync_conflict,
+            get_attachments_dir,
+            add_attachment_from_path,
+            add_attachment_from_bytes,
+            get_memo_attachments,
+            collect_unused_attachments,
+            global_search,
+            add_entity_link,
+            remove_entity_link,
+            get_entity_links,
+            create_memo_from_reading_note,
+            create_task_from_memo_line,
             get_folders,
+            get_folder_tree,
             create_folder,
             update_folder,
+            move_folder,
             delete_folder,
             get_reading_books,
+            get_reading_book,
             create_reading_book,
             update_reading_book,
             delete_reading_book,
+            get_book_covers_dir,
+            set_book_cover,
+            remove_book_cover,
+            cache_book_covers,
+            validate_isbn,
+            lookup_book_by_isbn,
             add_reading_note,
             update_reading_note,
             delete_reading_note,
@@ -328,6 +552,11 @@ pub fn run() {
             delete_subtask,
             delete_subtask,
             toggle_subtask,
+            reorder_subtasks,
+            update_subtask_details,
+            promote_subtask,
+            demote_task,
+            set_subtask_rollup,
             // Calendar
             get_calendar_events,
             create_calendar_event,
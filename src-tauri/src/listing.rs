@@ -0,0 +1,353 @@
+// 一覧取得コマンドのページング・並び替え・要約表示
+
+use chrono::{DateTime, Local, Utc};
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+
+use crate::book_cover::BookCover;
+use crate::memo::Memo;
+use crate::reading_memo::{ReadingBook, ReadingStatus};
+use crate::task::{Task, TaskPriority};
+use crate::task_date::TaskDate;
+
+/// 一覧の取得条件（すべて省略可能）
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct ListOptions {
+    /// 先頭から読み飛ばす件数（`cursor` とは同時に指定できない）
+    #[serde(default)]
+    pub offset: Option<usize>,
+    /// 最大件数（省略時はすべて）
+    #[serde(default)]
+    pub limit: Option<usize>,
+    /// 前のページの `next_cursor`（この要素の次から返す）
+    #[serde(default)]
+    pub cursor: Option<String>,
+    /// 並び替えの項目（`-` を付けると降順、省略時は保存順）
+    #[serde(default)]
+    pub sort: Option<String>,
+    /// 要約のみを返す（本文・読書メモ等の重い項目を除く）
+    #[serde(default)]
+    pub summary: bool,
+}
+
+/// 全体または要約の要素
+#[derive(Debug, Clone, Serialize, PartialEq)]
+#[serde(untagged)]
+pub enum Projected<F, S> {
+    Full(F),
+    Summary(S),
+}
+
+/// 一覧の1ページ分
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct Page<T> {
+    pub items: Vec<T>,
+    /// ページングする前の件数
+    pub total: usize,
+    /// 続きがある場合に次のページの取得に使うカーソル
+    pub next_cursor: Option<String>,
+}
+
+/// 一覧取得の対象
+pub trait Listable: Clone {
+    type Summary: Serialize;
+
+    /// 指定できる並び替えの項目
+    const SORT_FIELDS: &'static [&'static str];
+
+    /// カーソルに使うID
+    fn list_id(&self) -> String;
+    /// `field` での比較（`field` は `SORT_FIELDS` のいずれか）
+    fn compare_by(&self, other: &Self, field: &str) -> Ordering;
+    fn summary(&self) -> Self::Summary;
+}
+
+/// 値の無いものを最大として比較する（昇順では末尾になる）
+fn compare_option<T: Ord>(a: Option<T>, b: Option<T>) -> Ordering {
+    match (a, b) {
+        (Some(a), Some(b)) => a.cmp(&b),
+        (Some(_), None) => Ordering::Less,
+        (None, Some(_)) => Ordering::Greater,
+        (None, None) => Ordering::Equal,
+    }
+}
+
+fn compare_text(a: &str, b: &str) -> Ordering {
+    a.to_lowercase().cmp(&b.to_lowercase())
+}
+
+/// 並び替え・ページング・要約を適用する
+///
+/// 並び替えは安定ソートで、同じ値の要素は保存順のまま。
+/// カーソルの要素が削除されている場合はエラーを返す。
+pub fn list<T: Listable>(
+    items: &[T],
+    options: &ListOptions,
+) -> Result<Page<Projected<T, T::Summary>>, String> {
+    if options.offset.is_some() && options.cursor.is_some() {
+        return Err("Specify either offset or cursor, not both".to_string());
+    }
+    if options.limit == Some(0) {
+        return Err("Limit must be at least 1".to_string());
+    }
+
+    let mut sorted: Vec<&T> = items.iter().collect();
+    if let Some(sort) = options.sort.as_deref().map(str::trim) {
+        let (field, descending) = match sort.strip_prefix('-') {
+            Some(field) => (field, true),
+            None => (sort, false),
+        };
+        if !T::SORT_FIELDS.contains(&field) {
+            return Err(format!(
+                "Unknown sort field '{}': expected one of {}",
+                field,
+                T::SORT_FIELDS.join(", ")
+            ));
+        }
+        sorted.sort_by(|a, b| {
+            let ordering = a.compare_by(b, field);
+            if descending {
+                ordering.reverse()
+            } else {
+                ordering
+            }
+        });
+    }
+
+    let total = sorted.len();
+    let start = match &options.cursor {
+        Some(cursor) => {
+            sorted
+                .iter()
+                .position(|item| item.list_id() == *cursor)
+                .ok_or_else(|| format!("Cursor '{}' is no longer valid", cursor))?
+                + 1
+        }
+        None => options.offset.unwrap_or(0).min(total),
+    };
+    let end = match options.limit {
+        Some(limit) => start.saturating_add(limit).min(total),
+        None => total,
+    };
+
+    let page = &sorted[start..end];
+    let next_cursor = match page.last() {
+        Some(last) if end < total => Some(last.list_id()),
+        _ => None,
+    };
+    let items = page
+        .iter()
+        .map(|item| match options.summary {
+            true => Projected::Summary(item.summary()),
+            false => Projected::Full((*item).clone()),
+        })
+        .collect();
+
+    Ok(Page {
+        items,
+        total,
+        next_cursor,
+    })
+}
+
+// ========================================
+// タスク
+// ========================================
+
+/// タスクの要約（詳細・サブタスク・作業記録・状態の履歴を除く）
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct TaskSummary {
+    pub id: i32,
+    pub description: String,
+    pub start_date: Option<TaskDate>,
+    pub due_date: Option<TaskDate>,
+    pub group_id: Option<String>,
+    pub completed: bool,
+    pub priority: Option<TaskPriority>,
+    pub tags: Vec<String>,
+    pub state: Option<String>,
+    pub sort_key: String,
+    pub subtask_count: usize,
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+impl Listable for Task {
+    type Summary = TaskSummary;
+
+    const SORT_FIELDS: &'static [&'static str] = &[
+        "id",
+        "description",
+        "start_date",
+        "due_date",
+        "priority",
+        "sort_key",
+        "updated_at",
+    ];
+
+    fn list_id(&self) -> String {
+        self.id.to_string()
+    }
+
+    fn compare_by(&self, other: &Self, field: &str) -> Ordering {
+        let date = |d: Option<TaskDate>| d.map(|d| d.to_utc_in(&Local));
+        match field {
+            "description" => compare_text(&self.description, &other.description),
+            "start_date" => compare_option(date(self.start_date), date(other.start_date)),
+            "due_date" => compare_option(date(self.due_date), date(other.due_date)),
+            // Higher priority first when ascending, like the task list
+            "priority" => compare_option(
+                self.priority.map(std::cmp::Reverse),
+                other.priority.map(std::cmp::Reverse),
+            ),
+            "sort_key" => self.sort_key.cmp(&other.sort_key),
+            "updated_at" => compare_option(self.updated_at, other.updated_at),
+            _ => self.id.cmp(&other.id),
+        }
+    }
+
+    fn summary(&self) -> TaskSummary {
+        TaskSummary {
+            id: self.id,
+            description: self.description.clone(),
+            start_date: self.start_date,
+            due_date: self.due_date,
+            group_id: self.group_id.clone(),
+            completed: self.completed,
+            priority: self.priority,
+            tags: self.tags.clone(),
+            state: self.state.clone(),
+            sort_key: self.sort_key.clone(),
+            subtask_count: crate::subtask::flatten(&self.subtasks).len(),
+            updated_at: self.updated_at,
+        }
+    }
+}
+
+// ========================================
+// メモ
+// ========================================
+
+/// 本文の冒頭として要約に含める文字数
+pub const EXCERPT_CHARS: usize = 120;
+
+/// メモの要約（本文は冒頭のみ）
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct MemoSummary {
+    pub id: String,
+    pub title: String,
+    pub excerpt: String,
+    pub folder_id: Option<String>,
+    pub tags: Vec<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// 本文の冒頭（改行は空白にまとめる）
+pub fn excerpt(content: &str) -> String {
+    let text = content.split_whitespace().collect::<Vec<_>>().join(" ");
+    match text.char_indices().nth(EXCERPT_CHARS) {
+        Some((end, _)) => format!("{}…", &text[..end]),
+        None => text,
+    }
+}
+
+impl Listable for Memo {
+    type Summary = MemoSummary;
+
+    const SORT_FIELDS: &'static [&'static str] = &["title", "created_at", "updated_at"];
+
+    fn list_id(&self) -> String {
+        self.id.clone()
+    }
+
+    fn compare_by(&self, other: &Self, field: &str) -> Ordering {
+        match field {
+            "title" => compare_text(&self.title, &other.title),
+            "created_at" => self.created_at.cmp(&other.created_at),
+            _ => self.updated_at.cmp(&other.updated_at),
+        }
+    }
+
+    fn summary(&self) -> MemoSummary {
+        MemoSummary {
+            id: self.id.clone(),
+            title: self.title.clone(),
+            excerpt: excerpt(&self.content),
+            folder_id: self.folder_id.clone(),
+            tags: self.tags.clone(),
+            created_at: self.created_at,
+            updated_at: self.updated_at,
+        }
+    }
+}
+
+// ========================================
+// 読書記録
+// ========================================
+
+/// 書籍の要約（読書メモ・読書セッション・あらすじを除き、件数のみ含める）
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct ReadingBookSummary {
+    pub id: String,
+    pub title: String,
+    pub author: Option<String>,
+    pub cover_image_url: Option<String>,
+    pub cover: Option<BookCover>,
+    pub status: ReadingStatus,
+    pub progress_percent: Option<u8>,
+    pub rating: Option<u8>,
+    pub tags: Vec<String>,
+    pub note_count: usize,
+    pub session_count: usize,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl Listable for ReadingBook {
+    type Summary = ReadingBookSummary;
+
+    const SORT_FIELDS: &'static [&'static str] = &[
+        "title",
+        "author",
+        "rating",
+        "progress",
+        "created_at",
+        "updated_at",
+    ];
+
+    fn list_id(&self) -> String {
+        self.id.clone()
+    }
+
+    fn compare_by(&self, other: &Self, field: &str) -> Ordering {
+        match field {
+            "title" => compare_text(&self.title, &other.title),
+            "author" => compare_option(
+                self.author.as_deref().map(str::to_lowercase),
+                other.author.as_deref().map(str::to_lowercase),
+            ),
+            "rating" => compare_option(self.rating, other.rating),
+            "progress" => compare_option(self.progress_percent, other.progress_percent),
+            "created_at" => self.created_at.cmp(&other.created_at),
+            _ => self.updated_at.cmp(&other.updated_at),
+        }
+    }
+
+    fn summary(&self) -> ReadingBookSummary {
+        ReadingBookSummary {
+            id: self.id.clone(),
+            title: self.title.clone(),
+            author: self.author.clone(),
+            cover_image_url: self.cover_image_url.clone(),
+            cover: self.cover.clone(),
+            status: self.status.clone(),
+            progress_percent: self.progress_percent,
+            rating: self.rating,
+            tags: self.tags.clone(),
+            note_count: self.notes.len(),
+            session_count: self.reading_sessions.len(),
+            created_at: self.created_at,
+            updated_at: self.updated_at,
+        }
+    }
+}
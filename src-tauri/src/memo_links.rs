@@ -0,0 +1,287 @@
+// メモ間のリンク（`[[タイトル]]` / `[[ID]]`）の解析・索引・タイトル変更時の書き換え
+
+use chrono::Utc;
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+
+use crate::memo::Memo;
+
+/// 本文中のリンク（`start..end` は `[[` から `]]` までのバイト範囲）
+#[derive(Debug, Clone, PartialEq)]
+pub struct WikiLink {
+    pub target: String,
+    /// `[[タイトル|表示名]]` の表示名
+    pub alias: Option<String>,
+    pub start: usize,
+    pub end: usize,
+}
+
+/// 本文からリンクを取り出す
+///
+/// コードブロック（```）とインラインコード（`...`）の中は対象外。
+/// リンクは1行の中で閉じている必要がある。
+pub fn parse_links(content: &str) -> Vec<WikiLink> {
+    let mut links = Vec::new();
+    let mut in_fence = false;
+    let mut line_start = 0;
+    for line in content.split_inclusive('\n') {
+        let trimmed = line.trim_start();
+        if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+            in_fence = !in_fence;
+        } else if !in_fence {
+            parse_line(line, line_start, &mut links);
+        }
+        line_start += line.len();
+    }
+    links
+}
+
+fn parse_line(line: &str, offset: usize, links: &mut Vec<WikiLink>) {
+    let mut i = 0;
+    let mut in_code = false;
+    while i < line.len() {
+        let rest = &line[i..];
+        if rest.starts_with('`') {
+            in_code = !in_code;
+            i += 1;
+            continue;
+        }
+        if !in_code && rest.starts_with("[[") {
+            if let Some(close) = rest.find("]]") {
+                let inner = &rest[2..close];
+                if !inner.contains(['[', ']', '`']) {
+                    let (target, alias) = match inner.split_once('|') {
+                        Some((target, alias)) => (target, Some(alias.trim().to_string())),
+                        None => (inner, None),
+                    };
+                    if !target.trim().is_empty() {
+                        links.push(WikiLink {
+                            target: target.trim().to_string(),
+                            alias: alias.filter(|a| !a.is_empty()),
+                            start: offset + i,
+                            end: offset + i + close + 2,
+                        });
+                    }
+                    i += close + 2;
+                    continue;
+                }
+            }
+        }
+        i += rest.chars().next().map_or(1, char::len_utf8);
+    }
+}
+
+/// 大文字・小文字と前後の空白を区別しない比較用のキー
+fn link_key(target: &str) -> String {
+    target.trim().to_lowercase()
+}
+
+/// リンク先のメモ（IDが一致するものを優先し、無ければタイトルが一致する最初のメモ）
+pub fn resolve<'a>(memos: &'a [Memo], target: &str) -> Option<&'a Memo> {
+    let target = target.trim();
+    memos.iter().find(|m| m.id == target).or_else(|| {
+        let key = link_key(target);
+        memos.iter().find(|m| link_key(&m.title) == key)
+    })
+}
+
+/// 被リンク（リンク元のメモと、リンクを含む行）
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct Backlink {
+    pub memo_id: String,
+    pub title: String,
+    pub lines: Vec<String>,
+}
+
+/// リンク先が存在しないリンク
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct BrokenLink {
+    pub memo_id: String,
+    pub title: String,
+    pub target: String,
+}
+
+/// メモ間のリンクの索引
+///
+/// リンク先はタイトルの変更で変わるため、索引にはリンクの文字列のみを保持し、
+/// 参照のたびに現在のメモ一覧で解決する。
+#[derive(Debug, Default)]
+pub struct LinkIndex {
+    outgoing: HashMap<String, Vec<String>>,
+    incoming: HashMap<String, HashSet<String>>,
+}
+
+impl LinkIndex {
+    pub fn build(memos: &[Memo]) -> Self {
+        let mut index = LinkIndex::default();
+        for memo in memos {
+            index.update(memo);
+        }
+        index
+    }
+
+    /// メモのリンクを登録し直す
+    pub fn update(&mut self, memo: &Memo) {
+        self.remove(&memo.id);
+        let targets: Vec<String> = parse_links(&memo.content)
+            .into_iter()
+            .map(|link| link.target)
+            .collect();
+        for target in &targets {
+            self.incoming
+                .entry(link_key(target))
+                .or_default()
+                .insert(memo.id.clone());
+        }
+        if !targets.is_empty() {
+            self.outgoing.insert(memo.id.clone(), targets);
+        }
+    }
+
+    pub fn remove(&mut self, id: &str) {
+        let Some(targets) = self.outgoing.remove(id) else {
+            return;
+        };
+        for target in targets {
+            let key = link_key(&target);
+            if let Some(sources) = self.incoming.get_mut(&key) {
+                sources.remove(id);
+                if sources.is_empty() {
+                    self.incoming.remove(&key);
+                }
+            }
+        }
+    }
+
+    /// `id` のメモへリンクしているメモ（メモ一覧の順、自分自身へのリンクは除く）
+    pub fn backlinks(&self, memos: &[Memo], id: &str) -> Vec<Backlink> {
+        let Some(target) = memos.iter().find(|m| m.id == id) else {
+            return Vec::new();
+        };
+        let mut sources: HashSet<&String> = HashSet::new();
+        for key in [link_key(&target.id), link_key(&target.title)] {
+            sources.extend(self.incoming.get(&key).into_iter().flatten());
+        }
+
+        memos
+            .iter()
+            .filter(|m| m.id != id && sources.contains(&m.id))
+            .filter_map(|source| {
+                let mut lines: Vec<String> = Vec::new();
+                for link in parse_links(&source.content) {
+                    if resolve(memos, &link.target).map(|m| m.id.as_str()) != Some(id) {
+                        continue;
+                    }
+                    let line = line_around(&source.content, link.start);
+                    if lines.last() != Some(&line) {
+                        lines.push(line);
+                    }
+                }
+                (!lines.is_empty()).then(|| Backlink {
+                    memo_id: source.id.clone(),
+                    title: source.title.clone(),
+                    lines,
+                })
+            })
+            .collect()
+    }
+
+    /// リンク先が見つからないリンク（`memo_id` を指定した場合はそのメモのみ）
+    pub fn broken_links(&self, memos: &[Memo], memo_id: Option<&str>) -> Vec<BrokenLink> {
+        memos
+            .iter()
+            .filter(|m| memo_id.is_none_or(|id| m.id == id))
+            .flat_map(|memo| {
+                let targets = self.outgoing.get(&memo.id).into_iter().flatten();
+                let mut seen = HashSet::new();
+                targets
+                    .filter(|target| resolve(memos, target).is_none())
+                    .filter(move |target| seen.insert(link_key(target)))
+                    .map(|target| BrokenLink {
+                        memo_id: memo.id.clone(),
+                        title: memo.title.clone(),
+                        target: target.clone(),
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .collect()
+    }
+
+    /// タイトルで `old_title` を参照しているメモのID
+    fn title_references(&self, old_title: &str) -> HashSet<String> {
+        self.incoming
+            .get(&link_key(old_title))
+            .cloned()
+            .unwrap_or_default()
+    }
+}
+
+/// `byte` を含む行（前後の空白を除く）
+fn line_around(content: &str, byte: usize) -> String {
+    let start = content[..byte].rfind('\n').map_or(0, |i| i + 1);
+    let end = content[byte..]
+        .find('\n')
+        .map_or(content.len(), |i| byte + i);
+    content[start..end].trim().to_string()
+}
+
+/// `[[old_title]]` / `[[old_title|表示名]]` を新しいタイトルに書き換える（変更が無ければ `None`）
+pub fn replace_link_target(content: &str, old_title: &str, new_title: &str) -> Option<String> {
+    let key = link_key(old_title);
+    let links: Vec<WikiLink> = parse_links(content)
+        .into_iter()
+        .filter(|link| link_key(&link.target) == key)
+        .collect();
+    if links.is_empty() {
+        return None;
+    }
+
+    let mut result = String::with_capacity(content.len());
+    let mut cursor = 0;
+    for link in links {
+        result.push_str(&content[cursor..link.start]);
+        match &link.alias {
+            Some(alias) => result.push_str(&format!("[[{}|{}]]", new_title, alias)),
+            None => result.push_str(&format!("[[{}]]", new_title)),
+        }
+        cursor = link.end;
+    }
+    result.push_str(&content[cursor..]);
+    Some(result)
+}
+
+/// メモのタイトル変更に合わせて、他のメモのリンクを書き換える
+///
+/// `title_owner` は変更前に `old_title` のリンクが解決されていたメモのID。
+/// 変更したメモのIDを返す（リンクの索引も更新する）。
+pub fn rename_references(
+    memos: &mut [Memo],
+    index: &mut LinkIndex,
+    renamed_id: &str,
+    title_owner: Option<&str>,
+    old_title: &str,
+) -> Vec<String> {
+    let Some(new_title) = memos
+        .iter()
+        .find(|m| m.id == renamed_id)
+        .map(|m| m.title.clone())
+    else {
+        return Vec::new();
+    };
+    // Links that pointed at another memo with the same title, or a case-only change, stay as they are
+    if title_owner != Some(renamed_id) || link_key(old_title) == link_key(&new_title) {
+        return Vec::new();
+    }
+
+    let sources = index.title_references(old_title);
+    let mut changed = Vec::new();
+    for memo in memos.iter_mut().filter(|m| sources.contains(&m.id)) {
+        if let Some(content) = replace_link_target(&memo.content, old_title, &new_title) {
+            memo.content = content;
+            memo.updated_at = Utc::now();
+            index.update(memo);
+            changed.push(memo.id.clone());
+        }
+    }
+    changed
+}
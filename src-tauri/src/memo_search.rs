@@ -0,0 +1,670 @@
+// メモの全文検索（転置インデックス・BM25によるランキング・スニペット）
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use crate::memo::{Folder, Memo};
+
+/// 検索対象のフィールド（タイトル・本文・タグ）
+const FIELD_COUNT: usize = 3;
+const TITLE: usize = 0;
+const CONTENT: usize = 1;
+const TAGS: usize = 2;
+/// フィールドごとのスコアの重み
+const FIELD_WEIGHTS: [f64; FIELD_COUNT] = [2.0, 1.0, 1.5];
+
+/// BM25のパラメータ
+const K1: f64 = 1.2;
+const B: f64 = 0.75;
+
+/// 前方一致で展開する語の上限
+const MAX_PREFIX_EXPANSIONS: usize = 64;
+/// スニペットに含める一致箇所の前後の文字数
+const SNIPPET_CONTEXT_CHARS: usize = 40;
+/// 件数を指定しない場合の検索結果の上限
+pub const DEFAULT_SEARCH_LIMIT: usize = 50;
+
+// ========================================
+// トークナイザ
+// ========================================
+
+/// トークン（フィールド内の位置と、元の文字列中のバイト範囲）
+#[derive(Debug, Clone, PartialEq)]
+pub struct Token {
+    pub text: String,
+    pub position: u32,
+    pub start: usize,
+    pub end: usize,
+}
+
+/// 空白で区切られない文字（かな・カナ・漢字・ハングル）
+fn is_cjk(c: char) -> bool {
+    matches!(c as u32,
+        0x3040..=0x30FF   // Hiragana, Katakana
+        | 0x31F0..=0x31FF // Katakana phonetic extensions
+        | 0x3400..=0x4DBF // CJK extension A
+        | 0x4E00..=0x9FFF // CJK unified ideographs
+        | 0xF900..=0xFAFF // CJK compatibility ideographs
+        | 0xFF66..=0xFF9F // Half-width katakana
+        | 0xAC00..=0xD7AF // Hangul syllables
+    )
+}
+
+/// 全角英数字・記号を半角にする
+fn to_half_width(c: char) -> char {
+    match c as u32 {
+        0xFF01..=0xFF5E => char::from_u32(c as u32 - 0xFEE0).unwrap_or(c),
+        _ => c,
+    }
+}
+
+fn normalize(text: &str) -> String {
+    text.chars()
+        .map(to_half_width)
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+/// 文字列をトークンに分割する
+///
+/// 英数字などは連続する部分を1語とし、日本語などの空白で区切られない文字は
+/// 各文字から始まる2文字（連続の末尾は1文字）をトークンにする（bigram）。
+/// これにより任意の位置から始まる語を、連続した位置のトークンの並びとして検索できる。
+pub fn tokenize(text: &str) -> Vec<Token> {
+    let chars: Vec<(usize, char)> = text
+        .char_indices()
+        .map(|(i, c)| (i, to_half_width(c)))
+        .collect();
+    let offset = |i: usize| chars.get(i).map_or(text.len(), |(o, _)| *o);
+
+    let mut tokens = Vec::new();
+    let mut position = 0;
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i].1;
+        if is_cjk(c) {
+            let mut j = i;
+            while j < chars.len() && is_cjk(chars[j].1) {
+                j += 1;
+            }
+            for k in i..j {
+                let (start, end) = (offset(k), offset((k + 2).min(j)));
+                tokens.push(Token {
+                    text: normalize(&text[start..end]),
+                    position,
+                    start,
+                    end,
+                });
+                position += 1;
+            }
+            i = j;
+        } else if c.is_alphanumeric() {
+            let mut j = i;
+            while j < chars.len() && chars[j].1.is_alphanumeric() && !is_cjk(chars[j].1) {
+                j += 1;
+            }
+            let (start, end) = (offset(i), offset(j));
+            tokens.push(Token {
+                text: normalize(&text[start..end]),
+                position,
+                start,
+                end,
+            });
+            position += 1;
+            i = j;
+        } else {
+            i += 1;
+        }
+    }
+    tokens
+}
+
+/// 検索語のトークン（直前の2文字のトークンに含まれる末尾の1文字は除く）
+fn query_tokens(text: &str) -> Vec<Token> {
+    let tokens = tokenize(text);
+    let mut result: Vec<Token> = Vec::with_capacity(tokens.len());
+    for token in tokens {
+        if result.last().is_some_and(|last| last.end == token.end) {
+            continue;
+        }
+        result.push(token);
+    }
+    result
+}
+
+// ========================================
+// 検索クエリ
+// ========================================
+
+/// 検索条件の1項目
+#[derive(Debug, Clone, PartialEq)]
+pub enum Clause {
+    Term(String),
+    /// 連続して現れる語の並び
+    Phrase(Vec<String>),
+    /// 前方一致
+    Prefix(String),
+}
+
+/// 解析済みの検索クエリ
+///
+/// 例: `budget "review meeting" plan* tag:work folder:"Daily Notes" 会議室`
+/// 項目はすべて AND で結合される。
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MemoSearchQuery {
+    pub clauses: Vec<Clause>,
+    pub tags: Vec<String>,
+    pub folders: Vec<String>,
+}
+
+impl MemoSearchQuery {
+    pub fn parse(query: &str) -> Self {
+        let mut parsed = MemoSearchQuery::default();
+        let mut rest = query.trim_start();
+        while !rest.is_empty() {
+            let (word, quoted, remaining) = next_word(rest);
+            rest = remaining.trim_start();
+
+            if !quoted {
+                if let Some(tag) = word.strip_prefix("tag:") {
+                    let (value, remaining) = filter_value(tag, rest);
+                    rest = remaining;
+                    if !value.is_empty() {
+                        parsed.tags.push(value);
+                    }
+                    continue;
+                }
+                if let Some(folder) = word.strip_prefix("folder:") {
+                    let (value, remaining) = filter_value(folder, rest);
+                    rest = remaining;
+                    if !value.is_empty() {
+                        parsed.folders.push(value);
+                    }
+                    continue;
+                }
+            }
+            if let Some(clause) = clause_for(&word, quoted) {
+                parsed.clauses.push(clause);
+            }
+        }
+        parsed
+    }
+
+    /// 一致した語の判定に使う（スニペットの強調表示用）
+    fn matches_token(&self, token: &str) -> bool {
+        self.clauses.iter().any(|clause| match clause {
+            Clause::Term(term) => term == token,
+            Clause::Phrase(terms) => terms.iter().any(|t| t == token),
+            Clause::Prefix(prefix) => token.starts_with(prefix.as_str()),
+        })
+    }
+}
+
+/// 次の語（引用符で囲まれた部分はそのまま1語）と、引用符の有無・残りを返す
+fn next_word(input: &str) -> (String, bool, &str) {
+    if let Some(quoted) = input.strip_prefix('"') {
+        return match quoted.find('"') {
+            Some(end) => (quoted[..end].to_string(), true, &quoted[end + 1..]),
+            None => (quoted.to_string(), true, ""),
+        };
+    }
+    let end = input.find(char::is_whitespace).unwrap_or(input.len());
+    (input[..end].to_string(), false, &input[end..])
+}
+
+/// `tag:` / `folder:` の値（`folder:"Daily Notes"` のように引用符で空白を含められる）
+fn filter_value<'a>(value: &str, rest: &'a str) -> (String, &'a str) {
+    match value.strip_prefix('"') {
+        Some(start) if start.ends_with('"') && !start.is_empty() => {
+            (start[..start.len() - 1].to_string(), rest)
+        }
+        Some(start) => {
+            // The value continues after the whitespace that ended the word
+            let joined = format!("{} {}", start, rest);
+            match joined.find('"') {
+                Some(end) => {
+                    let consumed = end.saturating_sub(start.len() + 1);
+                    let remaining = rest.get(consumed + 1..).unwrap_or("");
+                    (joined[..end].trim().to_string(), remaining.trim_start())
+                }
+                None => (joined.trim().to_string(), ""),
+            }
+        }
+        None => (value.to_string(), rest),
+    }
+}
+
+fn clause_for(word: &str, quoted: bool) -> Option<Clause> {
+    let (text, prefix) = match word.strip_suffix('*') {
+        Some(text) if !quoted => (text, true),
+        _ => (word, false),
+    };
+    let tokens = query_tokens(text);
+    match tokens.as_slice() {
+        [] => None,
+        [token] => {
+            let single_cjk =
+                token.text.chars().count() == 1 && token.text.chars().next().is_some_and(is_cjk);
+            if prefix || single_cjk {
+                Some(Clause::Prefix(token.text.clone()))
+            } else {
+                Some(Clause::Term(token.text.clone()))
+            }
+        }
+        _ => Some(Clause::Phrase(
+            tokens.into_iter().map(|token| token.text).collect(),
+        )),
+    }
+}
+
+// ========================================
+// インデックス
+// ========================================
+
+/// 1つのメモでの語の出現位置（フィールドごと）
+type Positions = [Vec<u32>; FIELD_COUNT];
+
+#[derive(Debug, Clone, Default)]
+struct DocInfo {
+    lengths: [u32; FIELD_COUNT],
+    terms: Vec<String>,
+}
+
+/// メモの転置インデックス
+///
+/// メモの作成・更新・削除のたびに該当するメモの分だけ更新する。
+#[derive(Debug, Default)]
+pub struct MemoIndex {
+    terms: BTreeMap<String, HashMap<String, Positions>>,
+    docs: HashMap<String, DocInfo>,
+    total_lengths: [u64; FIELD_COUNT],
+}
+
+/// 検索結果の抜粋（`highlights` は `text` 内の一致箇所の文字単位の範囲）
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct Snippet {
+    pub text: String,
+    pub highlights: Vec<[usize; 2]>,
+}
+
+/// 検索結果
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct MemoSearchHit {
+    pub id: String,
+    pub title: String,
+    pub title_highlights: Vec<[usize; 2]>,
+    pub snippet: Snippet,
+    pub folder_id: Option<String>,
+    pub tags: Vec<String>,
+    pub updated_at: DateTime<Utc>,
+    pub score: f64,
+}
+
+impl MemoIndex {
+    pub fn build(memos: &[Memo]) -> Self {
+        let mut index = MemoIndex::default();
+        for memo in memos {
+            index.upsert(memo);
+        }
+        index
+    }
+
+    pub fn len(&self) -> usize {
+        self.docs.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.docs.is_empty()
+    }
+
+    /// メモを追加する（既にある場合は置き換える）
+    pub fn upsert(&mut self, memo: &Memo) {
+        self.remove(&memo.id);
+
+        let mut fields: [Vec<Token>; FIELD_COUNT] = Default::default();
+        fields[TITLE] = tokenize(&memo.title);
+        fields[CONTENT] = tokenize(&memo.content);
+        fields[TAGS] = tokenize(&memo.tags.join("\n"));
+        let mut doc = DocInfo::default();
+        for (field, tokens) in fields.into_iter().enumerate() {
+            doc.lengths[field] = tokens.len() as u32;
+            self.total_lengths[field] += tokens.len() as u64;
+            for token in tokens {
+                let postings = self.terms.entry(token.text.clone()).or_default();
+                let positions = postings.entry(memo.id.clone()).or_default();
+                if positions.iter().all(Vec::is_empty) {
+                    doc.terms.push(token.text);
+                }
+                positions[field].push(token.position);
+            }
+        }
+        self.docs.insert(memo.id.clone(), doc);
+    }
+
+    pub fn remove(&mut self, id: &str) {
+        let Some(doc) = self.docs.remove(id) else {
+            return;
+        };
+        for field in 0..FIELD_COUNT {
+            self.total_lengths[field] -= doc.lengths[field] as u64;
+        }
+        for term in doc.terms {
+            if let Some(postings) = self.terms.get_mut(&term) {
+                postings.remove(id);
+                if postings.is_empty() {
+                    self.terms.remove(&term);
+                }
+            }
+        }
+    }
+
+    fn bm25(&self, idf: f64, frequencies: &[u32; FIELD_COUNT], doc: &DocInfo) -> f64 {
+        let count = self.docs.len().max(1) as f64;
+        (0..FIELD_COUNT)
+            .filter(|&field| frequencies[field] > 0)
+            .map(|field| {
+                let tf = frequencies[field] as f64;
+                let average = (self.total_lengths[field] as f64 / count).max(1.0);
+                let norm = 1.0 - B + B * doc.lengths[field] as f64 / average;
+                FIELD_WEIGHTS[field] * idf * tf * (K1 + 1.0) / (tf + K1 * norm)
+            })
+            .sum()
+    }
+
+    fn idf(&self, document_frequency: usize) -> f64 {
+        let n = self.docs.len() as f64;
+        let df = document_frequency as f64;
+        (1.0 + (n - df + 0.5) / (df + 0.5)).ln()
+    }
+
+    /// 各フィールドでの出現回数からスコアを計算する
+    fn score_frequencies(
+        &self,
+        frequencies: HashMap<&str, [u32; FIELD_COUNT]>,
+    ) -> HashMap<String, f64> {
+        let idf = self.idf(frequencies.len());
+        frequencies
+            .into_iter()
+            .filter_map(|(id, freq)| {
+                let doc = self.docs.get(id)?;
+                Some((id.to_string(), self.bm25(idf, &freq, doc)))
+            })
+            .collect()
+    }
+
+    fn term_frequencies(&self, term: &str) -> HashMap<&str, [u32; FIELD_COUNT]> {
+        let Some(postings) = self.terms.get(term) else {
+            return HashMap::new();
+        };
+        postings
+            .iter()
+            .map(|(id, positions)| {
+                let mut freq = [0; FIELD_COUNT];
+                for field in 0..FIELD_COUNT {
+                    freq[field] = positions[field].len() as u32;
+                }
+                (id.as_str(), freq)
+            })
+            .collect()
+    }
+
+    fn phrase_frequencies(&self, terms: &[String]) -> HashMap<&str, [u32; FIELD_COUNT]> {
+        let postings: Vec<&HashMap<String, Positions>> =
+            match terms.iter().map(|t| self.terms.get(t)).collect() {
+                Some(postings) => postings,
+                None => return HashMap::new(),
+            };
+        let mut result = HashMap::new();
+        for (id, first) in postings[0] {
+            let mut freq = [0; FIELD_COUNT];
+            for field in 0..FIELD_COUNT {
+                freq[field] = first[field]
+                    .iter()
+                    .filter(|&&start| {
+                        postings[1..].iter().enumerate().all(|(offset, term)| {
+                            term.get(id).is_some_and(|positions| {
+                                positions[field]
+                                    .binary_search(&(start + offset as u32 + 1))
+                                    .is_ok()
+                            })
+                        })
+                    })
+                    .count() as u32;
+            }
+            if freq.iter().any(|&f| f > 0) {
+                result.insert(id.as_str(), freq);
+            }
+        }
+        result
+    }
+
+    /// 項目に一致するメモとそのスコア
+    fn clause_scores(&self, clause: &Clause) -> HashMap<String, f64> {
+        match clause {
+            Clause::Term(term) => self.score_frequencies(self.term_frequencies(term)),
+            Clause::Phrase(terms) => self.score_frequencies(self.phrase_frequencies(terms)),
+            Clause::Prefix(prefix) => {
+                let mut scores: HashMap<String, f64> = HashMap::new();
+                let expansions = self
+                    .terms
+                    .range(prefix.clone()..)
+                    .take_while(|(term, _)| term.starts_with(prefix.as_str()))
+                    .take(MAX_PREFIX_EXPANSIONS);
+                for (term, _) in expansions {
+                    for (id, score) in self.score_frequencies(self.term_frequencies(term)) {
+                        *scores.entry(id).or_default() += score;
+                    }
+                }
+                scores
+            }
+        }
+    }
+
+    /// 条件に一致するメモをスコアの高い順に返す
+    ///
+    /// 語の指定が無い場合はフィルタに一致するメモを更新日時の新しい順に返す。
+    pub fn ranked<'a>(
+        &self,
+        memos: &'a [Memo],
+        folders: &[Folder],
+        query: &MemoSearchQuery,
+    ) -> Vec<(&'a Memo, f64)> {
+        let folder_ids = resolve_folders(folders, &query.folders);
+        let passes_filters = |memo: &Memo| {
+            let tags_match = query
+                .tags
+                .iter()
+                .all(|tag| memo.tags.iter().any(|t| t.eq_ignore_ascii_case(tag)));
+            let folder_match = match &folder_ids {
+                Some(ids) => memo.folder_id.as_ref().is_some_and(|id| ids.contains(id)),
+                None => true,
+            };
+            tags_match && folder_match
+        };
+
+        let mut scored: Vec<(&Memo, f64)> = if query.clauses.is_empty() {
+            memos.iter().map(|memo| (memo, 0.0)).collect()
+        } else {
+            let mut totals: Option<HashMap<String, f64>> = None;
+            for clause in &query.clauses {
+                let scores = self.clause_scores(clause);
+                totals = Some(match totals {
+                    None => scores,
+                    Some(totals) => totals
+                        .into_iter()
+                        .filter_map(|(id, total)| scores.get(&id).map(|s| (id, total + s)))
+                        .collect(),
+                });
+            }
+            let totals = totals.unwrap_or_default();
+            memos
+                .iter()
+                .filter_map(|memo| totals.get(&memo.id).map(|score| (memo, *score)))
+                .collect()
+        };
+        scored.retain(|(memo, _)| passes_filters(memo));
+        scored.sort_by(|(a, score_a), (b, score_b)| {
+            score_b
+                .total_cmp(score_a)
+                .then_with(|| b.updated_at.cmp(&a.updated_at))
+        });
+        scored
+    }
+
+    /// 検索する（スニペット付き）
+    pub fn search(
+        &self,
+        memos: &[Memo],
+        folders: &[Folder],
+        query: &MemoSearchQuery,
+        limit: usize,
+    ) -> Vec<MemoSearchHit> {
+        self.ranked(memos, folders, query)
+            .into_iter()
+            .take(limit)
+            .map(|(memo, score)| MemoSearchHit {
+                id: memo.id.clone(),
+                title: memo.title.clone(),
+                title_highlights: highlights(&memo.title, query),
+                snippet: snippet(&memo.content, query),
+                folder_id: memo.folder_id.clone(),
+                tags: memo.tags.clone(),
+                updated_at: memo.updated_at,
+                score,
+            })
+            .collect()
+    }
+}
+
+/// フォルダの指定（IDまたは名前）を、子孫を含むフォルダIDの集合にする（指定が無ければ `None`）
+fn resolve_folders(folders: &[Folder], filters: &[String]) -> Option<HashSet<String>> {
+    if filters.is_empty() {
+        return None;
+    }
+    let mut ids: HashSet<String> = folders
+        .iter()
+        .filter(|f| {
+            filters
+                .iter()
+                .any(|filter| f.id == *filter || f.name.eq_ignore_ascii_case(filter))
+        })
+        .map(|f| f.id.clone())
+        .collect();
+    loop {
+        let children: Vec<String> = folders
+            .iter()
+            .filter(|f| !ids.contains(&f.id))
+            .filter(|f| f.parent_id.as_ref().is_some_and(|p| ids.contains(p)))
+            .map(|f| f.id.clone())
+            .collect();
+        if children.is_empty() {
+            return Some(ids);
+        }
+        ids.extend(children);
+    }
+}
+
+/// 索引を使わずに各フィールド（文字列と重み）を検索語と照合する
+///
+/// すべての項目がいずれかのフィールドに一致した場合にスコアを返す。
+/// メモ以外の検索に使うため、フィルタ（`tag:` / `folder:`）は呼び出し側で扱う。
+pub fn score_fields(query: &MemoSearchQuery, fields: &[(&str, f64)]) -> Option<f64> {
+    if query.clauses.is_empty() {
+        return None;
+    }
+    let tokenized: Vec<Vec<Token>> = fields.iter().map(|(text, _)| tokenize(text)).collect();
+    let mut score = 0.0;
+    for clause in &query.clauses {
+        let clause_score: f64 = fields
+            .iter()
+            .zip(&tokenized)
+            .map(
+                |((_, weight), tokens)| match clause_frequency(clause, tokens) {
+                    0 => 0.0,
+                    tf => weight * (1.0 + (tf as f64).ln()),
+                },
+            )
+            .sum();
+        if clause_score == 0.0 {
+            return None;
+        }
+        score += clause_score;
+    }
+    Some(score)
+}
+
+fn clause_frequency(clause: &Clause, tokens: &[Token]) -> usize {
+    match clause {
+        Clause::Term(term) => tokens.iter().filter(|t| t.text == *term).count(),
+        Clause::Prefix(prefix) => tokens
+            .iter()
+            .filter(|t| t.text.starts_with(prefix.as_str()))
+            .count(),
+        Clause::Phrase(terms) => tokens
+            .windows(terms.len())
+            .filter(|window| window.iter().zip(terms).all(|(t, term)| t.text == *term))
+            .count(),
+    }
+}
+
+/// 文字列全体での一致箇所
+pub fn highlights(text: &str, query: &MemoSearchQuery) -> Vec<[usize; 2]> {
+    highlights_in(text, query, 0, text.len())
+}
+
+/// `text[start..end]` 内の一致箇所（`start` からの文字単位の範囲、重なりは結合）
+fn highlights_in(text: &str, query: &MemoSearchQuery, start: usize, end: usize) -> Vec<[usize; 2]> {
+    let mut ranges: Vec<[usize; 2]> = Vec::new();
+    for token in tokenize(text) {
+        if token.start < start || token.end > end || !query.matches_token(&token.text) {
+            continue;
+        }
+        let from = text[start..token.start].chars().count();
+        let to = from + text[token.start..token.end].chars().count();
+        match ranges.last_mut() {
+            Some(last) if last[1] >= from => last[1] = last[1].max(to),
+            _ => ranges.push([from, to]),
+        }
+    }
+    ranges
+}
+
+/// 最初の一致箇所の前後を抜き出す（一致が無ければ冒頭）
+pub fn snippet(content: &str, query: &MemoSearchQuery) -> Snippet {
+    let first_match = tokenize(content)
+        .into_iter()
+        .find(|token| query.matches_token(&token.text));
+
+    let chars: Vec<(usize, char)> = content.char_indices().collect();
+    let char_index = |byte: usize| chars.partition_point(|(offset, _)| *offset < byte);
+    let (from, to) = match &first_match {
+        Some(token) => (
+            char_index(token.start).saturating_sub(SNIPPET_CONTEXT_CHARS),
+            (char_index(token.end) + SNIPPET_CONTEXT_CHARS).min(chars.len()),
+        ),
+        None => (0, (SNIPPET_CONTEXT_CHARS * 2).min(chars.len())),
+    };
+    let byte = |i: usize| chars.get(i).map_or(content.len(), |(offset, _)| *offset);
+    let (start, end) = (byte(from), byte(to));
+
+    let mut text: String = content[start..end]
+        .chars()
+        .map(|c| if c.is_whitespace() { ' ' } else { c })
+        .collect();
+    let mut ranges = highlights_in(content, query, start, end);
+    if from > 0 {
+        text.insert(0, '…');
+        for range in ranges.iter_mut() {
+            range[0] += 1;
+            range[1] += 1;
+        }
+    }
+    if to < chars.len() {
+        text.push('…');
+    }
+    Snippet {
+        text,
+        highlights: ranges,
+    }
+}
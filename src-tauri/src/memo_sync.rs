@@ -0,0 +1,598 @@
+// メモとディスク上のディレクトリの双方向同期（ディレクトリの `.md` ファイルを正とする）
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::Sender;
+
+use crate::hash::content_hash;
+use crate::memo::{Folder, Memo};
+use crate::memo_vault::{
+    folder_paths, is_markdown, modified_at, parse_markdown, render_markdown, sanitize_file_name,
+    unique_name,
+};
+
+/// 同期の設定
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct MemoSyncSettings {
+    #[serde(default)]
+    pub enabled: bool,
+    #[serde(default)]
+    pub directory: Option<String>,
+}
+
+/// 最後に同期した時点のメモとファイルの状態
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct SyncEntry {
+    /// 同期ディレクトリからの相対パス（`/` 区切り）
+    pub path: String,
+    /// ファイルの内容のハッシュ
+    pub hash: String,
+    pub title: String,
+    pub memo_updated_at: DateTime<Utc>,
+}
+
+/// メモとファイルの両方が前回の同期以降に変更されたもの
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct SyncConflict {
+    pub memo_id: String,
+    pub path: String,
+    pub detected_at: DateTime<Utc>,
+}
+
+/// 競合の解決でどちらを残すか
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum SyncSide {
+    App,
+    File,
+}
+
+/// 保存する同期の設定と状態
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct MemoSyncStore {
+    #[serde(default)]
+    pub settings: MemoSyncSettings,
+    /// メモのIDごとの同期の状態
+    #[serde(default)]
+    pub entries: HashMap<String, SyncEntry>,
+    #[serde(default)]
+    pub conflicts: Vec<SyncConflict>,
+}
+
+impl MemoSyncStore {
+    fn id_for_path(&self, path: &str) -> Option<String> {
+        self.entries
+            .iter()
+            .find(|(_, entry)| entry.path == path)
+            .map(|(id, _)| id.clone())
+    }
+
+    pub fn has_conflict(&self, memo_id: &str) -> bool {
+        self.conflicts.iter().any(|c| c.memo_id == memo_id)
+    }
+
+    fn add_conflict(&mut self, memo_id: &str, path: &str) {
+        if !self.has_conflict(memo_id) {
+            self.conflicts.push(SyncConflict {
+                memo_id: memo_id.to_string(),
+                path: path.to_string(),
+                detected_at: Utc::now(),
+            });
+        }
+    }
+}
+
+/// 同期ワーカーへの依頼
+#[derive(Debug, Clone, PartialEq)]
+pub enum SyncMessage {
+    /// ディスク上で変更されたパス（絶対パス）
+    Paths(Vec<PathBuf>),
+    MemoChanged(String),
+    MemoDeleted(String),
+    /// ディレクトリ全体を照合する
+    Rescan,
+    Resolve(String, SyncSide),
+}
+
+/// 実行中の同期（`AppState` に置く）
+#[derive(Default)]
+pub struct MemoSync {
+    pub store: MemoSyncStore,
+    /// 同期中のみ `Some`（破棄すると監視が止まる）
+    pub watcher: Option<notify::RecommendedWatcher>,
+    pub sender: Option<Sender<SyncMessage>>,
+    /// 直近の同期で発生したエラー
+    pub last_error: Option<String>,
+}
+
+/// 同期による `state.memos` の変更
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SyncChanges {
+    pub created: Vec<String>,
+    pub updated: Vec<String>,
+    pub deleted: Vec<String>,
+    pub folders_changed: bool,
+    /// 新たに検出した競合のメモID
+    pub conflicts: Vec<String>,
+}
+
+impl SyncChanges {
+    fn mark_updated(&mut self, id: &str) {
+        if !self.created.iter().chain(&self.updated).any(|c| c == id) {
+            self.updated.push(id.to_string());
+        }
+    }
+}
+
+/// 相対パスを `/` 区切りの文字列にする
+pub fn to_relative(path: &Path) -> String {
+    path.components()
+        .map(|c| c.as_os_str().to_string_lossy().into_owned())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+fn to_path(root: &Path, relative: &str) -> PathBuf {
+    relative
+        .split('/')
+        .filter(|s| !s.is_empty())
+        .fold(root.to_path_buf(), |path, segment| path.join(segment))
+}
+
+/// 同期の対象になるファイルか（`.` で始まるファイル・ディレクトリは対象外）
+pub fn is_sync_target(relative: &Path) -> bool {
+    is_markdown(relative)
+        && relative
+            .components()
+            .all(|c| !c.as_os_str().to_string_lossy().starts_with('.'))
+}
+
+fn file_hash(path: &Path) -> Option<String> {
+    fs::read(path).ok().map(|bytes| content_hash(&bytes))
+}
+
+fn parent_of(relative: &str) -> &str {
+    relative.rsplit_once('/').map_or("", |(parent, _)| parent)
+}
+
+fn stem_of(relative: &str) -> &str {
+    let name = relative.rsplit_once('/').map_or(relative, |(_, name)| name);
+    name.rsplit_once('.').map_or(name, |(stem, _)| stem)
+}
+
+/// ファイルを削除し、空になったディレクトリも削除する
+fn remove_file(root: &Path, relative: &str) {
+    let _ = fs::remove_file(to_path(root, relative));
+    let mut dir = parent_of(relative);
+    while !dir.is_empty() && fs::remove_dir(to_path(root, dir)).is_ok() {
+        dir = parent_of(dir);
+    }
+}
+
+/// メモを書き出す場所
+///
+/// タイトルが変わっていなければ既存のファイル名を使い（外部で付けた名前を保つ）、
+/// フォルダの変更はディレクトリの移動にする。
+fn target_path(
+    store: &MemoSyncStore,
+    root: &Path,
+    memo: &Memo,
+    folders: &[Folder],
+    entry: Option<&SyncEntry>,
+) -> String {
+    let dir = memo
+        .folder_id
+        .as_ref()
+        .and_then(|id| folder_paths(folders).remove(id))
+        .map(|path| to_relative(&path))
+        .unwrap_or_default();
+    let join = |stem: &str| {
+        if dir.is_empty() {
+            format!("{}.md", stem)
+        } else {
+            format!("{}/{}.md", dir, stem)
+        }
+    };
+    let kept_name = entry.filter(|e| e.title == memo.title);
+    if let Some(entry) = kept_name {
+        if parent_of(&entry.path) == dir {
+            return entry.path.clone();
+        }
+    }
+
+    // Names taken by other memos or by files that have not been read yet
+    let own_path = entry.map(|e| e.path.as_str());
+    let mut used: HashSet<String> = store
+        .entries
+        .values()
+        .filter(|e| Some(e.path.as_str()) != own_path && parent_of(&e.path) == dir)
+        .map(|e| stem_of(&e.path).to_lowercase())
+        .collect();
+    if let Ok(entries) = fs::read_dir(to_path(root, &dir)) {
+        for file in entries.flatten() {
+            let name = file.file_name().to_string_lossy().into_owned();
+            if Some(join(stem_of(&name)).as_str()) != own_path && is_markdown(Path::new(&name)) {
+                used.insert(stem_of(&name).to_lowercase());
+            }
+        }
+    }
+    let base = match kept_name {
+        Some(entry) => stem_of(&entry.path).to_string(),
+        None => sanitize_file_name(&memo.title),
+    };
+    join(&unique_name(&base, &mut used))
+}
+
+/// メモをファイルに書き、同期の状態を記録する
+fn write_file(
+    store: &mut MemoSyncStore,
+    root: &Path,
+    memo: &Memo,
+    relative: &str,
+) -> Result<(), String> {
+    let path = to_path(root, relative);
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create {}: {}", parent.display(), e))?;
+    }
+    let text = render_markdown(memo, stem_of(relative));
+    fs::write(&path, &text).map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+
+    let previous = store.entries.insert(
+        memo.id.clone(),
+        SyncEntry {
+            path: relative.to_string(),
+            hash: content_hash(text.as_bytes()),
+            title: memo.title.clone(),
+            memo_updated_at: memo.updated_at,
+        },
+    );
+    if let Some(previous) = previous.filter(|p| p.path != relative) {
+        remove_file(root, &previous.path);
+    }
+    Ok(())
+}
+
+/// アプリでの変更をファイルに書く（変更が無ければ書かない）
+///
+/// 前回の同期以降にファイルも変更されていた場合は書かずに競合として記録し、`false` を返す。
+pub fn write_memo(
+    store: &mut MemoSyncStore,
+    root: &Path,
+    memo: &Memo,
+    folders: &[Folder],
+) -> Result<bool, String> {
+    if store.has_conflict(&memo.id) {
+        return Ok(false);
+    }
+    let entry = store.entries.get(&memo.id).cloned();
+    let relative = target_path(store, root, memo, folders, entry.as_ref());
+    if let Some(entry) = &entry {
+        let current = file_hash(&to_path(root, &entry.path));
+        if current.as_ref().is_some_and(|hash| *hash != entry.hash) {
+            store.add_conflict(&memo.id, &entry.path);
+            return Ok(false);
+        }
+        if current.is_some() && entry.memo_updated_at == memo.updated_at && entry.path == relative {
+            return Ok(true);
+        }
+    }
+    write_file(store, root, memo, &relative)?;
+    Ok(true)
+}
+
+/// アプリで削除されたメモのファイルを削除する（外部で変更されていたファイルは残す）
+pub fn remove_memo_file(store: &mut MemoSyncStore, root: &Path, memo_id: &str) {
+    store.conflicts.retain(|c| c.memo_id != memo_id);
+    if let Some(entry) = store.entries.remove(memo_id) {
+        if file_hash(&to_path(root, &entry.path)).as_ref() == Some(&entry.hash) {
+            remove_file(root, &entry.path);
+        }
+    }
+}
+
+/// ディレクトリ（`/` 区切りの相対パス）に対応するフォルダ（無ければ作る）
+fn folder_for_dir(
+    folders: &mut Vec<Folder>,
+    dir: &str,
+    changes: &mut SyncChanges,
+) -> Option<String> {
+    let mut current: Option<String> = None;
+    let mut prefix = String::new();
+    for segment in dir.split('/').filter(|s| !s.is_empty()) {
+        if !prefix.is_empty() {
+            prefix.push('/');
+        }
+        prefix.push_str(segment);
+        let existing = folder_paths(folders)
+            .into_iter()
+            .find(|(_, path)| to_relative(path) == prefix)
+            .map(|(id, _)| id);
+        current = Some(match existing {
+            Some(id) => id,
+            None => {
+                let folder = Folder::new(segment.to_string(), current.clone());
+                let id = folder.id.clone();
+                folders.push(folder);
+                changes.folders_changed = true;
+                id
+            }
+        });
+    }
+    current
+}
+
+/// ディスク上のファイルの変更をメモに反映する（`relative` は同期ディレクトリからの相対パス）
+///
+/// - 前回書いた内容と同じファイルは何もしない
+/// - 削除されたファイルのメモは、アプリで変更されていなければ削除する（変更されていれば書き直す）
+/// - メモとファイルの両方が前回の同期以降に変更されていれば競合として記録する
+/// - 初めて対応付けるメモとファイルは、更新日時とファイルの更新日時の新しい方を残す
+pub fn apply_file(
+    store: &mut MemoSyncStore,
+    root: &Path,
+    relative: &str,
+    memos: &mut Vec<Memo>,
+    folders: &mut Vec<Folder>,
+    changes: &mut SyncChanges,
+) -> Result<(), String> {
+    let path = to_path(root, relative);
+    let entry_id = store.id_for_path(relative);
+
+    if !path.is_file() {
+        let Some(id) = entry_id else {
+            return Ok(());
+        };
+        if store.has_conflict(&id) {
+            return Ok(());
+        }
+        let memo_updated_at = store.entries[&id].memo_updated_at;
+        match memos.iter().position(|m| m.id == id) {
+            Some(i) if memos[i].updated_at == memo_updated_at => {
+                memos.remove(i);
+                store.entries.remove(&id);
+                changes.deleted.push(id);
+            }
+            Some(i) => {
+                let memo = memos[i].clone();
+                write_memo(store, root, &memo, folders)?;
+            }
+            None => {
+                store.entries.remove(&id);
+            }
+        }
+        return Ok(());
+    }
+
+    let Ok(text) = fs::read_to_string(&path) else {
+        return Ok(());
+    };
+    let hash = content_hash(text.as_bytes());
+    if let Some(id) = &entry_id {
+        if store.has_conflict(id) || store.entries[id].hash == hash {
+            return Ok(());
+        }
+    }
+
+    let (front_matter, content) = parse_markdown(&text);
+    let title = front_matter
+        .title
+        .clone()
+        .unwrap_or_else(|| stem_of(relative).to_string());
+    let folder_id = folder_for_dir(folders, parent_of(relative), changes);
+    let modified = modified_at(&path);
+    // A file whose id is still synced at another existing path is a copy
+    let id = entry_id.or_else(|| {
+        front_matter.id.clone().filter(|id| {
+            store
+                .entries
+                .get(id)
+                .is_none_or(|e| !to_path(root, &e.path).is_file())
+        })
+    });
+
+    let Some(memo) = id
+        .as_ref()
+        .and_then(|id| memos.iter_mut().find(|m| m.id == *id))
+    else {
+        let mut memo = Memo::new(title, content, folder_id, front_matter.tags);
+        if let Some(id) = id {
+            memo.id = id;
+        }
+        let now = Utc::now();
+        memo.created_at = front_matter.created_at.or(modified).unwrap_or(now);
+        memo.updated_at = front_matter.updated_at.or(modified).unwrap_or(now);
+        record(store, &memo, relative, hash);
+        changes.created.push(memo.id.clone());
+        memos.push(memo);
+        return Ok(());
+    };
+
+    match store.entries.get(&memo.id) {
+        Some(entry) if entry.memo_updated_at != memo.updated_at => {
+            store.add_conflict(&memo.id, relative);
+            changes.conflicts.push(memo.id.clone());
+            return Ok(());
+        }
+        None if modified.is_some_and(|m| memo.updated_at > m) => {
+            // The memo is newer than the file it has never been synced with
+            let memo = memo.clone();
+            write_file(store, root, &memo, relative)?;
+            return Ok(());
+        }
+        _ => {}
+    }
+
+    let changed = memo.title != title
+        || memo.content != content
+        || memo.folder_id != folder_id
+        || memo.tags != front_matter.tags;
+    if changed {
+        memo.title = title;
+        memo.content = content;
+        memo.folder_id = folder_id;
+        memo.tags = front_matter.tags;
+        memo.updated_at = Utc::now();
+        changes.mark_updated(&memo.id);
+    }
+    let memo = memo.clone();
+    record(store, &memo, relative, hash);
+    Ok(())
+}
+
+/// 読み込んだファイルの状態を記録する
+fn record(store: &mut MemoSyncStore, memo: &Memo, relative: &str, hash: String) {
+    store.entries.insert(
+        memo.id.clone(),
+        SyncEntry {
+            path: relative.to_string(),
+            hash,
+            title: memo.title.clone(),
+            memo_updated_at: memo.updated_at,
+        },
+    );
+}
+
+fn markdown_files(root: &Path, dir: &Path, files: &mut Vec<String>) -> Result<(), String> {
+    let mut entries: Vec<PathBuf> = fs::read_dir(dir)
+        .map_err(|e| format!("Failed to read {}: {}", dir.display(), e))?
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .collect();
+    entries.sort();
+    for path in entries {
+        let Ok(relative) = path.strip_prefix(root) else {
+            continue;
+        };
+        if relative
+            .file_name()
+            .is_some_and(|n| n.to_string_lossy().starts_with('.'))
+        {
+            continue;
+        }
+        if path.is_dir() {
+            markdown_files(root, &path, files)?;
+        } else if is_sync_target(relative) {
+            files.push(to_relative(relative));
+        }
+    }
+    Ok(())
+}
+
+/// ディレクトリ全体とメモを照合する（同期の開始時やフォルダの変更時）
+pub fn reconcile(
+    store: &mut MemoSyncStore,
+    root: &Path,
+    memos: &mut Vec<Memo>,
+    folders: &mut Vec<Folder>,
+    changes: &mut SyncChanges,
+) -> Result<(), String> {
+    fs::create_dir_all(root).map_err(|e| format!("Failed to create {}: {}", root.display(), e))?;
+
+    let mut files = Vec::new();
+    markdown_files(root, root, &mut files)?;
+    for relative in &files {
+        apply_file(store, root, relative, memos, folders, changes)?;
+    }
+
+    // Files removed while the app was not watching
+    let missing: Vec<String> = store
+        .entries
+        .values()
+        .filter(|e| !to_path(root, &e.path).is_file())
+        .map(|e| e.path.clone())
+        .collect();
+    for relative in missing {
+        apply_file(store, root, &relative, memos, folders, changes)?;
+    }
+
+    // Memos deleted in the app while the app was not syncing
+    let orphaned: Vec<String> = store
+        .entries
+        .keys()
+        .filter(|id| !memos.iter().any(|m| m.id == **id))
+        .cloned()
+        .collect();
+    for id in orphaned {
+        remove_memo_file(store, root, &id);
+    }
+
+    for memo in memos.iter() {
+        if write_memo(store, root, memo, folders)? {
+            continue;
+        }
+        if !changes.conflicts.contains(&memo.id) && store.has_conflict(&memo.id) {
+            changes.conflicts.push(memo.id.clone());
+        }
+    }
+    Ok(())
+}
+
+/// 競合を解決する（`keep` の側の内容でもう一方を上書きする）
+pub fn resolve_conflict(
+    store: &mut MemoSyncStore,
+    root: &Path,
+    memo_id: &str,
+    keep: SyncSide,
+    memos: &mut Vec<Memo>,
+    folders: &mut Vec<Folder>,
+    changes: &mut SyncChanges,
+) -> Result<(), String> {
+    let index = store
+        .conflicts
+        .iter()
+        .position(|c| c.memo_id == memo_id)
+        .ok_or_else(|| format!("No sync conflict for memo {}", memo_id))?;
+    let conflict = store.conflicts.remove(index);
+    let memo = memos
+        .iter()
+        .find(|m| m.id == memo_id)
+        .cloned()
+        .ok_or_else(|| format!("Memo {} not found", memo_id))?;
+
+    match keep {
+        SyncSide::App => {
+            let entry = store.entries.get(memo_id).cloned();
+            let relative = target_path(store, root, &memo, folders, entry.as_ref());
+            write_file(store, root, &memo, &relative)?;
+        }
+        SyncSide::File => {
+            // Treat the memo as unchanged since the last sync so the file wins
+            if let Some(entry) = store.entries.get_mut(memo_id) {
+                entry.memo_updated_at = memo.updated_at;
+                entry.hash.clear();
+            }
+            apply_file(store, root, &conflict.path, memos, folders, changes)?;
+            if !changes.deleted.iter().any(|id| id == memo_id) {
+                changes.mark_updated(memo_id);
+            }
+        }
+    }
+    Ok(())
+}
+
+pub fn get_memo_sync_file_path() -> PathBuf {
+    let app_data = dirs::data_local_dir().unwrap();
+    app_data.join("com.noruno.platform").join("memo_sync.json")
+}
+
+pub fn load_memo_sync() -> Result<MemoSyncStore, String> {
+    let path = get_memo_sync_file_path();
+    if !path.exists() {
+        return Ok(MemoSyncStore::default());
+    }
+
+    let contents =
+        fs::read_to_string(&path).map_err(|e| format!("Failed to read memo_sync file: {}", e))?;
+
+    serde_json::from_str(&contents).map_err(|e| format!("Failed to parse memo_sync: {}", e))
+}
+
+pub fn save_memo_sync(store: &MemoSyncStore) -> Result<(), String> {
+    let path = get_memo_sync_file_path();
+    let json = serde_json::to_string_pretty(store).map_err(|e| e.to_string())?;
+    fs::write(&path, json).map_err(|e| format!("Failed to save memo_sync: {}", e))?;
+    Ok(())
+}
@@ -0,0 +1,290 @@
+// メモのテンプレートとデイリーノート
+
+use chrono::format::{DelayedFormat, StrftimeItems};
+use chrono::{DateTime, NaiveDate, NaiveTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::fmt::Write;
+use std::fs;
+use std::path::PathBuf;
+use uuid::Uuid;
+
+use crate::memo::{Folder, Memo};
+
+/// デイリーノートのタイトルの既定の書式
+pub const DEFAULT_DAILY_TITLE_FORMAT: &str = "%Y-%m-%d";
+/// テンプレートが無い場合のデイリーノートの本文
+const DEFAULT_DAILY_CONTENT: &str = "# {{title}}\n\n";
+
+/// メモのテンプレート
+///
+/// `title` と `content` では次のプレースホルダを使える。
+/// `{{date}}` `{{time}}` `{{datetime}}` は `{{date:%Y/%m/%d}}` のように書式を指定できる。
+/// `{{title}}` は作成するメモのタイトル（`content` のみ）。
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct MemoTemplate {
+    pub id: String,
+    pub name: String,
+    pub title: String,
+    pub content: String,
+    #[serde(default)]
+    pub folder_id: Option<String>,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl MemoTemplate {
+    pub fn new(
+        name: String,
+        title: String,
+        content: String,
+        folder_id: Option<String>,
+        tags: Vec<String>,
+    ) -> Self {
+        let now = Utc::now();
+        Self {
+            id: Uuid::new_v4().to_string(),
+            name,
+            title,
+            content,
+            folder_id,
+            tags,
+            created_at: now,
+            updated_at: now,
+        }
+    }
+}
+
+/// デイリーノートの設定
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct DailyNoteSettings {
+    /// デイリーノートを置くフォルダ（`None` はフォルダなし）
+    #[serde(default)]
+    pub folder_id: Option<String>,
+    /// 新しいデイリーノートに使うテンプレート
+    #[serde(default)]
+    pub template_id: Option<String>,
+    /// タイトルの書式（chrono の strftime 形式）
+    #[serde(default = "default_daily_title_format")]
+    pub title_format: String,
+}
+
+fn default_daily_title_format() -> String {
+    DEFAULT_DAILY_TITLE_FORMAT.to_string()
+}
+
+impl Default for DailyNoteSettings {
+    fn default() -> Self {
+        DailyNoteSettings {
+            folder_id: None,
+            template_id: None,
+            title_format: default_daily_title_format(),
+        }
+    }
+}
+
+/// 保存するテンプレートと設定
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct MemoTemplateStore {
+    #[serde(default)]
+    pub templates: Vec<MemoTemplate>,
+    #[serde(default)]
+    pub daily_note: DailyNoteSettings,
+}
+
+impl MemoTemplateStore {
+    pub fn find(&self, id: &str) -> Result<&MemoTemplate, String> {
+        self.templates
+            .iter()
+            .find(|t| t.id == id)
+            .ok_or_else(|| format!("Template {} not found", id))
+    }
+}
+
+/// プレースホルダに入れる値
+#[derive(Debug, Clone)]
+pub struct TemplateContext {
+    pub date: NaiveDate,
+    pub time: NaiveTime,
+    pub title: String,
+}
+
+/// 書式化した文字列（不正な書式や、`%Z` のように値の型で使えない指定は None）
+fn try_format(formatted: DelayedFormat<StrftimeItems>) -> Option<String> {
+    let mut result = String::new();
+    write!(result, "{}", formatted).ok()?;
+    Some(result)
+}
+
+fn format_date(date: NaiveDate, format: &str) -> Option<String> {
+    try_format(date.format(format))
+}
+
+/// デイリーノートのタイトル書式として使えるか（日付だけで書式化できること）
+pub fn validate_format(format: &str) -> Result<(), String> {
+    let sample = NaiveDate::from_ymd_opt(2000, 1, 1).unwrap();
+    if format.trim().is_empty() || format_date(sample, format).is_none() {
+        return Err(format!("Invalid date format '{}'", format));
+    }
+    Ok(())
+}
+
+fn placeholder_value(name: &str, context: &TemplateContext) -> Option<String> {
+    let (name, format) = match name.split_once(':') {
+        Some((name, format)) => (name.trim(), Some(format)),
+        None => (name.trim(), None),
+    };
+    let datetime = context.date.and_time(context.time);
+    let formatted = |default: &str| {
+        let format = format.unwrap_or(default);
+        try_format(datetime.format(format))
+    };
+    match name {
+        "date" => formatted("%Y-%m-%d"),
+        "time" => formatted("%H:%M"),
+        "datetime" => formatted("%Y-%m-%d %H:%M"),
+        "title" if format.is_none() => Some(context.title.clone()),
+        _ => None,
+    }
+}
+
+/// プレースホルダを置き換える（不明なプレースホルダはそのまま残す）
+pub fn render(text: &str, context: &TemplateContext) -> String {
+    let mut result = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find("{{") {
+        let Some(length) = rest[start + 2..].find("}}") else {
+            break;
+        };
+        let end = start + 2 + length;
+        result.push_str(&rest[..start]);
+        match placeholder_value(&rest[start + 2..end], context) {
+            Some(value) => result.push_str(&value),
+            None => result.push_str(&rest[start..end + 2]),
+        }
+        rest = &rest[end + 2..];
+    }
+    result.push_str(rest);
+    result
+}
+
+/// テンプレートからメモを作る（`title` を省略した場合はテンプレートのタイトルを使う）
+pub fn instantiate(
+    template: &MemoTemplate,
+    title: Option<String>,
+    folder_id: Option<String>,
+    date: NaiveDate,
+    time: NaiveTime,
+) -> Memo {
+    let mut context = TemplateContext {
+        date,
+        time,
+        title: String::new(),
+    };
+    context.title = match title.filter(|t| !t.trim().is_empty()) {
+        Some(title) => title,
+        None => render(&template.title, &context),
+    };
+    Memo::new(
+        context.title.clone(),
+        render(&template.content, &context),
+        folder_id.or_else(|| template.folder_id.clone()),
+        template.tags.clone(),
+    )
+}
+
+/// デイリーノートのタイトル
+pub fn daily_note_title(settings: &DailyNoteSettings, date: NaiveDate) -> String {
+    format_date(date, &settings.title_format)
+        .or_else(|| format_date(date, DEFAULT_DAILY_TITLE_FORMAT))
+        .unwrap_or_default()
+}
+
+/// 設定のフォルダ・テンプレートが存在することを確認する
+pub fn validate_daily_note_settings(
+    settings: &DailyNoteSettings,
+    folders: &[Folder],
+    templates: &[MemoTemplate],
+) -> Result<(), String> {
+    crate::folder_tree::validate_parent(folders, settings.folder_id.as_deref())?;
+    if let Some(template_id) = &settings.template_id {
+        if !templates.iter().any(|t| t.id == *template_id) {
+            return Err(format!("Template {} not found", template_id));
+        }
+    }
+    validate_format(&settings.title_format)
+}
+
+/// 日付のデイリーノートを探す（設定のフォルダ内でタイトルが一致するメモ）
+pub fn find_daily_note<'a>(
+    memos: &'a [Memo],
+    settings: &DailyNoteSettings,
+    date: NaiveDate,
+) -> Option<&'a Memo> {
+    let title = daily_note_title(settings, date);
+    memos
+        .iter()
+        .find(|m| m.folder_id == settings.folder_id && m.title == title)
+}
+
+/// 日付のデイリーノートを新しく作る
+pub fn new_daily_note(
+    store: &MemoTemplateStore,
+    date: NaiveDate,
+    time: NaiveTime,
+) -> Result<Memo, String> {
+    let settings = &store.daily_note;
+    let title = daily_note_title(settings, date);
+    match &settings.template_id {
+        Some(template_id) => {
+            let template = store.find(template_id)?;
+            Ok(instantiate(
+                template,
+                Some(title),
+                settings.folder_id.clone(),
+                date,
+                time,
+            ))
+        }
+        None => {
+            let context = TemplateContext {
+                date,
+                time,
+                title: title.clone(),
+            };
+            Ok(Memo::new(
+                title,
+                render(DEFAULT_DAILY_CONTENT, &context),
+                settings.folder_id.clone(),
+                Vec::new(),
+            ))
+        }
+    }
+}
+
+pub fn get_memo_templates_file_path() -> PathBuf {
+    let app_data = dirs::data_local_dir().unwrap();
+    app_data
+        .join("com.noruno.platform")
+        .join("memo_templates.json")
+}
+
+pub fn load_memo_templates() -> Result<MemoTemplateStore, String> {
+    let path = get_memo_templates_file_path();
+    if !path.exists() {
+        return Ok(MemoTemplateStore::default());
+    }
+
+    let contents = fs::read_to_string(&path)
+        .map_err(|e| format!("Failed to read memo_templates file: {}", e))?;
+
+    serde_json::from_str(&contents).map_err(|e| format!("Failed to parse memo_templates: {}", e))
+}
+
+pub fn save_memo_templates(store: &MemoTemplateStore) -> Result<(), String> {
+    let path = get_memo_templates_file_path();
+    let json = serde_json::to_string_pretty(store).map_err(|e| e.to_string())?;
+    fs::write(&path, json).map_err(|e| format!("Failed to save memo_templates: {}", e))?;
+    Ok(())
+}
@@ -0,0 +1,532 @@
+// メモとMarkdownファイルのディレクトリ（Obsidianのvaultなど）の間の書き出し・読み込み
+
+use chrono::{DateTime, NaiveDate, NaiveDateTime, Utc};
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use uuid::Uuid;
+
+use crate::memo::{Folder, Memo};
+
+/// ファイル名に使えない文字を置き換えたもの（空の場合は `Untitled`）
+pub fn sanitize_file_name(name: &str) -> String {
+    let replaced: String = name
+        .chars()
+        .map(|c| match c {
+            '/' | '\\' | ':' | '*' | '?' | '"' | '<' | '>' | '|' => '_',
+            c if c.is_control() => '_',
+            c => c,
+        })
+        .collect();
+    let trimmed = replaced.trim().trim_end_matches('.').trim_end();
+    // Hidden entries are skipped on import
+    let name = match trimmed.strip_prefix('.') {
+        Some(rest) => format!("_{}", rest),
+        None => trimmed.to_string(),
+    };
+    if name.is_empty() {
+        "Untitled".to_string()
+    } else {
+        name
+    }
+}
+
+/// `used` と重ならない名前（大文字・小文字は区別しない）
+pub fn unique_name(base: &str, used: &mut HashSet<String>) -> String {
+    let mut name = base.to_string();
+    let mut n = 2;
+    while !used.insert(name.to_lowercase()) {
+        name = format!("{} ({})", base, n);
+        n += 1;
+    }
+    name
+}
+
+/// 各フォルダのディレクトリ（書き出し先からの相対パス）
+///
+/// 親が存在しないフォルダは最上位に置く。同じ親の下で名前が重なる場合は ` (2)` などを付ける。
+pub fn folder_paths(folders: &[Folder]) -> HashMap<String, PathBuf> {
+    let exists = |id: &str| folders.iter().any(|f| f.id == id);
+    let parent_of = |folder: &Folder| folder.parent_id.clone().filter(|p| exists(p));
+
+    let mut used: HashMap<Option<String>, HashSet<String>> = HashMap::new();
+    let mut dir_names: HashMap<&str, String> = HashMap::new();
+    for folder in folders {
+        let siblings = used.entry(parent_of(folder)).or_default();
+        let name = unique_name(&sanitize_file_name(&folder.name), siblings);
+        dir_names.insert(folder.id.as_str(), name);
+    }
+
+    let mut paths = HashMap::new();
+    for folder in folders {
+        let mut segments = Vec::new();
+        let mut visited = HashSet::new();
+        let mut current = Some(folder);
+        while let Some(f) = current {
+            if !visited.insert(f.id.as_str()) {
+                break;
+            }
+            segments.push(dir_names[f.id.as_str()].clone());
+            current = parent_of(f).and_then(|p| folders.iter().find(|x| x.id == p));
+        }
+        paths.insert(folder.id.clone(), segments.iter().rev().collect());
+    }
+    paths
+}
+
+/// 前置き（front matter）の値
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct FrontMatter {
+    pub id: Option<String>,
+    pub title: Option<String>,
+    pub tags: Vec<String>,
+    pub created_at: Option<DateTime<Utc>>,
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+/// YAMLの値として書ける形（必要な場合は二重引用符で囲む）
+fn yaml_scalar(value: &str) -> String {
+    let lower = value.to_lowercase();
+    let needs_quotes = value.is_empty()
+        || value.trim() != value
+        || value.starts_with([
+            '-', '?', ':', ',', '[', ']', '{', '}', '#', '&', '*', '!', '|', '>', '\'', '"', '%',
+            '@', '`',
+        ])
+        || value.contains(": ")
+        || value.contains(" #")
+        || value.ends_with(':')
+        || value.chars().any(char::is_control)
+        || matches!(
+            lower.as_str(),
+            "true" | "false" | "yes" | "no" | "on" | "off" | "null" | "~"
+        )
+        || value.parse::<f64>().is_ok();
+    if !needs_quotes {
+        return value.to_string();
+    }
+
+    let mut quoted = String::from("\"");
+    for c in value.chars() {
+        match c {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            '\n' => quoted.push_str("\\n"),
+            '\t' => quoted.push_str("\\t"),
+            '\r' => quoted.push_str("\\r"),
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+/// メモをMarkdownファイルの内容にする
+///
+/// タイトルはファイル名から分かるため、ファイル名で表せない場合（`file_stem` と異なる場合）のみ書く。
+pub fn render_markdown(memo: &Memo, file_stem: &str) -> String {
+    let mut text = String::from("---\n");
+    text.push_str(&format!("id: {}\n", yaml_scalar(&memo.id)));
+    if memo.title != file_stem {
+        text.push_str(&format!("title: {}\n", yaml_scalar(&memo.title)));
+    }
+    if memo.tags.is_empty() {
+        text.push_str("tags: []\n");
+    } else {
+        text.push_str("tags:\n");
+        for tag in &memo.tags {
+            text.push_str(&format!("  - {}\n", yaml_scalar(tag)));
+        }
+    }
+    text.push_str(&format!("created_at: {}\n", memo.created_at.to_rfc3339()));
+    text.push_str(&format!("updated_at: {}\n", memo.updated_at.to_rfc3339()));
+    text.push_str("---\n\n");
+    text.push_str(&memo.content);
+    text
+}
+
+fn unquote(value: &str) -> String {
+    let value = value.trim();
+    if value.len() >= 2 && value.starts_with('"') && value.ends_with('"') {
+        let mut result = String::new();
+        let mut chars = value[1..value.len() - 1].chars();
+        while let Some(c) = chars.next() {
+            if c != '\\' {
+                result.push(c);
+                continue;
+            }
+            match chars.next() {
+                Some('n') => result.push('\n'),
+                Some('t') => result.push('\t'),
+                Some('r') => result.push('\r'),
+                Some(other) => result.push(other),
+                None => result.push('\\'),
+            }
+        }
+        result
+    } else if value.len() >= 2 && value.starts_with('\'') && value.ends_with('\'') {
+        value[1..value.len() - 1].replace("''", "'")
+    } else {
+        // Plain scalars end at a comment
+        match value.find(" #") {
+            Some(i) => value[..i].trim_end().to_string(),
+            None => value.to_string(),
+        }
+    }
+}
+
+/// `[a, "b, c"]` の要素（引用符の中のカンマでは区切らない）
+fn split_flow_list(inner: &str) -> Vec<String> {
+    let mut items = Vec::new();
+    let mut current = String::new();
+    let mut quote: Option<char> = None;
+    for c in inner.chars() {
+        match (c, quote) {
+            ('"' | '\'', None) => {
+                quote = Some(c);
+                current.push(c);
+            }
+            (c, Some(q)) if c == q => {
+                quote = None;
+                current.push(c);
+            }
+            (',', None) => items.push(std::mem::take(&mut current)),
+            (c, _) => current.push(c),
+        }
+    }
+    items.push(current);
+    items
+        .iter()
+        .map(|item| unquote(item))
+        .filter(|item| !item.is_empty())
+        .collect()
+}
+
+fn parse_datetime(value: &str) -> Option<DateTime<Utc>> {
+    if let Ok(datetime) = DateTime::parse_from_rfc3339(value) {
+        return Some(datetime.with_timezone(&Utc));
+    }
+    for format in ["%Y-%m-%d %H:%M:%S", "%Y-%m-%dT%H:%M:%S", "%Y-%m-%d %H:%M"] {
+        if let Ok(datetime) = NaiveDateTime::parse_from_str(value, format) {
+            return Some(datetime.and_utc());
+        }
+    }
+    NaiveDate::parse_from_str(value, "%Y-%m-%d")
+        .ok()
+        .map(|date| date.and_time(Default::default()).and_utc())
+}
+
+/// Obsidianのタグの書き方（`#` 付き、1行ならカンマ・空白区切り）も受け付ける
+fn normalize_tags(values: &[String], scalar: bool) -> Vec<String> {
+    let separators: &[char] = if scalar { &[',', ' '] } else { &[] };
+    let mut tags: Vec<String> = Vec::new();
+    for value in values {
+        for tag in value.split(separators) {
+            let tag = tag.trim().trim_start_matches('#');
+            if !tag.is_empty() && !tags.iter().any(|t| t == tag) {
+                tags.push(tag.to_string());
+            }
+        }
+    }
+    tags
+}
+
+/// Markdownファイルを前置きと本文に分ける
+///
+/// 前置きは `id` `title` `tags`（`tag`）`created_at` `updated_at` のみ読み、他のキーは無視する。
+/// 前置きの後の空行は1行だけ取り除く。
+pub fn parse_markdown(text: &str) -> (FrontMatter, String) {
+    let text = text.strip_prefix('\u{feff}').unwrap_or(text);
+    let Some(rest) = text
+        .strip_prefix("---\n")
+        .or_else(|| text.strip_prefix("---\r\n"))
+    else {
+        return (FrontMatter::default(), text.to_string());
+    };
+
+    let mut header = Vec::new();
+    let mut body = None;
+    let mut offset = 0;
+    for line in rest.split_inclusive('\n') {
+        offset += line.len();
+        let trimmed = line.trim_end();
+        if trimmed == "---" || trimmed == "..." {
+            body = Some(&rest[offset..]);
+            break;
+        }
+        header.push(trimmed);
+    }
+    let Some(body) = body else {
+        return (FrontMatter::default(), text.to_string());
+    };
+    let body = body
+        .strip_prefix("\r\n")
+        .or_else(|| body.strip_prefix('\n'))
+        .unwrap_or(body);
+
+    let mut values: HashMap<String, Vec<String>> = HashMap::new();
+    let mut scalar_keys: HashSet<String> = HashSet::new();
+    let mut current_key: Option<String> = None;
+    for line in header {
+        if line.trim().is_empty() || line.trim_start().starts_with('#') {
+            continue;
+        }
+        if line.starts_with([' ', '\t', '-']) {
+            if let (Some(key), Some(item)) = (&current_key, line.trim().strip_prefix('-')) {
+                let item = unquote(item);
+                if !item.is_empty() {
+                    values.entry(key.clone()).or_default().push(item);
+                }
+            }
+            continue;
+        }
+        let Some((key, value)) = line.split_once(':') else {
+            current_key = None;
+            continue;
+        };
+        let key = key.trim().to_lowercase();
+        let value = value.trim();
+        let items = if let Some(inner) = value.strip_prefix('[').and_then(|v| v.strip_suffix(']')) {
+            split_flow_list(inner)
+        } else {
+            let value = unquote(value);
+            if value.is_empty() {
+                Vec::new()
+            } else {
+                scalar_keys.insert(key.clone());
+                vec![value]
+            }
+        };
+        values.insert(key.clone(), items);
+        current_key = Some(key);
+    }
+
+    let first = |key: &str| values.get(key).and_then(|v| v.first().cloned());
+    let front_matter = FrontMatter {
+        id: first("id"),
+        title: first("title"),
+        tags: ["tags", "tag"]
+            .into_iter()
+            .find_map(|key| {
+                let items = values.get(key)?;
+                Some(normalize_tags(items, scalar_keys.contains(key)))
+            })
+            .unwrap_or_default(),
+        created_at: first("created_at").and_then(|v| parse_datetime(&v)),
+        updated_at: first("updated_at").and_then(|v| parse_datetime(&v)),
+    };
+    (front_matter, body.to_string())
+}
+
+/// 書き出しの結果
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct VaultExportReport {
+    pub memo_count: usize,
+    pub folder_count: usize,
+}
+
+/// すべてのメモを `root` 以下に `.md` ファイルとして書き出す
+///
+/// フォルダの階層はディレクトリにする。同じ名前のファイルは上書きし、それ以外のファイルは残す。
+pub fn export_vault(
+    memos: &[Memo],
+    folders: &[Folder],
+    root: &Path,
+) -> Result<VaultExportReport, String> {
+    fs::create_dir_all(root).map_err(|e| format!("Failed to create {}: {}", root.display(), e))?;
+
+    let paths = folder_paths(folders);
+    for path in paths.values() {
+        let dir = root.join(path);
+        fs::create_dir_all(&dir)
+            .map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+    }
+
+    let mut used: HashMap<PathBuf, HashSet<String>> = HashMap::new();
+    for memo in memos {
+        let relative = memo
+            .folder_id
+            .as_ref()
+            .and_then(|id| paths.get(id))
+            .cloned()
+            .unwrap_or_default();
+        let stem = unique_name(
+            &sanitize_file_name(&memo.title),
+            used.entry(relative.clone()).or_default(),
+        );
+        let file = root.join(&relative).join(format!("{}.md", stem));
+        fs::write(&file, render_markdown(memo, &stem))
+            .map_err(|e| format!("Failed to write {}: {}", file.display(), e))?;
+    }
+
+    Ok(VaultExportReport {
+        memo_count: memos.len(),
+        folder_count: folders.len(),
+    })
+}
+
+/// 読み込みの結果
+#[derive(Debug, Clone, Default, Serialize, PartialEq)]
+pub struct VaultImportReport {
+    /// 新しく作ったメモのID
+    pub created: Vec<String>,
+    /// 内容が変わった既存のメモのID
+    pub updated: Vec<String>,
+    /// 新しく作ったフォルダのID
+    pub created_folders: Vec<String>,
+    /// 読めなかったファイル
+    pub skipped: Vec<String>,
+}
+
+pub fn is_markdown(path: &Path) -> bool {
+    path.extension()
+        .and_then(|e| e.to_str())
+        .is_some_and(|e| e.eq_ignore_ascii_case("md") || e.eq_ignore_ascii_case("markdown"))
+}
+
+pub fn modified_at(path: &Path) -> Option<DateTime<Utc>> {
+    let modified = fs::metadata(path).and_then(|m| m.modified()).ok()?;
+    Some(DateTime::<Utc>::from(modified))
+}
+
+/// `root` 以下の `.md` ファイルをメモとして読み込む
+///
+/// ディレクトリは `folder_paths` で書き出し先になるフォルダに対応させる（無ければ作る）。
+/// 前置きの `id` が既存のメモと一致する場合はそのメモを更新する。
+/// `.` で始まるファイル・ディレクトリ（`.obsidian` など）は読まない。
+pub fn import_vault(
+    memos: &mut Vec<Memo>,
+    folders: &mut Vec<Folder>,
+    root: &Path,
+) -> Result<VaultImportReport, String> {
+    if !root.is_dir() {
+        return Err(format!("{} is not a directory", root.display()));
+    }
+    let mut report = VaultImportReport::default();
+    let mut seen_ids = HashSet::new();
+    import_dir(
+        memos,
+        folders,
+        root,
+        Path::new(""),
+        None,
+        &mut seen_ids,
+        &mut report,
+    )?;
+    Ok(report)
+}
+
+fn import_dir(
+    memos: &mut Vec<Memo>,
+    folders: &mut Vec<Folder>,
+    dir: &Path,
+    relative: &Path,
+    folder_id: Option<String>,
+    seen_ids: &mut HashSet<String>,
+    report: &mut VaultImportReport,
+) -> Result<(), String> {
+    let mut entries: Vec<PathBuf> = fs::read_dir(dir)
+        .map_err(|e| format!("Failed to read {}: {}", dir.display(), e))?
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .collect();
+    entries.sort();
+
+    for path in entries {
+        let Some(name) = path.file_name().and_then(|n| n.to_str()) else {
+            report.skipped.push(path.display().to_string());
+            continue;
+        };
+        if name.starts_with('.') {
+            continue;
+        }
+
+        if path.is_dir() {
+            // Exported names are sanitized and deduplicated, so match on the export path
+            let child = relative.join(name);
+            let existing = folder_paths(folders)
+                .into_iter()
+                .find(|(_, path)| *path == child)
+                .map(|(id, _)| id);
+            let child_id = match existing {
+                Some(id) => id,
+                None => {
+                    let folder = Folder::new(name.to_string(), folder_id.clone());
+                    report.created_folders.push(folder.id.clone());
+                    let id = folder.id.clone();
+                    folders.push(folder);
+                    id
+                }
+            };
+            import_dir(
+                memos,
+                folders,
+                &path,
+                &child,
+                Some(child_id),
+                seen_ids,
+                report,
+            )?;
+        } else if is_markdown(&path) {
+            let Ok(text) = fs::read_to_string(&path) else {
+                report.skipped.push(path.display().to_string());
+                continue;
+            };
+            import_file(memos, &path, &text, folder_id.clone(), seen_ids, report);
+        }
+    }
+    Ok(())
+}
+
+fn import_file(
+    memos: &mut Vec<Memo>,
+    path: &Path,
+    text: &str,
+    folder_id: Option<String>,
+    seen_ids: &mut HashSet<String>,
+    report: &mut VaultImportReport,
+) {
+    let (front_matter, content) = parse_markdown(text);
+    let stem = path
+        .file_stem()
+        .and_then(|s| s.to_str())
+        .unwrap_or_default();
+    let title = front_matter.title.unwrap_or_else(|| stem.to_string());
+    // A copied file keeps the original id; only the first one updates the memo
+    let id = front_matter
+        .id
+        .filter(|id| !id.trim().is_empty() && !seen_ids.contains(id));
+    let now = Utc::now();
+
+    if let Some(memo) = id
+        .as_ref()
+        .and_then(|id| memos.iter_mut().find(|m| m.id == *id))
+    {
+        seen_ids.insert(memo.id.clone());
+        let changed = memo.title != title
+            || memo.content != content
+            || memo.folder_id != folder_id
+            || memo.tags != front_matter.tags;
+        if changed {
+            memo.title = title;
+            memo.content = content;
+            memo.folder_id = folder_id;
+            memo.tags = front_matter.tags;
+            memo.updated_at = front_matter
+                .updated_at
+                .filter(|t| *t > memo.updated_at)
+                .unwrap_or(now);
+            report.updated.push(memo.id.clone());
+        }
+        return;
+    }
+
+    let modified = modified_at(path);
+    let mut memo = Memo::new(title, content, folder_id, front_matter.tags);
+    memo.id = id.unwrap_or_else(|| Uuid::new_v4().to_string());
+    memo.created_at = front_matter.created_at.or(modified).unwrap_or(now);
+    memo.updated_at = front_matter.updated_at.or(modified).unwrap_or(now);
+    seen_ids.insert(memo.id.clone());
+    report.created.push(memo.id.clone());
+    memos.push(memo);
+}
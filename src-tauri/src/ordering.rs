@@ -0,0 +1,131 @@
+// 手動並び替え用のソートキー（分数インデックス）
+
+use crate::task::Task;
+
+/// キーに使う文字（辞書順と数値の順序が一致する）
+const DIGITS: &[u8] = b"0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz";
+
+/// ソートキーを持つ要素
+pub trait SortKey {
+    fn sort_key(&self) -> &str;
+    fn set_sort_key(&mut self, key: String);
+}
+
+fn digit_value(c: u8) -> usize {
+    DIGITS.iter().position(|&d| d == c).unwrap_or(0)
+}
+
+/// `key_between` が扱えるキーか（空でなく、`DIGITS` の文字だけで、末尾が `0` でない）
+fn is_valid_key(key: &str) -> bool {
+    key.bytes().all(|c| DIGITS.contains(&c)) && key.bytes().last().is_some_and(|c| c != DIGITS[0])
+}
+
+/// `a` と `b` の間に来るキーを生成する（`None` はそれぞれ先頭・末尾）
+///
+/// キーは末尾が `0` にならないため、任意の2つのキーの間に必ず新しいキーを作れる。
+/// 既存のキーを書き換える必要はない。
+pub fn key_between(a: Option<&str>, b: Option<&str>) -> String {
+    let a = a.unwrap_or("").as_bytes();
+    let b = b.map(str::as_bytes);
+    String::from_utf8(midpoint(a, b)).unwrap()
+}
+
+fn midpoint(a: &[u8], b: Option<&[u8]>) -> Vec<u8> {
+    // 共通の接頭辞（a の足りない桁は 0 とみなす）
+    if let Some(b) = b {
+        let mut n = 0;
+        while n < b.len() && a.get(n).copied().unwrap_or(DIGITS[0]) == b[n] {
+            n += 1;
+        }
+        if n > 0 {
+            let mut key = b[..n].to_vec();
+            let rest_a = if n < a.len() { &a[n..] } else { &[] };
+            key.extend(midpoint(rest_a, Some(&b[n..])));
+            return key;
+        }
+    }
+
+    let digit_a = a.first().map_or(0, |&c| digit_value(c));
+    let digit_b = b
+        .and_then(|b| b.first())
+        .map_or(DIGITS.len(), |&c| digit_value(c));
+
+    if digit_b.checked_sub(digit_a).is_some_and(|gap| gap > 1) {
+        vec![DIGITS[(digit_a + digit_b).div_ceil(2)]]
+    } else if b.is_some_and(|b| b.len() > 1) {
+        // b の先頭1桁だけのキーは a と b の間に来る
+        vec![b.unwrap()[0]]
+    } else {
+        let mut key = vec![DIGITS[digit_a]];
+        key.extend(midpoint(a.get(1..).unwrap_or(&[]), None));
+        key
+    }
+}
+
+/// `count` 個の昇順のキーを生成する
+pub fn sequential_keys(count: usize) -> Vec<String> {
+    let mut keys: Vec<String> = Vec::with_capacity(count);
+    for _ in 0..count {
+        keys.push(key_between(keys.last().map(String::as_str), None));
+    }
+    keys
+}
+
+/// 末尾に追加する要素のキー
+pub fn key_after_last<T: SortKey>(items: &[T]) -> String {
+    key_between(items.last().map(|item| item.sort_key()), None)
+}
+
+/// キーの順に並べ、キーが無い・重複している・不正な場合は現在の順序で振り直す
+///
+/// キーを振り直した場合は true を返す（旧データの移行用）。
+pub fn ensure_sort_keys<T: SortKey>(items: &mut [T]) -> bool {
+    if items.iter().all(|item| is_valid_key(item.sort_key())) {
+        items.sort_by(|a, b| a.sort_key().cmp(b.sort_key()));
+        if items
+            .windows(2)
+            .all(|pair| pair[0].sort_key() < pair[1].sort_key())
+        {
+            return false;
+        }
+    }
+
+    let keys = sequential_keys(items.len());
+    for (item, key) in items.iter_mut().zip(keys) {
+        item.set_sort_key(key);
+    }
+    true
+}
+
+/// `after` の直後（`None` は先頭）に要素を挿入し、その位置に合うキーを付ける
+///
+/// 書き換えるのは挿入する要素のキーだけ。
+pub fn insert_after<T: SortKey>(items: &mut Vec<T>, mut item: T, after: Option<usize>) {
+    ensure_sort_keys(items);
+    let position = after.map_or(0, |i| i + 1);
+    let previous = after.map(|i| items[i].sort_key());
+    let next = items.get(position).map(|next| next.sort_key());
+    item.set_sort_key(key_between(previous, next));
+    items.insert(position, item);
+}
+
+/// タスクを `after_id` の直後（`None` は先頭）に移動する
+pub fn move_task(tasks: &mut Vec<Task>, task_id: i32, after_id: Option<i32>) -> Result<(), String> {
+    if after_id == Some(task_id) {
+        return Err("A task cannot be placed after itself".to_string());
+    }
+    let index = tasks
+        .iter()
+        .position(|t| t.id == task_id)
+        .ok_or_else(|| format!("Task {} not found", task_id))?;
+    if let Some(after_id) = after_id {
+        if !tasks.iter().any(|t| t.id == after_id) {
+            return Err(format!("Task {} not found", after_id));
+        }
+    }
+
+    let task = tasks.remove(index);
+    let after = after_id.and_then(|id| tasks.iter().position(|t| t.id == id));
+    insert_after(tasks, task, after);
+    Ok(())
+}
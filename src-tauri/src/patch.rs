@@ -0,0 +1,36 @@
+// 部分更新（パッチ）の共通処理と競合検出
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Deserializer};
+
+/// 値が指定されていれば `null` でも `Some` として扱う
+///
+/// `Option<Option<T>>` のフィールドに `#[serde(default, deserialize_with = "present")]` を付けると、
+/// 省略（変更しない）と `null`（値を消す）を区別できる。
+pub fn present<'de, D, T>(deserializer: D) -> Result<Option<Option<T>>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: Deserialize<'de>,
+{
+    Option::<T>::deserialize(deserializer).map(Some)
+}
+
+/// 更新日時による競合検出（タスク・書籍・予定で共通）
+///
+/// クライアントが読み込んだ時点の更新日時 `expected` が現在の `current` と異なる場合は、
+/// 別のウィンドウなどで先に更新されているため `Conflict:` で始まるエラーを返す。
+/// `expected` が `None`（更新日時を送らない古いクライアント）の場合は確認しない。
+pub fn check_updated_at(
+    kind: &str,
+    id: &str,
+    current: Option<DateTime<Utc>>,
+    expected: Option<DateTime<Utc>>,
+) -> Result<(), String> {
+    if expected.is_some_and(|expected| current != Some(expected)) {
+        return Err(format!(
+            "Conflict: {} {} has been modified since it was loaded",
+            kind, id
+        ));
+    }
+    Ok(())
+}
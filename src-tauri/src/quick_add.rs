@@ -0,0 +1,560 @@
+// クイック追加（自然言語入力）のパーサー
+
+use chrono::{Datelike, Duration, NaiveDate, NaiveDateTime, NaiveTime, TimeDelta, Weekday};
+
+use crate::task_date::TaskDate;
+
+/// 日付・時刻の直前にある場合に取り除く英語の前置詞
+const CONNECTORS: [&str; 4] = ["at", "on", "by", "due"];
+
+/// 日付・時刻の直後にある場合に取り除く助詞（長いものから順に判定）
+const PARTICLES: [&str; 5] = ["までに", "まで", "に", "の", "、"];
+
+/// クイック追加の解析結果（`add_task` の各フィールドに対応）
+#[derive(Debug, Clone, PartialEq)]
+pub struct QuickAddTask {
+    pub description: String,
+    pub due_date: Option<TaskDate>,
+    pub group: Option<String>,
+    pub notification_minutes: Option<i32>,
+    pub dependencies: Option<Vec<i32>>,
+}
+
+/// 入力文字列を解析する
+///
+/// `"Submit report tomorrow 17:00 #Work !30m depends:12"` のような入力から
+/// 期限・グループ・通知タイミング・依存タスクを取り出し、残りを説明文とする。
+/// 日本語の相対日付（明日、来週月曜、3日後 など）にも対応する。
+/// 時刻のみ指定された場合、`now` を過ぎていれば翌日の時刻とする。
+pub fn parse_quick_add(text: &str, now: NaiveDateTime) -> Result<QuickAddTask, String> {
+    let normalized = normalize_width(text);
+    let tokens: Vec<&str> = normalized.split_whitespace().collect();
+
+    let mut parser = Parser::new(now.date());
+    // (トークン位置, 文字列)
+    let mut words: Vec<(usize, String)> = Vec::new();
+
+    let mut i = 0;
+    while i < tokens.len() {
+        match parser.parse_token(&tokens, i)? {
+            Consumed::Meta(count) => {
+                i += count;
+            }
+            Consumed::Temporal(count) => {
+                // "on friday" / "at 17:00" の前置詞を取り除く
+                if let Some((index, word)) = words.last() {
+                    if index + 1 == i && CONNECTORS.contains(&word.to_lowercase().as_str()) {
+                        words.pop();
+                    }
+                }
+                i += count;
+            }
+            Consumed::None => {
+                let rest = parser.scan_japanese(tokens[i]);
+                if !rest.is_empty() {
+                    words.push((i, rest));
+                }
+                i += 1;
+            }
+        }
+    }
+
+    let description = words
+        .into_iter()
+        .map(|(_, w)| w)
+        .collect::<Vec<_>>()
+        .join(" ");
+    if description.is_empty() {
+        return Err("Task description is empty".to_string());
+    }
+
+    let due_date = match (parser.date, parser.time) {
+        (Some(date), Some(time)) => Some(TaskDate::DateTime {
+            datetime: date.and_time(time),
+            timezone: None,
+        }),
+        (Some(date), None) => Some(TaskDate::Date(date)),
+        (None, Some(time)) => {
+            let date = if time <= now.time() {
+                now.date() + Duration::days(1)
+            } else {
+                now.date()
+            };
+            Some(TaskDate::DateTime {
+                datetime: date.and_time(time),
+                timezone: None,
+            })
+        }
+        (None, None) => None,
+    };
+
+    Ok(QuickAddTask {
+        description,
+        due_date,
+        group: parser.group,
+        notification_minutes: parser.notification_minutes,
+        dependencies: parser.dependencies,
+    })
+}
+
+/// トークンの消費結果
+enum Consumed {
+    None,
+    /// `#group` / `!30m` / `depends:` など（消費したトークン数）
+    Meta(usize),
+    /// 日付・時刻（消費したトークン数）
+    Temporal(usize),
+}
+
+struct Parser {
+    today: NaiveDate,
+    date: Option<NaiveDate>,
+    time: Option<NaiveTime>,
+    group: Option<String>,
+    notification_minutes: Option<i32>,
+    dependencies: Option<Vec<i32>>,
+}
+
+impl Parser {
+    fn new(today: NaiveDate) -> Self {
+        Self {
+            today,
+            date: None,
+            time: None,
+            group: None,
+            notification_minutes: None,
+            dependencies: None,
+        }
+    }
+
+    fn parse_token(&mut self, tokens: &[&str], i: usize) -> Result<Consumed, String> {
+        let token = tokens[i];
+
+        if let Some(name) = token.strip_prefix('#') {
+            if !name.is_empty() && self.group.is_none() {
+                self.group = Some(name.to_string());
+                return Ok(Consumed::Meta(1));
+            }
+        }
+
+        if let Some(spec) = token.strip_prefix('!') {
+            if self.notification_minutes.is_none() {
+                if let Some(minutes) = parse_notification(spec) {
+                    self.notification_minutes = Some(minutes);
+                    return Ok(Consumed::Meta(1));
+                }
+            }
+        }
+
+        if let Some(ids) = token.strip_prefix("depends:") {
+            let ids = ids
+                .split(',')
+                .filter(|s| !s.is_empty())
+                .map(|s| {
+                    s.trim_start_matches('#')
+                        .parse::<i32>()
+                        .map_err(|_| format!("Invalid dependency id '{}'", s))
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            let dependencies = self.dependencies.get_or_insert_with(Vec::new);
+            for id in ids {
+                if !dependencies.contains(&id) {
+                    dependencies.push(id);
+                }
+            }
+            return Ok(Consumed::Meta(1));
+        }
+
+        let lower: Vec<String> = tokens[i..]
+            .iter()
+            .take(3)
+            .map(|t| t.trim_end_matches([',', '.']).to_lowercase())
+            .collect();
+
+        if self.date.is_none() {
+            if let Some((date, count)) = self.parse_english_date(&lower) {
+                self.date = Some(date);
+                return Ok(Consumed::Temporal(count));
+            }
+        }
+
+        if self.time.is_none() {
+            if let Some((time, count)) = parse_english_time(&lower) {
+                self.time = Some(time);
+                return Ok(Consumed::Temporal(count));
+            }
+        }
+
+        Ok(Consumed::None)
+    }
+
+    fn parse_english_date(&self, words: &[String]) -> Option<(NaiveDate, usize)> {
+        let first = words[0].as_str();
+        let second = words.get(1).map(String::as_str);
+
+        match first {
+            "today" | "tonight" => return Some((self.today, 1)),
+            "tomorrow" | "tmr" | "tmrw" => return Some((self.today + Duration::days(1), 1)),
+            "next" => match second {
+                Some("week") => return Some((self.weekday_in_week(1, Weekday::Mon), 2)),
+                Some(day) => {
+                    let weekday = parse_english_weekday(day)?;
+                    return Some((self.weekday_in_week(1, weekday), 2));
+                }
+                None => return None,
+            },
+            "this" => {
+                let weekday = parse_english_weekday(second?)?;
+                return Some((self.weekday_in_week(0, weekday), 2));
+            }
+            "in" => {
+                let amount: i64 = second?.parse().ok()?;
+                let days = match words.get(2)?.as_str() {
+                    "day" | "days" => amount,
+                    "week" | "weeks" => amount.checked_mul(7)?,
+                    _ => return None,
+                };
+                let date = self.today.checked_add_signed(TimeDelta::try_days(days)?)?;
+                return Some((date, 3));
+            }
+            _ => {}
+        }
+
+        if let Some(weekday) = parse_english_weekday(first) {
+            return Some((self.upcoming_weekday(weekday), 1));
+        }
+
+        if let Ok(date) = NaiveDate::parse_from_str(first, "%Y-%m-%d") {
+            return Some((date, 1));
+        }
+
+        // "2024/6/1", "6/1/2024", "6/1/24", "6/1"
+        let parts: Vec<&str> = first.split('/').collect();
+        let numbers: Vec<u32> = parts.iter().filter_map(|p| p.parse().ok()).collect();
+        if numbers.len() != parts.len() {
+            return None;
+        }
+        // Two-digit years are 20xx; any other length but four is not a year
+        let year = |index: usize| match parts[index].len() {
+            2 => Some(2000 + numbers[index] as i32),
+            4 => Some(numbers[index] as i32),
+            _ => None,
+        };
+        match numbers.as_slice() {
+            [_, month, day] if parts[0].len() == 4 => {
+                NaiveDate::from_ymd_opt(year(0)?, *month, *day).map(|d| (d, 1))
+            }
+            [month, day, _] => NaiveDate::from_ymd_opt(year(2)?, *month, *day).map(|d| (d, 1)),
+            [month, day] => self.month_day(*month, *day).map(|d| (d, 1)),
+            _ => None,
+        }
+    }
+
+    /// トークン内の日本語の日付・時刻表現を取り除き、残りの文字列を返す
+    fn scan_japanese(&mut self, token: &str) -> String {
+        let chars: Vec<char> = token.chars().collect();
+        let mut rest = String::new();
+        let mut pos = 0;
+
+        while pos < chars.len() {
+            // "25:00" の途中の "5:00" のように数字の途中からはマッチさせない
+            let after_digit = pos > 0 && chars[pos - 1].is_ascii_digit();
+            let consumed = if after_digit {
+                0
+            } else {
+                self.match_japanese(&chars[pos..])
+            };
+            if consumed > 0 {
+                pos += consumed;
+                if let Some(particle) = PARTICLES.iter().find(|p| starts_with(&chars[pos..], p)) {
+                    pos += particle.chars().count();
+                }
+            } else {
+                rest.push(chars[pos]);
+                pos += 1;
+            }
+        }
+
+        rest
+    }
+
+    /// 先頭から日付・時刻表現にマッチした文字数を返す（0はマッチなし）
+    fn match_japanese(&mut self, chars: &[char]) -> usize {
+        if self.date.is_none() {
+            if let Some((date, len)) = self.match_japanese_date(chars) {
+                self.date = Some(date);
+                return len;
+            }
+        }
+        if self.time.is_none() {
+            if let Some((time, len)) = match_japanese_time(chars) {
+                self.time = Some(time);
+                return len;
+            }
+        }
+        0
+    }
+
+    fn match_japanese_date(&self, chars: &[char]) -> Option<(NaiveDate, usize)> {
+        const RELATIVE_DAYS: [(&str, i64); 7] = [
+            ("明後日", 2),
+            ("あさって", 2),
+            ("明日", 1),
+            ("あした", 1),
+            ("今日", 0),
+            ("きょう", 0),
+            ("今夜", 0),
+        ];
+        for (word, days) in RELATIVE_DAYS {
+            if starts_with(chars, word) {
+                return Some((self.today + Duration::days(days), word.chars().count()));
+            }
+        }
+
+        const RELATIVE_WEEKS: [(&str, i64); 3] = [("再来週", 2), ("来週", 1), ("今週", 0)];
+        for (word, weeks) in RELATIVE_WEEKS {
+            if starts_with(chars, word) {
+                let mut len = word.chars().count();
+                if starts_with(&chars[len..], "の") {
+                    len += 1;
+                }
+                if let Some((weekday, weekday_len)) = match_japanese_weekday(&chars[len..]) {
+                    return Some((self.weekday_in_week(weeks, weekday), len + weekday_len));
+                }
+                // 「今週」単体は期限として扱わない
+                if weeks > 0 {
+                    let len = word.chars().count();
+                    return Some((self.weekday_in_week(weeks, Weekday::Mon), len));
+                }
+                return None;
+            }
+        }
+
+        if let Some((weekday, len)) = match_japanese_weekday(chars) {
+            return Some((self.upcoming_weekday(weekday), len));
+        }
+
+        let (first, first_len) = take_number(chars)?;
+        let after = &chars[first_len..];
+
+        if starts_with(after, "日後") {
+            return Some((self.today + Duration::days(first as i64), first_len + 2));
+        }
+        if starts_with(after, "週間後") {
+            return Some((self.today + Duration::weeks(first as i64), first_len + 3));
+        }
+
+        // "2025年1月10日"
+        if starts_with(after, "年") {
+            let (month, month_len) = take_number(&after[1..])?;
+            let after_month = &after[1 + month_len..];
+            if !starts_with(after_month, "月") {
+                return None;
+            }
+            let (day, day_len) = take_number(&after_month[1..])?;
+            if !starts_with(&after_month[1 + day_len..], "日") {
+                return None;
+            }
+            let date = NaiveDate::from_ymd_opt(first as i32, month, day)?;
+            return Some((date, first_len + 1 + month_len + 1 + day_len + 1));
+        }
+
+        // "6月1日"
+        if starts_with(after, "月") {
+            let (day, day_len) = take_number(&after[1..])?;
+            if !starts_with(&after[1 + day_len..], "日") {
+                return None;
+            }
+            let date = self.month_day(first, day)?;
+            return Some((date, first_len + 1 + day_len + 1));
+        }
+
+        None
+    }
+
+    /// 今週を0として `weeks` 週後の指定曜日（週は月曜始まり）
+    fn weekday_in_week(&self, weeks: i64, weekday: Weekday) -> NaiveDate {
+        let monday =
+            self.today - Duration::days(self.today.weekday().num_days_from_monday() as i64);
+        monday + Duration::weeks(weeks) + Duration::days(weekday.num_days_from_monday() as i64)
+    }
+
+    /// 今日より後で最も近い指定曜日
+    fn upcoming_weekday(&self, weekday: Weekday) -> NaiveDate {
+        let diff = (weekday.num_days_from_monday() as i64
+            - self.today.weekday().num_days_from_monday() as i64)
+            .rem_euclid(7);
+        let diff = if diff == 0 { 7 } else { diff };
+        self.today + Duration::days(diff)
+    }
+
+    /// 年の指定が無い月日。既に過ぎていれば翌年とする
+    fn month_day(&self, month: u32, day: u32) -> Option<NaiveDate> {
+        let date = NaiveDate::from_ymd_opt(self.today.year(), month, day)?;
+        if date < self.today {
+            NaiveDate::from_ymd_opt(self.today.year() + 1, month, day)
+        } else {
+            Some(date)
+        }
+    }
+}
+
+/// 全角の英数字・記号・スペースを半角に変換
+fn normalize_width(text: &str) -> String {
+    text.chars()
+        .map(|c| match c {
+            '！'..='～' => char::from_u32(c as u32 - 0xFEE0).unwrap_or(c),
+            '\u{3000}' => ' ',
+            _ => c,
+        })
+        .collect()
+}
+
+/// `30m` / `2h` / `1d` / `45` を分数に変換
+fn parse_notification(spec: &str) -> Option<i32> {
+    let digits_end = spec
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(spec.len());
+    let amount: i32 = spec[..digits_end].parse().ok()?;
+    let multiplier = match &spec[digits_end..] {
+        "" | "m" | "min" => 1,
+        "h" => 60,
+        "d" => 1440,
+        _ => return None,
+    };
+    amount.checked_mul(multiplier)
+}
+
+/// 曜日名（"sun" などの略称は普通の単語と紛らわしいため受け付けない）
+fn parse_english_weekday(word: &str) -> Option<Weekday> {
+    match word {
+        "monday" => Some(Weekday::Mon),
+        "tuesday" => Some(Weekday::Tue),
+        "wednesday" => Some(Weekday::Wed),
+        "thursday" => Some(Weekday::Thu),
+        "friday" => Some(Weekday::Fri),
+        "saturday" => Some(Weekday::Sat),
+        "sunday" => Some(Weekday::Sun),
+        _ => None,
+    }
+}
+
+/// `17:00` / `9:30am` / `5pm` / `5 pm` / `noon`
+fn parse_english_time(words: &[String]) -> Option<(NaiveTime, usize)> {
+    let first = words[0].as_str();
+    if first == "noon" {
+        return NaiveTime::from_hms_opt(12, 0, 0).map(|t| (t, 1));
+    }
+
+    let (clock, suffix, count) = if let Some(clock) = first.strip_suffix("am") {
+        (clock, Some("am"), 1)
+    } else if let Some(clock) = first.strip_suffix("pm") {
+        (clock, Some("pm"), 1)
+    } else {
+        match words.get(1).map(String::as_str) {
+            Some(suffix @ ("am" | "pm")) => (first, Some(suffix), 2),
+            _ => (first, None, 1),
+        }
+    };
+
+    let (hour, minute) = match clock.split_once(':') {
+        Some((h, m)) if m.len() == 2 => (h.parse::<u32>().ok()?, m.parse::<u32>().ok()?),
+        // 時のみの指定は am/pm が付いている場合に限る
+        None if suffix.is_some() => (clock.parse::<u32>().ok()?, 0),
+        _ => return None,
+    };
+
+    let hour = match suffix {
+        Some(_) if !(1..=12).contains(&hour) => return None,
+        Some("am") => hour % 12,
+        Some(_) => hour % 12 + 12,
+        None => hour,
+    };
+
+    NaiveTime::from_hms_opt(hour, minute, 0).map(|t| (t, count))
+}
+
+/// `月曜` / `月曜日`
+fn match_japanese_weekday(chars: &[char]) -> Option<(Weekday, usize)> {
+    if chars.len() < 2 || chars[1] != '曜' {
+        return None;
+    }
+    let weekday = match chars[0] {
+        '月' => Weekday::Mon,
+        '火' => Weekday::Tue,
+        '水' => Weekday::Wed,
+        '木' => Weekday::Thu,
+        '金' => Weekday::Fri,
+        '土' => Weekday::Sat,
+        '日' => Weekday::Sun,
+        _ => return None,
+    };
+    let len = if chars.get(2) == Some(&'日') { 3 } else { 2 };
+    Some((weekday, len))
+}
+
+/// `17時` / `17時30分` / `午後3時半` / `17:30` / `正午`
+fn match_japanese_time(chars: &[char]) -> Option<(NaiveTime, usize)> {
+    if starts_with(chars, "正午") {
+        return NaiveTime::from_hms_opt(12, 0, 0).map(|t| (t, 2));
+    }
+
+    let (afternoon, mut len) = if starts_with(chars, "午後") {
+        (Some(true), 2)
+    } else if starts_with(chars, "午前") {
+        (Some(false), 2)
+    } else {
+        (None, 0)
+    };
+
+    let (mut hour, hour_len) = take_number(&chars[len..])?;
+    len += hour_len;
+
+    let minute = if starts_with(&chars[len..], "時") {
+        len += 1;
+        if starts_with(&chars[len..], "半") {
+            len += 1;
+            30
+        } else if let Some((minute, minute_len)) = take_number(&chars[len..]) {
+            if !starts_with(&chars[len + minute_len..], "分") {
+                return None;
+            }
+            len += minute_len + 1;
+            minute
+        } else {
+            0
+        }
+    } else if chars.get(len) == Some(&':') {
+        let (minute, minute_len) = take_number(&chars[len + 1..])?;
+        if minute_len != 2 {
+            return None;
+        }
+        len += 1 + minute_len;
+        minute
+    } else {
+        return None;
+    };
+
+    match afternoon {
+        Some(true) if hour < 12 => hour += 12,
+        Some(false) if hour == 12 => hour = 0,
+        _ => {}
+    }
+
+    NaiveTime::from_hms_opt(hour, minute, 0).map(|t| (t, len))
+}
+
+/// 先頭の数字列を読み取る
+fn take_number(chars: &[char]) -> Option<(u32, usize)> {
+    let len = chars.iter().take_while(|c| c.is_ascii_digit()).count();
+    if len == 0 || len > 4 {
+        return None;
+    }
+    let number: String = chars[..len].iter().collect();
+    number.parse().ok().map(|n| (n, len))
+}
+
+fn starts_with(chars: &[char], prefix: &str) -> bool {
+    let mut iter = chars.iter();
+    prefix.chars().all(|p| iter.next() == Some(&p))
+}
@@ -4,6 +4,9 @@ use std::fs;
 use std::path::PathBuf;
 use uuid::Uuid;
 
+use crate::book_cover::BookCover;
+use crate::patch::present;
+
 /// 読書ステータス
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 #[serde(rename_all = "snake_case")]
@@ -15,7 +18,7 @@ pub enum ReadingStatus {
 }
 
 /// 読書メモ/引用
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct ReadingNote {
     pub id: String,
     pub page_number: Option<u32>,
@@ -37,7 +40,7 @@ impl ReadingNote {
 }
 
 /// 読書セッション（日毎の読書記録）
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct ReadingSession {
     pub id: String,
     pub session_date: DateTime<Utc>,
@@ -69,8 +72,43 @@ impl ReadingSession {
     }
 }
 
+/// 書籍情報の部分更新（指定された項目だけを変更する）
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct ReadingBookPatch {
+    #[serde(default)]
+    pub title: Option<String>,
+    #[serde(default, deserialize_with = "present")]
+    pub author: Option<Option<String>>,
+    #[serde(default, deserialize_with = "present")]
+    pub isbn: Option<Option<String>>,
+    #[serde(default, deserialize_with = "present")]
+    pub publisher: Option<Option<String>>,
+    #[serde(default, deserialize_with = "present")]
+    pub published_year: Option<Option<i32>>,
+    #[serde(default, deserialize_with = "present")]
+    pub cover_image_url: Option<Option<String>>,
+    #[serde(default)]
+    pub genres: Option<Vec<String>>,
+    #[serde(default)]
+    pub status: Option<ReadingStatus>,
+    #[serde(default, deserialize_with = "present")]
+    pub start_date: Option<Option<DateTime<Utc>>>,
+    #[serde(default, deserialize_with = "present")]
+    pub finish_date: Option<Option<DateTime<Utc>>>,
+    #[serde(default, deserialize_with = "present")]
+    pub total_pages: Option<Option<u32>>,
+    #[serde(default, deserialize_with = "present")]
+    pub current_page: Option<Option<u32>>,
+    #[serde(default, deserialize_with = "present")]
+    pub rating: Option<Option<u8>>,
+    #[serde(default)]
+    pub summary: Option<String>,
+    #[serde(default)]
+    pub tags: Option<Vec<String>>,
+}
+
 /// 読書書籍情報
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct ReadingBook {
     pub id: String,
     pub title: String,
@@ -79,6 +117,9 @@ pub struct ReadingBook {
     pub publisher: Option<String>,
     pub published_year: Option<i32>,
     pub cover_image_url: Option<String>,
+    /// データディレクトリに保存した表紙画像
+    #[serde(default)]
+    pub cover: Option<BookCover>,
     pub genres: Vec<String>,
     pub status: ReadingStatus,
     pub start_date: Option<DateTime<Utc>>,
@@ -107,6 +148,7 @@ impl ReadingBook {
             publisher: None,
             published_year: None,
             cover_image_url: None,
+            cover: None,
             genres: Vec::new(),
             status: ReadingStatus::WantToRead,
             start_date: None,
@@ -124,41 +166,54 @@ impl ReadingBook {
         }
     }
 
-    /// 書籍情報を更新
-    pub fn update(
-        &mut self,
-        title: String,
-        author: Option<String>,
-        isbn: Option<String>,
-        publisher: Option<String>,
-        published_year: Option<i32>,
-        cover_image_url: Option<String>,
-        genres: Vec<String>,
-        status: ReadingStatus,
-        start_date: Option<DateTime<Utc>>,
-        finish_date: Option<DateTime<Utc>>,
-        total_pages: Option<u32>,
-        current_page: Option<u32>,
-        rating: Option<u8>,
-        summary: String,
-        tags: Vec<String>,
-    ) {
-        self.title = title;
-        self.author = author;
-        self.isbn = isbn;
-        self.publisher = publisher;
-        self.published_year = published_year;
-        self.cover_image_url = cover_image_url;
-        self.genres = genres;
-        self.status = status;
-        self.start_date = start_date;
-        self.finish_date = finish_date;
-        self.total_pages = total_pages;
-        self.current_page = current_page;
-        self.rating = rating;
-        self.summary = summary;
-        self.tags = tags;
-        self.updated_at = Utc::now();
+    /// 部分更新を適用する
+    pub fn apply_patch(&mut self, patch: ReadingBookPatch, now: DateTime<Utc>) {
+        if let Some(title) = patch.title {
+            self.title = title;
+        }
+        if let Some(author) = patch.author {
+            self.author = author;
+        }
+        if let Some(isbn) = patch.isbn {
+            self.isbn = isbn;
+        }
+        if let Some(publisher) = patch.publisher {
+            self.publisher = publisher;
+        }
+        if let Some(published_year) = patch.published_year {
+            self.published_year = published_year;
+        }
+        if let Some(cover_image_url) = patch.cover_image_url {
+            self.cover_image_url = cover_image_url;
+        }
+        if let Some(genres) = patch.genres {
+            self.genres = genres;
+        }
+        if let Some(status) = patch.status {
+            self.status = status;
+        }
+        if let Some(start_date) = patch.start_date {
+            self.start_date = start_date;
+        }
+        if let Some(finish_date) = patch.finish_date {
+            self.finish_date = finish_date;
+        }
+        if let Some(total_pages) = patch.total_pages {
+            self.total_pages = total_pages;
+        }
+        if let Some(current_page) = patch.current_page {
+            self.current_page = current_page;
+        }
+        if let Some(rating) = patch.rating {
+            self.rating = rating;
+        }
+        if let Some(summary) = patch.summary {
+            self.summary = summary;
+        }
+        if let Some(tags) = patch.tags {
+            self.tags = tags;
+        }
+        self.updated_at = now;
 
         // 進捗率を計算
         if let (Some(total), Some(current)) = (self.total_pages, self.current_page) {
@@ -0,0 +1,178 @@
+// スケジュール計算（クリティカルパス・余裕日数・自動スケジュール）
+
+use std::collections::HashMap;
+
+use chrono::{Duration, NaiveDate};
+use serde::Serialize;
+
+use crate::dependency::topological_order;
+use crate::task::Task;
+use crate::task_date::TaskDate;
+
+/// タスクごとの計算結果（日単位）
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct TaskSchedule {
+    pub task_id: i32,
+    pub earliest_start: NaiveDate,
+    pub earliest_finish: NaiveDate,
+    pub latest_start: NaiveDate,
+    pub latest_finish: NaiveDate,
+    pub slack_days: i64,
+    pub critical: bool,
+}
+
+/// プロジェクト全体のスケジュール
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct ProjectSchedule {
+    pub tasks: Vec<TaskSchedule>,
+    /// 余裕日数0のタスク（最早開始日順）
+    pub critical_path: Vec<i32>,
+    pub project_start: Option<NaiveDate>,
+    pub project_end: Option<NaiveDate>,
+}
+
+/// 予定上の開始日と所要日数
+///
+/// 期限の無いタスクは配置できないため `None`。開始日が無い場合は期限日に
+/// 所要0日のマイルストーンとして扱う。
+fn planned(task: &Task) -> Option<(NaiveDate, i64)> {
+    let due = task.due_date?.date();
+    let start = task.start_date.map(|d| d.date()).unwrap_or(due).min(due);
+    Some((start, (due - start).num_days()))
+}
+
+/// クリティカルパスと各タスクの余裕日数を計算する
+///
+/// 依存関係は「依存先の期限日以降に開始できる」（終了-開始、同日可）として扱う。
+/// 期限の無いタスクは計算から除外し、それを経由する依存も無視する。
+pub fn compute_schedule(tasks: &[Task]) -> ProjectSchedule {
+    let by_id: HashMap<i32, &Task> = tasks.iter().map(|t| (t.id, t)).collect();
+    let order: Vec<i32> = topological_order(tasks)
+        .into_iter()
+        .filter(|id| planned(by_id[id]).is_some())
+        .collect();
+
+    let blockers_of = |id: i32| -> Vec<i32> {
+        by_id[&id]
+            .dependencies
+            .iter()
+            .flatten()
+            .filter(|dep| by_id.get(dep).is_some_and(|t| planned(t).is_some()))
+            .copied()
+            .collect()
+    };
+
+    // Forward pass (completed tasks stay where they are)
+    let mut earliest: HashMap<i32, (NaiveDate, NaiveDate)> = HashMap::new();
+    for &id in &order {
+        let (planned_start, duration) = planned(by_id[&id]).unwrap();
+        if by_id[&id].completed {
+            earliest.insert(
+                id,
+                (planned_start, planned_start + Duration::days(duration)),
+            );
+            continue;
+        }
+        let start = blockers_of(id)
+            .iter()
+            .filter_map(|dep| earliest.get(dep).map(|(_, finish)| *finish))
+            .fold(planned_start, NaiveDate::max);
+        earliest.insert(id, (start, start + Duration::days(duration)));
+    }
+
+    let project_start = earliest.values().map(|(start, _)| *start).min();
+    let project_end = earliest.values().map(|(_, finish)| *finish).max();
+
+    // Backward pass
+    let mut dependents: HashMap<i32, Vec<i32>> = HashMap::new();
+    for &id in &order {
+        for dep in blockers_of(id) {
+            dependents.entry(dep).or_default().push(id);
+        }
+    }
+
+    let mut latest: HashMap<i32, (NaiveDate, NaiveDate)> = HashMap::new();
+    for &id in order.iter().rev() {
+        let (_, duration) = planned(by_id[&id]).unwrap();
+        let finish = dependents
+            .get(&id)
+            .into_iter()
+            .flatten()
+            .filter_map(|d| latest.get(d).map(|(start, _)| *start))
+            .fold(project_end.unwrap(), NaiveDate::min);
+        latest.insert(id, (finish - Duration::days(duration), finish));
+    }
+
+    let mut schedules: Vec<TaskSchedule> = order
+        .iter()
+        .map(|id| {
+            let (earliest_start, earliest_finish) = earliest[id];
+            let (latest_start, latest_finish) = latest[id];
+            let slack_days = (latest_start - earliest_start).num_days();
+            TaskSchedule {
+                task_id: *id,
+                earliest_start,
+                earliest_finish,
+                latest_start,
+                latest_finish,
+                slack_days,
+                critical: slack_days == 0,
+            }
+        })
+        .collect();
+
+    let mut critical: Vec<&TaskSchedule> = schedules.iter().filter(|s| s.critical).collect();
+    critical.sort_by_key(|s| (s.earliest_start, s.earliest_finish));
+    let critical_path = critical.iter().map(|s| s.task_id).collect();
+
+    // Keep the original task order in the per-task list
+    let position: HashMap<i32, usize> = tasks.iter().enumerate().map(|(i, t)| (t.id, i)).collect();
+    schedules.sort_by_key(|s| position[&s.task_id]);
+
+    ProjectSchedule {
+        tasks: schedules,
+        critical_path,
+        project_start,
+        project_end,
+    }
+}
+
+/// 依存先の遅れに合わせて後続タスクの日付を後ろにずらす
+///
+/// 最早開始日が予定開始日より後になっている未完了タスクについて、開始日と
+/// 期限を同じ日数だけ移動する（前倒しはしない）。移動したタスクのIDを返す。
+pub fn auto_schedule(tasks: &mut [Task]) -> Vec<i32> {
+    let schedule = compute_schedule(tasks);
+    let mut moved = Vec::new();
+
+    for entry in schedule.tasks {
+        let Some(task) = tasks.iter_mut().find(|t| t.id == entry.task_id) else {
+            continue;
+        };
+        if task.completed {
+            continue;
+        }
+        let Some((planned_start, _)) = planned(task) else {
+            continue;
+        };
+        let delta = (entry.earliest_start - planned_start).num_days();
+        if delta <= 0 {
+            continue;
+        }
+
+        let shift = |date: Option<TaskDate>| match date {
+            Some(date) => date.shift_days(delta).map(Some),
+            None => Some(None),
+        };
+        let (Some(start_date), Some(due_date)) = (shift(task.start_date), shift(task.due_date))
+        else {
+            continue;
+        };
+        task.start_date = start_date;
+        task.due_date = due_date;
+        task.notified = false;
+        moved.push(task.id);
+    }
+
+    moved
+}
@@ -0,0 +1,314 @@
+// サブタスクの入れ子構造の操作（移動・昇格・降格・完了状態の集約）
+
+use chrono::{DateTime, Utc};
+
+use crate::dependency::{incomplete_blockers, remove_dependency_references};
+use crate::group::TaskGroup;
+use crate::ordering::{self, insert_after};
+use crate::task::{Subtask, Task};
+use crate::workflow;
+
+/// IDでサブタスクを探す（子孫も含む）
+pub fn find(subtasks: &[Subtask], id: i32) -> Option<&Subtask> {
+    for subtask in subtasks {
+        if subtask.id == id {
+            return Some(subtask);
+        }
+        if let Some(found) = find(&subtask.subtasks, id) {
+            return Some(found);
+        }
+    }
+    None
+}
+
+/// IDでサブタスクを探す（子孫も含む）
+pub fn find_mut(subtasks: &mut [Subtask], id: i32) -> Option<&mut Subtask> {
+    for subtask in subtasks.iter_mut() {
+        if subtask.id == id {
+            return Some(subtask);
+        }
+        if let Some(found) = find_mut(&mut subtask.subtasks, id) {
+            return Some(found);
+        }
+    }
+    None
+}
+
+/// 子孫を含むすべてのサブタスク（親が先）
+pub fn flatten(subtasks: &[Subtask]) -> Vec<&Subtask> {
+    let mut all = Vec::new();
+    for subtask in subtasks {
+        all.push(subtask);
+        all.extend(flatten(&subtask.subtasks));
+    }
+    all
+}
+
+/// 子孫を含むサブタスクIDの最大値（無ければ 0）
+pub fn max_id(subtasks: &[Subtask]) -> i32 {
+    subtasks
+        .iter()
+        .map(|s| s.id.max(max_id(&s.subtasks)))
+        .max()
+        .unwrap_or(0)
+}
+
+/// サブタスクを子孫ごと取り出す
+pub fn take(subtasks: &mut Vec<Subtask>, id: i32) -> Option<Subtask> {
+    if let Some(pos) = subtasks.iter().position(|s| s.id == id) {
+        return Some(subtasks.remove(pos));
+    }
+    subtasks.iter_mut().find_map(|s| take(&mut s.subtasks, id))
+}
+
+/// `parent_id` の子の一覧（`None` はタスク直下）
+pub fn children(subtasks: &[Subtask], parent_id: Option<i32>) -> Result<&[Subtask], String> {
+    match parent_id {
+        None => Ok(subtasks),
+        Some(id) => find(subtasks, id)
+            .map(|s| s.subtasks.as_slice())
+            .ok_or_else(|| format!("Subtask {} not found", id)),
+    }
+}
+
+/// `parent_id` の子の一覧（`None` はタスク直下）
+pub fn children_mut(
+    subtasks: &mut Vec<Subtask>,
+    parent_id: Option<i32>,
+) -> Result<&mut Vec<Subtask>, String> {
+    match parent_id {
+        None => Ok(subtasks),
+        Some(id) => find_mut(subtasks, id)
+            .map(|s| &mut s.subtasks)
+            .ok_or_else(|| format!("Subtask {} not found", id)),
+    }
+}
+
+/// サブタスクと子孫のIDを `next_id` から振り直す
+fn renumber(subtask: &mut Subtask, next_id: &mut i32) {
+    subtask.id = *next_id;
+    *next_id += 1;
+    for child in subtask.subtasks.iter_mut() {
+        renumber(child, next_id);
+    }
+}
+
+/// 子孫を含めてキーの順に並べる（旧データの移行用、キーを振り直した場合は true）
+pub fn ensure_sort_keys(subtasks: &mut [Subtask]) -> bool {
+    let mut changed = ordering::ensure_sort_keys(subtasks);
+    for subtask in subtasks.iter_mut() {
+        changed |= ensure_sort_keys(&mut subtask.subtasks);
+    }
+    changed
+}
+
+/// サブタスクを子孫ごと移動する
+///
+/// `target_task_id` のタスクの `target_parent_id` の子（`None` はタスク直下）として、
+/// `after_subtask_id` の直後（`None` は先頭）に配置する。
+/// 別のタスクへ移動する場合、IDが移動先で重複しないよう振り直す。
+pub fn move_subtask(
+    tasks: &mut [Task],
+    task_id: i32,
+    subtask_id: i32,
+    target_task_id: i32,
+    target_parent_id: Option<i32>,
+    after_subtask_id: Option<i32>,
+) -> Result<(), String> {
+    let source = tasks
+        .iter()
+        .position(|t| t.id == task_id)
+        .ok_or_else(|| format!("Task {} not found", task_id))?;
+    let target = tasks
+        .iter()
+        .position(|t| t.id == target_task_id)
+        .ok_or_else(|| format!("Task {} not found", target_task_id))?;
+    let moving = find(&tasks[source].subtasks, subtask_id)
+        .ok_or_else(|| format!("Subtask {} not found", subtask_id))?;
+
+    if let (true, Some(parent_id)) = (source == target, target_parent_id) {
+        if parent_id == subtask_id || find(&moving.subtasks, parent_id).is_some() {
+            return Err("A subtask cannot be moved into itself or its descendants".to_string());
+        }
+    }
+    let siblings = children(&tasks[target].subtasks, target_parent_id)?;
+    if let Some(after_id) = after_subtask_id {
+        let same_item = source == target && after_id == subtask_id;
+        if same_item || !siblings.iter().any(|s| s.id == after_id) {
+            return Err(format!("Subtask {} not found", after_id));
+        }
+    }
+
+    let mut subtask = take(&mut tasks[source].subtasks, subtask_id).unwrap();
+    if source != target {
+        let mut next_id = tasks[target].next_subtask_id();
+        renumber(&mut subtask, &mut next_id);
+    }
+    let siblings = children_mut(&mut tasks[target].subtasks, target_parent_id)?;
+    let after = after_subtask_id.and_then(|id| siblings.iter().position(|s| s.id == id));
+    insert_after(siblings, subtask, after);
+    Ok(())
+}
+
+/// サブタスクをタスクに昇格させる
+///
+/// 子孫のサブタスクはそのまま引き継ぎ、メモは詳細になる。
+/// 新しいタスクは親タスクの直後に置かれ、グループと集約の設定を親から引き継ぐ。
+pub fn promote(
+    tasks: &mut Vec<Task>,
+    task_id: i32,
+    subtask_id: i32,
+    new_id: i32,
+) -> Result<(), String> {
+    let index = tasks
+        .iter()
+        .position(|t| t.id == task_id)
+        .ok_or_else(|| format!("Task {} not found", task_id))?;
+    let subtask = take(&mut tasks[index].subtasks, subtask_id)
+        .ok_or_else(|| format!("Subtask {} not found", subtask_id))?;
+
+    let parent = &tasks[index];
+    let task = Task {
+        id: new_id,
+        description: subtask.description,
+        start_date: None,
+        due_date: subtask.due_date,
+        group_id: parent.group_id.clone(),
+        details: subtask.notes,
+        completed: subtask.completed,
+        notified: false,
+        notification_minutes: None,
+        subtasks: subtask.subtasks,
+        dependencies: None,
+        priority: None,
+        estimated_minutes: None,
+        time_entries: Vec::new(),
+        tags: Vec::new(),
+        state: None,
+        state_history: Vec::new(),
+        sort_key: String::new(),
+        rollup_subtasks: parent.rollup_subtasks,
+        updated_at: None,
+    };
+    insert_after(tasks, task, Some(index));
+    Ok(())
+}
+
+/// タスクを別のタスク（`parent_subtask_id` を指定した場合はそのサブタスク）の
+/// 最後のサブタスクに降格させる
+///
+/// 引き継ぐのは説明・期日・詳細（メモ）・完了状態・サブタスクのみ。
+/// それ以外の情報（タグ・依存先・優先度など）を持つタスクは、`force` でなければエラーにする。
+/// 作業記録のあるタスクは降格できない。他のタスクからの依存は削除する。
+/// 依存を削除したタスクのIDを返す。
+pub fn demote(
+    tasks: &mut Vec<Task>,
+    task_id: i32,
+    parent_task_id: i32,
+    parent_subtask_id: Option<i32>,
+    force: bool,
+) -> Result<Vec<i32>, String> {
+    if task_id == parent_task_id {
+        return Err("A task cannot be demoted into itself".to_string());
+    }
+    let index = tasks
+        .iter()
+        .position(|t| t.id == task_id)
+        .ok_or_else(|| format!("Task {} not found", task_id))?;
+    let parent = tasks
+        .iter()
+        .find(|t| t.id == parent_task_id)
+        .ok_or_else(|| format!("Task {} not found", parent_task_id))?;
+    children(&parent.subtasks, parent_subtask_id)?;
+    if !tasks[index].time_entries.is_empty() {
+        return Err("A task with time entries cannot be demoted".to_string());
+    }
+    let lost = demote_losses(&tasks[index], parent);
+    if !force && !lost.is_empty() {
+        return Err(format!(
+            "Task {} has data a subtask cannot keep ({}); demote with force to discard it",
+            task_id,
+            lost.join(", ")
+        ));
+    }
+
+    let task = tasks.remove(index);
+    let dependents = remove_dependency_references(tasks, task_id);
+
+    let parent = tasks.iter_mut().find(|t| t.id == parent_task_id).unwrap();
+    let mut subtask = Subtask {
+        id: 0,
+        description: task.description,
+        completed: task.completed,
+        due_date: task.due_date,
+        notes: task.details,
+        subtasks: task.subtasks,
+        sort_key: String::new(),
+    };
+    let mut next_id = parent.next_subtask_id();
+    renumber(&mut subtask, &mut next_id);
+
+    let siblings = children_mut(&mut parent.subtasks, parent_subtask_id)?;
+    let after = siblings.len().checked_sub(1);
+    insert_after(siblings, subtask, after);
+    Ok(dependents)
+}
+
+/// 降格すると失われるタスクの項目名（グループは親と異なる場合のみ）
+fn demote_losses(task: &Task, parent: &Task) -> Vec<&'static str> {
+    [
+        ("start_date", task.start_date.is_some()),
+        ("group", task.group_id != parent.group_id),
+        ("notification", task.notification_minutes.is_some()),
+        ("dependencies", task.dependencies.is_some()),
+        ("priority", task.priority.is_some()),
+        ("estimate", task.estimated_minutes.is_some()),
+        ("tags", !task.tags.is_empty()),
+        ("state", task.state.is_some()),
+    ]
+    .into_iter()
+    .filter_map(|(name, lost)| lost.then_some(name))
+    .collect()
+}
+
+/// 子を持つサブタスクの完了状態を子から決める（変更があれば true）
+fn roll_up_subtasks(subtasks: &mut [Subtask]) -> bool {
+    let mut changed = false;
+    for subtask in subtasks.iter_mut() {
+        changed |= roll_up_subtasks(&mut subtask.subtasks);
+        if subtask.subtasks.is_empty() {
+            continue;
+        }
+        let completed = subtask.subtasks.iter().all(|s| s.completed);
+        if subtask.completed != completed {
+            subtask.completed = completed;
+            changed = true;
+        }
+    }
+    changed
+}
+
+/// 完了状態の集約が有効なタスクについて、子の完了状態から親の完了状態を決める
+///
+/// 子を持つサブタスクとタスクは、子がすべて完了していれば完了、それ以外は未完了になる。
+/// ただしタスクは未完了の依存先がある間は完了にしない。変更があれば true を返す。
+pub fn roll_up(tasks: &mut [Task], groups: &[TaskGroup], task_id: i32, now: DateTime<Utc>) -> bool {
+    let Some(index) = tasks.iter().position(|t| t.id == task_id) else {
+        return false;
+    };
+    if !tasks[index].rollup_subtasks {
+        return false;
+    }
+
+    let mut changed = roll_up_subtasks(&mut tasks[index].subtasks);
+    let task = &tasks[index];
+    if !task.subtasks.is_empty() {
+        let completed = task.subtasks.iter().all(|s| s.completed);
+        let blocked = completed && !incomplete_blockers(tasks, task).is_empty();
+        if task.completed != completed && !blocked {
+            workflow::set_completed(&mut tasks[index], groups, completed, now);
+            changed = true;
+        }
+    }
+    changed
+}
@@ -1,16 +1,38 @@
 // Task構造体とファイルI/O処理
 
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use std::fs;
 use std::io::Write;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+
+use crate::dependency::validate_dependencies;
+use crate::group::{migrate_task_group, resolve_group_id, TaskGroup};
+use crate::ordering::{ensure_sort_keys, SortKey};
+use crate::patch::{check_updated_at, present};
+use crate::subtask;
+use crate::task_date::{validate_task_dates, TaskDate};
+use crate::time_tracking::TimeEntry;
+use crate::workflow::{normalize_state, StateTransition};
 
 /// サブタスク構造体
-#[derive(Debug, Serialize, Deserialize, Clone)]
+///
+/// IDはタスク内（入れ子のサブタスクを含む）で一意。
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
 pub struct Subtask {
     pub id: i32,
     pub description: String,
     pub completed: bool,
+    #[serde(default)]
+    pub due_date: Option<TaskDate>,
+    #[serde(default)]
+    pub notes: String,
+    /// 子のサブタスク
+    #[serde(default)]
+    pub subtasks: Vec<Subtask>,
+    /// 手動並び替え用のキー（親タスク内での順序）
+    #[serde(default)]
+    pub sort_key: String,
 }
 
 impl Subtask {
@@ -19,18 +41,35 @@ impl Subtask {
             id,
             description,
             completed: false,
+            due_date: None,
+            notes: String::new(),
+            subtasks: Vec::new(),
+            sort_key: String::new(),
         }
     }
 }
 
+/// タスクの優先度
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "snake_case")]
+pub enum TaskPriority {
+    Low,
+    Medium,
+    High,
+    Urgent,
+}
+
 /// タスク構造体
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Debug, Default, Serialize, Deserialize, Clone, PartialEq)]
 pub struct Task {
     pub id: i32,
     pub description: String,
-    pub start_date: Option<String>,
-    pub due_date: String,
-    pub group: String,
+    pub start_date: Option<TaskDate>,
+    #[serde(default)]
+    pub due_date: Option<TaskDate>,
+    /// 所属グループのID（旧形式のグループ名は読み込み時に移行）
+    #[serde(default)]
+    pub group_id: Option<String>,
     pub details: String,
     pub completed: bool,
     pub notified: bool,
@@ -39,13 +78,187 @@ pub struct Task {
     pub subtasks: Vec<Subtask>,
     #[serde(default)]
     pub dependencies: Option<Vec<i32>>,
+    #[serde(default)]
+    pub priority: Option<TaskPriority>,
+    #[serde(default)]
+    pub estimated_minutes: Option<u32>,
+    #[serde(default)]
+    pub time_entries: Vec<TimeEntry>,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// ワークフロー上の状態ID（未設定は完了フラグから決まる既定の状態）
+    #[serde(default)]
+    pub state: Option<String>,
+    #[serde(default)]
+    pub state_history: Vec<StateTransition>,
+    /// 手動並び替え用のキー
+    #[serde(default)]
+    pub sort_key: String,
+    /// サブタスクの完了状態から親の完了状態を自動で決めるか
+    #[serde(default)]
+    pub rollup_subtasks: bool,
+    /// 最終更新日時（競合検出用、旧データは `None`）
+    #[serde(default)]
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+/// タスクの部分更新（指定された項目だけを変更する）
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct TaskPatch {
+    #[serde(default)]
+    pub description: Option<String>,
+    #[serde(default, deserialize_with = "present")]
+    pub start_date: Option<Option<String>>,
+    #[serde(default, deserialize_with = "present")]
+    pub due_date: Option<Option<String>>,
+    #[serde(default, deserialize_with = "present")]
+    pub group_id: Option<Option<String>>,
+    #[serde(default)]
+    pub details: Option<String>,
+    #[serde(default, deserialize_with = "present")]
+    pub notification_minutes: Option<Option<i32>>,
+    #[serde(default, deserialize_with = "present")]
+    pub dependencies: Option<Option<Vec<i32>>>,
+    #[serde(default, deserialize_with = "present")]
+    pub priority: Option<Option<TaskPriority>>,
+    #[serde(default, deserialize_with = "present")]
+    pub estimated_minutes: Option<Option<u32>>,
+    #[serde(default)]
+    pub tags: Option<Vec<String>>,
 }
 
 impl Task {
-    /// サブタスク用の次のIDを取得
+    /// サブタスク用の次のIDを取得（入れ子のサブタスクも含めて重複しない）
     pub fn next_subtask_id(&self) -> i32 {
-        self.subtasks.iter().map(|s| s.id).max().unwrap_or(0) + 1
+        subtask::max_id(&self.subtasks) + 1
+    }
+}
+
+/// タスクに部分更新を適用する
+///
+/// `expected_updated_at` はクライアントが読み込んだ時点の更新日時で、
+/// 現在の値と異なる場合は競合としてエラーにする（`None` の場合は確認しない）。
+/// 検証はすべての項目について変更前に行うため、エラーの場合は何も変更しない。
+pub fn apply_task_patch(
+    tasks: &mut [Task],
+    groups: &[TaskGroup],
+    id: i32,
+    patch: TaskPatch,
+    expected_updated_at: Option<DateTime<Utc>>,
+    now: DateTime<Utc>,
+) -> Result<(), String> {
+    let index = tasks
+        .iter()
+        .position(|t| t.id == id)
+        .ok_or_else(|| format!("Task {} not found", id))?;
+    let current = &tasks[index];
+    check_updated_at(
+        "Task",
+        &id.to_string(),
+        current.updated_at,
+        expected_updated_at,
+    )?;
+
+    let start_date = match patch.start_date {
+        Some(value) => TaskDate::parse_optional(value.as_deref())?,
+        None => current.start_date,
+    };
+    let due_date = match patch.due_date {
+        Some(value) => TaskDate::parse_optional(value.as_deref())?,
+        None => current.due_date,
+    };
+    validate_task_dates(start_date.as_ref(), due_date.as_ref())?;
+    let group_id = match patch.group_id {
+        Some(value) => resolve_group_id(groups, value)?,
+        None => current.group_id.clone(),
+    };
+    let dependencies = match patch.dependencies {
+        Some(value) => validate_dependencies(tasks, id, value)?,
+        None => current.dependencies.clone(),
+    };
+
+    let task = &mut tasks[index];
+    let notification_minutes = patch
+        .notification_minutes
+        .unwrap_or(task.notification_minutes);
+    // Reset notified flag if due_date or notification settings change
+    if task.due_date != due_date || task.notification_minutes != notification_minutes {
+        task.notified = false;
+    }
+
+    if let Some(description) = patch.description {
+        task.description = description;
+    }
+    task.start_date = start_date;
+    task.due_date = due_date;
+    task.group_id = group_id;
+    normalize_state(task, groups);
+    if let Some(details) = patch.details {
+        task.details = details;
+    }
+    task.notification_minutes = notification_minutes;
+    task.dependencies = dependencies;
+    if let Some(priority) = patch.priority {
+        task.priority = priority;
+    }
+    if let Some(estimated_minutes) = patch.estimated_minutes {
+        task.estimated_minutes = estimated_minutes;
     }
+    if let Some(tags) = patch.tags {
+        task.tags = normalize_tags(tags);
+    }
+    task.updated_at = Some(now);
+    Ok(())
+}
+
+/// 指定したタスクの更新日時を記録する（競合検出用）
+pub fn touch_task(tasks: &mut [Task], id: i32, now: DateTime<Utc>) {
+    if let Some(task) = tasks.iter_mut().find(|t| t.id == id) {
+        task.updated_at = Some(now);
+    }
+}
+
+/// 複数のタスクの `updated_at` を更新する
+pub fn touch_tasks(tasks: &mut [Task], ids: &[i32], now: DateTime<Utc>) {
+    for task in tasks.iter_mut().filter(|t| ids.contains(&t.id)) {
+        task.updated_at = Some(now);
+    }
+}
+
+impl SortKey for Task {
+    fn sort_key(&self) -> &str {
+        &self.sort_key
+    }
+
+    fn set_sort_key(&mut self, key: String) {
+        self.sort_key = key;
+    }
+}
+
+impl SortKey for Subtask {
+    fn sort_key(&self) -> &str {
+        &self.sort_key
+    }
+
+    fn set_sort_key(&mut self, key: String) {
+        self.sort_key = key;
+    }
+}
+
+/// タグを正規化する（前後の空白と先頭の `#` を除去、空と重複は除外）
+pub fn normalize_tags(tags: Vec<String>) -> Vec<String> {
+    let mut normalized: Vec<String> = Vec::new();
+    for tag in tags {
+        let tag = tag.trim().trim_start_matches('#').trim().to_string();
+        if !tag.is_empty()
+            && !normalized
+                .iter()
+                .any(|t| t.to_lowercase() == tag.to_lowercase())
+        {
+            normalized.push(tag);
+        }
+    }
+    normalized
 }
 
 /// タスクデータをファイルに保存
@@ -57,29 +270,114 @@ pub fn save_tasks(tasks: &Vec<Task>, file_path: &PathBuf) -> Result<(), String>
 }
 
 /// タスクデータをファイルから読み込み
-pub fn load_tasks(file_path: &PathBuf) -> Vec<Task> {
-    if let Ok(content) = fs::read_to_string(file_path) {
-        if let Ok(tasks) = serde_json::from_str(&content) {
-            return tasks;
+///
+/// 旧形式のグループ名の参照は `groups` のIDに置き換える（無いグループは作成される）。
+/// 並び替えキーの無いデータには保存されていた順にキーを振る。
+/// ファイルが無い場合は空、読み込めないタスクがある場合はエラー（ファイルには触れない）。
+pub fn load_tasks(file_path: &PathBuf, groups: &mut Vec<TaskGroup>) -> Result<Vec<Task>, String> {
+    if !file_path.exists() {
+        return Ok(Vec::new());
+    }
+    let content = fs::read_to_string(file_path)
+        .map_err(|e| format!("Failed to read {}: {}", file_path.display(), e))?;
+    let mut values = serde_json::from_str::<Vec<serde_json::Value>>(&content)
+        .map_err(|e| format!("Failed to parse {}: {}", file_path.display(), e))?;
+
+    for value in values.iter_mut() {
+        migrate_task_dates(value);
+        migrate_task_group(value, groups);
+    }
+    let mut tasks = values
+        .into_iter()
+        .enumerate()
+        .map(|(i, value)| {
+            serde_json::from_value::<Task>(value)
+                .map_err(|e| format!("Failed to load task #{}: {}", i + 1, e))
+        })
+        .collect::<Result<Vec<Task>, String>>()?;
+    ensure_sort_keys(&mut tasks);
+    for task in tasks.iter_mut() {
+        subtask::ensure_sort_keys(&mut task.subtasks);
+    }
+    Ok(tasks)
+}
+
+/// 読み込み時に移行した内容がファイルと異なる場合だけ保存し直す
+///
+/// 保存し直す前に元のファイルを `<ファイル名>.bak` に退避する。保存した場合は `true`。
+pub fn save_if_migrated<T: Serialize>(
+    file_path: &PathBuf,
+    data: &T,
+    save: impl FnOnce() -> Result<(), String>,
+) -> Result<bool, String> {
+    let current = serde_json::to_value(data).map_err(|e| e.to_string())?;
+    let original = match fs::read_to_string(file_path) {
+        Ok(content) => serde_json::from_str::<serde_json::Value>(&content).ok(),
+        Err(_) => None,
+    };
+    match original {
+        Some(original) if original == current => return Ok(false),
+        Some(_) => {
+            fs::copy(file_path, backup_path(file_path))
+                .map_err(|e| format!("Failed to back up {}: {}", file_path.display(), e))?;
         }
+        // Never overwrite a file that cannot be read back
+        None if file_path.exists() => return Ok(false),
+        None if current.as_array().is_some_and(Vec::is_empty) => return Ok(false),
+        None => {}
     }
-    Vec::new()
+    save()?;
+    Ok(true)
 }
 
-/// グループデータをファイルに保存
-pub fn save_groups(groups: &Vec<String>, file_path: &PathBuf) -> Result<(), String> {
-    let json = serde_json::to_string_pretty(groups).map_err(|e| e.to_string())?;
-    let mut file = fs::File::create(file_path).map_err(|e| e.to_string())?;
-    file.write_all(json.as_bytes()).map_err(|e| e.to_string())?;
-    Ok(())
+/// 移行前のファイルの退避先
+pub fn backup_path(file_path: &Path) -> PathBuf {
+    let mut name = file_path.file_name().unwrap_or_default().to_os_string();
+    name.push(".bak");
+    file_path.with_file_name(name)
 }
 
-/// グループデータをファイルから読み込み
-pub fn load_groups(file_path: &PathBuf) -> Vec<String> {
-    if let Ok(content) = fs::read_to_string(file_path) {
-        if let Ok(groups) = serde_json::from_str(&content) {
-            return groups;
+/// 旧形式（自由入力文字列）の日付を移行
+///
+/// 空文字列は `null` に変換する。パースできない文字列は失われないよう
+/// `details` の末尾に退避してから `null` にする。
+pub fn migrate_task_dates(value: &mut serde_json::Value) {
+    let Some(obj) = value.as_object_mut() else {
+        return;
+    };
+
+    let mut unparsed = Vec::new();
+    for (field, label) in [("start_date", "Start date"), ("due_date", "Due date")] {
+        let Some(raw) = obj.get(field).and_then(|v| v.as_str()).map(str::to_string) else {
+            continue;
+        };
+        match TaskDate::parse_optional(Some(&raw)) {
+            Ok(Some(date)) => {
+                obj.insert(
+                    field.to_string(),
+                    serde_json::Value::String(date.to_string()),
+                );
+            }
+            Ok(None) => {
+                obj.insert(field.to_string(), serde_json::Value::Null);
+            }
+            Err(_) => {
+                unparsed.push(format!("{}: {}", label, raw));
+                obj.insert(field.to_string(), serde_json::Value::Null);
+            }
         }
     }
-    Vec::new()
+
+    if !unparsed.is_empty() {
+        let details = obj
+            .get("details")
+            .and_then(|v| v.as_str())
+            .unwrap_or_default()
+            .to_string();
+        let separator = if details.is_empty() { "" } else { "\n" };
+        obj.insert(
+            "details".to_string(),
+            serde_json::Value::String(format!("{}{}{}", details, separator, unparsed.join("\n"))),
+        );
+    }
 }
@@ -0,0 +1,189 @@
+// タスクの日付・日時型
+
+use chrono::{
+    DateTime, Duration, Local, LocalResult, NaiveDate, NaiveDateTime, NaiveTime, TimeDelta,
+    TimeZone, Timelike, Utc,
+};
+use chrono_tz::Tz;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::fmt;
+
+const DATE_FORMAT: &str = "%Y-%m-%d";
+const DATETIME_FORMAT: &str = "%Y-%m-%d %H:%M";
+/// 秒が0でない日時の書式（秒を落とさないため）
+const DATETIME_SECONDS_FORMAT: &str = "%Y-%m-%d %H:%M:%S";
+
+/// タスクの開始日・期限
+///
+/// 日付のみ、またはローカル日時（IANAタイムゾーン付きも可）を保持する。
+/// JSON上は `"2024-05-01"` / `"2024-05-01 17:00"` / `"2024-05-01 17:00 Asia/Tokyo"`
+/// の文字列として表現される。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TaskDate {
+    /// 日付のみ（端末のローカル時刻の0:00として扱う）
+    Date(NaiveDate),
+    /// 日時。タイムゾーンが無い場合は端末のローカル時刻として扱う
+    DateTime {
+        datetime: NaiveDateTime,
+        timezone: Option<Tz>,
+    },
+}
+
+impl TaskDate {
+    /// 文字列から日付・日時をパース
+    pub fn parse(value: &str) -> Result<Self, String> {
+        let value = value.trim();
+        let mut parts = value.split_whitespace();
+        let date_part = parts.next().ok_or_else(|| "Date is empty".to_string())?;
+
+        // "2024-05-01T17:00" (datetime-local) 形式も受け付ける
+        let (date_str, time_str) = match date_part.split_once('T') {
+            Some((d, t)) => (d, Some(t)),
+            None => (date_part, parts.next()),
+        };
+
+        let date = NaiveDate::parse_from_str(date_str, DATE_FORMAT)
+            .map_err(|_| format!("Invalid date '{}': expected YYYY-MM-DD", value))?;
+
+        let Some(time_str) = time_str else {
+            return Ok(TaskDate::Date(date));
+        };
+
+        let time = NaiveTime::parse_from_str(time_str, "%H:%M")
+            .or_else(|_| NaiveTime::parse_from_str(time_str, "%H:%M:%S"))
+            .map_err(|_| format!("Invalid time '{}': expected HH:MM or HH:MM:SS", value))?;
+
+        let timezone = match parts.next() {
+            Some(tz) => Some(
+                tz.parse::<Tz>()
+                    .map_err(|_| format!("Unknown timezone '{}'", tz))?,
+            ),
+            None => None,
+        };
+
+        if parts.next().is_some() {
+            return Err(format!(
+                "Invalid date '{}': unexpected trailing text",
+                value
+            ));
+        }
+
+        Ok(TaskDate::DateTime {
+            datetime: date.and_time(time),
+            timezone,
+        })
+    }
+
+    /// 空文字列を `None` として扱うパース（コマンド引数用）
+    pub fn parse_optional(value: Option<&str>) -> Result<Option<Self>, String> {
+        match value.map(str::trim) {
+            None | Some("") => Ok(None),
+            Some(v) => Self::parse(v).map(Some),
+        }
+    }
+
+    /// 暦上の日付
+    pub fn date(&self) -> NaiveDate {
+        match self {
+            TaskDate::Date(date) => *date,
+            TaskDate::DateTime { datetime, .. } => datetime.date(),
+        }
+    }
+
+    /// 指定したタイムゾーン（通常は端末のローカル）で解釈した絶対時刻
+    ///
+    /// タイムゾーン付きの日時はそのタイムゾーンで解決されるため、
+    /// 端末のタイムゾーンが変わっても期限は動かない。
+    pub fn to_utc_in<L: TimeZone>(self, local: &L) -> DateTime<Utc> {
+        match self {
+            TaskDate::Date(date) => resolve_local(local, &date.and_time(NaiveTime::MIN)),
+            TaskDate::DateTime {
+                datetime,
+                timezone: Some(tz),
+            } => resolve_local(&tz, &datetime),
+            TaskDate::DateTime {
+                datetime,
+                timezone: None,
+            } => resolve_local(local, &datetime),
+        }
+    }
+
+    /// 現在時刻から期限までの分数
+    pub fn minutes_until<L: TimeZone>(&self, now: &DateTime<L>) -> i64 {
+        self.to_utc_in(&now.timezone())
+            .signed_duration_since(now.with_timezone(&Utc))
+            .num_minutes()
+    }
+
+    /// 日付をN日ずらす（時刻とタイムゾーンは維持、扱える範囲を超える場合は None）
+    pub fn shift_days(&self, days: i64) -> Option<Self> {
+        let delta = TimeDelta::try_days(days)?;
+        Some(match self {
+            TaskDate::Date(date) => TaskDate::Date(date.checked_add_signed(delta)?),
+            TaskDate::DateTime { datetime, timezone } => TaskDate::DateTime {
+                datetime: datetime.checked_add_signed(delta)?,
+                timezone: *timezone,
+            },
+        })
+    }
+}
+
+/// ローカル日時を絶対時刻に解決する
+///
+/// 夏時間の切り替えで重複する時刻は早い方、存在しない時刻は1時間後に寄せる。
+fn resolve_local<Z: TimeZone>(tz: &Z, datetime: &NaiveDateTime) -> DateTime<Utc> {
+    match tz.from_local_datetime(datetime) {
+        LocalResult::Single(dt) => dt.with_timezone(&Utc),
+        LocalResult::Ambiguous(earliest, _) => earliest.with_timezone(&Utc),
+        LocalResult::None => match tz.from_local_datetime(&(*datetime + Duration::hours(1))) {
+            LocalResult::Single(dt) | LocalResult::Ambiguous(dt, _) => dt.with_timezone(&Utc),
+            LocalResult::None => tz.from_utc_datetime(datetime).with_timezone(&Utc),
+        },
+    }
+}
+
+impl fmt::Display for TaskDate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TaskDate::Date(date) => write!(f, "{}", date.format(DATE_FORMAT)),
+            TaskDate::DateTime { datetime, timezone } => {
+                let format = if datetime.second() == 0 {
+                    DATETIME_FORMAT
+                } else {
+                    DATETIME_SECONDS_FORMAT
+                };
+                write!(f, "{}", datetime.format(format))?;
+                if let Some(tz) = timezone {
+                    write!(f, " {}", tz.name())?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+impl Serialize for TaskDate {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for TaskDate {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let value = String::deserialize(deserializer)?;
+        TaskDate::parse(&value).map_err(serde::de::Error::custom)
+    }
+}
+
+/// 開始日と期限の整合性を検証
+pub fn validate_task_dates(
+    start_date: Option<&TaskDate>,
+    due_date: Option<&TaskDate>,
+) -> Result<(), String> {
+    if let (Some(start), Some(due)) = (start_date, due_date) {
+        if start.to_utc_in(&Local) > due.to_utc_in(&Local) {
+            return Err(format!("Start date {} is after due date {}", start, due));
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,420 @@
+// タスクの絞り込みクエリと保存ビュー
+
+use chrono::{DateTime, NaiveDate, TimeDelta, TimeZone};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::PathBuf;
+use uuid::Uuid;
+
+use crate::dependency::incomplete_blockers;
+use crate::group::{ancestors, TaskGroup};
+use crate::subtask;
+use crate::task::{Task, TaskPriority};
+use crate::task_date::TaskDate;
+
+/// 比較演算子
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Comparison {
+    Lt,
+    Le,
+    Eq,
+    Ge,
+    Gt,
+}
+
+impl Comparison {
+    fn compare<T: Ord>(self, left: T, right: T) -> bool {
+        match self {
+            Comparison::Lt => left < right,
+            Comparison::Le => left <= right,
+            Comparison::Eq => left == right,
+            Comparison::Ge => left >= right,
+            Comparison::Gt => left > right,
+        }
+    }
+}
+
+/// 日付条件の基準日
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DateBound {
+    /// 固定の日付（`2024-06-01`）
+    Date(NaiveDate),
+    /// 今日からの相対日数（`7d` / `2w` / `today` / `tomorrow`）
+    Relative(i64),
+}
+
+/// 相対日数の上限（これを超える指定はクエリのエラーにする）
+const MAX_RELATIVE_DAYS: i64 = 365_000;
+
+impl DateBound {
+    fn parse(value: &str) -> Option<Self> {
+        let value = value.to_lowercase();
+        match value.as_str() {
+            "today" => return Some(DateBound::Relative(0)),
+            "tomorrow" => return Some(DateBound::Relative(1)),
+            "yesterday" => return Some(DateBound::Relative(-1)),
+            _ => {}
+        }
+        let relative =
+            |days: i64| (days.abs() <= MAX_RELATIVE_DAYS).then_some(DateBound::Relative(days));
+        if let Some(days) = value.strip_suffix('d') {
+            return days.parse().ok().and_then(relative);
+        }
+        if let Some(weeks) = value.strip_suffix('w') {
+            return weeks
+                .parse::<i64>()
+                .ok()
+                .and_then(|w| w.checked_mul(7))
+                .and_then(relative);
+        }
+        NaiveDate::parse_from_str(&value, "%Y-%m-%d")
+            .ok()
+            .map(DateBound::Date)
+    }
+
+    fn resolve(self, today: NaiveDate) -> Option<NaiveDate> {
+        match self {
+            DateBound::Date(date) => Some(date),
+            DateBound::Relative(days) => {
+                TimeDelta::try_days(days).and_then(|delta| today.checked_add_signed(delta))
+            }
+        }
+    }
+}
+
+/// `has:` で存在を確認できる項目
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TaskField {
+    Group,
+    Subtasks,
+    Dependencies,
+    Due,
+    Start,
+    Tags,
+    Priority,
+    Estimate,
+    Details,
+}
+
+/// 単一の絞り込み条件
+#[derive(Debug, Clone, PartialEq)]
+pub enum Filter {
+    /// 説明・詳細・サブタスクの部分一致（大文字小文字を区別しない）
+    Text(String),
+    Group(String),
+    Tag(String),
+    Priority(Comparison, TaskPriority),
+    Due(Comparison, DateBound),
+    Start(Comparison, DateBound),
+    Has(TaskField),
+    Completed,
+    Overdue,
+    Blocked,
+}
+
+/// 否定可能な条件
+#[derive(Debug, Clone, PartialEq)]
+pub struct Term {
+    pub negated: bool,
+    pub filter: Filter,
+}
+
+/// パース済みのクエリ（すべての条件の AND）
+///
+/// 例: `group:Work tag:urgent due<7d !completed has:subtasks`
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct TaskQuery {
+    pub terms: Vec<Term>,
+}
+
+impl TaskQuery {
+    /// クエリ文字列をパースする
+    ///
+    /// 値に空白を含む場合は `group:"My Work"` のように引用符で囲む。
+    /// 先頭の `!` または `-` で条件を否定する。
+    pub fn parse(query: &str) -> Result<Self, String> {
+        let terms = tokenize(query)?
+            .iter()
+            .map(|token| parse_term(token))
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(TaskQuery { terms })
+    }
+
+    /// `now` を基準にタスクが条件をすべて満たすか判定する
+    ///
+    /// `tasks` は依存先の完了状態（`is:blocked`）、`groups` はグループ名の
+    /// 解決に使う。`group:` は子グループに属するタスクにも一致する。
+    pub fn matches<L: TimeZone>(
+        &self,
+        task: &Task,
+        tasks: &[Task],
+        groups: &[TaskGroup],
+        now: &DateTime<L>,
+    ) -> bool {
+        self.terms
+            .iter()
+            .all(|term| term.negated != matches_filter(&term.filter, task, tasks, groups, now))
+    }
+
+    /// 条件に一致するタスクを元の順序で返す
+    pub fn filter<L: TimeZone>(
+        &self,
+        tasks: &[Task],
+        groups: &[TaskGroup],
+        now: &DateTime<L>,
+    ) -> Vec<Task> {
+        tasks
+            .iter()
+            .filter(|t| self.matches(t, tasks, groups, now))
+            .cloned()
+            .collect()
+    }
+}
+
+/// 空白区切りで分割する（引用符内の空白は区切らない）
+fn tokenize(query: &str) -> Result<Vec<String>, String> {
+    let mut tokens = Vec::new();
+    let mut current = String::new();
+    let mut in_quotes = false;
+
+    for c in query.chars() {
+        match c {
+            '"' => {
+                in_quotes = !in_quotes;
+                current.push(c);
+            }
+            c if c.is_whitespace() && !in_quotes => {
+                if !current.is_empty() {
+                    tokens.push(std::mem::take(&mut current));
+                }
+            }
+            c => current.push(c),
+        }
+    }
+
+    if in_quotes {
+        return Err("Unterminated quote in query".to_string());
+    }
+    if !current.is_empty() {
+        tokens.push(current);
+    }
+    Ok(tokens)
+}
+
+fn unquote(value: &str) -> String {
+    value.replace('"', "")
+}
+
+fn parse_term(token: &str) -> Result<Term, String> {
+    let (negated, body) = match token.strip_prefix(['!', '-']) {
+        Some(rest) if !rest.is_empty() => (true, rest),
+        _ => (false, token),
+    };
+
+    let filter = if body.starts_with('"') {
+        Filter::Text(unquote(body))
+    } else {
+        let key_len = body
+            .find(|c: char| !c.is_ascii_alphabetic())
+            .unwrap_or(body.len());
+        let key = body[..key_len].to_lowercase();
+        let rest = &body[key_len..];
+
+        match split_operator(rest) {
+            Some((op, value)) => parse_filter(&key, op, &unquote(value))?,
+            None => match key.as_str() {
+                _ if key_len != body.len() => Filter::Text(unquote(body)),
+                "completed" => Filter::Completed,
+                "overdue" => Filter::Overdue,
+                "blocked" => Filter::Blocked,
+                _ => Filter::Text(body.to_string()),
+            },
+        }
+    };
+
+    Ok(Term { negated, filter })
+}
+
+/// 先頭の演算子と値に分割する（`:` は等価として扱う）
+fn split_operator(rest: &str) -> Option<(Comparison, &str)> {
+    for (prefix, op) in [
+        ("<=", Comparison::Le),
+        (">=", Comparison::Ge),
+        ("<", Comparison::Lt),
+        (">", Comparison::Gt),
+        ("=", Comparison::Eq),
+        (":", Comparison::Eq),
+    ] {
+        if let Some(value) = rest.strip_prefix(prefix) {
+            return Some((op, value));
+        }
+    }
+    None
+}
+
+fn parse_filter(key: &str, op: Comparison, value: &str) -> Result<Filter, String> {
+    if value.is_empty() {
+        return Err(format!("Missing value for '{}'", key));
+    }
+
+    let equality_only = |filter: Filter| {
+        if op == Comparison::Eq {
+            Ok(filter)
+        } else {
+            Err(format!("'{}' only supports ':'", key))
+        }
+    };
+
+    match key {
+        "group" => equality_only(Filter::Group(value.to_string())),
+        "tag" => equality_only(Filter::Tag(value.trim_start_matches('#').to_string())),
+        "priority" => {
+            let priority = match value.to_lowercase().as_str() {
+                "low" => TaskPriority::Low,
+                "medium" => TaskPriority::Medium,
+                "high" => TaskPriority::High,
+                "urgent" => TaskPriority::Urgent,
+                _ => return Err(format!("Unknown priority '{}'", value)),
+            };
+            Ok(Filter::Priority(op, priority))
+        }
+        "due" | "start" => {
+            let bound = DateBound::parse(value)
+                .ok_or_else(|| format!("Invalid date '{}' for '{}'", value, key))?;
+            Ok(if key == "due" {
+                Filter::Due(op, bound)
+            } else {
+                Filter::Start(op, bound)
+            })
+        }
+        "has" => {
+            let field = match value.to_lowercase().as_str() {
+                "group" => TaskField::Group,
+                "subtasks" => TaskField::Subtasks,
+                "dependencies" | "deps" => TaskField::Dependencies,
+                "due" => TaskField::Due,
+                "start" => TaskField::Start,
+                "tags" => TaskField::Tags,
+                "priority" => TaskField::Priority,
+                "estimate" => TaskField::Estimate,
+                "details" => TaskField::Details,
+                _ => return Err(format!("Unknown field 'has:{}'", value)),
+            };
+            equality_only(Filter::Has(field))
+        }
+        "is" => {
+            let filter = match value.to_lowercase().as_str() {
+                "completed" => Filter::Completed,
+                "overdue" => Filter::Overdue,
+                "blocked" => Filter::Blocked,
+                _ => return Err(format!("Unknown state 'is:{}'", value)),
+            };
+            equality_only(filter)
+        }
+        _ => Err(format!("Unknown filter '{}'", key)),
+    }
+}
+
+fn matches_filter<L: TimeZone>(
+    filter: &Filter,
+    task: &Task,
+    tasks: &[Task],
+    groups: &[TaskGroup],
+    now: &DateTime<L>,
+) -> bool {
+    let today = now.date_naive();
+    let date_matches = |date: Option<TaskDate>, op: Comparison, bound: &DateBound| {
+        date.zip(bound.resolve(today))
+            .is_some_and(|(d, bound)| op.compare(d.date(), bound))
+    };
+
+    match filter {
+        Filter::Text(text) => {
+            let text = text.to_lowercase();
+            task.description.to_lowercase().contains(&text)
+                || task.details.to_lowercase().contains(&text)
+                || subtask::flatten(&task.subtasks).iter().any(|s| {
+                    s.description.to_lowercase().contains(&text)
+                        || s.notes.to_lowercase().contains(&text)
+                })
+        }
+        Filter::Group(name) => task.group_id.as_deref().is_some_and(|id| {
+            ancestors(groups, id)
+                .iter()
+                .any(|g| g.name.to_lowercase() == name.to_lowercase())
+        }),
+        Filter::Tag(tag) => task
+            .tags
+            .iter()
+            .any(|t| t.to_lowercase() == tag.to_lowercase()),
+        Filter::Priority(op, priority) => task.priority.is_some_and(|p| op.compare(p, *priority)),
+        Filter::Due(op, bound) => date_matches(task.due_date, *op, bound),
+        Filter::Start(op, bound) => date_matches(task.start_date, *op, bound),
+        Filter::Has(field) => match field {
+            TaskField::Group => task.group_id.is_some(),
+            TaskField::Subtasks => !task.subtasks.is_empty(),
+            TaskField::Dependencies => task.dependencies.as_ref().is_some_and(|d| !d.is_empty()),
+            TaskField::Due => task.due_date.is_some(),
+            TaskField::Start => task.start_date.is_some(),
+            TaskField::Tags => !task.tags.is_empty(),
+            TaskField::Priority => task.priority.is_some(),
+            TaskField::Estimate => task.estimated_minutes.is_some(),
+            TaskField::Details => !task.details.trim().is_empty(),
+        },
+        Filter::Completed => task.completed,
+        // 日付のみの期限はその日の終わりまで期限切れにしない
+        Filter::Overdue => {
+            !task.completed
+                && match task.due_date {
+                    Some(TaskDate::Date(date)) => date < today,
+                    Some(due) => due.minutes_until(now) < 0,
+                    None => false,
+                }
+        }
+        Filter::Blocked => !task.completed && !incomplete_blockers(tasks, task).is_empty(),
+    }
+}
+
+/// 名前を付けて保存したクエリ
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct SavedView {
+    pub id: String,
+    pub name: String,
+    pub query: String,
+}
+
+impl SavedView {
+    pub fn new(name: String, query: String) -> Self {
+        Self {
+            id: Uuid::new_v4().to_string(),
+            name,
+            query,
+        }
+    }
+}
+
+/// 保存ビューの名前とクエリを検証する
+pub fn validate_saved_view(name: &str, query: &str) -> Result<(), String> {
+    if name.trim().is_empty() {
+        return Err("View name cannot be empty".to_string());
+    }
+    TaskQuery::parse(query)?;
+    Ok(())
+}
+
+/// 保存ビューをファイルに保存
+pub fn save_saved_views(views: &Vec<SavedView>, file_path: &PathBuf) -> Result<(), String> {
+    let json = serde_json::to_string_pretty(views).map_err(|e| e.to_string())?;
+    fs::write(file_path, json).map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+/// 保存ビューをファイルから読み込み
+pub fn load_saved_views(file_path: &PathBuf) -> Vec<SavedView> {
+    if let Ok(content) = fs::read_to_string(file_path) {
+        if let Ok(views) = serde_json::from_str(&content) {
+            return views;
+        }
+    }
+    Vec::new()
+}
@@ -1,6 +1,7 @@
 #[cfg(test)]
 mod test_utils {
     use crate::task::{load_tasks, save_tasks, Subtask, Task};
+    use crate::task_date::TaskDate;
     use tempfile::tempdir;
 
     #[test]
@@ -16,15 +17,10 @@ mod test_utils {
         let mut task = Task {
             id: 1,
             description: "Main Task".to_string(),
-            start_date: None,
-            due_date: "2023-12-31".to_string(),
-            group: "Work".to_string(),
+            due_date: TaskDate::parse_optional(Some("2023-12-31")).unwrap(),
+            group_id: Some("work".to_string()),
             details: "Details".to_string(),
-            completed: false,
-            notified: false,
-            notification_minutes: None,
-            subtasks: Vec::new(),
-            dependencies: None,
+            ..Task::default()
         };
 
         // No subtasks
@@ -45,15 +41,14 @@ mod test_utils {
         let task = Task {
             id: 1,
             description: "Test Task".to_string(),
-            start_date: Some("2023-01-01".to_string()),
-            due_date: "2023-12-31".to_string(),
-            group: "Work".to_string(),
+            start_date: Some(TaskDate::parse("2023-01-01").unwrap()),
+            due_date: TaskDate::parse_optional(Some("2023-12-31")).unwrap(),
+            group_id: Some("work".to_string()),
             details: "Details".to_string(),
-            completed: false,
-            notified: false,
             notification_minutes: Some(30),
             subtasks: vec![Subtask::new(1, "Sub 1".to_string())],
             dependencies: Some(vec![2, 3]),
+            ..Task::default()
         };
 
         let serialized = serde_json::to_string(&task).expect("Serialization failed");
@@ -62,6 +57,7 @@ mod test_utils {
         assert_eq!(task.id, deserialized.id);
         assert_eq!(task.description, deserialized.description);
         assert_eq!(task.start_date, deserialized.start_date);
+        assert_eq!(task.due_date, deserialized.due_date);
         assert_eq!(task.notification_minutes, deserialized.notification_minutes);
         assert_eq!(task.subtasks.len(), deserialized.subtasks.len());
         assert_eq!(task.dependencies, deserialized.dependencies);
@@ -76,28 +72,13 @@ mod test_utils {
             Task {
                 id: 1,
                 description: "Task 1".to_string(),
-                start_date: None,
-                due_date: "".to_string(),
-                group: "".to_string(),
-                details: "".to_string(),
-                completed: false,
-                notified: false,
-                notification_minutes: None,
-                subtasks: Vec::new(),
-                dependencies: None,
+                ..Task::default()
             },
             Task {
                 id: 2,
                 description: "Task 2".to_string(),
-                start_date: None,
-                due_date: "".to_string(),
-                group: "".to_string(),
-                details: "".to_string(),
                 completed: true,
-                notified: false,
-                notification_minutes: None,
-                subtasks: Vec::new(),
-                dependencies: None,
+                ..Task::default()
             },
         ];
 
@@ -106,7 +87,7 @@ mod test_utils {
         assert!(save_result.is_ok());
 
         // Load tasks
-        let loaded_tasks = load_tasks(&file_path);
+        let loaded_tasks = load_tasks(&file_path, &mut Vec::new()).unwrap();
         assert_eq!(loaded_tasks.len(), 2);
         assert_eq!(loaded_tasks[0].description, "Task 1");
         assert_eq!(loaded_tasks[1].completed, true);
@@ -117,7 +98,4635 @@ mod test_utils {
         let dir = tempdir().expect("Failed to create temp dir");
         let file_path = dir.path().join("nonexistent.json");
 
-        let tasks = load_tasks(&file_path);
+        let tasks = load_tasks(&file_path, &mut Vec::new()).unwrap();
         assert!(tasks.is_empty());
     }
 }
+
+#[cfg(test)]
+mod task_date_tests {
+    use crate::task::{backup_path, load_tasks, migrate_task_dates, save_if_migrated, save_tasks};
+    use crate::task_date::{validate_task_dates, TaskDate};
+    use chrono::{NaiveDate, TimeZone, Utc};
+    use chrono_tz::Tz;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_parse_date_only() {
+        let date = TaskDate::parse("2024-05-01").unwrap();
+        assert_eq!(
+            date,
+            TaskDate::Date(NaiveDate::from_ymd_opt(2024, 5, 1).unwrap())
+        );
+        assert_eq!(date.to_string(), "2024-05-01");
+    }
+
+    #[test]
+    fn test_parse_datetime_variants() {
+        let local = TaskDate::parse("2024-05-01 17:00").unwrap();
+        assert_eq!(local.to_string(), "2024-05-01 17:00");
+
+        let iso = TaskDate::parse("2024-05-01T17:00").unwrap();
+        assert_eq!(iso, local);
+
+        let zoned = TaskDate::parse("2024-05-01 17:00 Asia/Tokyo").unwrap();
+        assert_eq!(zoned.to_string(), "2024-05-01 17:00 Asia/Tokyo");
+    }
+
+    #[test]
+    fn test_parse_rejects_invalid_input() {
+        assert!(TaskDate::parse("tomorrow").is_err());
+        assert!(TaskDate::parse("2024-13-01").is_err());
+        assert!(TaskDate::parse("2024-05-01 25:00").is_err());
+        assert!(TaskDate::parse("2024-05-01 17:00 Mars/Olympus").is_err());
+        assert_eq!(TaskDate::parse_optional(Some("  ")).unwrap(), None);
+        assert_eq!(TaskDate::parse_optional(None).unwrap(), None);
+    }
+
+    #[test]
+    fn test_zoned_deadline_is_independent_of_device_timezone() {
+        let due = TaskDate::parse("2024-05-01 17:00 Asia/Tokyo").unwrap();
+        let expected = Utc.with_ymd_and_hms(2024, 5, 1, 8, 0, 0).unwrap();
+
+        let new_york: Tz = "America/New_York".parse().unwrap();
+        assert_eq!(due.to_utc_in(&Utc), expected);
+        assert_eq!(due.to_utc_in(&new_york), expected);
+    }
+
+    #[test]
+    fn test_dst_gap_and_overlap_are_resolved() {
+        let new_york: Tz = "America/New_York".parse().unwrap();
+
+        // 2024-03-10 02:30 does not exist in New York
+        let gap = TaskDate::parse("2024-03-10 02:30 America/New_York").unwrap();
+        assert_eq!(
+            gap.to_utc_in(&new_york),
+            Utc.with_ymd_and_hms(2024, 3, 10, 7, 30, 0).unwrap()
+        );
+
+        // 2024-11-03 01:30 happens twice; the earlier one is used
+        let overlap = TaskDate::parse("2024-11-03 01:30 America/New_York").unwrap();
+        assert_eq!(
+            overlap.to_utc_in(&new_york),
+            Utc.with_ymd_and_hms(2024, 11, 3, 5, 30, 0).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_minutes_until() {
+        let due = TaskDate::parse("2024-05-01 17:00").unwrap();
+        let now = Utc.with_ymd_and_hms(2024, 5, 1, 16, 30, 0).unwrap();
+        assert_eq!(due.minutes_until(&now), 30);
+
+        let date_only = TaskDate::parse("2024-05-02").unwrap();
+        assert_eq!(date_only.minutes_until(&now), 450);
+    }
+
+    #[test]
+    fn test_validate_task_dates() {
+        let start = TaskDate::parse("2024-05-01").unwrap();
+        let due = TaskDate::parse("2024-05-03 12:00").unwrap();
+        assert!(validate_task_dates(Some(&start), Some(&due)).is_ok());
+        assert!(validate_task_dates(Some(&due), Some(&start)).is_err());
+        assert!(validate_task_dates(None, Some(&due)).is_ok());
+    }
+
+    #[test]
+    fn test_migrate_legacy_dates() {
+        let mut value = serde_json::json!({
+            "start_date": "",
+            "due_date": "next friday",
+            "details": "Bring slides",
+        });
+        migrate_task_dates(&mut value);
+
+        assert!(value["start_date"].is_null());
+        assert!(value["due_date"].is_null());
+        assert_eq!(value["details"], "Bring slides\nDue date: next friday");
+    }
+
+    #[test]
+    fn test_load_legacy_tasks_file() {
+        let dir = tempdir().expect("Failed to create temp dir");
+        let file_path = dir.path().join("tasks.json");
+        let legacy = r#"[
+            {"id": 1, "description": "A", "start_date": null, "due_date": "2024-05-01 09:30",
+             "group": "", "details": "", "completed": false, "notified": false,
+             "notification_minutes": null},
+            {"id": 2, "description": "B", "start_date": "2024-04-01", "due_date": "",
+             "group": "", "details": "", "completed": false, "notified": false,
+             "notification_minutes": null}
+        ]"#;
+        std::fs::write(&file_path, legacy).unwrap();
+
+        let tasks = load_tasks(&file_path, &mut Vec::new()).unwrap();
+        assert_eq!(tasks.len(), 2);
+        assert_eq!(
+            tasks[0].due_date.map(|d| d.to_string()),
+            Some("2024-05-01 09:30".to_string())
+        );
+        assert_eq!(tasks[1].due_date, None);
+        assert_eq!(
+            tasks[1].start_date,
+            Some(TaskDate::parse("2024-04-01").unwrap())
+        );
+    }
+
+    #[test]
+    fn test_seconds_are_kept() {
+        let date = TaskDate::parse("2024-05-01 09:30:15").unwrap();
+        assert_eq!(date.to_string(), "2024-05-01 09:30:15");
+        assert_eq!(TaskDate::parse(&date.to_string()).unwrap(), date);
+        // Whole minutes keep the short form
+        let date = TaskDate::parse("2024-05-01 09:30:00 Asia/Tokyo").unwrap();
+        assert_eq!(date.to_string(), "2024-05-01 09:30 Asia/Tokyo");
+    }
+
+    #[test]
+    fn test_unreadable_tasks_file_is_left_untouched() {
+        let dir = tempdir().expect("Failed to create temp dir");
+        let file_path = dir.path().join("tasks.json");
+        let broken = r#"[
+            {"id": 1, "description": "A", "start_date": null, "due_date": null,
+             "group": "", "details": "", "completed": false, "notified": false,
+             "notification_minutes": null},
+            {"id": "not a number", "description": "B"}
+        ]"#;
+        std::fs::write(&file_path, broken).unwrap();
+
+        assert!(load_tasks(&file_path, &mut Vec::new()).is_err());
+        assert_eq!(std::fs::read_to_string(&file_path).unwrap(), broken);
+
+        std::fs::write(&file_path, "[{").unwrap();
+        assert!(load_tasks(&file_path, &mut Vec::new()).is_err());
+        assert_eq!(std::fs::read_to_string(&file_path).unwrap(), "[{");
+    }
+
+    #[test]
+    fn test_save_if_migrated_backs_up_legacy_file() {
+        let dir = tempdir().expect("Failed to create temp dir");
+        let file_path = dir.path().join("tasks.json");
+        let legacy = r#"[
+            {"id": 1, "description": "A", "start_date": null, "due_date": "2024-05-01 09:30",
+             "group": "", "details": "", "completed": false, "notified": false,
+             "notification_minutes": null}
+        ]"#;
+        std::fs::write(&file_path, legacy).unwrap();
+
+        let tasks = load_tasks(&file_path, &mut Vec::new()).unwrap();
+        let saved =
+            save_if_migrated(&file_path, &tasks, || save_tasks(&tasks, &file_path)).unwrap();
+        assert!(saved);
+        assert_eq!(
+            std::fs::read_to_string(backup_path(&file_path)).unwrap(),
+            legacy
+        );
+        assert_eq!(load_tasks(&file_path, &mut Vec::new()).unwrap(), tasks);
+
+        // Already in the current format: nothing is written
+        std::fs::remove_file(backup_path(&file_path)).unwrap();
+        let tasks = load_tasks(&file_path, &mut Vec::new()).unwrap();
+        let saved = save_if_migrated(&file_path, &tasks, || {
+            panic!("must not save an unchanged file")
+        })
+        .unwrap();
+        assert!(!saved);
+        assert!(!backup_path(&file_path).exists());
+    }
+
+    #[test]
+    fn test_save_if_migrated_skips_missing_empty_file() {
+        let dir = tempdir().expect("Failed to create temp dir");
+        let file_path = dir.path().join("tasks.json");
+        let tasks = load_tasks(&file_path, &mut Vec::new()).unwrap();
+        assert!(!save_if_migrated(&file_path, &tasks, || unreachable!()).unwrap());
+        assert!(!file_path.exists());
+    }
+}
+
+#[cfg(test)]
+mod quick_add_tests {
+    use crate::quick_add::parse_quick_add;
+    use chrono::{NaiveDate, NaiveDateTime};
+
+    /// 2024-05-15 (水) 10:00
+    fn now() -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(2024, 5, 15)
+            .unwrap()
+            .and_hms_opt(10, 0, 0)
+            .unwrap()
+    }
+
+    /// (入力, 説明文, 期限, グループ, 通知分, 依存タスク)
+    type Case = (
+        &'static str,
+        &'static str,
+        Option<&'static str>,
+        Option<&'static str>,
+        Option<i32>,
+        Option<Vec<i32>>,
+    );
+
+    #[test]
+    fn test_parse_table() {
+        let cases: Vec<Case> = vec![
+            (
+                "Submit report tomorrow 17:00 #Work !30m depends:12",
+                "Submit report",
+                Some("2024-05-16 17:00"),
+                Some("Work"),
+                Some(30),
+                Some(vec![12]),
+            ),
+            (
+                "Call mom today",
+                "Call mom",
+                Some("2024-05-15"),
+                None,
+                None,
+                None,
+            ),
+            (
+                "Buy milk tonight",
+                "Buy milk",
+                Some("2024-05-15"),
+                None,
+                None,
+                None,
+            ),
+            (
+                "Pay rent next monday",
+                "Pay rent",
+                Some("2024-05-20"),
+                None,
+                None,
+                None,
+            ),
+            (
+                "Pay rent monday",
+                "Pay rent",
+                Some("2024-05-20"),
+                None,
+                None,
+                None,
+            ),
+            ("Gym wednesday", "Gym", Some("2024-05-22"), None, None, None),
+            (
+                "Gym Friday 7pm",
+                "Gym",
+                Some("2024-05-17 19:00"),
+                None,
+                None,
+                None,
+            ),
+            (
+                "Review this friday",
+                "Review",
+                Some("2024-05-17"),
+                None,
+                None,
+                None,
+            ),
+            (
+                "Retro next week",
+                "Retro",
+                Some("2024-05-20"),
+                None,
+                None,
+                None,
+            ),
+            (
+                "Plan in 3 days",
+                "Plan",
+                Some("2024-05-18"),
+                None,
+                None,
+                None,
+            ),
+            (
+                "Plan in 2 weeks",
+                "Plan",
+                Some("2024-05-29"),
+                None,
+                None,
+                None,
+            ),
+            (
+                "Plan in 99999999999 days",
+                "Plan in 99999999999 days",
+                None,
+                None,
+                None,
+                None,
+            ),
+            (
+                "Plan in 2000000000000000000 weeks",
+                "Plan in 2000000000000000000 weeks",
+                None,
+                None,
+                None,
+                None,
+            ),
+            (
+                "Ship 2024-06-01",
+                "Ship",
+                Some("2024-06-01"),
+                None,
+                None,
+                None,
+            ),
+            ("Ship on 6/1", "Ship", Some("2024-06-01"), None, None, None),
+            (
+                "Ship 2024/6/1",
+                "Ship",
+                Some("2024-06-01"),
+                None,
+                None,
+                None,
+            ),
+            (
+                "Ship 6/1/2025",
+                "Ship",
+                Some("2025-06-01"),
+                None,
+                None,
+                None,
+            ),
+            ("Ship 6/1/24", "Ship", Some("2024-06-01"), None, None, None),
+            (
+                "Ship 12/31/99",
+                "Ship",
+                Some("2099-12-31"),
+                None,
+                None,
+                None,
+            ),
+            ("Ship 6/1/024", "Ship 6/1/024", None, None, None, None),
+            ("Ship 24/6/1", "Ship 24/6/1", None, None, None, None),
+            (
+                "Renew license 3/1",
+                "Renew license",
+                Some("2025-03-01"),
+                None,
+                None,
+                None,
+            ),
+            (
+                "Standup at 9:30",
+                "Standup",
+                Some("2024-05-16 09:30"),
+                None,
+                None,
+                None,
+            ),
+            (
+                "Lunch at noon",
+                "Lunch",
+                Some("2024-05-15 12:00"),
+                None,
+                None,
+                None,
+            ),
+            (
+                "Call at 5 pm",
+                "Call",
+                Some("2024-05-15 17:00"),
+                None,
+                None,
+                None,
+            ),
+            (
+                "Wake up at 12am",
+                "Wake up",
+                Some("2024-05-16 00:00"),
+                None,
+                None,
+                None,
+            ),
+            (
+                "Meeting 10:30am tomorrow",
+                "Meeting",
+                Some("2024-05-16 10:30"),
+                None,
+                None,
+                None,
+            ),
+            (
+                "Meet by 2024-05-20 14:00",
+                "Meet",
+                Some("2024-05-20 14:00"),
+                None,
+                None,
+                None,
+            ),
+            (
+                "Email Bob about Q3 budget friday",
+                "Email Bob about Q3 budget",
+                Some("2024-05-17"),
+                None,
+                None,
+                None,
+            ),
+            (
+                "Read book #reading !2h",
+                "Read book",
+                None,
+                Some("reading"),
+                Some(120),
+                None,
+            ),
+            ("Backup !1d", "Backup", None, None, Some(1440), None),
+            ("Stretch !45", "Stretch", None, None, Some(45), None),
+            (
+                "Deploy depends:3,4",
+                "Deploy",
+                None,
+                None,
+                None,
+                Some(vec![3, 4]),
+            ),
+            (
+                "Deploy depends:3 depends:#5,3",
+                "Deploy",
+                None,
+                None,
+                None,
+                Some(vec![3, 5]),
+            ),
+            ("Hurry! now", "Hurry! now", None, None, None, None),
+            (
+                "Put it in the box",
+                "Put it in the box",
+                None,
+                None,
+                None,
+                None,
+            ),
+            ("Buy 3 apples", "Buy 3 apples", None, None, None, None),
+            ("Enjoy the sun", "Enjoy the sun", None, None, None, None),
+            (
+                "Fix build at 25:00",
+                "Fix build at 25:00",
+                None,
+                None,
+                None,
+                None,
+            ),
+            // 日本語
+            (
+                "明日17時に資料提出 #仕事",
+                "資料提出",
+                Some("2024-05-16 17:00"),
+                Some("仕事"),
+                None,
+                None,
+            ),
+            (
+                "来週月曜 会議",
+                "会議",
+                Some("2024-05-20"),
+                None,
+                None,
+                None,
+            ),
+            (
+                "来週月曜日に会議",
+                "会議",
+                Some("2024-05-20"),
+                None,
+                None,
+                None,
+            ),
+            (
+                "来週 企画書",
+                "企画書",
+                Some("2024-05-20"),
+                None,
+                None,
+                None,
+            ),
+            (
+                "再来週の金曜日までにレポート",
+                "レポート",
+                Some("2024-05-31"),
+                None,
+                None,
+                None,
+            ),
+            (
+                "今週金曜 定例",
+                "定例",
+                Some("2024-05-17"),
+                None,
+                None,
+                None,
+            ),
+            ("今週中に掃除", "今週中に掃除", None, None, None, None),
+            ("金曜 掃除", "掃除", Some("2024-05-17"), None, None, None),
+            (
+                "水曜日 ゴミ出し",
+                "ゴミ出し",
+                Some("2024-05-22"),
+                None,
+                None,
+                None,
+            ),
+            (
+                "今日 買い物",
+                "買い物",
+                Some("2024-05-15"),
+                None,
+                None,
+                None,
+            ),
+            ("あした 洗濯", "洗濯", Some("2024-05-16"), None, None, None),
+            (
+                "明後日 午後3時半 歯医者",
+                "歯医者",
+                Some("2024-05-17 15:30"),
+                None,
+                None,
+                None,
+            ),
+            (
+                "午前9時 朝会",
+                "朝会",
+                Some("2024-05-16 09:00"),
+                None,
+                None,
+                None,
+            ),
+            (
+                "明日の正午 ランチ",
+                "ランチ",
+                Some("2024-05-16 12:00"),
+                None,
+                None,
+                None,
+            ),
+            ("3日後に返信", "返信", Some("2024-05-18"), None, None, None),
+            (
+                "2週間後 振り返り",
+                "振り返り",
+                Some("2024-05-29"),
+                None,
+                None,
+                None,
+            ),
+            (
+                "6月1日 打ち合わせ",
+                "打ち合わせ",
+                Some("2024-06-01"),
+                None,
+                None,
+                None,
+            ),
+            ("1月10日 締切", "締切", Some("2025-01-10"), None, None, None),
+            (
+                "2025年1月10日 締切",
+                "締切",
+                Some("2025-01-10"),
+                None,
+                None,
+                None,
+            ),
+            (
+                "明日18時30分 飲み会",
+                "飲み会",
+                Some("2024-05-16 18:30"),
+                None,
+                None,
+                None,
+            ),
+            (
+                "１７：３０ 電話",
+                "電話",
+                Some("2024-05-15 17:30"),
+                None,
+                None,
+                None,
+            ),
+            (
+                "明日 レビュー　＃開発　！１５ｍ",
+                "レビュー",
+                Some("2024-05-16"),
+                Some("開発"),
+                Some(15),
+                None,
+            ),
+        ];
+
+        for (input, description, due, group, minutes, dependencies) in cases {
+            let parsed = parse_quick_add(input, now())
+                .unwrap_or_else(|e| panic!("'{}' failed to parse: {}", input, e));
+            assert_eq!(
+                parsed.description, description,
+                "description of '{}'",
+                input
+            );
+            assert_eq!(
+                parsed.due_date.map(|d| d.to_string()).as_deref(),
+                due,
+                "due date of '{}'",
+                input
+            );
+            assert_eq!(parsed.group.as_deref(), group, "group of '{}'", input);
+            assert_eq!(
+                parsed.notification_minutes, minutes,
+                "minutes of '{}'",
+                input
+            );
+            assert_eq!(
+                parsed.dependencies, dependencies,
+                "dependencies of '{}'",
+                input
+            );
+        }
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert!(parse_quick_add("", now()).is_err());
+        assert!(parse_quick_add("   ", now()).is_err());
+        assert!(parse_quick_add("#Work tomorrow", now()).is_err());
+        assert!(parse_quick_add("明日17時", now()).is_err());
+        assert!(parse_quick_add("Deploy depends:abc", now()).is_err());
+    }
+}
+
+#[cfg(test)]
+mod dependency_tests {
+    use crate::dependency::{
+        blocked_tasks, ready_tasks, remove_dependency_references, topological_order,
+        validate_dependencies,
+    };
+    use crate::task::Task;
+
+    fn task(id: i32, dependencies: &[i32], completed: bool) -> Task {
+        Task {
+            id,
+            description: format!("Task {}", id),
+            completed,
+            dependencies: if dependencies.is_empty() {
+                None
+            } else {
+                Some(dependencies.to_vec())
+            },
+            ..Task::default()
+        }
+    }
+
+    #[test]
+    fn test_validate_rejects_unknown_and_self() {
+        let tasks = vec![task(1, &[], false), task(2, &[1], false)];
+
+        assert!(validate_dependencies(&tasks, 3, Some(vec![99])).is_err());
+        assert!(validate_dependencies(&tasks, 2, Some(vec![2])).is_err());
+        assert_eq!(
+            validate_dependencies(&tasks, 3, Some(vec![1, 2, 1])).unwrap(),
+            Some(vec![1, 2])
+        );
+        assert_eq!(validate_dependencies(&tasks, 3, None).unwrap(), None);
+    }
+
+    #[test]
+    fn test_validate_rejects_cycles() {
+        // 3 -> 2 -> 1
+        let tasks = vec![
+            task(1, &[], false),
+            task(2, &[1], false),
+            task(3, &[2], false),
+        ];
+
+        let err = validate_dependencies(&tasks, 1, Some(vec![3])).unwrap_err();
+        assert_eq!(err, "Dependency cycle detected: 1 -> 3 -> 2 -> 1");
+
+        // Replacing the existing dependency list must not count the old edges
+        assert!(validate_dependencies(&tasks, 2, Some(vec![])).is_ok());
+        assert!(validate_dependencies(&tasks, 3, Some(vec![1])).is_ok());
+    }
+
+    #[test]
+    fn test_remove_dependency_references() {
+        let mut tasks = vec![task(2, &[1], false), task(3, &[1, 2], false)];
+
+        assert_eq!(remove_dependency_references(&mut tasks, 1), vec![2, 3]);
+        assert_eq!(tasks[0].dependencies, None);
+        assert_eq!(tasks[1].dependencies, Some(vec![2]));
+        assert!(remove_dependency_references(&mut tasks, 1).is_empty());
+    }
+
+    #[test]
+    fn test_blocked_and_ready_tasks() {
+        let tasks = vec![
+            task(1, &[], true),
+            task(2, &[1], false),
+            task(3, &[2], false),
+            task(4, &[99], false),
+        ];
+
+        let blocked: Vec<i32> = blocked_tasks(&tasks).iter().map(|t| t.id).collect();
+        let ready: Vec<i32> = ready_tasks(&tasks).iter().map(|t| t.id).collect();
+        assert_eq!(blocked, vec![3]);
+        // Dangling references are treated as satisfied
+        assert_eq!(ready, vec![2, 4]);
+    }
+
+    #[test]
+    fn test_topological_order() {
+        let tasks = vec![
+            task(5, &[4], false),
+            task(4, &[], false),
+            task(3, &[1, 2], false),
+            task(2, &[], false),
+            task(1, &[], false),
+        ];
+        assert_eq!(topological_order(&tasks), vec![4, 5, 2, 1, 3]);
+
+        // Legacy cycles are appended in their original order
+        let cyclic = vec![
+            task(1, &[2], false),
+            task(2, &[1], false),
+            task(3, &[], false),
+        ];
+        assert_eq!(topological_order(&cyclic), vec![3, 1, 2]);
+    }
+}
+
+#[cfg(test)]
+mod schedule_tests {
+    use crate::schedule::{auto_schedule, compute_schedule};
+    use crate::task::Task;
+    use crate::task_date::TaskDate;
+    use chrono::NaiveDate;
+
+    fn task(id: i32, start: Option<&str>, due: Option<&str>, dependencies: &[i32]) -> Task {
+        Task {
+            id,
+            description: format!("Task {}", id),
+            start_date: start.map(|s| TaskDate::parse(s).unwrap()),
+            due_date: due.map(|s| TaskDate::parse(s).unwrap()),
+            dependencies: if dependencies.is_empty() {
+                None
+            } else {
+                Some(dependencies.to_vec())
+            },
+            ..Task::default()
+        }
+    }
+
+    fn date(s: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(s, "%Y-%m-%d").unwrap()
+    }
+
+    /// 1 (05-01..05-03) -> 2 (05-03..05-08) -> 4 (05-08..05-10)
+    /// 1 (05-01..05-03) -> 3 (05-03..05-04) -> 4
+    fn project() -> Vec<Task> {
+        vec![
+            task(1, Some("2024-05-01"), Some("2024-05-03"), &[]),
+            task(2, Some("2024-05-03"), Some("2024-05-08"), &[1]),
+            task(3, Some("2024-05-03"), Some("2024-05-04"), &[1]),
+            task(4, Some("2024-05-08"), Some("2024-05-10"), &[2, 3]),
+            task(5, None, None, &[]),
+        ]
+    }
+
+    #[test]
+    fn test_critical_path_and_slack() {
+        let schedule = compute_schedule(&project());
+
+        assert_eq!(schedule.critical_path, vec![1, 2, 4]);
+        assert_eq!(schedule.project_start, Some(date("2024-05-01")));
+        assert_eq!(schedule.project_end, Some(date("2024-05-10")));
+        // Tasks without a due date are not scheduled
+        assert_eq!(schedule.tasks.len(), 4);
+
+        let third = schedule.tasks.iter().find(|s| s.task_id == 3).unwrap();
+        assert_eq!(third.slack_days, 4);
+        assert_eq!(third.latest_start, date("2024-05-07"));
+        assert!(!third.critical);
+    }
+
+    #[test]
+    fn test_earliest_start_respects_slipped_blocker() {
+        let mut tasks = project();
+        // Task 1 slips by two days
+        tasks[0].due_date = Some(TaskDate::parse("2024-05-05").unwrap());
+
+        let schedule = compute_schedule(&tasks);
+        let second = schedule.tasks.iter().find(|s| s.task_id == 2).unwrap();
+        assert_eq!(second.earliest_start, date("2024-05-05"));
+        assert_eq!(second.earliest_finish, date("2024-05-10"));
+        assert_eq!(schedule.project_end, Some(date("2024-05-12")));
+    }
+
+    #[test]
+    fn test_auto_schedule_moves_dependents() {
+        let mut tasks = project();
+        tasks[0].due_date = Some(TaskDate::parse("2024-05-05").unwrap());
+        tasks[3].due_date = Some(TaskDate::parse("2024-05-10 18:00").unwrap());
+        tasks[3].notified = true;
+
+        let moved = auto_schedule(&mut tasks);
+        assert_eq!(moved, vec![2, 3, 4]);
+
+        assert_eq!(tasks[1].start_date.unwrap().to_string(), "2024-05-05");
+        assert_eq!(tasks[1].due_date.unwrap().to_string(), "2024-05-10");
+        assert_eq!(tasks[2].due_date.unwrap().to_string(), "2024-05-06");
+        // Time of day is preserved and the notification is re-armed
+        assert_eq!(tasks[3].due_date.unwrap().to_string(), "2024-05-12 18:00");
+        assert!(!tasks[3].notified);
+
+        // Running again is a no-op
+        assert!(auto_schedule(&mut tasks).is_empty());
+    }
+
+    #[test]
+    fn test_auto_schedule_keeps_completed_tasks() {
+        let mut tasks = project();
+        tasks[0].due_date = Some(TaskDate::parse("2024-05-05").unwrap());
+        tasks[1].completed = true;
+
+        let moved = auto_schedule(&mut tasks);
+        assert_eq!(moved, vec![3]);
+        assert_eq!(tasks[1].start_date.unwrap().to_string(), "2024-05-03");
+    }
+}
+
+#[cfg(test)]
+mod time_tracking_tests {
+    use crate::group::TaskGroup;
+    use crate::task::{Task, TaskPriority};
+    use crate::time_tracking::{start_timer, stop_timer, time_report, TimeEntry};
+    use chrono::{DateTime, TimeZone, Utc};
+
+    fn task(id: i32, group_id: &str, estimated_minutes: Option<u32>) -> Task {
+        Task {
+            id,
+            description: format!("Task {}", id),
+            group_id: Some(group_id.to_string()),
+            estimated_minutes,
+            ..Task::default()
+        }
+    }
+
+    fn at(hour: u32, minute: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2024, 5, 1, hour, minute, 0).unwrap()
+    }
+
+    #[test]
+    fn test_start_and_stop_timer() {
+        let mut tasks = vec![task(1, "work", Some(60))];
+
+        start_timer(&mut tasks, 1, at(9, 0)).unwrap();
+        assert!(start_timer(&mut tasks, 1, at(9, 5)).is_err());
+
+        let entry = stop_timer(&mut tasks, 1, Some("Drafted".to_string()), at(9, 45)).unwrap();
+        assert_eq!(entry.duration_minutes(at(12, 0)), 45);
+        assert_eq!(entry.memo.as_deref(), Some("Drafted"));
+        assert!(stop_timer(&mut tasks, 1, None, at(10, 0)).is_err());
+
+        assert!(start_timer(&mut tasks, 99, at(10, 0)).is_err());
+    }
+
+    #[test]
+    fn test_starting_timer_stops_other_tasks() {
+        let mut tasks = vec![task(1, "work", None), task(2, "work", None)];
+
+        assert!(start_timer(&mut tasks, 1, at(9, 0)).unwrap().is_empty());
+        assert_eq!(start_timer(&mut tasks, 2, at(9, 30)).unwrap(), vec![1]);
+
+        assert_eq!(tasks[0].time_entries[0].ended_at, Some(at(9, 30)));
+        assert!(tasks[1].time_entries[0].is_running());
+    }
+
+    #[test]
+    fn test_time_report_by_group() {
+        let mut tasks = vec![
+            task(1, "work", Some(60)),
+            task(2, "work", None),
+            task(3, "home", Some(30)),
+        ];
+        tasks[0].time_entries.push(TimeEntry {
+            ended_at: Some(at(10, 30)),
+            ..TimeEntry::new(at(9, 0))
+        });
+        tasks[1].time_entries.push(TimeEntry {
+            ended_at: Some(at(11, 20)),
+            ..TimeEntry::new(at(11, 0))
+        });
+        // Running timers count up to now
+        tasks[2].time_entries.push(TimeEntry::new(at(11, 50)));
+
+        let groups = vec![
+            TaskGroup {
+                id: "work".to_string(),
+                ..TaskGroup::new("Work".to_string(), None, 0)
+            },
+            TaskGroup {
+                id: "home".to_string(),
+                ..TaskGroup::new("Home".to_string(), None, 1)
+            },
+        ];
+
+        let report = time_report(&tasks, &groups, at(12, 0));
+        assert_eq!(report.len(), 2);
+
+        assert_eq!(report[0].group, "Home");
+        assert_eq!(report[0].group_id.as_deref(), Some("home"));
+        assert_eq!(report[0].estimated_minutes, 30);
+        assert_eq!(report[0].actual_minutes, 10);
+
+        assert_eq!(report[1].group, "Work");
+        assert_eq!(report[1].task_count, 2);
+        assert_eq!(report[1].estimated_minutes, 60);
+        assert_eq!(report[1].actual_minutes, 110);
+        assert_eq!(report[1].unestimated_minutes, 20);
+    }
+
+    #[test]
+    fn test_legacy_task_defaults() {
+        let json = r#"{"id": 1, "description": "A", "start_date": null, "due_date": null,
+            "group": "", "details": "", "completed": false, "notified": false,
+            "notification_minutes": null}"#;
+        let task: Task = serde_json::from_str(json).unwrap();
+        assert_eq!(task.priority, None);
+        assert!(task.time_entries.is_empty());
+
+        let priority: TaskPriority = serde_json::from_str("\"urgent\"").unwrap();
+        assert_eq!(priority, TaskPriority::Urgent);
+        assert!(TaskPriority::High > TaskPriority::Low);
+    }
+}
+
+#[cfg(test)]
+mod task_query_tests {
+    use crate::group::TaskGroup;
+    use crate::task::{normalize_tags, Subtask, Task, TaskPriority};
+    use crate::task_date::TaskDate;
+    use crate::task_query::{Comparison, DateBound, Filter, TaskQuery};
+    use chrono::{DateTime, TimeZone, Utc};
+
+    fn task(id: i32, group_id: &str, tags: &[&str], due: Option<&str>) -> Task {
+        Task {
+            id,
+            description: format!("Task {}", id),
+            due_date: due.map(|d| TaskDate::parse(d).unwrap()),
+            group_id: Some(group_id.to_string()).filter(|id| !id.is_empty()),
+            tags: tags.iter().map(|t| t.to_string()).collect(),
+            ..Task::default()
+        }
+    }
+
+    // 2024-05-15 10:00 UTC
+    fn now() -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2024, 5, 15, 10, 0, 0).unwrap()
+    }
+
+    fn groups() -> Vec<TaskGroup> {
+        vec![
+            TaskGroup {
+                id: "work".to_string(),
+                ..TaskGroup::new("Work".to_string(), None, 0)
+            },
+            TaskGroup {
+                id: "home".to_string(),
+                ..TaskGroup::new("Home".to_string(), None, 1)
+            },
+            TaskGroup {
+                id: "sprint".to_string(),
+                ..TaskGroup::new("Sprint".to_string(), Some("work".to_string()), 2)
+            },
+        ]
+    }
+
+    fn ids(query: &str, tasks: &[Task]) -> Vec<i32> {
+        TaskQuery::parse(query)
+            .unwrap()
+            .filter(tasks, &groups(), &now())
+            .iter()
+            .map(|t| t.id)
+            .collect()
+    }
+
+    #[test]
+    fn test_parse_query() {
+        let query =
+            TaskQuery::parse(r#"group:"My Work" -tag:#urgent due<7d !completed report"#).unwrap();
+        let filters: Vec<(bool, Filter)> = query
+            .terms
+            .into_iter()
+            .map(|t| (t.negated, t.filter))
+            .collect();
+        assert_eq!(
+            filters,
+            vec![
+                (false, Filter::Group("My Work".to_string())),
+                (true, Filter::Tag("urgent".to_string())),
+                (false, Filter::Due(Comparison::Lt, DateBound::Relative(7))),
+                (true, Filter::Completed),
+                (false, Filter::Text("report".to_string())),
+            ]
+        );
+
+        assert!(TaskQuery::parse("").unwrap().terms.is_empty());
+        assert!(TaskQuery::parse("colour:red").is_err());
+        assert!(TaskQuery::parse("due<soon").is_err());
+        assert!(TaskQuery::parse("tag<urgent").is_err());
+        assert!(TaskQuery::parse("priority:critical").is_err());
+        assert!(TaskQuery::parse("has:nothing").is_err());
+        assert!(TaskQuery::parse("group:\"Work").is_err());
+        assert!(TaskQuery::parse("due<100000000d").is_err());
+        assert!(TaskQuery::parse("start>-100000000d").is_err());
+        assert!(TaskQuery::parse("due<2000000000000000000w").is_err());
+    }
+
+    #[test]
+    fn test_query_example() {
+        let mut tasks = vec![
+            task(1, "work", &["urgent"], Some("2024-05-20")),
+            task(2, "work", &["urgent"], Some("2024-05-20")),
+            task(3, "work", &["Urgent"], Some("2024-05-30")),
+            task(4, "home", &["urgent"], Some("2024-05-16")),
+            // Sub-groups match their parent's name
+            task(5, "sprint", &["URGENT"], Some("2024-05-17 09:00")),
+        ];
+        for t in tasks.iter_mut() {
+            t.subtasks.push(Subtask::new(1, "Step".to_string()));
+        }
+        tasks[1].completed = true;
+        tasks[4].subtasks.clear();
+
+        assert_eq!(
+            ids(
+                "group:Work tag:urgent due<7d !completed has:subtasks",
+                &tasks
+            ),
+            vec![1]
+        );
+        assert_eq!(ids("group:work tag:urgent due<7d", &tasks), vec![1, 2, 5]);
+        assert_eq!(ids("group:sprint", &tasks), vec![5]);
+        assert!(ids("group:unknown", &tasks).is_empty());
+    }
+
+    #[test]
+    fn test_date_and_state_filters() {
+        let mut tasks = vec![
+            task(1, "", &[], Some("2024-05-14")),
+            task(2, "", &[], Some("2024-05-15")),
+            task(3, "", &[], Some("2024-05-15 09:00")),
+            task(4, "", &[], None),
+            task(5, "", &[], Some("2024-06-01")),
+        ];
+        tasks[3].dependencies = Some(vec![5]);
+
+        assert_eq!(ids("due:today", &tasks), vec![2, 3]);
+        assert_eq!(ids("due>=2024-06-01", &tasks), vec![5]);
+        assert_eq!(ids("due<=yesterday", &tasks), vec![1]);
+        assert_eq!(ids("!has:due", &tasks), vec![4]);
+        // Date-only tasks due today are not overdue yet
+        assert_eq!(ids("overdue", &tasks), vec![1, 3]);
+        assert_eq!(ids("is:blocked", &tasks), vec![4]);
+
+        tasks[4].completed = true;
+        assert!(ids("blocked", &tasks).is_empty());
+        assert_eq!(ids("completed", &tasks), vec![5]);
+        assert_eq!(ids("\"completed\"", &tasks), Vec::<i32>::new());
+    }
+
+    #[test]
+    fn test_priority_and_text_filters() {
+        let mut tasks = vec![
+            task(1, "", &[], None),
+            task(2, "", &[], None),
+            task(3, "", &[], None),
+        ];
+        tasks[0].priority = Some(TaskPriority::Low);
+        tasks[1].priority = Some(TaskPriority::Urgent);
+        tasks[2].details = "Call the bank".to_string();
+
+        assert_eq!(ids("priority>=high", &tasks), vec![2]);
+        assert_eq!(ids("priority:low", &tasks), vec![1]);
+        assert_eq!(ids("-has:priority", &tasks), vec![3]);
+        assert_eq!(ids("\"the BANK\"", &tasks), vec![3]);
+        assert_eq!(ids("task", &tasks), vec![1, 2, 3]);
+    }
+
+    #[test]
+    fn test_normalize_tags() {
+        let tags = vec![
+            " urgent ".to_string(),
+            "#Urgent".to_string(),
+            "".to_string(),
+            "#".to_string(),
+            "home".to_string(),
+        ];
+        assert_eq!(normalize_tags(tags), vec!["urgent", "home"]);
+
+        let json = r#"{"id": 1, "description": "A", "start_date": null, "due_date": null,
+            "group": "", "details": "", "completed": false, "notified": false,
+            "notification_minutes": null}"#;
+        let task: Task = serde_json::from_str(json).unwrap();
+        assert!(task.tags.is_empty());
+    }
+}
+
+#[cfg(test)]
+mod group_tests {
+    use crate::group::{
+        descendant_ids, effective_notification_minutes, load_groups, remove_group, reorder_groups,
+        resolve_group_id, save_groups, validate_group_name, validate_parent, TaskGroup,
+    };
+    use crate::task::{load_tasks, Task};
+    use tempfile::tempdir;
+
+    fn group(id: &str, name: &str, parent_id: Option<&str>) -> TaskGroup {
+        TaskGroup {
+            id: id.to_string(),
+            ..TaskGroup::new(name.to_string(), parent_id.map(str::to_string), 0)
+        }
+    }
+
+    fn task(id: i32, group_id: Option<&str>) -> Task {
+        Task {
+            id,
+            description: format!("Task {}", id),
+            group_id: group_id.map(str::to_string),
+            ..Task::default()
+        }
+    }
+
+    #[test]
+    fn test_migrate_legacy_groups_and_tasks() {
+        let dir = tempdir().unwrap();
+        let groups_file = dir.path().join("groups.json");
+        let tasks_file = dir.path().join("tasks.json");
+        std::fs::write(&groups_file, r#"["Work", "Home", "work", ""]"#).unwrap();
+        std::fs::write(
+            &tasks_file,
+            r#"[
+            {"id": 1, "description": "A", "start_date": null, "due_date": null,
+             "group": "Work", "details": "", "completed": false, "notified": false,
+             "notification_minutes": null},
+            {"id": 2, "description": "B", "start_date": null, "due_date": null,
+             "group": "Errands", "details": "", "completed": false, "notified": false,
+             "notification_minutes": null},
+            {"id": 3, "description": "C", "start_date": null, "due_date": null,
+             "group": "", "details": "", "completed": false, "notified": false,
+             "notification_minutes": null}
+        ]"#,
+        )
+        .unwrap();
+
+        let mut groups = load_groups(&groups_file).unwrap();
+        let names: Vec<&str> = groups.iter().map(|g| g.name.as_str()).collect();
+        assert_eq!(names, vec!["Work", "Home"]);
+        assert_eq!(groups[1].sort_order, 1);
+
+        let tasks = load_tasks(&tasks_file, &mut groups).unwrap();
+        assert_eq!(tasks.len(), 3);
+        assert_eq!(tasks[0].group_id.as_deref(), Some(groups[0].id.as_str()));
+        // Names without a group are created on the fly
+        assert_eq!(groups[2].name, "Errands");
+        assert_eq!(tasks[1].group_id.as_deref(), Some(groups[2].id.as_str()));
+        assert_eq!(tasks[2].group_id, None);
+
+        // The new format round-trips
+        save_groups(&groups, &groups_file).unwrap();
+        assert_eq!(load_groups(&groups_file).unwrap(), groups);
+    }
+
+    #[test]
+    fn test_validate_names_and_parents() {
+        let groups = vec![
+            group("work", "Work", None),
+            group("sprint", "Sprint", Some("work")),
+            group("review", "Review", Some("sprint")),
+        ];
+
+        assert!(validate_group_name(&groups, "  ", None, None).is_err());
+        assert!(validate_group_name(&groups, "work", None, None).is_err());
+        assert!(validate_group_name(&groups, "Work", None, Some("work")).is_ok());
+        // The same name is allowed under a different parent
+        assert!(validate_group_name(&groups, "Work", Some("sprint"), None).is_ok());
+
+        assert!(validate_parent(&groups, Some("work"), Some("review")).is_err());
+        assert!(validate_parent(&groups, Some("work"), Some("work")).is_err());
+        assert!(validate_parent(&groups, Some("review"), None).is_ok());
+        assert!(validate_parent(&groups, None, Some("missing")).is_err());
+
+        assert_eq!(
+            descendant_ids(&groups, "work"),
+            vec!["work", "sprint", "review"]
+        );
+
+        assert_eq!(resolve_group_id(&groups, Some(String::new())), Ok(None));
+        assert!(resolve_group_id(&groups, Some("missing".to_string())).is_err());
+    }
+
+    #[test]
+    fn test_remove_group_reparents_children() {
+        let mut groups = vec![
+            group("work", "Work", None),
+            group("sprint", "Sprint", Some("work")),
+            group("review", "Review", Some("sprint")),
+        ];
+        let mut tasks = vec![task(1, Some("sprint")), task(2, Some("work"))];
+
+        assert_eq!(
+            remove_group(&mut groups, &mut tasks, "sprint").unwrap(),
+            vec![1]
+        );
+        assert_eq!(groups.len(), 2);
+        assert_eq!(groups[1].parent_id.as_deref(), Some("work"));
+        assert_eq!(tasks[0].group_id, None);
+        assert_eq!(tasks[1].group_id.as_deref(), Some("work"));
+
+        assert!(remove_group(&mut groups, &mut tasks, "sprint").is_err());
+    }
+
+    #[test]
+    fn test_reorder_groups() {
+        let mut groups = vec![
+            group("a", "A", None),
+            group("b", "B", None),
+            group("c", "C", None),
+        ];
+
+        reorder_groups(&mut groups, &["c".to_string(), "a".to_string()]).unwrap();
+        let order: Vec<(&str, i32)> = groups
+            .iter()
+            .map(|g| (g.id.as_str(), g.sort_order))
+            .collect();
+        assert_eq!(order, vec![("c", 0), ("a", 1), ("b", 2)]);
+
+        assert!(reorder_groups(&mut groups, &["x".to_string()]).is_err());
+    }
+
+    #[test]
+    fn test_notification_minutes_inherited_from_groups() {
+        let mut groups = vec![
+            group("work", "Work", None),
+            group("sprint", "Sprint", Some("work")),
+        ];
+        groups[0].notification_minutes = Some(120);
+
+        let mut t = task(1, Some("sprint"));
+        assert_eq!(effective_notification_minutes(&t, &groups), Some(120));
+
+        groups[1].notification_minutes = Some(30);
+        assert_eq!(effective_notification_minutes(&t, &groups), Some(30));
+
+        t.notification_minutes = Some(5);
+        assert_eq!(effective_notification_minutes(&t, &groups), Some(5));
+
+        assert_eq!(
+            effective_notification_minutes(&task(2, None), &groups),
+            None
+        );
+    }
+}
+
+#[cfg(test)]
+mod workflow_tests {
+    use crate::group::TaskGroup;
+    use crate::task::Task;
+    use crate::workflow::{
+        board, move_task_state, normalize_state, set_completed, validate_workflow, workflow_for,
+        WorkflowState,
+    };
+    use chrono::{DateTime, TimeZone, Utc};
+
+    fn task(id: i32, group_id: Option<&str>) -> Task {
+        Task {
+            id,
+            description: format!("Task {}", id),
+            group_id: group_id.map(str::to_string),
+            ..Task::default()
+        }
+    }
+
+    fn state(id: &str, terminal: bool) -> WorkflowState {
+        WorkflowState {
+            id: id.to_string(),
+            name: id.to_uppercase(),
+            terminal,
+        }
+    }
+
+    fn groups() -> Vec<TaskGroup> {
+        vec![
+            TaskGroup {
+                id: "ops".to_string(),
+                workflow: vec![
+                    state("open", false),
+                    state("doing", false),
+                    state("shipped", true),
+                ],
+                ..TaskGroup::new("Ops".to_string(), None, 0)
+            },
+            TaskGroup {
+                id: "oncall".to_string(),
+                ..TaskGroup::new("On-call".to_string(), Some("ops".to_string()), 1)
+            },
+        ]
+    }
+
+    fn at(day: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2024, 5, day, 9, 0, 0).unwrap()
+    }
+
+    #[test]
+    fn test_workflow_inheritance() {
+        let groups = groups();
+        let ids = |group: Option<&str>| -> Vec<String> {
+            workflow_for(&groups, group)
+                .into_iter()
+                .map(|s| s.id)
+                .collect()
+        };
+
+        assert_eq!(
+            ids(None),
+            vec!["backlog", "todo", "in_progress", "review", "done"]
+        );
+        assert_eq!(ids(Some("ops")), vec!["open", "doing", "shipped"]);
+        assert_eq!(ids(Some("oncall")), vec!["open", "doing", "shipped"]);
+    }
+
+    #[test]
+    fn test_board_places_legacy_tasks() {
+        let mut tasks = vec![task(1, None), task(2, None), task(3, Some("ops"))];
+        tasks[1].completed = true;
+
+        let ungrouped = board(&tasks, &groups(), None);
+        let counts: Vec<(&str, usize)> = ungrouped
+            .columns
+            .iter()
+            .map(|c| (c.state.id.as_str(), c.tasks.len()))
+            .collect();
+        assert_eq!(
+            counts,
+            vec![
+                ("backlog", 1),
+                ("todo", 0),
+                ("in_progress", 0),
+                ("review", 0),
+                ("done", 1)
+            ]
+        );
+
+        let ops = board(&tasks, &groups(), Some("ops"));
+        assert_eq!(ops.columns[0].tasks[0].id, 3);
+    }
+
+    #[test]
+    fn test_move_task_state_records_transitions() {
+        let groups = groups();
+        let mut tasks = vec![task(1, Some("ops")), task(2, Some("ops"))];
+        tasks[1].dependencies = Some(vec![1]);
+
+        move_task_state(&mut tasks, &groups, 1, "doing", at(1)).unwrap();
+        move_task_state(&mut tasks, &groups, 1, "shipped", at(3)).unwrap();
+        assert!(tasks[0].completed);
+        assert_eq!(tasks[0].state.as_deref(), Some("shipped"));
+        let history: Vec<(Option<&str>, &str, DateTime<Utc>)> = tasks[0]
+            .state_history
+            .iter()
+            .map(|t| (t.from.as_deref(), t.to.as_str(), t.at))
+            .collect();
+        assert_eq!(
+            history,
+            vec![
+                (Some("open"), "doing", at(1)),
+                (Some("doing"), "shipped", at(3))
+            ]
+        );
+
+        // Moving to the current state is a no-op
+        move_task_state(&mut tasks, &groups, 1, "shipped", at(4)).unwrap();
+        assert_eq!(tasks[0].state_history.len(), 2);
+
+        assert!(move_task_state(&mut tasks, &groups, 1, "done", at(4)).is_err());
+        assert!(move_task_state(&mut tasks, &groups, 9, "open", at(4)).is_err());
+
+        // Reopen the blocker: task 2 cannot be shipped
+        move_task_state(&mut tasks, &groups, 1, "open", at(5)).unwrap();
+        assert!(!tasks[0].completed);
+        let err = move_task_state(&mut tasks, &groups, 2, "shipped", at(5)).unwrap_err();
+        assert!(err.contains("blocked"), "{}", err);
+    }
+
+    #[test]
+    fn test_set_completed_maps_to_terminal_state() {
+        let groups = groups();
+        let mut t = task(1, None);
+
+        set_completed(&mut t, &groups, true, at(1));
+        assert_eq!(t.state.as_deref(), Some("done"));
+        assert!(t.completed);
+
+        set_completed(&mut t, &groups, false, at(2));
+        assert_eq!(t.state.as_deref(), Some("backlog"));
+
+        let mut tasks = vec![t];
+        move_task_state(&mut tasks, &groups, 1, "review", at(3)).unwrap();
+        set_completed(&mut tasks[0], &groups, true, at(4));
+        // Reopening returns to the state the task was completed from
+        set_completed(&mut tasks[0], &groups, false, at(5));
+        assert_eq!(tasks[0].state.as_deref(), Some("review"));
+        assert!(!tasks[0].completed);
+    }
+
+    #[test]
+    fn test_validate_and_normalize() {
+        assert!(validate_workflow(&[state("a", false), state("b", true)]).is_ok());
+        assert!(validate_workflow(&[state("a", false)]).is_err());
+        assert!(validate_workflow(&[state("a", true)]).is_err());
+        assert!(validate_workflow(&[state("a", false), state("a", true)]).is_err());
+        assert!(validate_workflow(&[state("", false), state("b", true)]).is_err());
+
+        let groups = groups();
+        let mut t = task(1, Some("ops"));
+        t.state = Some("doing".to_string());
+        assert!(!normalize_state(&mut t, &groups));
+
+        t.group_id = None;
+        assert!(normalize_state(&mut t, &groups));
+        assert_eq!(t.state, None);
+    }
+}
+
+#[cfg(test)]
+mod ordering_tests {
+    use crate::ordering::{ensure_sort_keys, key_between, move_task, sequential_keys};
+    use crate::subtask::move_subtask;
+    use crate::task::{Subtask, Task};
+
+    fn task(id: i32, subtasks: &[i32]) -> Task {
+        let mut subtasks: Vec<Subtask> = subtasks
+            .iter()
+            .map(|&id| Subtask::new(id, format!("Subtask {}", id)))
+            .collect();
+        ensure_sort_keys(&mut subtasks);
+        Task {
+            id,
+            description: format!("Task {}", id),
+            subtasks,
+            ..Task::default()
+        }
+    }
+
+    fn tasks(ids: &[i32]) -> Vec<Task> {
+        let mut tasks: Vec<Task> = ids.iter().map(|&id| task(id, &[])).collect();
+        ensure_sort_keys(&mut tasks);
+        tasks
+    }
+
+    fn ids(tasks: &[Task]) -> Vec<i32> {
+        tasks.iter().map(|t| t.id).collect()
+    }
+
+    #[test]
+    fn test_key_between_is_strictly_ordered() {
+        let keys = sequential_keys(100);
+        assert!(keys.windows(2).all(|pair| pair[0] < pair[1]));
+
+        // Repeatedly inserting at the same spot never runs out of room
+        let mut low = keys[0].clone();
+        let high = keys[1].clone();
+        for _ in 0..200 {
+            let mid = key_between(Some(&low), Some(&high));
+            assert!(low < mid && mid < high, "{} {} {}", low, mid, high);
+            low = mid;
+        }
+
+        let first = key_between(None, Some(&keys[0]));
+        assert!(first < keys[0]);
+        let mut high = keys[0].clone();
+        for _ in 0..200 {
+            let mid = key_between(None, Some(&high));
+            assert!(mid < high && !mid.ends_with('0'), "{} {}", mid, high);
+            high = mid;
+        }
+    }
+
+    #[test]
+    fn test_ensure_sort_keys_migrates_legacy_order() {
+        let mut tasks = vec![task(3, &[]), task(1, &[]), task(2, &[])];
+        assert!(ensure_sort_keys(&mut tasks));
+        assert_eq!(ids(&tasks), vec![3, 1, 2]);
+        assert!(!ensure_sort_keys(&mut tasks));
+
+        // Existing keys win over vector order
+        tasks.swap(0, 2);
+        assert!(!ensure_sort_keys(&mut tasks));
+        assert_eq!(ids(&tasks), vec![3, 1, 2]);
+    }
+
+    #[test]
+    fn test_ensure_sort_keys_rekeys_malformed_keys() {
+        for malformed in ["~", "V0", "Vé"] {
+            let mut tasks = tasks(&[1, 2, 3]);
+            tasks[1].sort_key = malformed.to_string();
+            assert!(ensure_sort_keys(&mut tasks), "{}", malformed);
+            assert_eq!(ids(&tasks), vec![1, 2, 3]);
+
+            // New keys can be generated around every item afterwards
+            move_task(&mut tasks, 3, Some(1)).unwrap();
+            move_task(&mut tasks, 1, None).unwrap();
+            assert_eq!(ids(&tasks), vec![1, 3, 2]);
+        }
+
+        // Keys outside the alphabet do not panic even when passed directly
+        assert!(!key_between(Some("a"), Some("~")).is_empty());
+    }
+
+    #[test]
+    fn test_move_task_only_rewrites_moved_key() {
+        let mut tasks = tasks(&[1, 2, 3, 4]);
+        let before: Vec<String> = tasks.iter().map(|t| t.sort_key.clone()).collect();
+
+        move_task(&mut tasks, 4, Some(1)).unwrap();
+        assert_eq!(ids(&tasks), vec![1, 4, 2, 3]);
+        move_task(&mut tasks, 3, None).unwrap();
+        assert_eq!(ids(&tasks), vec![3, 1, 4, 2]);
+        assert_eq!(tasks[1].sort_key, before[0]);
+        assert_eq!(tasks[3].sort_key, before[1]);
+
+        assert!(move_task(&mut tasks, 1, Some(1)).is_err());
+        assert!(move_task(&mut tasks, 9, None).is_err());
+        assert!(move_task(&mut tasks, 1, Some(9)).is_err());
+        assert_eq!(ids(&tasks), vec![3, 1, 4, 2]);
+    }
+
+    #[test]
+    fn test_move_subtask_within_and_between_tasks() {
+        let mut tasks = vec![task(1, &[1, 2, 3]), task(2, &[1])];
+
+        move_subtask(&mut tasks, 1, 3, 1, None, None).unwrap();
+        let order: Vec<i32> = tasks[0].subtasks.iter().map(|s| s.id).collect();
+        assert_eq!(order, vec![3, 1, 2]);
+
+        // Moving to another task renumbers on collision
+        move_subtask(&mut tasks, 1, 1, 2, None, Some(1)).unwrap();
+        assert_eq!(tasks[0].subtasks.len(), 2);
+        let moved = &tasks[1].subtasks[1];
+        assert_eq!((moved.id, moved.description.as_str()), (2, "Subtask 1"));
+        assert!(tasks[1].subtasks[0].sort_key < moved.sort_key);
+
+        assert!(move_subtask(&mut tasks, 1, 9, 2, None, None).is_err());
+        assert!(move_subtask(&mut tasks, 1, 2, 2, None, Some(9)).is_err());
+        assert!(move_subtask(&mut tasks, 1, 2, 1, None, Some(2)).is_err());
+        assert_eq!(tasks[0].subtasks.len(), 2);
+    }
+}
+
+#[cfg(test)]
+mod subtask_tests {
+    use crate::group::TaskGroup;
+    use crate::subtask::{demote, find, move_subtask, promote, roll_up};
+    use crate::task::{Subtask, Task, TaskPriority};
+    use crate::task_date::TaskDate;
+    use crate::time_tracking::TimeEntry;
+    use chrono::{DateTime, TimeZone, Utc};
+
+    fn subtask(id: i32, children: Vec<Subtask>) -> Subtask {
+        Subtask {
+            subtasks: children,
+            sort_key: format!("a{}", id),
+            ..Subtask::new(id, format!("Subtask {}", id))
+        }
+    }
+
+    fn task(id: i32, subtasks: Vec<Subtask>) -> Task {
+        Task {
+            id,
+            description: format!("Task {}", id),
+            group_id: Some("work".to_string()),
+            subtasks,
+            sort_key: format!("a{}", id),
+            rollup_subtasks: true,
+            ..Task::default()
+        }
+    }
+
+    /// Task 1: [1: [2: [3]], 4]
+    fn tree() -> Task {
+        task(
+            1,
+            vec![
+                subtask(1, vec![subtask(2, vec![subtask(3, vec![])])]),
+                subtask(4, vec![]),
+            ],
+        )
+    }
+
+    fn ids(subtasks: &[Subtask]) -> Vec<i32> {
+        subtasks.iter().map(|s| s.id).collect()
+    }
+
+    fn now() -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2024, 5, 1, 9, 0, 0).unwrap()
+    }
+
+    #[test]
+    fn test_nested_ids_and_lookup() {
+        let t = tree();
+        assert_eq!(t.next_subtask_id(), 5);
+        assert_eq!(find(&t.subtasks, 3).unwrap().description, "Subtask 3");
+        assert!(find(&t.subtasks, 9).is_none());
+    }
+
+    #[test]
+    fn test_legacy_subtask_json_loads() {
+        let s: Subtask =
+            serde_json::from_str(r#"{"id":1,"description":"Old","completed":true}"#).unwrap();
+        assert!(s.completed && s.subtasks.is_empty() && s.notes.is_empty());
+        assert_eq!(s.due_date, None);
+    }
+
+    #[test]
+    fn test_move_subtask_between_levels() {
+        let mut tasks = vec![tree(), task(2, vec![subtask(1, vec![])])];
+
+        // Move 4 under 2, before 3
+        move_subtask(&mut tasks, 1, 4, 1, Some(2), None).unwrap();
+        assert_eq!(
+            ids(&find(&tasks[0].subtasks, 2).unwrap().subtasks),
+            vec![4, 3]
+        );
+
+        // A subtree cannot move into itself
+        assert!(move_subtask(&mut tasks, 1, 1, 1, Some(3), None).is_err());
+        assert!(move_subtask(&mut tasks, 1, 1, 1, Some(1), None).is_err());
+        assert!(move_subtask(&mut tasks, 1, 4, 1, Some(9), None).is_err());
+
+        // Moving a subtree to another task renumbers every node
+        move_subtask(&mut tasks, 1, 1, 2, None, Some(1)).unwrap();
+        assert!(tasks[0].subtasks.is_empty());
+        assert_eq!(ids(&tasks[1].subtasks), vec![1, 2]);
+        let moved = &tasks[1].subtasks[1];
+        assert_eq!(moved.description, "Subtask 1");
+        assert_eq!(ids(&moved.subtasks), vec![3]);
+        assert_eq!(ids(&moved.subtasks[0].subtasks), vec![4, 5]);
+    }
+
+    #[test]
+    fn test_promote_and_demote() {
+        let mut tasks = vec![tree(), task(2, vec![])];
+        tasks[0].subtasks[0].notes = "notes".to_string();
+        tasks[0].subtasks[0].due_date = Some(TaskDate::parse("2024-06-01").unwrap());
+
+        promote(&mut tasks, 1, 1, 10).unwrap();
+        let order: Vec<i32> = tasks.iter().map(|t| t.id).collect();
+        assert_eq!(order, vec![1, 10, 2]);
+        let promoted = &tasks[1];
+        assert_eq!(promoted.details, "notes");
+        assert_eq!(promoted.group_id.as_deref(), Some("work"));
+        assert!(promoted.due_date.is_some());
+        assert_eq!(ids(&promoted.subtasks), vec![2]);
+        assert_eq!(ids(&tasks[0].subtasks), vec![4]);
+        assert!(promote(&mut tasks, 1, 1, 11).is_err());
+
+        tasks[2].dependencies = Some(vec![10]);
+        assert_eq!(demote(&mut tasks, 10, 1, Some(4), false).unwrap(), vec![2]);
+        assert_eq!(tasks.len(), 2);
+        assert_eq!(tasks[1].dependencies, None);
+        let demoted = &find(&tasks[0].subtasks, 4).unwrap().subtasks[0];
+        assert_eq!((demoted.id, demoted.notes.as_str()), (5, "notes"));
+        assert_eq!(ids(&demoted.subtasks), vec![6]);
+        assert_eq!(ids(&demoted.subtasks[0].subtasks), vec![7]);
+
+        assert!(demote(&mut tasks, 1, 1, None, false).is_err());
+        assert!(demote(&mut tasks, 2, 1, Some(99), false).is_err());
+        tasks[1].time_entries.push(TimeEntry::new(now()));
+        assert!(demote(&mut tasks, 2, 1, None, true).is_err());
+    }
+
+    #[test]
+    fn test_demote_requires_force_to_discard_task_data() {
+        let mut child = task(2, vec![subtask(1, vec![])]);
+        child.tags = vec!["urgent".to_string()];
+        child.priority = Some(TaskPriority::High);
+        child.group_id = Some("home".to_string());
+        child.details = "details".to_string();
+        let mut tasks = vec![tree(), child, task(3, vec![])];
+        tasks[2].dependencies = Some(vec![2, 1]);
+        let before = tasks.clone();
+
+        let error = demote(&mut tasks, 2, 1, None, false).unwrap_err();
+        assert!(error.contains("group, priority, tags"), "{}", error);
+        assert_eq!(tasks, before);
+
+        // Details and subtasks carry over, which alone needs no force
+        tasks[1].tags.clear();
+        tasks[1].priority = None;
+        tasks[1].group_id = Some("work".to_string());
+        let mut unforced = tasks.clone();
+        assert_eq!(demote(&mut unforced, 2, 1, None, false).unwrap(), vec![3]);
+
+        let mut tasks = before;
+        assert_eq!(demote(&mut tasks, 2, 1, None, true).unwrap(), vec![3]);
+        let order: Vec<i32> = tasks.iter().map(|t| t.id).collect();
+        assert_eq!(order, vec![1, 3]);
+        assert_eq!(tasks[1].dependencies, Some(vec![1]));
+        let demoted = tasks[0].subtasks.last().unwrap();
+        assert_eq!((demoted.id, demoted.notes.as_str()), (5, "details"));
+        assert_eq!(ids(&demoted.subtasks), vec![6]);
+    }
+
+    #[test]
+    fn test_roll_up_completion() {
+        let groups: Vec<TaskGroup> = Vec::new();
+        let mut tasks = vec![tree(), task(2, vec![])];
+
+        tasks[0].subtasks[0].subtasks[0].subtasks[0].completed = true;
+        assert!(roll_up(&mut tasks, &groups, 1, now()));
+        assert!(tasks[0].subtasks[0].completed);
+        assert!(tasks[0].subtasks[0].subtasks[0].completed);
+        assert!(!tasks[0].completed);
+
+        tasks[0].subtasks[1].completed = true;
+        assert!(roll_up(&mut tasks, &groups, 1, now()));
+        assert!(tasks[0].completed);
+        assert_eq!(tasks[0].state.as_deref(), Some("done"));
+
+        tasks[0].subtasks[1].completed = false;
+        assert!(roll_up(&mut tasks, &groups, 1, now()));
+        assert!(!tasks[0].completed);
+
+        // Blocked tasks are not completed by roll-up
+        tasks[0].subtasks[1].completed = true;
+        tasks[0].dependencies = Some(vec![2]);
+        assert!(!roll_up(&mut tasks, &groups, 1, now()));
+        assert!(!tasks[0].completed);
+
+        // Disabled roll-up leaves everything alone
+        tasks[0].dependencies = None;
+        tasks[0].rollup_subtasks = false;
+        assert!(!roll_up(&mut tasks, &groups, 1, now()));
+        assert!(!tasks[0].completed);
+    }
+}
+
+#[cfg(test)]
+mod bulk_tests {
+    use crate::bulk::{bulk_update, undo, BulkTaskPatch, UndoHistory, UNDO_LIMIT};
+    use crate::group::TaskGroup;
+    use crate::ordering::ensure_sort_keys;
+    use crate::task::Task;
+    use crate::task_date::TaskDate;
+    use chrono::{DateTime, TimeZone, Utc};
+
+    fn task(id: i32, due: Option<&str>, dependencies: &[i32]) -> Task {
+        Task {
+            id,
+            description: format!("Task {}", id),
+            due_date: due.map(|d| TaskDate::parse(d).unwrap()),
+            notified: true,
+            dependencies: (!dependencies.is_empty()).then(|| dependencies.to_vec()),
+            tags: vec!["old".to_string()],
+            ..Task::default()
+        }
+    }
+
+    fn tasks() -> Vec<Task> {
+        let mut tasks = vec![
+            task(1, Some("2024-05-10"), &[]),
+            task(2, None, &[1]),
+            task(3, Some("2024-05-20"), &[2]),
+        ];
+        ensure_sort_keys(&mut tasks);
+        tasks
+    }
+
+    fn groups() -> Vec<TaskGroup> {
+        vec![TaskGroup {
+            id: "work".to_string(),
+            ..TaskGroup::new("Work".to_string(), None, 0)
+        }]
+    }
+
+    fn patch(json: &str) -> BulkTaskPatch {
+        serde_json::from_str(json).unwrap()
+    }
+
+    fn now() -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2024, 5, 1, 9, 0, 0).unwrap()
+    }
+
+    #[test]
+    fn test_patch_distinguishes_missing_and_null_group() {
+        assert_eq!(patch("{}").group_id, None);
+        assert_eq!(patch(r#"{"group_id":null}"#).group_id, Some(None));
+        assert_eq!(
+            patch(r#"{"group_id":"work"}"#).group_id,
+            Some(Some("work".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_bulk_update_fields() {
+        let groups = groups();
+        let mut tasks = tasks();
+        let p = patch(r##"{"group_id":"work","shift_due_days":3,"add_tags":["#New","OLD"]}"##);
+
+        let entry = bulk_update(&mut tasks, &groups, &[1, 2], &p, now()).unwrap();
+        assert_eq!(entry.previous.len(), 2);
+        assert_eq!(tasks[0].group_id.as_deref(), Some("work"));
+        assert_eq!(tasks[0].due_date.unwrap().to_string(), "2024-05-13");
+        assert!(!tasks[0].notified);
+        assert_eq!(tasks[1].due_date, None);
+        assert_eq!(tasks[1].tags, vec!["old", "New"]);
+        assert_eq!(tasks[2].group_id, None);
+
+        assert!(bulk_update(&mut tasks, &groups, &[1, 9], &p, now()).is_err());
+        assert!(bulk_update(&mut tasks, &groups, &[], &p, now()).is_err());
+        let unknown_group = patch(r#"{"group_id":"nope"}"#);
+        assert!(bulk_update(&mut tasks, &groups, &[1], &unknown_group, now()).is_err());
+
+        let before = tasks.clone();
+        for days in [i64::MAX, -100_000_000] {
+            let huge_shift = patch(&format!(
+                r#"{{"shift_due_days":{},"add_tags":["x"]}}"#,
+                days
+            ));
+            assert!(bulk_update(&mut tasks, &groups, &[1, 3], &huge_shift, now()).is_err());
+            assert_eq!(tasks, before);
+        }
+    }
+
+    #[test]
+    fn test_bulk_complete_respects_blockers_outside_batch() {
+        let groups = groups();
+        let mut tasks = tasks();
+        let complete = patch(r#"{"completed":true}"#);
+
+        // Task 3 is blocked by task 2, which is not in the batch
+        assert!(bulk_update(&mut tasks, &groups, &[1, 3], &complete, now()).is_err());
+        assert!(tasks.iter().all(|t| !t.completed));
+
+        bulk_update(&mut tasks, &groups, &[3, 2, 1], &complete, now()).unwrap();
+        assert!(tasks.iter().all(|t| t.completed));
+        assert_eq!(tasks[0].state.as_deref(), Some("done"));
+    }
+
+    #[test]
+    fn test_bulk_delete_and_undo() {
+        let groups = groups();
+        let mut tasks = tasks();
+        let entry = bulk_update(
+            &mut tasks,
+            &groups,
+            &[1, 2],
+            &patch(r#"{"delete":true}"#),
+            now(),
+        )
+        .unwrap();
+        assert_eq!(tasks.len(), 1);
+        assert_eq!(tasks[0].dependencies, None);
+
+        // Tasks touched by the bulk update, including removed references, are restored
+        undo(&mut tasks, &groups, entry).unwrap();
+        let ids: Vec<i32> = tasks.iter().map(|t| t.id).collect();
+        assert_eq!(ids, vec![1, 2, 3]);
+        assert_eq!(tasks[2].dependencies, Some(vec![2]));
+
+        let entry = bulk_update(
+            &mut tasks,
+            &groups,
+            &[1],
+            &patch(r#"{"group_id":"work"}"#),
+            now(),
+        )
+        .unwrap();
+        // Restoring into a group deleted in the meantime falls back to no group
+        tasks[0].group_id = Some("gone".to_string());
+        undo(&mut tasks, &[], entry).unwrap();
+        assert_eq!(tasks[0].group_id, None);
+    }
+
+    #[test]
+    fn test_undo_refuses_after_later_edit() {
+        let groups = groups();
+        let mut tasks = tasks();
+        let p = patch(r#"{"shift_due_days":2,"add_tags":["bulk"]}"#);
+        let entry = bulk_update(&mut tasks, &groups, &[1, 3], &p, now()).unwrap();
+
+        // Task 3 is edited on its own between the bulk edit and the undo
+        let later = now() + chrono::Duration::minutes(5);
+        tasks[2].details = "edited later".to_string();
+        tasks[2].updated_at = Some(later);
+        let edited = tasks.clone();
+        let error = undo(&mut tasks, &groups, entry.clone()).unwrap_err();
+        assert!(error.contains('3'), "{}", error);
+        assert!(!error.contains('1'), "{}", error);
+        assert_eq!(tasks, edited);
+
+        // A task deleted afterwards is not resurrected either
+        let mut tasks = edited;
+        tasks.retain(|t| t.id != 3);
+        let remaining = tasks.clone();
+        assert!(undo(&mut tasks, &groups, entry).is_err());
+        assert_eq!(tasks, remaining);
+
+        // Deleted tasks can be restored while nothing else touched the batch
+        let mut tasks = self::tasks();
+        let delete = patch(r#"{"delete":true}"#);
+        let entry = bulk_update(&mut tasks, &groups, &[2], &delete, now()).unwrap();
+        tasks[0].details = "unrelated".to_string();
+        tasks[0].updated_at = Some(later);
+        undo(&mut tasks, &groups, entry).unwrap();
+        assert_eq!(tasks.len(), 3);
+        assert_eq!(tasks[0].details, "unrelated");
+    }
+
+    #[test]
+    fn test_undo_history_is_bounded() {
+        let groups = groups();
+        let mut tasks = tasks();
+        let mut history = UndoHistory::default();
+        for _ in 0..UNDO_LIMIT + 5 {
+            let p = patch(r#"{"shift_due_days":1}"#);
+            history.push(bulk_update(&mut tasks, &groups, &[1], &p, now()).unwrap());
+        }
+        let mut count = 0;
+        while let Some(entry) = history.pop() {
+            undo(&mut tasks, &groups, entry).unwrap();
+            count += 1;
+        }
+        assert_eq!(count, UNDO_LIMIT);
+        assert_eq!(tasks[0].due_date.unwrap().to_string(), "2024-05-15");
+    }
+}
+
+#[cfg(test)]
+mod patch_tests {
+    use crate::calendar::{CalendarEvent, CalendarEventPatch};
+    use crate::group::TaskGroup;
+    use crate::patch::check_updated_at;
+    use crate::reading_memo::{ReadingBook, ReadingBookPatch, ReadingStatus};
+    use crate::task::{apply_task_patch, Task, TaskPatch, TaskPriority};
+    use crate::task_date::TaskDate;
+    use chrono::{DateTime, TimeZone, Utc};
+
+    fn task(id: i32) -> Task {
+        Task {
+            id,
+            description: format!("Task {}", id),
+            due_date: Some(TaskDate::parse("2024-05-10").unwrap()),
+            group_id: Some("work".to_string()),
+            details: "details".to_string(),
+            notified: true,
+            notification_minutes: Some(30),
+            priority: Some(TaskPriority::High),
+            tags: vec!["a".to_string()],
+            updated_at: Some(at(1)),
+            ..Task::default()
+        }
+    }
+
+    fn groups() -> Vec<TaskGroup> {
+        vec![TaskGroup {
+            id: "work".to_string(),
+            ..TaskGroup::new("Work".to_string(), None, 0)
+        }]
+    }
+
+    fn at(hour: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2024, 5, 1, hour, 0, 0).unwrap()
+    }
+
+    #[test]
+    fn test_task_patch_changes_only_supplied_fields() {
+        let groups = groups();
+        let mut tasks = vec![task(1), task(2)];
+        let patch: TaskPatch =
+            serde_json::from_str(r#"{"description":"Renamed","priority":null}"#).unwrap();
+
+        apply_task_patch(&mut tasks, &groups, 1, patch, Some(at(1)), at(2)).unwrap();
+        let t = &tasks[0];
+        assert_eq!(t.description, "Renamed");
+        assert_eq!(t.priority, None);
+        assert_eq!(t.details, "details");
+        assert_eq!(t.group_id.as_deref(), Some("work"));
+        assert_eq!(t.tags, vec!["a"]);
+        assert!(t.due_date.is_some());
+        // Due date and notification untouched, so the notified flag stays
+        assert!(t.notified);
+        assert_eq!(t.updated_at, Some(at(2)));
+
+        let patch: TaskPatch =
+            serde_json::from_str(r#"{"due_date":null,"group_id":null,"dependencies":[2]}"#)
+                .unwrap();
+        apply_task_patch(&mut tasks, &groups, 1, patch, Some(at(2)), at(3)).unwrap();
+        assert_eq!(tasks[0].due_date, None);
+        assert_eq!(tasks[0].group_id, None);
+        assert_eq!(tasks[0].dependencies, Some(vec![2]));
+        assert!(!tasks[0].notified);
+    }
+
+    #[test]
+    fn test_task_patch_conflict_and_validation() {
+        let groups = groups();
+        let mut tasks = vec![task(1)];
+        let rename: TaskPatch = serde_json::from_str(r#"{"description":"x"}"#).unwrap();
+
+        let err = apply_task_patch(&mut tasks, &groups, 1, rename.clone(), Some(at(0)), at(2))
+            .unwrap_err();
+        assert!(err.starts_with("Conflict:"), "{}", err);
+        assert!(apply_task_patch(&mut tasks, &groups, 9, rename, Some(at(1)), at(2)).is_err());
+
+        // Invalid values leave the task untouched
+        let bad: TaskPatch =
+            serde_json::from_str(r#"{"description":"x","start_date":"2024-06-01"}"#).unwrap();
+        assert!(apply_task_patch(&mut tasks, &groups, 1, bad, Some(at(1)), at(2)).is_err());
+        let bad: TaskPatch =
+            serde_json::from_str(r#"{"description":"x","dependencies":[1]}"#).unwrap();
+        assert!(apply_task_patch(&mut tasks, &groups, 1, bad, Some(at(1)), at(2)).is_err());
+        assert_eq!(tasks[0].description, "Task 1");
+        assert_eq!(tasks[0].updated_at, Some(at(1)));
+
+        // A client that sends no timestamp skips the check
+        let rename: TaskPatch = serde_json::from_str(r#"{"description":"x"}"#).unwrap();
+        apply_task_patch(&mut tasks, &groups, 1, rename.clone(), None, at(2)).unwrap();
+        assert_eq!(tasks[0].description, "x");
+
+        // Legacy tasks without a timestamp conflict with any expected one
+        tasks[0].updated_at = None;
+        assert!(
+            apply_task_patch(&mut tasks, &groups, 1, rename.clone(), Some(at(2)), at(3)).is_err()
+        );
+        apply_task_patch(&mut tasks, &groups, 1, rename, None, at(3)).unwrap();
+        assert_eq!(tasks[0].updated_at, Some(at(3)));
+    }
+
+    #[test]
+    fn test_calendar_and_book_patches() {
+        let mut event = CalendarEvent {
+            id: "e1".to_string(),
+            title: "Standup".to_string(),
+            description: String::new(),
+            start_datetime: "2024-05-01T09:00".to_string(),
+            end_datetime: Some("2024-05-01T09:15".to_string()),
+            all_day: false,
+            color: Some("#ff0000".to_string()),
+            recurrence_rule: None,
+            reminder_minutes: Some(5),
+            created_at: at(1),
+            updated_at: at(1),
+        };
+        let patch: CalendarEventPatch =
+            serde_json::from_str(r#"{"title":"Daily","end_datetime":null}"#).unwrap();
+        event.apply_patch(patch, at(2));
+        assert_eq!(event.title, "Daily");
+        assert_eq!(event.end_datetime, None);
+        assert_eq!(event.color.as_deref(), Some("#ff0000"));
+        assert_eq!(event.reminder_minutes, Some(5));
+        assert_eq!(event.updated_at, at(2));
+
+        let mut book = ReadingBook::new("Book".to_string());
+        book.total_pages = Some(200);
+        let patch: ReadingBookPatch =
+            serde_json::from_str(r#"{"rating":4,"current_page":50,"status":"reading"}"#).unwrap();
+        book.apply_patch(patch, at(3));
+        assert_eq!(book.rating, Some(4));
+        assert_eq!(book.status, ReadingStatus::Reading);
+        assert_eq!(book.progress_percent, Some(25));
+        assert_eq!(book.title, "Book");
+
+        assert!(check_updated_at("Book", "b", Some(at(3)), Some(at(3))).is_ok());
+        assert!(check_updated_at("Book", "b", Some(at(3)), None).is_ok());
+        assert!(check_updated_at("Book", "b", Some(at(3)), Some(at(2)))
+            .unwrap_err()
+            .starts_with("Conflict:"));
+    }
+}
+
+#[cfg(test)]
+mod events_tests {
+    use crate::events::{BookItemChange, ChangeEvent, TaskChanges};
+    use crate::memo::Memo;
+    use crate::reading_memo::ReadingSession;
+    use crate::task::Task;
+    use chrono::{TimeZone, Utc};
+    use serde_json::json;
+
+    fn task(id: i32, description: &str) -> Task {
+        Task {
+            id,
+            description: description.to_string(),
+            ..Task::default()
+        }
+    }
+
+    #[test]
+    fn test_from_ids_picks_created_updated_and_deleted() {
+        let tasks = vec![task(1, "a"), task(3, "changed"), task(4, "new")];
+
+        let changes = TaskChanges::from_ids(&tasks, &[4], &[4, 3, 3, 99], &[2, 2]);
+        assert_eq!(changes.created, vec![task(4, "new")]);
+        assert_eq!(changes.updated, vec![task(3, "changed")]);
+        assert_eq!(changes.deleted, vec![2]);
+    }
+
+    #[test]
+    fn test_from_ids_without_ids_is_empty() {
+        let tasks = vec![task(1, "a"), task(2, "b")];
+        let changes = TaskChanges::from_ids(&tasks, &[], &[], &[]);
+        assert!(changes.is_empty());
+        assert!(changes.events().is_empty());
+    }
+
+    #[test]
+    fn test_task_changes_events_carry_only_changed_tasks() {
+        let tasks = vec![task(1, "edited"), task(3, "new")];
+
+        let events = TaskChanges::from_ids(&tasks, &[3], &[1], &[2]).events();
+        let names: Vec<&str> = events.iter().map(|e| e.name()).collect();
+        assert_eq!(names, vec!["task-created", "task-updated", "task-deleted"]);
+        assert_eq!(events[1].payload()["description"], json!("edited"));
+        assert_eq!(events[2].payload(), json!({ "id": 2 }));
+    }
+
+    #[test]
+    fn test_memo_event_names_and_payloads() {
+        let memo = Memo::new("Title".to_string(), "Body".to_string(), None, Vec::new());
+        let updated = ChangeEvent::MemoUpdated(memo.clone());
+        assert_eq!(updated.name(), "memo-updated");
+        assert_eq!(updated.payload()["id"], json!(memo.id));
+
+        let deleted = ChangeEvent::MemoDeleted(memo.id.clone());
+        assert_eq!(deleted.name(), "memo-deleted");
+        assert_eq!(deleted.payload(), json!({ "id": memo.id }));
+    }
+
+    #[test]
+    fn test_book_session_event_includes_book_timestamp() {
+        let updated_at = Utc.with_ymd_and_hms(2024, 5, 1, 9, 0, 0).unwrap();
+        let session = ReadingSession::new(updated_at, Some(1), Some(20), 20, Some(30), None);
+        let event = ChangeEvent::BookSessionAdded(BookItemChange {
+            book_id: "book-1".to_string(),
+            book_updated_at: updated_at,
+            item: session.clone(),
+        });
+
+        assert_eq!(event.name(), "book-session-added");
+        let payload = event.payload();
+        assert_eq!(payload["book_id"], json!("book-1"));
+        assert_eq!(payload["book_updated_at"], json!(updated_at));
+        assert_eq!(payload["item"]["id"], json!(session.id));
+        assert_eq!(payload["item"]["pages_read"], json!(20));
+    }
+}
+
+#[cfg(test)]
+mod listing_tests {
+    use crate::listing::{excerpt, list, ListOptions, Projected, EXCERPT_CHARS};
+    use crate::memo::Memo;
+    use crate::reading_memo::{ReadingBook, ReadingNote};
+    use crate::task::{Task, TaskPriority};
+    use crate::task_date::TaskDate;
+
+    fn task(id: i32, due: Option<&str>, priority: Option<TaskPriority>) -> Task {
+        Task {
+            id,
+            description: format!("Task {}", id),
+            due_date: due.map(|d| TaskDate::parse(d).unwrap()),
+            details: "long details".to_string(),
+            priority,
+            ..Task::default()
+        }
+    }
+
+    fn ids(page: &[Projected<Task, crate::listing::TaskSummary>]) -> Vec<i32> {
+        page.iter()
+            .map(|item| match item {
+                Projected::Full(task) => task.id,
+                Projected::Summary(summary) => summary.id,
+            })
+            .collect()
+    }
+
+    fn options(sort: Option<&str>, limit: Option<usize>) -> ListOptions {
+        ListOptions {
+            sort: sort.map(str::to_string),
+            limit,
+            ..ListOptions::default()
+        }
+    }
+
+    #[test]
+    fn test_default_options_return_everything_in_stored_order() {
+        let tasks = vec![
+            task(3, None, None),
+            task(1, None, None),
+            task(2, None, None),
+        ];
+        let page = list(&tasks, &ListOptions::default()).unwrap();
+        assert_eq!(ids(&page.items), vec![3, 1, 2]);
+        assert_eq!(page.total, 3);
+        assert_eq!(page.next_cursor, None);
+    }
+
+    #[test]
+    fn test_sort_by_due_date_puts_missing_values_last() {
+        let tasks = vec![
+            task(1, None, None),
+            task(2, Some("2024-05-03"), None),
+            task(3, Some("2024-05-01 09:00"), None),
+        ];
+        let page = list(&tasks, &options(Some("due_date"), None)).unwrap();
+        assert_eq!(ids(&page.items), vec![3, 2, 1]);
+
+        let page = list(&tasks, &options(Some("-due_date"), None)).unwrap();
+        assert_eq!(ids(&page.items), vec![1, 2, 3]);
+    }
+
+    #[test]
+    fn test_sort_by_priority_is_highest_first_and_stable() {
+        let tasks = vec![
+            task(1, None, Some(TaskPriority::Low)),
+            task(2, None, Some(TaskPriority::Urgent)),
+            task(3, None, None),
+            task(4, None, Some(TaskPriority::Low)),
+        ];
+        let page = list(&tasks, &options(Some("priority"), None)).unwrap();
+        assert_eq!(ids(&page.items), vec![2, 1, 4, 3]);
+    }
+
+    #[test]
+    fn test_unknown_sort_field_is_rejected() {
+        let tasks = vec![task(1, None, None)];
+        let err = list(&tasks, &options(Some("color"), None)).unwrap_err();
+        assert!(err.contains("Unknown sort field 'color'"));
+    }
+
+    #[test]
+    fn test_offset_and_limit() {
+        let tasks: Vec<Task> = (1..=5).map(|id| task(id, None, None)).collect();
+        let page = list(
+            &tasks,
+            &ListOptions {
+                offset: Some(1),
+                limit: Some(2),
+                ..ListOptions::default()
+            },
+        )
+        .unwrap();
+        assert_eq!(ids(&page.items), vec![2, 3]);
+        assert_eq!(page.total, 5);
+        assert_eq!(page.next_cursor, Some("3".to_string()));
+
+        let past_end = ListOptions {
+            offset: Some(10),
+            ..ListOptions::default()
+        };
+        assert!(list(&tasks, &past_end).unwrap().items.is_empty());
+    }
+
+    #[test]
+    fn test_cursor_continues_after_last_item() {
+        let tasks: Vec<Task> = (1..=5).map(|id| task(id, None, None)).collect();
+        let first = list(&tasks, &options(Some("-id"), Some(2))).unwrap();
+        assert_eq!(ids(&first.items), vec![5, 4]);
+
+        let second = list(
+            &tasks,
+            &ListOptions {
+                cursor: first.next_cursor.clone(),
+                ..options(Some("-id"), Some(2))
+            },
+        )
+        .unwrap();
+        assert_eq!(ids(&second.items), vec![3, 2]);
+
+        let third = list(
+            &tasks,
+            &ListOptions {
+                cursor: second.next_cursor.clone(),
+                ..options(Some("-id"), Some(2))
+            },
+        )
+        .unwrap();
+        assert_eq!(ids(&third.items), vec![1]);
+        assert_eq!(third.next_cursor, None);
+    }
+
+    #[test]
+    fn test_invalid_paging_options_are_rejected() {
+        let tasks = vec![task(1, None, None)];
+        let stale = ListOptions {
+            cursor: Some("99".to_string()),
+            ..ListOptions::default()
+        };
+        assert!(list(&tasks, &stale)
+            .unwrap_err()
+            .contains("no longer valid"));
+
+        let both = ListOptions {
+            cursor: Some("1".to_string()),
+            offset: Some(0),
+            ..ListOptions::default()
+        };
+        assert!(list(&tasks, &both).is_err());
+        assert!(list(&tasks, &options(None, Some(0))).is_err());
+    }
+
+    #[test]
+    fn test_memo_summary_omits_content() {
+        let content = "あ".repeat(EXCERPT_CHARS + 10);
+        let memos = vec![Memo::new("Title".to_string(), content, None, Vec::new())];
+        let page = list(
+            &memos,
+            &ListOptions {
+                summary: true,
+                ..ListOptions::default()
+            },
+        )
+        .unwrap();
+
+        let value = serde_json::to_value(&page.items[0]).unwrap();
+        assert!(value.get("content").is_none());
+        let excerpt = value["excerpt"].as_str().unwrap();
+        assert_eq!(excerpt.chars().count(), EXCERPT_CHARS + 1);
+        assert!(excerpt.ends_with('…'));
+    }
+
+    #[test]
+    fn test_excerpt_collapses_whitespace() {
+        assert_eq!(
+            excerpt("# Title\n\nfirst  line\nsecond"),
+            "# Title first line second"
+        );
+    }
+
+    #[test]
+    fn test_book_summary_counts_notes_instead_of_including_them() {
+        let mut book = ReadingBook::new("Book".to_string());
+        book.notes
+            .push(ReadingNote::new(Some(1), None, "note".to_string()));
+        book.notes
+            .push(ReadingNote::new(Some(2), None, "note".to_string()));
+        let page = list(
+            &[book],
+            &ListOptions {
+                summary: true,
+                ..ListOptions::default()
+            },
+        )
+        .unwrap();
+
+        let value = serde_json::to_value(&page.items[0]).unwrap();
+        assert!(value.get("notes").is_none());
+        assert!(value.get("reading_sessions").is_none());
+        assert_eq!(value["note_count"], 2);
+        assert_eq!(value["session_count"], 0);
+    }
+
+    #[test]
+    fn test_sort_books_by_title_ignores_case() {
+        let books = vec![
+            ReadingBook::new("beta".to_string()),
+            ReadingBook::new("Alpha".to_string()),
+        ];
+        let page = list(&books, &options(Some("title"), None)).unwrap();
+        let titles: Vec<String> = page
+            .items
+            .iter()
+            .map(|item| match item {
+                Projected::Full(book) => book.title.clone(),
+                Projected::Summary(summary) => summary.title.clone(),
+            })
+            .collect();
+        assert_eq!(titles, vec!["Alpha", "beta"]);
+    }
+}
+
+#[cfg(test)]
+mod memo_search_tests {
+    use crate::memo::{Folder, Memo};
+    use crate::memo_search::{tokenize, Clause, MemoIndex, MemoSearchQuery};
+
+    fn memo(id: &str, title: &str, content: &str, tags: &[&str]) -> Memo {
+        let mut memo = Memo::new(
+            title.to_string(),
+            content.to_string(),
+            None,
+            tags.iter().map(|t| t.to_string()).collect(),
+        );
+        memo.id = id.to_string();
+        memo
+    }
+
+    fn search(memos: &[Memo], folders: &[Folder], query: &str) -> Vec<String> {
+        let index = MemoIndex::build(memos);
+        index
+            .search(memos, folders, &MemoSearchQuery::parse(query), 50)
+            .into_iter()
+            .map(|hit| hit.id)
+            .collect()
+    }
+
+    #[test]
+    fn test_tokenize_words_and_japanese_bigrams() {
+        let tokens: Vec<String> = tokenize("Rust入門メモ ＡＢＣ")
+            .into_iter()
+            .map(|t| t.text)
+            .collect();
+        assert_eq!(tokens, vec!["rust", "入門", "門メ", "メモ", "モ", "abc"]);
+    }
+
+    #[test]
+    fn test_parse_query() {
+        let query = MemoSearchQuery::parse(
+            r#"budget "review meeting" plan* tag:work folder:"Daily Notes" 会議室"#,
+        );
+        assert_eq!(
+            query.clauses,
+            vec![
+                Clause::Term("budget".to_string()),
+                Clause::Phrase(vec!["review".to_string(), "meeting".to_string()]),
+                Clause::Prefix("plan".to_string()),
+                Clause::Phrase(vec!["会議".to_string(), "議室".to_string()]),
+            ]
+        );
+        assert_eq!(query.tags, vec!["work"]);
+        assert_eq!(query.folders, vec!["Daily Notes"]);
+    }
+
+    #[test]
+    fn test_all_terms_must_match() {
+        let memos = vec![
+            memo("a", "Budget", "quarterly plan", &[]),
+            memo("b", "Budget", "nothing else", &[]),
+        ];
+        assert_eq!(search(&memos, &[], "budget plan"), vec!["a"]);
+    }
+
+    #[test]
+    fn test_phrase_requires_adjacent_words() {
+        let memos = vec![
+            memo("a", "A", "the review meeting is today", &[]),
+            memo("b", "B", "meeting after the review", &[]),
+        ];
+        assert_eq!(search(&memos, &[], "\"review meeting\""), vec!["a"]);
+    }
+
+    #[test]
+    fn test_prefix_matching() {
+        let memos = vec![
+            memo("a", "A", "planning session", &[]),
+            memo("b", "B", "airplane", &[]),
+        ];
+        assert_eq!(search(&memos, &[], "plan*"), vec!["a"]);
+    }
+
+    #[test]
+    fn test_japanese_substring_search() {
+        let memos = vec![
+            memo("a", "議事録", "来週の会議室を予約する", &[]),
+            memo("b", "買い物", "会話の練習", &[]),
+        ];
+        assert_eq!(search(&memos, &[], "会議室"), vec!["a"]);
+        assert_eq!(search(&memos, &[], "議室"), vec!["a"]);
+        let mut single = search(&memos, &[], "会");
+        single.sort();
+        assert_eq!(single, vec!["a", "b"]);
+    }
+
+    #[test]
+    fn test_title_matches_rank_higher() {
+        let memos = vec![
+            memo("body", "Notes", "rust rust and more text here", &[]),
+            memo("title", "Rust", "some unrelated text here too", &[]),
+        ];
+        assert_eq!(search(&memos, &[], "rust"), vec!["title", "body"]);
+    }
+
+    #[test]
+    fn test_tag_and_folder_filters() {
+        let parent = Folder {
+            id: "f1".to_string(),
+            name: "Work".to_string(),
+            parent_id: None,
+        };
+        let child = Folder {
+            id: "f2".to_string(),
+            name: "Meetings".to_string(),
+            parent_id: Some("f1".to_string()),
+        };
+        let mut a = memo("a", "Plan", "text", &["Project"]);
+        a.folder_id = Some("f2".to_string());
+        let b = memo("b", "Plan", "text", &["project"]);
+        let memos = vec![a, b];
+
+        assert_eq!(
+            search(&memos, &[parent.clone(), child.clone()], "plan folder:work"),
+            vec!["a"]
+        );
+        let mut tagged = search(&memos, &[], "tag:PROJECT");
+        tagged.sort();
+        assert_eq!(tagged, vec!["a", "b"]);
+        assert!(search(&memos, &[parent, child], "folder:missing").is_empty());
+    }
+
+    #[test]
+    fn test_incremental_update_and_remove() {
+        let mut memos = vec![memo("a", "Alpha", "first draft", &[])];
+        let mut index = MemoIndex::build(&memos);
+
+        memos[0].content = "second version".to_string();
+        index.upsert(&memos[0]);
+        let query = MemoSearchQuery::parse("draft");
+        assert!(index.search(&memos, &[], &query, 10).is_empty());
+        let query = MemoSearchQuery::parse("second");
+        assert_eq!(index.search(&memos, &[], &query, 10).len(), 1);
+
+        index.remove("a");
+        assert!(index.is_empty());
+        assert!(index.search(&memos, &[], &query, 10).is_empty());
+    }
+
+    #[test]
+    fn test_snippet_highlights_match() {
+        let content = format!("{}needle in the haystack", "x ".repeat(40));
+        let memos = vec![memo("a", "Find the needle", &content, &[])];
+        let index = MemoIndex::build(&memos);
+        let hits = index.search(&memos, &[], &MemoSearchQuery::parse("needle"), 10);
+
+        let snippet = &hits[0].snippet;
+        assert!(snippet.text.starts_with('…'));
+        let [start, end] = snippet.highlights[0];
+        let highlighted: String = snippet.text.chars().skip(start).take(end - start).collect();
+        assert_eq!(highlighted, "needle");
+        assert_eq!(hits[0].title_highlights, vec![[9, 15]]);
+    }
+}
+
+#[cfg(test)]
+mod global_search_tests {
+    use crate::calendar::CalendarEvent;
+    use crate::global_search::{search, SearchSources, SearchTarget};
+    use crate::memo::Memo;
+    use crate::memo_search::MemoIndex;
+    use crate::reading_memo::{ReadingBook, ReadingNote};
+    use crate::task::{Subtask, Task};
+    use chrono::Utc;
+
+    fn task(id: i32, description: &str, details: &str, tags: &[&str]) -> Task {
+        Task {
+            id,
+            description: description.to_string(),
+            details: details.to_string(),
+            tags: tags.iter().map(|t| t.to_string()).collect(),
+            ..Task::default()
+        }
+    }
+
+    fn event(id: &str, title: &str, description: &str) -> CalendarEvent {
+        CalendarEvent {
+            id: id.to_string(),
+            title: title.to_string(),
+            description: description.to_string(),
+            start_datetime: "2024-05-15T10:00:00".to_string(),
+            end_datetime: None,
+            all_day: false,
+            color: None,
+            recurrence_rule: None,
+            reminder_minutes: None,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        }
+    }
+
+    struct Fixture {
+        tasks: Vec<Task>,
+        memos: Vec<Memo>,
+        books: Vec<ReadingBook>,
+        events: Vec<CalendarEvent>,
+    }
+
+    impl Fixture {
+        fn new() -> Self {
+            let mut with_subtask = task(2, "Prepare slides", "", &[]);
+            let mut subtask = Subtask::new(1, "Check budget numbers".to_string());
+            subtask.notes = "ask finance".to_string();
+            with_subtask.subtasks.push(subtask);
+
+            let mut memo = Memo::new(
+                "Budget 2025".to_string(),
+                "Draft of the yearly budget".to_string(),
+                None,
+                vec!["work".to_string()],
+            );
+            memo.id = "memo".to_string();
+
+            let mut book = ReadingBook::new("The Money Book".to_string());
+            book.id = "book".to_string();
+            book.author = Some("Jane Budget".to_string());
+            let mut note = ReadingNote::new(
+                Some(12),
+                Some("A budget is telling your money where to go".to_string()),
+                "good quote".to_string(),
+            );
+            note.id = "note".to_string();
+            book.notes.push(note);
+
+            Fixture {
+                tasks: vec![
+                    task(1, "Review budget", "Compare with last year", &["work"]),
+                    with_subtask,
+                ],
+                memos: vec![memo],
+                books: vec![book],
+                events: vec![event("event", "Budget meeting", "Room 3")],
+            }
+        }
+
+        fn search(&self, query: &str) -> Vec<SearchTarget> {
+            let index = MemoIndex::build(&self.memos);
+            let sources = SearchSources {
+                tasks: &self.tasks,
+                memos: &self.memos,
+                memo_index: &index,
+                folders: &[],
+                books: &self.books,
+                events: &self.events,
+            };
+            search(&sources, query, 50)
+                .into_iter()
+                .map(|hit| hit.target)
+                .collect()
+        }
+    }
+
+    #[test]
+    fn test_finds_every_kind() {
+        let targets = Fixture::new().search("budget");
+        assert_eq!(targets.len(), 6);
+        for expected in [
+            SearchTarget::Task { task_id: 1 },
+            SearchTarget::Subtask {
+                task_id: 2,
+                subtask_id: 1,
+            },
+            SearchTarget::Memo {
+                memo_id: "memo".to_string(),
+            },
+            SearchTarget::Book {
+                book_id: "book".to_string(),
+            },
+            SearchTarget::ReadingNote {
+                book_id: "book".to_string(),
+                note_id: "note".to_string(),
+            },
+            SearchTarget::CalendarEvent {
+                event_id: "event".to_string(),
+            },
+        ] {
+            assert!(targets.contains(&expected), "missing {:?}", expected);
+        }
+    }
+
+    #[test]
+    fn test_body_fields_are_searched() {
+        let fixture = Fixture::new();
+        assert_eq!(
+            fixture.search("last year"),
+            vec![SearchTarget::Task { task_id: 1 }]
+        );
+        assert_eq!(
+            fixture.search("finance"),
+            vec![SearchTarget::Subtask {
+                task_id: 2,
+                subtask_id: 1
+            }]
+        );
+        assert_eq!(
+            fixture.search("\"where to go\""),
+            vec![SearchTarget::ReadingNote {
+                book_id: "book".to_string(),
+                note_id: "note".to_string()
+            }]
+        );
+        assert_eq!(
+            fixture.search("room"),
+            vec![SearchTarget::CalendarEvent {
+                event_id: "event".to_string()
+            }]
+        );
+    }
+
+    #[test]
+    fn test_tag_filter_limits_to_tagged_entities() {
+        let targets = Fixture::new().search("budget tag:work");
+        assert_eq!(targets.len(), 2);
+        assert!(targets.contains(&SearchTarget::Task { task_id: 1 }));
+        assert!(targets.contains(&SearchTarget::Memo {
+            memo_id: "memo".to_string()
+        }));
+    }
+
+    #[test]
+    fn test_hit_serializes_kind_and_ids() {
+        let fixture = Fixture::new();
+        let index = MemoIndex::build(&fixture.memos);
+        let sources = SearchSources {
+            tasks: &fixture.tasks,
+            memos: &fixture.memos,
+            memo_index: &index,
+            folders: &[],
+            books: &fixture.books,
+            events: &fixture.events,
+        };
+        let hits = search(&sources, "finance", 10);
+        let json = serde_json::to_value(&hits[0]).unwrap();
+        assert_eq!(json["kind"], "subtask");
+        assert_eq!(json["task_id"], 2);
+        assert_eq!(json["subtask_id"], 1);
+        assert_eq!(json["context"], "Prepare slides");
+        assert_eq!(json["snippet"]["text"], "ask finance");
+    }
+
+    #[test]
+    fn test_empty_query_returns_nothing() {
+        assert!(Fixture::new().search("  ").is_empty());
+    }
+}
+
+#[cfg(test)]
+mod memo_links_tests {
+    use crate::memo::Memo;
+    use crate::memo_links::{
+        parse_links, rename_references, replace_link_target, resolve, LinkIndex,
+    };
+
+    fn memo(id: &str, title: &str, content: &str) -> Memo {
+        let mut memo = Memo::new(title.to_string(), content.to_string(), None, Vec::new());
+        memo.id = id.to_string();
+        memo
+    }
+
+    #[test]
+    fn test_parse_links_with_alias_and_code() {
+        let content = "See [[Project Plan]] and [[abc-123|the spec]].\n`[[not a link]]`\n```\n[[also not]]\n```\n[[ ]] [[Last]]";
+        let links = parse_links(content);
+        let targets: Vec<&str> = links.iter().map(|l| l.target.as_str()).collect();
+        assert_eq!(targets, vec!["Project Plan", "abc-123", "Last"]);
+        assert_eq!(links[1].alias.as_deref(), Some("the spec"));
+        assert_eq!(&content[links[0].start..links[0].end], "[[Project Plan]]");
+    }
+
+    #[test]
+    fn test_resolve_prefers_id_then_title() {
+        let memos = vec![memo("a", "Alpha", ""), memo("b", "a", "")];
+        assert_eq!(resolve(&memos, "a").unwrap().id, "a");
+        assert_eq!(resolve(&memos, " alpha ").unwrap().id, "a");
+        assert!(resolve(&memos, "Gamma").is_none());
+    }
+
+    #[test]
+    fn test_backlinks_by_title_and_id() {
+        let memos = vec![
+            memo("target", "Target", "self link [[Target]]"),
+            memo("by-title", "One", "intro\nlinks to [[target]] here\nend"),
+            memo("by-id", "Two", "[[target|alias]]"),
+            memo("other", "Three", "[[Missing]]"),
+        ];
+        let index = LinkIndex::build(&memos);
+        let backlinks = index.backlinks(&memos, "target");
+        let ids: Vec<&str> = backlinks.iter().map(|b| b.memo_id.as_str()).collect();
+        assert_eq!(ids, vec!["by-title", "by-id"]);
+        assert_eq!(backlinks[0].lines, vec!["links to [[target]] here"]);
+    }
+
+    #[test]
+    fn test_broken_links() {
+        let memos = vec![
+            memo("a", "A", "[[B]] [[Missing]] [[missing]]"),
+            memo("b", "B", "[[Gone]]"),
+        ];
+        let index = LinkIndex::build(&memos);
+        let broken = index.broken_links(&memos, None);
+        let targets: Vec<(&str, &str)> = broken
+            .iter()
+            .map(|b| (b.memo_id.as_str(), b.target.as_str()))
+            .collect();
+        assert_eq!(targets, vec![("a", "Missing"), ("b", "Gone")]);
+        assert_eq!(index.broken_links(&memos, Some("b")).len(), 1);
+    }
+
+    #[test]
+    fn test_index_updates_incrementally() {
+        let mut memos = vec![memo("a", "A", ""), memo("b", "B", "[[A]]")];
+        let mut index = LinkIndex::build(&memos);
+        assert_eq!(index.backlinks(&memos, "a").len(), 1);
+
+        memos[1].content = "no links".to_string();
+        index.update(&memos[1]);
+        assert!(index.backlinks(&memos, "a").is_empty());
+
+        memos[1].content = "[[A]]".to_string();
+        index.update(&memos[1]);
+        index.remove("b");
+        assert!(index.backlinks(&memos, "a").is_empty());
+    }
+
+    #[test]
+    fn test_replace_link_target_keeps_alias() {
+        let replaced = replace_link_target("[[old]] and [[Old|shown]] and [[Other]]", "Old", "New");
+        assert_eq!(
+            replaced.as_deref(),
+            Some("[[New]] and [[New|shown]] and [[Other]]")
+        );
+        assert_eq!(replace_link_target("[[Other]]", "Old", "New"), None);
+    }
+
+    #[test]
+    fn test_rename_references_rewrites_other_memos() {
+        let mut memos = vec![
+            memo("a", "Old", ""),
+            memo("b", "B", "see [[Old]]"),
+            memo("c", "C", "by id [[a]]"),
+        ];
+        let mut index = LinkIndex::build(&memos);
+        let owner = resolve(&memos, "Old").map(|m| m.id.clone());
+        memos[0].title = "New".to_string();
+
+        let changed = rename_references(&mut memos, &mut index, "a", owner.as_deref(), "Old");
+        assert_eq!(changed, vec!["b"]);
+        assert_eq!(memos[1].content, "see [[New]]");
+        assert_eq!(memos[2].content, "by id [[a]]");
+        assert_eq!(index.backlinks(&memos, "a").len(), 2);
+    }
+
+    #[test]
+    fn test_rename_skips_links_owned_by_another_memo() {
+        let mut memos = vec![
+            memo("first", "Same", ""),
+            memo("second", "Same", ""),
+            memo("b", "B", "[[Same]]"),
+        ];
+        let mut index = LinkIndex::build(&memos);
+        let owner = resolve(&memos, "Same").map(|m| m.id.clone());
+        memos[1].title = "Renamed".to_string();
+
+        let changed = rename_references(&mut memos, &mut index, "second", owner.as_deref(), "Same");
+        assert!(changed.is_empty());
+        assert_eq!(memos[2].content, "[[Same]]");
+    }
+}
+
+#[cfg(test)]
+mod entity_links_tests {
+    use crate::bulk::{bulk_update, undo, BulkTaskPatch};
+    use crate::entity_links::{
+        add_link, linked_entities, memo_from_note, remove_links_to, restore_links,
+        task_text_from_line, EntityKind, EntityLink, EntityRef, EntitySources,
+    };
+    use crate::events::TaskChanges;
+    use crate::memo::Memo;
+    use crate::reading_memo::{ReadingBook, ReadingNote};
+    use crate::subtask::demote;
+    use crate::task::{Subtask, Task};
+    use chrono::Utc;
+
+    fn task(id: i32) -> Task {
+        Task {
+            id,
+            description: format!("Task {}", id),
+            ..Task::default()
+        }
+    }
+
+    fn fixture() -> (Vec<Task>, Vec<Memo>, Vec<ReadingBook>) {
+        let mut memo = Memo::new("Meeting notes".to_string(), String::new(), None, Vec::new());
+        memo.id = "memo".to_string();
+        let mut book = ReadingBook::new("Deep Work".to_string());
+        book.id = "book".to_string();
+        let mut note = ReadingNote::new(Some(42), None, "focus".to_string());
+        note.id = "note".to_string();
+        book.notes.push(note);
+        (vec![task(1)], vec![memo], vec![book])
+    }
+
+    fn memo_ref() -> EntityRef {
+        EntityRef::new(EntityKind::Memo, "memo")
+    }
+
+    #[test]
+    fn test_add_link_validates_and_deduplicates() {
+        let (tasks, memos, books) = fixture();
+        let sources = EntitySources {
+            tasks: &tasks,
+            memos: &memos,
+            books: &books,
+            events: &[],
+        };
+        let mut links = Vec::new();
+
+        let (link, added) =
+            add_link(&mut links, &sources, EntityRef::task(1), memo_ref(), None).unwrap();
+        assert!(added);
+        // The same pair in the other direction is the same link
+        let (again, added) =
+            add_link(&mut links, &sources, memo_ref(), EntityRef::task(1), None).unwrap();
+        assert!(!added);
+        assert_eq!(again.id, link.id);
+        assert_eq!(links.len(), 1);
+
+        assert!(add_link(&mut links, &sources, memo_ref(), memo_ref(), None).is_err());
+        let missing = EntityRef::task(99);
+        assert!(add_link(&mut links, &sources, missing, memo_ref(), None).is_err());
+    }
+
+    #[test]
+    fn test_linked_entities_from_either_end() {
+        let (tasks, memos, books) = fixture();
+        let sources = EntitySources {
+            tasks: &tasks,
+            memos: &memos,
+            books: &books,
+            events: &[],
+        };
+        let note = EntityRef::new(EntityKind::ReadingNote, "note");
+        let links = vec![
+            EntityLink::new(EntityRef::task(1), memo_ref(), Some("agenda".to_string())),
+            EntityLink::new(memo_ref(), note.clone(), None),
+        ];
+
+        let from_memo = linked_entities(&links, &sources, &memo_ref());
+        let titles: Vec<&str> = from_memo.iter().map(|l| l.title.as_str()).collect();
+        assert_eq!(titles, vec!["Task 1", "Deep Work (p. 42)"]);
+        assert_eq!(from_memo[0].label.as_deref(), Some("agenda"));
+
+        let from_note = linked_entities(&links, &sources, &note);
+        assert_eq!(from_note.len(), 1);
+        assert_eq!(from_note[0].entity, memo_ref());
+    }
+
+    #[test]
+    fn test_remove_links_to_deleted_entities() {
+        let mut links = vec![
+            EntityLink::new(EntityRef::task(1), memo_ref(), None),
+            EntityLink::new(EntityRef::task(2), memo_ref(), None),
+        ];
+        let kept = links[1].id.clone();
+        let removed = remove_links_to(&mut links, &[EntityRef::task(1)]);
+        assert_eq!(removed.len(), 1);
+        assert_eq!(links.len(), 1);
+        assert_eq!(links[0].id, kept);
+    }
+
+    #[test]
+    fn test_bulk_delete_removes_task_links() {
+        let mut tasks = vec![task(1), task(2), task(3)];
+        let delete: BulkTaskPatch = serde_json::from_str(r#"{"delete":true}"#).unwrap();
+        let entry = bulk_update(&mut tasks, &[], &[3, 1], &delete, Utc::now()).unwrap();
+        let mut links = vec![
+            EntityLink::new(EntityRef::task(1), memo_ref(), None),
+            EntityLink::new(EntityRef::task(2), memo_ref(), None),
+            EntityLink::new(memo_ref(), EntityRef::task(3), None),
+        ];
+
+        let changes = TaskChanges::from_ids(&tasks, &[], &entry.updated_ids(), &entry.deleted);
+        assert_eq!(
+            changes.deleted_refs(),
+            vec![EntityRef::task(1), EntityRef::task(3)]
+        );
+        assert_eq!(
+            remove_links_to(&mut links, &changes.deleted_refs()).len(),
+            2
+        );
+        assert_eq!(links.len(), 1);
+        assert_eq!(links[0].source, EntityRef::task(2));
+    }
+
+    #[test]
+    fn test_undo_bulk_delete_restores_task_links() {
+        let (mut tasks, memos, books) = fixture();
+        tasks.extend([task(2), task(3)]);
+        let delete: BulkTaskPatch = serde_json::from_str(r#"{"delete":true}"#).unwrap();
+        let mut entry = bulk_update(&mut tasks, &[], &[1, 3], &delete, Utc::now()).unwrap();
+        let mut links = vec![
+            EntityLink::new(EntityRef::task(1), memo_ref(), None),
+            EntityLink::new(EntityRef::task(2), memo_ref(), None),
+            EntityLink::new(EntityRef::task(3), EntityRef::task(1), None),
+        ];
+        let original = links.clone();
+        let changes = TaskChanges::from_ids(&tasks, &[], &entry.updated_ids(), &entry.deleted);
+        entry.links = remove_links_to(&mut links, &changes.deleted_refs());
+        assert_eq!(links.len(), 1);
+
+        let removed = std::mem::take(&mut entry.links);
+        undo(&mut tasks, &[], entry).unwrap();
+        let sources = EntitySources {
+            tasks: &tasks,
+            memos: &memos,
+            books: &books,
+            events: &[],
+        };
+        let restored = restore_links(&mut links, &sources, removed.clone());
+        assert_eq!(restored.len(), 2);
+        let mut ids: Vec<&str> = links.iter().map(|l| l.id.as_str()).collect();
+        let mut expected: Vec<&str> = original.iter().map(|l| l.id.as_str()).collect();
+        ids.sort();
+        expected.sort();
+        assert_eq!(ids, expected);
+
+        // Links already present or to entities deleted since are not restored
+        let no_memos = EntitySources {
+            memos: &[],
+            ..sources
+        };
+        let mut links = vec![original[2].clone()];
+        assert!(restore_links(&mut links, &no_memos, removed).is_empty());
+        assert_eq!(links.len(), 1);
+    }
+
+    #[test]
+    fn test_demote_removes_task_links() {
+        let mut parent = task(1);
+        parent.subtasks = vec![Subtask::new(5, "Subtask".to_string())];
+        let mut tasks = vec![parent, task(2)];
+        let updated = demote(&mut tasks, 2, 1, None, false).unwrap();
+        let mut links = vec![
+            EntityLink::new(EntityRef::task(1), memo_ref(), None),
+            EntityLink::new(EntityRef::task(2), memo_ref(), None),
+        ];
+
+        let changes = TaskChanges::from_ids(&tasks, &[], &updated, &[2]);
+        assert_eq!(changes.deleted_refs(), vec![EntityRef::task(2)]);
+        assert_eq!(
+            remove_links_to(&mut links, &changes.deleted_refs()).len(),
+            1
+        );
+        assert_eq!(links.len(), 1);
+        assert_eq!(links[0].source, EntityRef::task(1));
+    }
+
+    #[test]
+    fn test_task_text_from_line() {
+        assert_eq!(
+            task_text_from_line("- [ ] Send the report"),
+            "Send the report"
+        );
+        assert_eq!(task_text_from_line("  * [x] Done thing"), "Done thing");
+        assert_eq!(task_text_from_line("2) Call the bank"), "Call the bank");
+        assert_eq!(task_text_from_line("## Plan trip"), "Plan trip");
+        assert_eq!(
+            task_text_from_line("2024 budget review"),
+            "2024 budget review"
+        );
+        assert_eq!(task_text_from_line("   "), "");
+    }
+
+    #[test]
+    fn test_memo_from_note() {
+        let mut book = ReadingBook::new("Deep Work".to_string());
+        book.author = Some("Cal Newport".to_string());
+        let note = ReadingNote::new(
+            Some(42),
+            Some("line one\nline two".to_string()),
+            "Worth trying".to_string(),
+        );
+        let (title, content) = memo_from_note(&book, &note);
+        assert_eq!(title, "Deep Work p.42");
+        assert_eq!(
+            content,
+            "> line one\n> line two\n\nWorth trying\n\nSource: Deep Work / Cal Newport p.42"
+        );
+    }
+
+    #[test]
+    fn test_entity_ref_serialization() {
+        let json = serde_json::to_value(EntityRef::new(EntityKind::CalendarEvent, "e1")).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!({ "kind": "calendar_event", "id": "e1" })
+        );
+    }
+}
+
+#[cfg(test)]
+mod folder_tree_tests {
+    use crate::folder_tree::{
+        build_tree, delete_folder, descendants, move_folder, validate_parent, DeleteFolderOptions,
+    };
+    use crate::memo::{Folder, Memo};
+    use chrono::{DateTime, TimeZone, Utc};
+
+    fn now() -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2030, 1, 2, 3, 4, 5).unwrap()
+    }
+
+    fn folder(id: &str, parent: Option<&str>) -> Folder {
+        Folder {
+            id: id.to_string(),
+            name: id.to_uppercase(),
+            parent_id: parent.map(str::to_string),
+        }
+    }
+
+    fn memo(id: &str, folder_id: Option<&str>) -> Memo {
+        let mut memo = Memo::new(
+            id.to_string(),
+            String::new(),
+            folder_id.map(str::to_string),
+            Vec::new(),
+        );
+        memo.id = id.to_string();
+        memo
+    }
+
+    /// a ─ b ─ c, d
+    fn folders() -> Vec<Folder> {
+        vec![
+            folder("a", None),
+            folder("b", Some("a")),
+            folder("c", Some("b")),
+            folder("d", None),
+        ]
+    }
+
+    fn parent_of<'a>(folders: &'a [Folder], id: &str) -> Option<&'a str> {
+        folders
+            .iter()
+            .find(|f| f.id == id)
+            .unwrap()
+            .parent_id
+            .as_deref()
+    }
+
+    #[test]
+    fn test_validate_parent() {
+        let folders = folders();
+        assert!(validate_parent(&folders, None).is_ok());
+        assert!(validate_parent(&folders, Some("b")).is_ok());
+        assert!(validate_parent(&folders, Some("missing")).is_err());
+    }
+
+    #[test]
+    fn test_descendants() {
+        assert_eq!(descendants(&folders(), "a"), vec!["b", "c"]);
+        assert!(descendants(&folders(), "d").is_empty());
+    }
+
+    #[test]
+    fn test_move_folder_rejects_cycles() {
+        let mut folders = folders();
+        assert!(move_folder(&mut folders, "a", Some("c".to_string())).is_err());
+        assert!(move_folder(&mut folders, "a", Some("a".to_string())).is_err());
+        assert!(move_folder(&mut folders, "a", Some("missing".to_string())).is_err());
+        assert!(move_folder(&mut folders, "missing", None).is_err());
+
+        move_folder(&mut folders, "b", Some("d".to_string())).unwrap();
+        assert_eq!(parent_of(&folders, "b"), Some("d"));
+        move_folder(&mut folders, "b", None).unwrap();
+        assert_eq!(parent_of(&folders, "b"), None);
+    }
+
+    #[test]
+    fn test_delete_reparents_children_by_default() {
+        let mut folders = folders();
+        let mut memos = vec![memo("m1", Some("b")), memo("m2", Some("c"))];
+        let before = memos[1].updated_at;
+        let moved = delete_folder(
+            &mut folders,
+            &mut memos,
+            "b",
+            &DeleteFolderOptions::default(),
+            now(),
+        )
+        .unwrap();
+
+        assert_eq!(moved, vec!["m1"]);
+        assert_eq!(parent_of(&folders, "c"), Some("a"));
+        assert_eq!(memos[0].folder_id, None);
+        assert_eq!(memos[0].updated_at, now());
+        assert_eq!(memos[1].folder_id.as_deref(), Some("c"));
+        assert_eq!(memos[1].updated_at, before);
+    }
+
+    #[test]
+    fn test_delete_recursive_moves_memos_to_parent() {
+        let mut folders = folders();
+        let mut memos = vec![
+            memo("m1", Some("b")),
+            memo("m2", Some("c")),
+            memo("m3", Some("d")),
+        ];
+        let options = DeleteFolderOptions {
+            recursive: true,
+            move_memos_to_parent: true,
+        };
+        let moved = delete_folder(&mut folders, &mut memos, "b", &options, now()).unwrap();
+
+        assert_eq!(moved, vec!["m1", "m2"]);
+        let ids: Vec<&str> = folders.iter().map(|f| f.id.as_str()).collect();
+        assert_eq!(ids, vec!["a", "d"]);
+        assert_eq!(memos[0].folder_id.as_deref(), Some("a"));
+        assert_eq!(memos[1].folder_id.as_deref(), Some("a"));
+        assert_eq!(memos[2].folder_id.as_deref(), Some("d"));
+        assert!(memos[..2].iter().all(|m| m.updated_at == now()));
+        assert_ne!(memos[2].updated_at, now());
+        assert!(delete_folder(&mut folders, &mut memos, "b", &options, now()).is_err());
+    }
+
+    #[test]
+    fn test_build_tree_with_counts() {
+        let memos = vec![
+            memo("m1", Some("a")),
+            memo("m2", Some("c")),
+            memo("m3", Some("c")),
+            memo("m4", None),
+        ];
+        let tree = build_tree(&folders(), &memos);
+
+        assert_eq!(tree.unfiled_memo_count, 1);
+        assert_eq!(tree.roots.len(), 2);
+        let a = &tree.roots[0];
+        assert_eq!((a.memo_count, a.total_memo_count), (1, 3));
+        let c = &a.children[0].children[0];
+        assert_eq!(c.id, "c");
+        assert_eq!(c.memo_count, 2);
+    }
+
+    #[test]
+    fn test_build_tree_survives_existing_cycles_and_orphans() {
+        let folders = vec![
+            folder("x", Some("y")),
+            folder("y", Some("x")),
+            folder("orphan", Some("gone")),
+        ];
+        let tree = build_tree(&folders, &[]);
+        let roots: Vec<&str> = tree.roots.iter().map(|n| n.id.as_str()).collect();
+        assert_eq!(roots, vec!["orphan", "x"]);
+        assert_eq!(tree.roots[1].children[0].id, "y");
+        assert!(tree.roots[1].children[0].children.is_empty());
+    }
+}
+
+#[cfg(test)]
+mod memo_template_tests {
+    use crate::memo::{Folder, Memo};
+    use crate::memo_template::{
+        daily_note_title, find_daily_note, instantiate, new_daily_note, render,
+        validate_daily_note_settings, DailyNoteSettings, MemoTemplate, MemoTemplateStore,
+        TemplateContext,
+    };
+    use chrono::{NaiveDate, NaiveTime};
+
+    fn date() -> NaiveDate {
+        NaiveDate::from_ymd_opt(2024, 3, 5).unwrap()
+    }
+
+    fn time() -> NaiveTime {
+        NaiveTime::from_hms_opt(9, 7, 0).unwrap()
+    }
+
+    fn context() -> TemplateContext {
+        TemplateContext {
+            date: date(),
+            time: time(),
+            title: "Standup".to_string(),
+        }
+    }
+
+    fn standup() -> MemoTemplate {
+        MemoTemplate::new(
+            "Standup".to_string(),
+            "Standup {{date}}".to_string(),
+            "# {{title}}\n{{date:%m/%d}} {{time}}\n- Yesterday\n- Today\n".to_string(),
+            Some("team".to_string()),
+            vec!["standup".to_string()],
+        )
+    }
+
+    #[test]
+    fn test_render_placeholders() {
+        let rendered = render(
+            "{{date}} {{time}} {{datetime}} {{ title }} {{date:%Y年%m月%d日}}",
+            &context(),
+        );
+        assert_eq!(
+            rendered,
+            "2024-03-05 09:07 2024-03-05 09:07 Standup 2024年03月05日"
+        );
+    }
+
+    #[test]
+    fn test_render_keeps_unknown_and_invalid_placeholders() {
+        let text = "{{author}} {{date:%Q}} {{title:%Y}} {{date";
+        assert_eq!(render(text, &context()), text);
+    }
+
+    #[test]
+    fn test_render_keeps_placeholders_needing_a_time_zone() {
+        let text = "{{date:%Z}} {{time:%z}} {{datetime:%:z}}";
+        assert_eq!(render(text, &context()), text);
+    }
+
+    #[test]
+    fn test_instantiate_uses_template_title_and_folder() {
+        let memo = instantiate(&standup(), None, None, date(), time());
+        assert_eq!(memo.title, "Standup 2024-03-05");
+        assert_eq!(
+            memo.content,
+            "# Standup 2024-03-05\n03/05 09:07\n- Yesterday\n- Today\n"
+        );
+        assert_eq!(memo.folder_id.as_deref(), Some("team"));
+        assert_eq!(memo.tags, vec!["standup"]);
+    }
+
+    #[test]
+    fn test_instantiate_with_explicit_title_and_folder() {
+        let memo = instantiate(
+            &standup(),
+            Some("Retro".to_string()),
+            Some("other".to_string()),
+            date(),
+            time(),
+        );
+        assert_eq!(memo.title, "Retro");
+        assert!(memo.content.starts_with("# Retro\n"));
+        assert_eq!(memo.folder_id.as_deref(), Some("other"));
+    }
+
+    #[test]
+    fn test_daily_note_default_content() {
+        let store = MemoTemplateStore::default();
+        let memo = new_daily_note(&store, date(), time()).unwrap();
+        assert_eq!(memo.title, "2024-03-05");
+        assert_eq!(memo.content, "# 2024-03-05\n\n");
+        assert_eq!(memo.folder_id, None);
+    }
+
+    #[test]
+    fn test_daily_note_with_template_and_folder() {
+        let template = standup();
+        let store = MemoTemplateStore {
+            daily_note: DailyNoteSettings {
+                folder_id: Some("journal".to_string()),
+                template_id: Some(template.id.clone()),
+                title_format: "%Y/%m/%d".to_string(),
+            },
+            templates: vec![template],
+        };
+        let memo = new_daily_note(&store, date(), time()).unwrap();
+        assert_eq!(memo.title, "2024/03/05");
+        assert!(memo.content.starts_with("# 2024/03/05\n03/05"));
+        assert_eq!(memo.folder_id.as_deref(), Some("journal"));
+        assert_eq!(memo.tags, vec!["standup"]);
+    }
+
+    #[test]
+    fn test_find_daily_note_matches_folder_and_title() {
+        let settings = DailyNoteSettings {
+            folder_id: Some("journal".to_string()),
+            ..DailyNoteSettings::default()
+        };
+        let elsewhere = Memo::new("2024-03-05".to_string(), String::new(), None, Vec::new());
+        let daily = Memo::new(
+            "2024-03-05".to_string(),
+            String::new(),
+            Some("journal".to_string()),
+            Vec::new(),
+        );
+        let memos = vec![elsewhere, daily.clone()];
+
+        let found = find_daily_note(&memos, &settings, date()).unwrap();
+        assert_eq!(found.id, daily.id);
+        let next_day = date().succ_opt().unwrap();
+        assert!(find_daily_note(&memos, &settings, next_day).is_none());
+    }
+
+    #[test]
+    fn test_validate_daily_note_settings() {
+        let folders = vec![Folder::new("Journal".to_string(), None)];
+        let templates = vec![standup()];
+        let valid = DailyNoteSettings {
+            folder_id: Some(folders[0].id.clone()),
+            template_id: Some(templates[0].id.clone()),
+            title_format: "%Y-%m-%d (%a)".to_string(),
+        };
+        assert!(validate_daily_note_settings(&valid, &folders, &templates).is_ok());
+
+        let missing_folder = DailyNoteSettings {
+            folder_id: Some("missing".to_string()),
+            ..valid.clone()
+        };
+        assert!(validate_daily_note_settings(&missing_folder, &folders, &templates).is_err());
+
+        let missing_template = DailyNoteSettings {
+            template_id: Some("missing".to_string()),
+            ..valid.clone()
+        };
+        assert!(validate_daily_note_settings(&missing_template, &folders, &templates).is_err());
+
+        let bad_format = DailyNoteSettings {
+            title_format: "%Q".to_string(),
+            ..valid
+        };
+        assert!(validate_daily_note_settings(&bad_format, &folders, &templates).is_err());
+
+        let time_format = DailyNoteSettings {
+            title_format: "%Y-%m-%d %H:%M".to_string(),
+            ..bad_format
+        };
+        assert!(validate_daily_note_settings(&time_format, &folders, &templates).is_err());
+    }
+
+    #[test]
+    fn test_daily_note_title_falls_back_when_format_needs_a_time() {
+        // Settings saved before validation tightened may still hold such a format
+        let settings = DailyNoteSettings {
+            title_format: "%Y-%m-%d %H:%M".to_string(),
+            ..DailyNoteSettings::default()
+        };
+        assert_eq!(daily_note_title(&settings, date()), "2024-03-05");
+    }
+
+    #[test]
+    fn test_store_defaults_when_fields_are_missing() {
+        let store: MemoTemplateStore = serde_json::from_str("{}").unwrap();
+        assert!(store.templates.is_empty());
+        assert_eq!(store.daily_note.title_format, "%Y-%m-%d");
+    }
+}
+
+#[cfg(test)]
+mod memo_vault_tests {
+    use crate::memo::{Folder, Memo};
+    use crate::memo_vault::{
+        export_vault, folder_paths, import_vault, parse_markdown, render_markdown,
+        sanitize_file_name,
+    };
+    use chrono::{TimeZone, Utc};
+    use std::fs;
+    use std::path::PathBuf;
+    use tempfile::tempdir;
+
+    fn folder(id: &str, name: &str, parent: Option<&str>) -> Folder {
+        Folder {
+            id: id.to_string(),
+            name: name.to_string(),
+            parent_id: parent.map(str::to_string),
+        }
+    }
+
+    fn memo(id: &str, title: &str, content: &str, folder_id: Option<&str>) -> Memo {
+        let mut memo = Memo::new(
+            title.to_string(),
+            content.to_string(),
+            folder_id.map(str::to_string),
+            vec!["work".to_string(), "2024".to_string()],
+        );
+        memo.id = id.to_string();
+        memo.created_at = Utc.with_ymd_and_hms(2024, 1, 2, 3, 4, 5).unwrap();
+        memo.updated_at = Utc.with_ymd_and_hms(2024, 2, 3, 4, 5, 6).unwrap();
+        memo
+    }
+
+    #[test]
+    fn test_sanitize_file_name() {
+        assert_eq!(sanitize_file_name("a/b: c?"), "a_b_ c_");
+        assert_eq!(sanitize_file_name("  .hidden. "), "_hidden");
+        assert_eq!(sanitize_file_name("  "), "Untitled");
+        assert_eq!(sanitize_file_name("日報"), "日報");
+    }
+
+    #[test]
+    fn test_folder_paths_nest_and_dedupe_siblings() {
+        let folders = vec![
+            folder("a", "Work", None),
+            folder("b", "Notes", Some("a")),
+            folder("c", "notes", Some("a")),
+            folder("d", "Orphan", Some("missing")),
+        ];
+        let paths = folder_paths(&folders);
+        assert_eq!(paths["b"], PathBuf::from("Work").join("Notes"));
+        assert_eq!(paths["c"], PathBuf::from("Work").join("notes (2)"));
+        assert_eq!(paths["d"], PathBuf::from("Orphan"));
+    }
+
+    #[test]
+    fn test_render_and_parse_round_trip() {
+        let memo = memo("m1", "Plan: Q1", "---\nbody\n", None);
+        let text = render_markdown(&memo, "Plan_ Q1");
+        assert!(
+            text.starts_with("---\nid: m1\ntitle: \"Plan: Q1\"\ntags:\n  - work\n  - \"2024\"\n")
+        );
+
+        let (front_matter, body) = parse_markdown(&text);
+        assert_eq!(front_matter.id.as_deref(), Some("m1"));
+        assert_eq!(front_matter.title.as_deref(), Some("Plan: Q1"));
+        assert_eq!(front_matter.tags, vec!["work", "2024"]);
+        assert_eq!(front_matter.created_at, Some(memo.created_at));
+        assert_eq!(front_matter.updated_at, Some(memo.updated_at));
+        assert_eq!(body, "---\nbody\n");
+    }
+
+    #[test]
+    fn test_parse_obsidian_front_matter() {
+        let text = "---\naliases: [x]\ntags: [\"#project\", 'a, b']\ncreated_at: 2024-05-01\n---\n# Heading\n";
+        let (front_matter, body) = parse_markdown(text);
+        assert_eq!(front_matter.id, None);
+        assert_eq!(front_matter.tags, vec!["project", "a, b"]);
+        assert_eq!(
+            front_matter.created_at,
+            Some(Utc.with_ymd_and_hms(2024, 5, 1, 0, 0, 0).unwrap())
+        );
+        assert_eq!(body, "# Heading\n");
+
+        let (front_matter, _) = parse_markdown("---\ntags: one, two three # comment\n---\n");
+        assert_eq!(front_matter.tags, vec!["one", "two", "three"]);
+    }
+
+    #[test]
+    fn test_parse_without_front_matter() {
+        let (front_matter, body) = parse_markdown("plain\n---\n");
+        assert_eq!(front_matter, Default::default());
+        assert_eq!(body, "plain\n---\n");
+
+        // An unterminated header is treated as content
+        let (front_matter, body) = parse_markdown("---\nid: x\n");
+        assert_eq!(front_matter.id, None);
+        assert_eq!(body, "---\nid: x\n");
+    }
+
+    #[test]
+    fn test_export_writes_folder_tree() {
+        let dir = tempdir().unwrap();
+        let folders = vec![folder("a", "Work", None), folder("b", "Daily", Some("a"))];
+        let memos = vec![
+            memo("m1", "Standup", "today", Some("b")),
+            memo("m2", "Standup", "again", Some("b")),
+            memo("m3", "Inbox", "loose", None),
+        ];
+
+        let report = export_vault(&memos, &folders, dir.path()).unwrap();
+        assert_eq!(report.memo_count, 3);
+        assert_eq!(report.folder_count, 2);
+
+        let daily = dir.path().join("Work").join("Daily");
+        assert!(fs::read_to_string(daily.join("Standup.md"))
+            .unwrap()
+            .ends_with("---\n\ntoday"));
+        let second = fs::read_to_string(daily.join("Standup (2).md")).unwrap();
+        assert!(second.contains("title: Standup\n"));
+        assert!(dir.path().join("Inbox.md").exists());
+    }
+
+    #[test]
+    fn test_reimport_updates_existing_memos() {
+        let dir = tempdir().unwrap();
+        let mut folders = vec![folder("a", "Work", None)];
+        let mut memos = vec![memo("m1", "Standup", "today", Some("a"))];
+        export_vault(&memos, &folders, dir.path()).unwrap();
+
+        // Unchanged files are not reported
+        let report = import_vault(&mut memos, &mut folders, dir.path()).unwrap();
+        assert!(report.created.is_empty());
+        assert!(report.updated.is_empty());
+        assert!(report.created_folders.is_empty());
+
+        let file = dir.path().join("Work").join("Standup.md");
+        let text = fs::read_to_string(&file).unwrap();
+        fs::write(&file, text.replace("today", "edited")).unwrap();
+        let report = import_vault(&mut memos, &mut folders, dir.path()).unwrap();
+        assert_eq!(report.updated, vec!["m1"]);
+        assert_eq!(memos.len(), 1);
+        assert_eq!(memos[0].content, "edited");
+        assert_eq!(memos[0].folder_id.as_deref(), Some("a"));
+        assert!(memos[0].updated_at > Utc.with_ymd_and_hms(2024, 2, 3, 4, 5, 6).unwrap());
+    }
+
+    #[test]
+    fn test_reimport_matches_sanitized_and_deduplicated_folders() {
+        let dir = tempdir().unwrap();
+        let mut folders = vec![
+            folder("f1", "Plans / Q1: why?", None),
+            folder("f2", "*draft*", Some("f1")),
+            folder("f3", ".hidden", None),
+            folder("f4", "Notes", None),
+            folder("f5", "Notes", None),
+            folder("f6", "Daily", Some("f5")),
+        ];
+        let mut memos = vec![
+            memo("m1", "Budget", "plan", Some("f1")),
+            memo("m2", "Sketch", "draft", Some("f2")),
+            memo("m3", "Secret", "hidden", Some("f3")),
+            memo("m4", "First", "notes", Some("f4")),
+            memo("m5", "Second", "more notes", Some("f5")),
+            memo("m6", "Today", "daily", Some("f6")),
+        ];
+        export_vault(&memos, &folders, dir.path()).unwrap();
+        assert!(dir.path().join("Notes (2)").join("Daily").is_dir());
+        assert!(dir.path().join("_hidden").join("Secret.md").exists());
+
+        let snapshot = |folders: &[Folder]| -> Vec<(String, String, Option<String>)> {
+            folders
+                .iter()
+                .map(|f| (f.id.clone(), f.name.clone(), f.parent_id.clone()))
+                .collect()
+        };
+        let (before_folders, before_memos) = (snapshot(&folders), memos.clone());
+        let report = import_vault(&mut memos, &mut folders, dir.path()).unwrap();
+        assert!(report.created_folders.is_empty(), "{:?}", report);
+        assert!(report.created.is_empty(), "{:?}", report);
+        assert!(report.updated.is_empty(), "{:?}", report);
+        assert_eq!(snapshot(&folders), before_folders);
+        assert_eq!(memos, before_memos);
+    }
+
+    #[test]
+    fn test_import_vault_creates_folders_and_memos() {
+        let dir = tempdir().unwrap();
+        fs::create_dir_all(dir.path().join("Projects").join("Alpha")).unwrap();
+        fs::create_dir_all(dir.path().join(".obsidian")).unwrap();
+        fs::write(dir.path().join(".obsidian").join("note.md"), "skip").unwrap();
+        fs::write(
+            dir.path().join("Projects").join("Alpha").join("Kickoff.md"),
+            "# Kickoff",
+        )
+        .unwrap();
+        fs::write(dir.path().join("Projects").join("image.png"), [0u8, 1]).unwrap();
+        let copy = "---\nid: m1\n---\ncopy";
+        fs::write(dir.path().join("a.md"), copy).unwrap();
+        fs::write(dir.path().join("b.md"), copy).unwrap();
+
+        let mut folders = vec![folder("p", "Projects", None)];
+        let mut memos = Vec::new();
+        let report = import_vault(&mut memos, &mut folders, dir.path()).unwrap();
+
+        // The existing "Projects" folder is reused
+        assert_eq!(report.created_folders.len(), 1);
+        let alpha = folders.iter().find(|f| f.name == "Alpha").unwrap();
+        assert_eq!(alpha.parent_id.as_deref(), Some("p"));
+
+        assert_eq!(report.created.len(), 3);
+        let kickoff = memos.iter().find(|m| m.title == "Kickoff").unwrap();
+        assert_eq!(kickoff.folder_id.as_deref(), Some(alpha.id.as_str()));
+        assert_eq!(kickoff.content, "# Kickoff");
+
+        // A duplicated id only keeps the first file's id
+        let copies: Vec<&Memo> = memos.iter().filter(|m| m.content == "copy").collect();
+        assert_eq!(copies.len(), 2);
+        assert_eq!(copies[0].id, "m1");
+        assert_ne!(copies[1].id, "m1");
+    }
+
+    #[test]
+    fn test_import_requires_directory() {
+        let dir = tempdir().unwrap();
+        let missing = dir.path().join("missing");
+        assert!(import_vault(&mut Vec::new(), &mut Vec::new(), &missing).is_err());
+    }
+}
+
+#[cfg(test)]
+mod hash_tests {
+    use crate::hash::content_hash;
+
+    #[test]
+    fn test_content_hash() {
+        assert_eq!(
+            content_hash(b"abc"),
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+    }
+}
+
+#[cfg(test)]
+mod memo_sync_tests {
+    use crate::memo::{Folder, Memo};
+    use crate::memo_sync::{
+        apply_file, reconcile, remove_memo_file, resolve_conflict, write_memo, MemoSyncStore,
+        SyncChanges, SyncSide,
+    };
+    use chrono::{Duration, Utc};
+    use std::fs;
+    use std::path::Path;
+    use std::time::SystemTime;
+    use tempfile::tempdir;
+
+    struct Fixture {
+        store: MemoSyncStore,
+        memos: Vec<Memo>,
+        folders: Vec<Folder>,
+    }
+
+    impl Fixture {
+        fn new(memos: Vec<Memo>, folders: Vec<Folder>) -> Self {
+            Fixture {
+                store: MemoSyncStore::default(),
+                memos,
+                folders,
+            }
+        }
+
+        fn reconcile(&mut self, root: &Path) -> SyncChanges {
+            let mut changes = SyncChanges::default();
+            reconcile(
+                &mut self.store,
+                root,
+                &mut self.memos,
+                &mut self.folders,
+                &mut changes,
+            )
+            .unwrap();
+            changes
+        }
+
+        fn apply(&mut self, root: &Path, relative: &str) -> SyncChanges {
+            let mut changes = SyncChanges::default();
+            apply_file(
+                &mut self.store,
+                root,
+                relative,
+                &mut self.memos,
+                &mut self.folders,
+                &mut changes,
+            )
+            .unwrap();
+            changes
+        }
+
+        /// Simulates an edit made in the app
+        fn edit(&mut self, id: &str, content: &str) -> Memo {
+            let memo = self.memos.iter_mut().find(|m| m.id == id).unwrap();
+            memo.content = content.to_string();
+            memo.updated_at += Duration::seconds(1);
+            memo.clone()
+        }
+
+        fn memo(&self, id: &str) -> &Memo {
+            self.memos.iter().find(|m| m.id == id).unwrap()
+        }
+    }
+
+    fn memo(id: &str, title: &str, content: &str, folder_id: Option<&str>) -> Memo {
+        let mut memo = Memo::new(
+            title.to_string(),
+            content.to_string(),
+            folder_id.map(str::to_string),
+            Vec::new(),
+        );
+        memo.id = id.to_string();
+        memo.updated_at = Utc::now() - Duration::days(1);
+        memo
+    }
+
+    fn folder(id: &str, name: &str) -> Folder {
+        Folder {
+            id: id.to_string(),
+            name: name.to_string(),
+            parent_id: None,
+        }
+    }
+
+    fn replace_in(path: &Path, from: &str, to: &str) {
+        let text = fs::read_to_string(path).unwrap();
+        fs::write(path, text.replace(from, to)).unwrap();
+    }
+
+    #[test]
+    fn test_initial_reconcile_merges_both_sides() {
+        let dir = tempdir().unwrap();
+        fs::create_dir_all(dir.path().join("Work")).unwrap();
+        fs::write(dir.path().join("Work").join("External.md"), "from vim").unwrap();
+        let mut fixture = Fixture::new(
+            vec![memo("m1", "Inbox", "from app", None)],
+            vec![folder("w", "Work")],
+        );
+
+        let changes = fixture.reconcile(dir.path());
+        assert_eq!(changes.created.len(), 1);
+        let external = fixture.memo(&changes.created[0]);
+        assert_eq!(external.content, "from vim");
+        assert_eq!(external.folder_id.as_deref(), Some("w"));
+        assert!(fs::read_to_string(dir.path().join("Inbox.md"))
+            .unwrap()
+            .ends_with("from app"));
+
+        // Nothing changes on a second pass
+        assert_eq!(fixture.reconcile(dir.path()), SyncChanges::default());
+        // The externally created file is not rewritten
+        assert_eq!(
+            fs::read_to_string(dir.path().join("Work").join("External.md")).unwrap(),
+            "from vim"
+        );
+    }
+
+    #[test]
+    fn test_external_edit_updates_memo() {
+        let dir = tempdir().unwrap();
+        let mut fixture = Fixture::new(vec![memo("m1", "Note", "old", None)], Vec::new());
+        fixture.reconcile(dir.path());
+        let before = fixture.memo("m1").updated_at;
+
+        // The watcher echo of our own write is ignored
+        assert_eq!(fixture.apply(dir.path(), "Note.md"), SyncChanges::default());
+
+        replace_in(&dir.path().join("Note.md"), "old", "new");
+        let changes = fixture.apply(dir.path(), "Note.md");
+        assert_eq!(changes.updated, vec!["m1"]);
+        assert_eq!(fixture.memo("m1").content, "new");
+        assert!(fixture.memo("m1").updated_at > before);
+
+        // The update made from the file is not written back
+        let memo = fixture.memo("m1").clone();
+        let text = fs::read_to_string(dir.path().join("Note.md")).unwrap();
+        assert!(write_memo(&mut fixture.store, dir.path(), &memo, &[]).unwrap());
+        assert_eq!(
+            fs::read_to_string(dir.path().join("Note.md")).unwrap(),
+            text
+        );
+    }
+
+    #[test]
+    fn test_app_edit_writes_file_and_follows_renames() {
+        let dir = tempdir().unwrap();
+        let mut fixture = Fixture::new(
+            vec![memo("m1", "Note", "old", None)],
+            vec![folder("w", "Work")],
+        );
+        fixture.reconcile(dir.path());
+
+        let mut edited = fixture.edit("m1", "edited in app");
+        edited.title = "Renamed".to_string();
+        edited.folder_id = Some("w".to_string());
+        assert!(write_memo(&mut fixture.store, dir.path(), &edited, &fixture.folders).unwrap());
+
+        let path = dir.path().join("Work").join("Renamed.md");
+        assert!(fs::read_to_string(&path)
+            .unwrap()
+            .ends_with("edited in app"));
+        assert!(!dir.path().join("Note.md").exists());
+        assert_eq!(fixture.store.entries["m1"].path, "Work/Renamed.md");
+    }
+
+    #[test]
+    fn test_folder_rename_moves_directory() {
+        let dir = tempdir().unwrap();
+        let mut fixture = Fixture::new(
+            vec![memo("m1", "Note", "body", Some("w"))],
+            vec![folder("w", "Work")],
+        );
+        fixture.reconcile(dir.path());
+        assert!(dir.path().join("Work").join("Note.md").exists());
+
+        fixture.folders[0].name = "Projects".to_string();
+        let changes = fixture.reconcile(dir.path());
+        assert!(!changes.folders_changed);
+        assert!(dir.path().join("Projects").join("Note.md").exists());
+        assert!(!dir.path().join("Work").exists());
+    }
+
+    #[test]
+    fn test_conflict_when_both_sides_change() {
+        let dir = tempdir().unwrap();
+        let mut fixture = Fixture::new(vec![memo("m1", "Note", "base", None)], Vec::new());
+        fixture.reconcile(dir.path());
+        let path = dir.path().join("Note.md");
+
+        let edited = fixture.edit("m1", "app version");
+        replace_in(&path, "base", "file version");
+
+        assert!(!write_memo(&mut fixture.store, dir.path(), &edited, &[]).unwrap());
+        let changes = fixture.apply(dir.path(), "Note.md");
+        assert!(changes.updated.is_empty());
+        assert_eq!(fixture.store.conflicts.len(), 1);
+        assert_eq!(fixture.store.conflicts[0].memo_id, "m1");
+        // Neither side was overwritten
+        assert_eq!(fixture.memo("m1").content, "app version");
+        assert!(fs::read_to_string(&path).unwrap().ends_with("file version"));
+
+        let mut changes = SyncChanges::default();
+        resolve_conflict(
+            &mut fixture.store,
+            dir.path(),
+            "m1",
+            SyncSide::File,
+            &mut fixture.memos,
+            &mut fixture.folders,
+            &mut changes,
+        )
+        .unwrap();
+        assert!(fixture.store.conflicts.is_empty());
+        assert_eq!(changes.updated, vec!["m1"]);
+        assert_eq!(fixture.memo("m1").content, "file version");
+    }
+
+    #[test]
+    fn test_resolve_conflict_keeping_app() {
+        let dir = tempdir().unwrap();
+        let mut fixture = Fixture::new(vec![memo("m1", "Note", "base", None)], Vec::new());
+        fixture.reconcile(dir.path());
+        let path = dir.path().join("Note.md");
+        fixture.edit("m1", "app version");
+        replace_in(&path, "base", "file version");
+        fixture.apply(dir.path(), "Note.md");
+
+        let mut changes = SyncChanges::default();
+        resolve_conflict(
+            &mut fixture.store,
+            dir.path(),
+            "m1",
+            SyncSide::App,
+            &mut fixture.memos,
+            &mut fixture.folders,
+            &mut changes,
+        )
+        .unwrap();
+        assert!(fixture.store.conflicts.is_empty());
+        assert!(fs::read_to_string(&path).unwrap().ends_with("app version"));
+        assert_eq!(fixture.reconcile(dir.path()), SyncChanges::default());
+    }
+
+    #[test]
+    fn test_external_delete() {
+        let dir = tempdir().unwrap();
+        let mut fixture = Fixture::new(
+            vec![memo("m1", "Gone", "", None), memo("m2", "Kept", "", None)],
+            Vec::new(),
+        );
+        fixture.reconcile(dir.path());
+
+        fs::remove_file(dir.path().join("Gone.md")).unwrap();
+        let changes = fixture.apply(dir.path(), "Gone.md");
+        assert_eq!(changes.deleted, vec!["m1"]);
+        assert!(fixture.memos.iter().all(|m| m.id != "m1"));
+
+        // A memo edited in the app is written back instead of being deleted
+        fixture.edit("m2", "still here");
+        fs::remove_file(dir.path().join("Kept.md")).unwrap();
+        let changes = fixture.apply(dir.path(), "Kept.md");
+        assert!(changes.deleted.is_empty());
+        assert!(fs::read_to_string(dir.path().join("Kept.md"))
+            .unwrap()
+            .ends_with("still here"));
+    }
+
+    #[test]
+    fn test_app_delete_removes_unchanged_file_only() {
+        let dir = tempdir().unwrap();
+        let mut fixture = Fixture::new(
+            vec![memo("m1", "A", "", None), memo("m2", "B", "", None)],
+            Vec::new(),
+        );
+        fixture.reconcile(dir.path());
+
+        remove_memo_file(&mut fixture.store, dir.path(), "m1");
+        assert!(!dir.path().join("A.md").exists());
+
+        replace_in(&dir.path().join("B.md"), "---\n\n", "---\n\nedited");
+        remove_memo_file(&mut fixture.store, dir.path(), "m2");
+        assert!(dir.path().join("B.md").exists());
+        assert!(fixture.store.entries.is_empty());
+    }
+
+    #[test]
+    fn test_first_contact_keeps_newer_side() {
+        let dir = tempdir().unwrap();
+        let old = SystemTime::now() - std::time::Duration::from_secs(7 * 24 * 3600);
+        let file = fs::File::create(dir.path().join("Old.md")).unwrap();
+        fs::write(dir.path().join("Old.md"), "---\nid: m1\n---\nstale file").unwrap();
+        file.set_modified(old).unwrap();
+        fs::write(dir.path().join("New.md"), "---\nid: m2\n---\nfresh file").unwrap();
+
+        let mut fixture = Fixture::new(
+            vec![
+                memo("m1", "Old", "newer memo", None),
+                memo("m2", "New", "older memo", None),
+            ],
+            Vec::new(),
+        );
+        let changes = fixture.reconcile(dir.path());
+        assert_eq!(changes.updated, vec!["m2"]);
+        assert_eq!(fixture.memo("m1").content, "newer memo");
+        assert!(fs::read_to_string(dir.path().join("Old.md"))
+            .unwrap()
+            .ends_with("newer memo"));
+        assert_eq!(fixture.memo("m2").content, "fresh file");
+    }
+
+    #[test]
+    fn test_copied_file_becomes_new_memo() {
+        let dir = tempdir().unwrap();
+        let mut fixture = Fixture::new(vec![memo("m1", "Note", "body", None)], Vec::new());
+        fixture.reconcile(dir.path());
+
+        fs::copy(dir.path().join("Note.md"), dir.path().join("Copy.md")).unwrap();
+        let changes = fixture.apply(dir.path(), "Copy.md");
+        assert_eq!(changes.created.len(), 1);
+        assert_ne!(changes.created[0], "m1");
+        assert_eq!(fixture.memos.len(), 2);
+    }
+}
+
+#[cfg(test)]
+mod attachments_tests {
+    use crate::attachments::{
+        add_bytes, add_file, attachments_in, collect_garbage, Attachment, GC_GRACE_HOURS,
+    };
+    use crate::hash::content_hash;
+    use chrono::{Duration, Utc};
+    use std::fs;
+    use tempfile::tempdir;
+
+    const PNG: &[u8] = b"\x89PNG\r\n\x1a\nfake image";
+
+    #[test]
+    fn test_add_bytes_stores_by_hash_and_dedupes() {
+        let dir = tempdir().unwrap();
+        let mut attachments = Vec::new();
+
+        let first = add_bytes(&mut attachments, dir.path(), PNG, "", Some("image/png")).unwrap();
+        assert_eq!(first.hash, content_hash(PNG));
+        assert_eq!(first.extension, "png");
+        assert_eq!(first.file_name, "attachment.png");
+        assert_eq!(first.size, PNG.len() as u64);
+        assert_eq!(fs::read(dir.path().join(first.stored_name())).unwrap(), PNG);
+
+        let second = add_bytes(&mut attachments, dir.path(), PNG, "other.png", None).unwrap();
+        assert_eq!(second, first);
+        assert_eq!(attachments.len(), 1);
+
+        assert!(add_bytes(&mut attachments, dir.path(), b"", "empty.txt", None).is_err());
+    }
+
+    #[test]
+    fn test_add_file_uses_source_name_and_extension() {
+        let dir = tempdir().unwrap();
+        let source = dir.path().join("Report.PDF");
+        fs::write(&source, b"%PDF-1.4").unwrap();
+        let mut attachments = Vec::new();
+
+        let attachment = add_file(&mut attachments, &dir.path().join("store"), &source).unwrap();
+        assert_eq!(attachment.file_name, "Report.PDF");
+        assert_eq!(attachment.extension, "pdf");
+        assert_eq!(attachment.mime_type, "application/pdf");
+        assert!(source.exists());
+        assert_eq!(
+            attachment.markdown(),
+            format!("[Report.PDF](attachment:{}.pdf)", attachment.hash)
+        );
+    }
+
+    #[test]
+    fn test_image_markdown_and_attachments_in() {
+        let dir = tempdir().unwrap();
+        let mut attachments = Vec::new();
+        let image = add_bytes(&mut attachments, dir.path(), PNG, "shot.png", None).unwrap();
+        let text = add_bytes(&mut attachments, dir.path(), b"hello", "a.txt", None).unwrap();
+        add_bytes(&mut attachments, dir.path(), b"unused", "b.txt", None).unwrap();
+
+        assert_eq!(
+            image.markdown(),
+            format!("![shot.png]({})", image.reference())
+        );
+
+        let content = format!(
+            "{}\n{}\n{}\n[missing](attachment:{})",
+            text.markdown(),
+            image.markdown(),
+            text.markdown(),
+            "0".repeat(64)
+        );
+        let found: Vec<String> = attachments_in(&attachments, &content)
+            .into_iter()
+            .map(|a| a.hash)
+            .collect();
+        assert_eq!(found, vec![text.hash, image.hash]);
+    }
+
+    #[test]
+    fn test_collect_garbage_keeps_referenced_and_recent() {
+        let dir = tempdir().unwrap();
+        let mut attachments: Vec<Attachment> = Vec::new();
+        let used = add_bytes(&mut attachments, dir.path(), b"used", "used.txt", None).unwrap();
+        let unused =
+            add_bytes(&mut attachments, dir.path(), b"unused", "unused.txt", None).unwrap();
+        let content = used.markdown();
+
+        // Within the grace period nothing is removed
+        let report = collect_garbage(&mut attachments, dir.path(), [content.as_str()], Utc::now());
+        assert!(report.removed.is_empty());
+        assert_eq!(attachments.len(), 2);
+
+        let later = Utc::now() + Duration::hours(GC_GRACE_HOURS + 1);
+        let report = collect_garbage(&mut attachments, dir.path(), [content.as_str()], later);
+        assert_eq!(report.removed, vec![unused.hash.clone()]);
+        assert_eq!(report.freed_bytes, unused.size);
+        assert_eq!(attachments, vec![used.clone()]);
+        assert!(dir.path().join(used.stored_name()).exists());
+        assert!(!dir.path().join(unused.stored_name()).exists());
+    }
+
+    #[test]
+    fn test_collect_garbage_removes_untracked_files() {
+        let dir = tempdir().unwrap();
+        let orphan = format!("{}.png", "a".repeat(64));
+        fs::write(dir.path().join(&orphan), b"orphan").unwrap();
+        fs::write(dir.path().join("notes.txt"), b"not an attachment").unwrap();
+        let mut attachments = Vec::new();
+
+        let later = Utc::now() + Duration::hours(GC_GRACE_HOURS + 1);
+        let report = collect_garbage(&mut attachments, dir.path(), [], later);
+        assert_eq!(report.removed, vec!["a".repeat(64)]);
+        assert!(!dir.path().join(&orphan).exists());
+        assert!(dir.path().join("notes.txt").exists());
+    }
+}
+
+/// テスト用のHTTPサーバー（パスごとに固定の応答を返す）
+#[cfg(test)]
+mod mock_http {
+    use std::collections::HashMap;
+    use std::io::{BufRead, BufReader, Write};
+    use std::net::TcpListener;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+    use std::thread;
+
+    pub struct MockServer {
+        pub url: String,
+        hits: Arc<AtomicUsize>,
+    }
+
+    impl MockServer {
+        /// `routes` は パス → (ステータス, Content-Type, 本文)。無いパスは404を返す
+        pub fn start(routes: Vec<(&str, u16, &str, Vec<u8>)>) -> Self {
+            let routes: HashMap<String, (u16, String, Vec<u8>)> = routes
+                .into_iter()
+                .map(|(path, status, content_type, body)| {
+                    (path.to_string(), (status, content_type.to_string(), body))
+                })
+                .collect();
+            let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+            let url = format!("http://{}", listener.local_addr().unwrap());
+            let hits = Arc::new(AtomicUsize::new(0));
+            let counter = hits.clone();
+
+            thread::spawn(move || {
+                for stream in listener.incoming().flatten() {
+                    counter.fetch_add(1, Ordering::SeqCst);
+                    let mut reader = BufReader::new(stream.try_clone().unwrap());
+                    let mut request_line = String::new();
+                    reader.read_line(&mut request_line).unwrap_or_default();
+                    // Drain the headers so the client sees a clean response
+                    let mut header = String::new();
+                    while reader.read_line(&mut header).unwrap_or(0) > 2 {
+                        header.clear();
+                    }
+
+                    let path = request_line.split_whitespace().nth(1).unwrap_or("/");
+                    let (status, content_type, body) = routes.get(path).cloned().unwrap_or((
+                        404,
+                        "text/plain".to_string(),
+                        b"not found".to_vec(),
+                    ));
+                    let mut stream = stream;
+                    let head = format!(
+                        "HTTP/1.1 {} X\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+                        status,
+                        content_type,
+                        body.len()
+                    );
+                    let _ = stream.write_all(head.as_bytes());
+                    let _ = stream.write_all(&body);
+                }
+            });
+            MockServer { url, hits }
+        }
+
+        /// 受けたリクエストの数
+        pub fn hits(&self) -> usize {
+            self.hits.load(Ordering::SeqCst)
+        }
+    }
+}
+
+#[cfg(test)]
+mod book_cover_tests {
+    use super::mock_http::MockServer;
+    use crate::book_cover::{
+        cached_cover_for_url, download_cover, remove_cover_if_unused, store_cover,
+        uncached_cover_urls, THUMBNAIL_HEIGHT, THUMBNAIL_WIDTH,
+    };
+    use crate::commands::reading_commands::download_covers;
+    use crate::reading_memo::ReadingBook;
+    use chrono::Utc;
+    use image::{ImageFormat, RgbaImage};
+    use std::io::Cursor;
+    use tempfile::tempdir;
+
+    fn png(width: u32, height: u32) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        RgbaImage::from_pixel(width, height, image::Rgba([200, 40, 40, 128]))
+            .write_to(&mut Cursor::new(&mut bytes), ImageFormat::Png)
+            .unwrap();
+        bytes
+    }
+
+    #[test]
+    fn test_store_cover_writes_image_and_thumbnail() {
+        let dir = tempdir().unwrap();
+        let bytes = png(400, 1200);
+
+        let cover = store_cover(dir.path(), &bytes, None, Utc::now()).unwrap();
+        assert_eq!(cover.extension, "png");
+        assert_eq!((cover.width, cover.height), (400, 1200));
+        assert!(cover.is_stored(dir.path()));
+
+        let thumbnail = image::open(dir.path().join(cover.thumbnail_name())).unwrap();
+        assert!(thumbnail.width() <= THUMBNAIL_WIDTH);
+        assert_eq!(thumbnail.height(), THUMBNAIL_HEIGHT);
+
+        // The same image is stored once
+        let again = store_cover(dir.path(), &bytes, None, Utc::now()).unwrap();
+        assert_eq!(again.hash, cover.hash);
+        assert_eq!(std::fs::read_dir(dir.path()).unwrap().count(), 2);
+    }
+
+    #[test]
+    fn test_store_cover_rejects_non_images() {
+        let dir = tempdir().unwrap();
+        assert!(store_cover(dir.path(), b"<html></html>", None, Utc::now()).is_err());
+        assert_eq!(std::fs::read_dir(dir.path()).unwrap().count(), 0);
+    }
+
+    #[test]
+    fn test_download_cover_from_mock_server() {
+        let bytes = png(10, 15);
+        let server = MockServer::start(vec![("/cover.png", 200, "image/png", bytes.clone())]);
+
+        let downloaded = download_cover(&format!("{}/cover.png", server.url)).unwrap();
+        assert_eq!(downloaded, bytes);
+        assert!(download_cover(&format!("{}/missing.png", server.url)).is_err());
+        assert!(download_cover("file:///etc/passwd").is_err());
+        assert_eq!(server.hits(), 2);
+    }
+
+    #[test]
+    fn test_cached_and_uncached_urls() {
+        let dir = tempdir().unwrap();
+        let url = "https://example.com/a.png".to_string();
+        let cover = store_cover(dir.path(), &png(4, 6), Some(url.clone()), Utc::now()).unwrap();
+
+        let mut cached = ReadingBook::new("Cached".to_string());
+        cached.cover_image_url = Some(url.clone());
+        cached.cover = Some(cover.clone());
+        let mut pending = ReadingBook::new("Pending".to_string());
+        pending.cover_image_url = Some(url.clone());
+        let mut local = ReadingBook::new("Local".to_string());
+        local.cover_image_url = Some("covers/a.png".to_string());
+        let books = vec![cached, pending.clone(), local];
+
+        assert_eq!(
+            uncached_cover_urls(&books),
+            vec![(pending.id.clone(), url.clone())]
+        );
+        assert_eq!(cached_cover_for_url(&books, dir.path(), &url), Some(cover));
+        assert_eq!(
+            cached_cover_for_url(&books, dir.path(), "https://example.com/b.png"),
+            None
+        );
+    }
+
+    #[test]
+    fn test_remove_cover_if_unused() {
+        let dir = tempdir().unwrap();
+        let cover = store_cover(dir.path(), &png(4, 6), None, Utc::now()).unwrap();
+        let mut book = ReadingBook::new("Book".to_string());
+        book.cover = Some(cover.clone());
+
+        remove_cover_if_unused(dir.path(), &[book], &cover);
+        assert!(cover.is_stored(dir.path()));
+
+        remove_cover_if_unused(dir.path(), &[], &cover);
+        assert!(!dir.path().join(cover.file_name()).exists());
+        assert!(!dir.path().join(cover.thumbnail_name()).exists());
+    }
+
+    #[test]
+    fn test_download_covers_fetches_each_url_once() {
+        let server = MockServer::start(vec![
+            ("/a.png", 200, "image/png", png(4, 6)),
+            ("/b.png", 200, "image/png", png(8, 2)),
+        ]);
+        let dir = tempdir().unwrap();
+        let (a, b, missing) = (
+            format!("{}/a.png", server.url),
+            format!("{}/b.png", server.url),
+            format!("{}/missing.png", server.url),
+        );
+
+        let urls = vec![a.clone(), missing.clone(), a.clone(), b.clone()];
+        let results =
+            tauri::async_runtime::block_on(download_covers(dir.path().to_path_buf(), urls));
+        let order: Vec<&str> = results.iter().map(|(url, _)| url.as_str()).collect();
+        assert_eq!(order, vec![a.as_str(), missing.as_str(), b.as_str()]);
+        assert_eq!(server.hits(), 3);
+
+        let cover = results[0].1.as_ref().unwrap();
+        assert_eq!((cover.width, cover.height), (4, 6));
+        assert_eq!(cover.source_url.as_deref(), Some(a.as_str()));
+        assert!(cover.is_stored(dir.path()));
+        assert!(results[1].1.is_err());
+        assert_eq!(results[2].1.as_ref().unwrap().width, 8);
+    }
+}
+
+#[cfg(test)]
+mod isbn_tests {
+    use crate::isbn::{normalize_isbn, to_isbn13};
+
+    #[test]
+    fn test_normalize_valid_isbns() {
+        assert_eq!(
+            normalize_isbn("978-4-06-293838-9").unwrap(),
+            "9784062938389"
+        );
+        assert_eq!(
+            normalize_isbn(" ISBN 4-06-293838-3 ").unwrap(),
+            "4062938383"
+        );
+        assert_eq!(
+            normalize_isbn("ISBN-13: 978-0-306-40615-7").unwrap(),
+            "9780306406157"
+        );
+        assert_eq!(normalize_isbn("0-8044-2957-x").unwrap(), "080442957X");
+        assert_eq!(normalize_isbn("isbn:0306406152").unwrap(), "0306406152");
+    }
+
+    #[test]
+    fn test_reject_invalid_isbns() {
+        // Wrong check digits
+        assert!(normalize_isbn("978-0-306-40615-8").is_err());
+        assert!(normalize_isbn("0-306-40615-3").is_err());
+        // X is only valid as the last ISBN-10 digit
+        assert!(normalize_isbn("X306406152").is_err());
+        // ISBN-13 must use the 978/979 prefix
+        assert!(normalize_isbn("1234567890128").is_err());
+        assert!(normalize_isbn("12345").is_err());
+        assert!(normalize_isbn("").is_err());
+    }
+
+    #[test]
+    fn test_non_ascii_input_is_rejected_without_panicking() {
+        assert!(normalize_isbn("読書メモ").is_err());
+        assert!(normalize_isbn("IS日本").is_err());
+        assert!(normalize_isbn("ISBN：978-0-306-40615-7").is_err());
+        assert!(normalize_isbn("é9780306406157").is_err());
+    }
+
+    #[test]
+    fn test_to_isbn13() {
+        assert_eq!(to_isbn13("0306406152"), "9780306406157");
+        assert_eq!(to_isbn13("080442957X"), "9780804429573");
+        assert_eq!(to_isbn13("9784062938389"), "9784062938389");
+        assert!(normalize_isbn(&to_isbn13("4062938383")).is_ok());
+    }
+}
+
+#[cfg(test)]
+mod book_metadata_tests {
+    use super::mock_http::MockServer;
+    use crate::book_metadata::{
+        metadata_patch, parse_year, BookMetadata, BookMetadataProvider, OpenLibraryProvider,
+    };
+    use crate::commands::reading_commands::lookup_book_metadata;
+    use crate::reading_memo::ReadingBook;
+    use chrono::Utc;
+    use std::sync::{Arc, Mutex};
+
+    /// 決まった結果を返し、問い合わせたISBNを記録する取得先
+    struct FakeProvider {
+        result: Result<Option<BookMetadata>, String>,
+        queries: Mutex<Vec<String>>,
+    }
+
+    impl FakeProvider {
+        fn new(result: Result<Option<BookMetadata>, String>) -> Arc<Self> {
+            Arc::new(FakeProvider {
+                result,
+                queries: Mutex::new(Vec::new()),
+            })
+        }
+    }
+
+    impl BookMetadataProvider for FakeProvider {
+        fn lookup_isbn(&self, isbn: &str) -> Result<Option<BookMetadata>, String> {
+            self.queries.lock().unwrap().push(isbn.to_string());
+            self.result.clone()
+        }
+    }
+
+    fn lookup(
+        books: &Mutex<Vec<ReadingBook>>,
+        provider: Arc<dyn BookMetadataProvider>,
+        book_id: &str,
+        isbn: Option<&str>,
+        overwrite: bool,
+    ) -> Result<(ReadingBook, Option<String>), String> {
+        tauri::async_runtime::block_on(lookup_book_metadata(
+            books,
+            provider,
+            book_id,
+            isbn.map(str::to_string),
+            overwrite,
+        ))
+    }
+
+    const PATH: &str = "/api/books?bibkeys=ISBN:9780306406157&format=json&jscmd=data";
+
+    fn open_library_body() -> Vec<u8> {
+        serde_json::json!({
+            "ISBN:9780306406157": {
+                "title": "Sample Book",
+                "authors": [{ "name": "Jane Doe" }, { "name": "John Roe" }],
+                "publishers": [{ "name": "Example Press" }],
+                "publish_date": "March 2008",
+                "number_of_pages": 320,
+                "cover": {
+                    "small": "https://covers.example.com/s.jpg",
+                    "large": "https://covers.example.com/l.jpg"
+                }
+            }
+        })
+        .to_string()
+        .into_bytes()
+    }
+
+    #[test]
+    fn test_open_library_lookup() {
+        let server = MockServer::start(vec![
+            (PATH, 200, "application/json", open_library_body()),
+            (
+                "/api/books?bibkeys=ISBN:9784062938389&format=json&jscmd=data",
+                200,
+                "application/json",
+                b"{}".to_vec(),
+            ),
+        ]);
+        let provider: Box<dyn BookMetadataProvider> = Box::new(OpenLibraryProvider::with_base_url(
+            &format!("{}/", server.url),
+        ));
+
+        let metadata = provider.lookup_isbn("9780306406157").unwrap().unwrap();
+        assert_eq!(
+            metadata,
+            BookMetadata {
+                title: Some("Sample Book".to_string()),
+                author: Some("Jane Doe, John Roe".to_string()),
+                publisher: Some("Example Press".to_string()),
+                published_year: Some(2008),
+                total_pages: Some(320),
+                cover_url: Some("https://covers.example.com/l.jpg".to_string()),
+            }
+        );
+
+        assert_eq!(provider.lookup_isbn("9784062938389").unwrap(), None);
+    }
+
+    #[test]
+    fn test_open_library_errors() {
+        let server = MockServer::start(vec![(PATH, 500, "text/plain", b"oops".to_vec())]);
+        let provider = OpenLibraryProvider::with_base_url(&server.url);
+        assert!(provider.lookup_isbn("9780306406157").is_err());
+
+        let server = MockServer::start(vec![(PATH, 200, "application/json", b"<html>".to_vec())]);
+        let provider = OpenLibraryProvider::with_base_url(&server.url);
+        assert!(provider.lookup_isbn("9780306406157").is_err());
+    }
+
+    #[test]
+    fn test_parse_year() {
+        assert_eq!(parse_year("2008"), Some(2008));
+        assert_eq!(parse_year("March 2008"), Some(2008));
+        assert_eq!(parse_year("2008-03-01"), Some(2008));
+        assert_eq!(parse_year("c1999."), Some(1999));
+        assert_eq!(parse_year("12345"), None);
+        assert_eq!(parse_year("unknown"), None);
+    }
+
+    #[test]
+    fn test_metadata_patch_fills_empty_fields() {
+        let metadata = BookMetadata {
+            title: Some("Looked Up".to_string()),
+            author: Some("Jane Doe".to_string()),
+            publisher: Some("Example Press".to_string()),
+            published_year: Some(2008),
+            total_pages: Some(200),
+            cover_url: None,
+        };
+        let mut book = ReadingBook::new("My Title".to_string());
+        book.author = Some("Someone".to_string());
+        book.current_page = Some(50);
+
+        let patch = metadata_patch(&book, &metadata, false);
+        book.apply_patch(patch, Utc::now());
+        assert_eq!(book.title, "My Title");
+        assert_eq!(book.author.as_deref(), Some("Someone"));
+        assert_eq!(book.publisher.as_deref(), Some("Example Press"));
+        assert_eq!(book.published_year, Some(2008));
+        assert_eq!(book.total_pages, Some(200));
+        assert_eq!(book.progress_percent, Some(25));
+
+        let patch = metadata_patch(&book, &metadata, true);
+        book.apply_patch(patch, Utc::now());
+        assert_eq!(book.title, "Looked Up");
+        assert_eq!(book.author.as_deref(), Some("Jane Doe"));
+    }
+
+    #[test]
+    fn test_lookup_command_path_uses_injected_provider() {
+        let provider = FakeProvider::new(Ok(Some(BookMetadata {
+            author: Some("Jane Doe".to_string()),
+            total_pages: Some(320),
+            cover_url: Some("https://covers.example.com/l.jpg".to_string()),
+            ..BookMetadata::default()
+        })));
+        let mut book = ReadingBook::new("Mine".to_string());
+        book.isbn = Some("0-306-40615-2".to_string());
+        let id = book.id.clone();
+        let books = Mutex::new(vec![book]);
+
+        let (book, cover_url) = lookup(&books, provider.clone(), &id, None, false).unwrap();
+        // ISBN-10 is queried as ISBN-13 but stored as entered, normalized
+        assert_eq!(*provider.queries.lock().unwrap(), vec!["9780306406157"]);
+        assert_eq!(book.isbn.as_deref(), Some("0306406152"));
+        assert_eq!(book.title, "Mine");
+        assert_eq!(book.author.as_deref(), Some("Jane Doe"));
+        assert_eq!(book.total_pages, Some(320));
+        assert_eq!(
+            cover_url.as_deref(),
+            Some("https://covers.example.com/l.jpg")
+        );
+        assert_eq!(books.lock().unwrap()[0], book);
+    }
+
+    #[test]
+    fn test_lookup_command_path_errors() {
+        let book = ReadingBook::new("No ISBN".to_string());
+        let id = book.id.clone();
+        let books = Mutex::new(vec![book.clone()]);
+
+        let provider = FakeProvider::new(Ok(None));
+        assert!(lookup(&books, provider.clone(), &id, None, false).is_err());
+        assert!(lookup(
+            &books,
+            provider.clone(),
+            &id,
+            Some("978-0-306-40615-8"),
+            false
+        )
+        .is_err());
+        assert!(lookup(
+            &books,
+            provider.clone(),
+            "missing",
+            Some("0306406152"),
+            false
+        )
+        .is_err());
+        // Invalid input never reaches the provider
+        assert!(provider.queries.lock().unwrap().is_empty());
+
+        let not_found = lookup(&books, provider.clone(), &id, Some("0306406152"), false);
+        assert!(not_found.unwrap_err().contains("No book found"));
+        let failing = FakeProvider::new(Err("offline".to_string()));
+        assert_eq!(
+            lookup(&books, failing, &id, Some("0306406152"), false).unwrap_err(),
+            "offline"
+        );
+        assert_eq!(books.lock().unwrap()[0], book);
+    }
+
+    #[test]
+    fn test_lookup_command_path_with_open_library_mock() {
+        let server = MockServer::start(vec![(PATH, 200, "application/json", open_library_body())]);
+        let provider = Arc::new(OpenLibraryProvider::with_base_url(&server.url));
+        let mut book = ReadingBook::new(String::new());
+        book.cover_image_url = Some("https://example.com/mine.jpg".to_string());
+        let id = book.id.clone();
+        let books = Mutex::new(vec![book]);
+
+        let (book, cover_url) =
+            lookup(&books, provider, &id, Some("ISBN 978-0-306-40615-7"), false).unwrap();
+        assert_eq!(book.title, "Sample Book");
+        assert_eq!(book.publisher.as_deref(), Some("Example Press"));
+        assert_eq!(book.published_year, Some(2008));
+        // An existing cover is kept unless overwriting
+        assert_eq!(cover_url, None);
+        assert_eq!(
+            book.cover_image_url.as_deref(),
+            Some("https://example.com/mine.jpg")
+        );
+    }
+}
@@ -0,0 +1,150 @@
+// タスクの作業時間記録（タイマー・レポート）
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::group::{group_name, TaskGroup};
+use crate::task::Task;
+
+/// 作業記録（タイマー1回分）
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct TimeEntry {
+    pub id: String,
+    pub started_at: DateTime<Utc>,
+    /// 計測中は `None`
+    pub ended_at: Option<DateTime<Utc>>,
+    pub memo: Option<String>,
+}
+
+impl TimeEntry {
+    pub fn new(started_at: DateTime<Utc>) -> Self {
+        Self {
+            id: Uuid::new_v4().to_string(),
+            started_at,
+            ended_at: None,
+            memo: None,
+        }
+    }
+
+    /// 作業時間（分）。計測中の場合は `now` までの時間
+    pub fn duration_minutes(&self, now: DateTime<Utc>) -> i64 {
+        let end = self.ended_at.unwrap_or(now);
+        end.signed_duration_since(self.started_at)
+            .num_minutes()
+            .max(0)
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.ended_at.is_none()
+    }
+}
+
+/// グループ別の見積もりと実績
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct GroupTimeReport {
+    pub group_id: Option<String>,
+    /// グループ名（グループ無しは空文字列）
+    pub group: String,
+    pub task_count: usize,
+    pub estimated_minutes: i64,
+    pub actual_minutes: i64,
+    /// 見積もりが設定されていないタスクの実績時間
+    pub unestimated_minutes: i64,
+}
+
+/// タイマーを開始する
+///
+/// 同時に計測できるのは1件のみのため、他のタスクで計測中のタイマーは停止する。
+/// タイマーを停止した他のタスクのIDを返す。
+pub fn start_timer(
+    tasks: &mut [Task],
+    task_id: i32,
+    now: DateTime<Utc>,
+) -> Result<Vec<i32>, String> {
+    let task = tasks
+        .iter()
+        .find(|t| t.id == task_id)
+        .ok_or_else(|| format!("Task {} not found", task_id))?;
+    if task.time_entries.iter().any(TimeEntry::is_running) {
+        return Err("Timer is already running for this task".to_string());
+    }
+
+    let mut stopped = Vec::new();
+    for task in tasks.iter_mut() {
+        for entry in task.time_entries.iter_mut().filter(|e| e.is_running()) {
+            entry.ended_at = Some(now);
+            stopped.push(task.id);
+        }
+        if task.id == task_id {
+            task.time_entries.push(TimeEntry::new(now));
+        }
+    }
+    stopped.dedup();
+
+    Ok(stopped)
+}
+
+/// 計測中のタイマーを停止する
+pub fn stop_timer(
+    tasks: &mut [Task],
+    task_id: i32,
+    memo: Option<String>,
+    now: DateTime<Utc>,
+) -> Result<TimeEntry, String> {
+    let task = tasks
+        .iter_mut()
+        .find(|t| t.id == task_id)
+        .ok_or_else(|| format!("Task {} not found", task_id))?;
+    let entry = task
+        .time_entries
+        .iter_mut()
+        .find(|e| e.is_running())
+        .ok_or_else(|| "No timer is running for this task".to_string())?;
+
+    entry.ended_at = Some(now);
+    entry.memo = memo.filter(|m| !m.trim().is_empty());
+    Ok(entry.clone())
+}
+
+/// グループ別に見積もりと実績を集計する（グループ名順）
+pub fn time_report(
+    tasks: &[Task],
+    groups: &[TaskGroup],
+    now: DateTime<Utc>,
+) -> Vec<GroupTimeReport> {
+    let mut reports: Vec<GroupTimeReport> = Vec::new();
+
+    for task in tasks {
+        let actual: i64 = task
+            .time_entries
+            .iter()
+            .map(|e| e.duration_minutes(now))
+            .sum();
+
+        let report = match reports.iter_mut().find(|r| r.group_id == task.group_id) {
+            Some(report) => report,
+            None => {
+                reports.push(GroupTimeReport {
+                    group_id: task.group_id.clone(),
+                    group: group_name(groups, task.group_id.as_deref()),
+                    task_count: 0,
+                    estimated_minutes: 0,
+                    actual_minutes: 0,
+                    unestimated_minutes: 0,
+                });
+                reports.last_mut().unwrap()
+            }
+        };
+
+        report.task_count += 1;
+        report.actual_minutes += actual;
+        match task.estimated_minutes {
+            Some(estimate) => report.estimated_minutes += estimate as i64,
+            None => report.unestimated_minutes += actual,
+        }
+    }
+
+    reports.sort_by(|a, b| a.group.cmp(&b.group));
+    reports
+}
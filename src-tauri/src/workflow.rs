@@ -0,0 +1,241 @@
+// グループごとのワークフロー状態（カンバンボード）
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::dependency::incomplete_blockers;
+use crate::group::{ancestors, TaskGroup};
+use crate::task::Task;
+
+/// ワークフロー上の状態（ボードの列）
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct WorkflowState {
+    pub id: String,
+    pub name: String,
+    /// 完了扱いの状態か
+    #[serde(default)]
+    pub terminal: bool,
+}
+
+impl WorkflowState {
+    fn new(id: &str, name: &str, terminal: bool) -> Self {
+        Self {
+            id: id.to_string(),
+            name: name.to_string(),
+            terminal,
+        }
+    }
+}
+
+/// 状態遷移の記録
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct StateTransition {
+    pub from: Option<String>,
+    pub to: String,
+    pub at: DateTime<Utc>,
+}
+
+/// ボードの1列
+#[derive(Debug, Clone, Serialize)]
+pub struct BoardColumn {
+    pub state: WorkflowState,
+    pub tasks: Vec<Task>,
+}
+
+/// グループのカンバンボード
+#[derive(Debug, Clone, Serialize)]
+pub struct Board {
+    pub group_id: Option<String>,
+    pub columns: Vec<BoardColumn>,
+}
+
+/// グループにワークフローが設定されていない場合の状態一覧
+pub fn default_workflow() -> Vec<WorkflowState> {
+    vec![
+        WorkflowState::new("backlog", "Backlog", false),
+        WorkflowState::new("todo", "Todo", false),
+        WorkflowState::new("in_progress", "In Progress", false),
+        WorkflowState::new("review", "Review", false),
+        WorkflowState::new("done", "Done", true),
+    ]
+}
+
+/// グループに適用されるワークフロー
+///
+/// グループ自身、親グループの順に設定を探し、どこにも無ければ既定のワークフローを使う。
+pub fn workflow_for(groups: &[TaskGroup], group_id: Option<&str>) -> Vec<WorkflowState> {
+    group_id
+        .and_then(|id| {
+            ancestors(groups, id)
+                .into_iter()
+                .find(|g| !g.workflow.is_empty())
+        })
+        .map(|g| g.workflow.clone())
+        .unwrap_or_else(default_workflow)
+}
+
+/// ワークフローの定義を検証する
+pub fn validate_workflow(states: &[WorkflowState]) -> Result<(), String> {
+    for (i, state) in states.iter().enumerate() {
+        if state.id.trim().is_empty() || state.name.trim().is_empty() {
+            return Err("Workflow state id and name cannot be empty".to_string());
+        }
+        if states[..i].iter().any(|s| s.id == state.id) {
+            return Err(format!("Duplicate workflow state '{}'", state.id));
+        }
+    }
+    if !states.iter().any(|s| s.terminal) {
+        return Err("A workflow needs at least one terminal state".to_string());
+    }
+    if states.iter().all(|s| s.terminal) {
+        return Err("A workflow needs at least one non-terminal state".to_string());
+    }
+    Ok(())
+}
+
+/// タスクの現在の状態
+///
+/// 状態が未設定（または現在のワークフローに無い）場合は、完了済みなら最初の終了状態、
+/// 未完了なら最初の未完了状態として扱う。
+pub fn current_state<'a>(task: &Task, workflow: &'a [WorkflowState]) -> &'a WorkflowState {
+    task.state
+        .as_deref()
+        .and_then(|id| workflow.iter().find(|s| s.id == id))
+        .or_else(|| workflow.iter().find(|s| s.terminal == task.completed))
+        .unwrap_or(&workflow[0])
+}
+
+/// 状態を遷移させ、完了フラグを状態に合わせる
+fn apply_state(
+    task: &mut Task,
+    workflow: &[WorkflowState],
+    target: &WorkflowState,
+    now: DateTime<Utc>,
+) {
+    let from = current_state(task, workflow).id.clone();
+    if task.state.is_some() && from == target.id {
+        return;
+    }
+    task.state_history.push(StateTransition {
+        from: Some(from),
+        to: target.id.clone(),
+        at: now,
+    });
+    task.state = Some(target.id.clone());
+    task.completed = target.terminal;
+}
+
+/// タスクを指定した状態に移動する
+///
+/// 終了状態への移動は、未完了の依存先がある場合はエラーにする。
+pub fn move_task_state(
+    tasks: &mut [Task],
+    groups: &[TaskGroup],
+    task_id: i32,
+    state_id: &str,
+    now: DateTime<Utc>,
+) -> Result<(), String> {
+    let index = tasks
+        .iter()
+        .position(|t| t.id == task_id)
+        .ok_or_else(|| format!("Task {} not found", task_id))?;
+    let workflow = workflow_for(groups, tasks[index].group_id.as_deref());
+    let target = workflow
+        .iter()
+        .find(|s| s.id == state_id)
+        .ok_or_else(|| format!("Unknown workflow state '{}'", state_id))?;
+
+    if target.terminal && !tasks[index].completed {
+        let blockers = incomplete_blockers(tasks, &tasks[index]);
+        if !blockers.is_empty() {
+            let ids = blockers
+                .iter()
+                .map(|id| id.to_string())
+                .collect::<Vec<_>>()
+                .join(", ");
+            return Err(format!("Task is blocked by incomplete tasks: {}", ids));
+        }
+    }
+
+    apply_state(&mut tasks[index], &workflow, target, now);
+    Ok(())
+}
+
+/// 完了・未完了の切り替えをワークフロー上の遷移として記録する
+///
+/// 完了時は最初の終了状態へ、未完了に戻す時は直前にいた未完了の状態
+/// （記録が無ければ最初の未完了状態）へ移動する。
+pub fn set_completed(task: &mut Task, groups: &[TaskGroup], completed: bool, now: DateTime<Utc>) {
+    let workflow = workflow_for(groups, task.group_id.as_deref());
+    let target = if completed {
+        workflow.iter().find(|s| s.terminal)
+    } else {
+        task.state_history
+            .iter()
+            .rev()
+            .filter_map(|t| t.from.as_deref())
+            .find_map(|id| workflow.iter().find(|s| s.id == id && !s.terminal))
+            .or_else(|| workflow.iter().find(|s| !s.terminal))
+    };
+
+    match target {
+        Some(target) => {
+            let target = target.clone();
+            apply_state(task, &workflow, &target, now);
+        }
+        None => task.completed = completed,
+    }
+}
+
+/// 現在のワークフローに存在しない状態をリセットする（変更があれば true）
+pub fn normalize_state(task: &mut Task, groups: &[TaskGroup]) -> bool {
+    let Some(state) = task.state.as_deref() else {
+        return false;
+    };
+    let workflow = workflow_for(groups, task.group_id.as_deref());
+    if workflow.iter().any(|s| s.id == state) {
+        return false;
+    }
+    task.state = None;
+    true
+}
+
+/// すべてのタスクに `normalize_state` を適用し、変更したタスクの `updated_at` を更新する
+///
+/// 変更したタスクのIDを返す。
+pub fn normalize_states(tasks: &mut [Task], groups: &[TaskGroup], now: DateTime<Utc>) -> Vec<i32> {
+    let mut changed = Vec::new();
+    for task in tasks.iter_mut() {
+        if normalize_state(task, groups) {
+            task.updated_at = Some(now);
+            changed.push(task.id);
+        }
+    }
+    changed
+}
+
+/// グループ（`None` はグループ無し）のボードを作成する
+///
+/// サブグループのタスクは含めない。各列のタスクは元の順序を保つ。
+pub fn board(tasks: &[Task], groups: &[TaskGroup], group_id: Option<&str>) -> Board {
+    let workflow = workflow_for(groups, group_id);
+    let mut columns: Vec<BoardColumn> = workflow
+        .iter()
+        .map(|state| BoardColumn {
+            state: state.clone(),
+            tasks: Vec::new(),
+        })
+        .collect();
+
+    for task in tasks.iter().filter(|t| t.group_id.as_deref() == group_id) {
+        let state = current_state(task, &workflow);
+        if let Some(column) = columns.iter_mut().find(|c| c.state.id == state.id) {
+            column.tasks.push(task.clone());
+        }
+    }
+
+    Board {
+        group_id: group_id.map(str::to_string),
+        columns,
+    }
+}
@@ -11,7 +11,7 @@ import MemoView from "./components/memo";
 import ReadingMemoView from "./components/reading";
 import DashboardView from "./components/dashboard";
 import GanttView from "./components/GanttView";
-import type { Task, ReadingBook } from "./types";
+import type { Task, TaskGroup, ReadingBook } from "./types";
 import {
   addTask,
   completeTask,
@@ -21,15 +21,22 @@ import {
   getGroups,
   getTasks,
   renameGroups,
+  setGroupArchived,
   updateTask,
 } from "./tauri/task_api";
 import { getReadingBooks } from "./tauri/reading_api";
+import {
+  applyTaskChanges,
+  listenBookEvents,
+  listenTaskEvents,
+  upsertById,
+} from "./tauri/events";
 
 // Reading Book interface for dashboard
 
 function App() {
   const [tasks, setTasks] = useState<Task[]>([]);
-  const [groups, setGroups] = useState<string[]>([]);
+  const [groups, setGroups] = useState<TaskGroup[]>([]);
   const [currentGroup, setCurrentGroup] = useState<string>("__ALL__");
   const [editingTask, setEditingTask] = useState<Task | null>(null);
   const [theme, setTheme] = useState<string>("dark");
@@ -44,6 +51,17 @@ function App() {
     document.documentElement.className = `theme-${savedTheme}`;
   }, []);
 
+  // Keep tasks and books in sync with changes made here or in other windows
+  useEffect(() => {
+    const unlisteners = [
+      listenTaskEvents(setTasks),
+      listenBookEvents(setReadingBooks),
+    ];
+    return () => {
+      unlisteners.forEach((unlisten) => unlisten.then((fn) => fn()));
+    };
+  }, []);
+
   useEffect(() => {
     const handler = (e: MouseEvent) => e.preventDefault();
     document.addEventListener("contextmenu", handler);
@@ -62,67 +80,73 @@ function App() {
   const handleAddTask = async (
     desc: string,
     date: string,
-    group: string,
+    groupId: string | null,
     details: string,
     notificationMinutes?: number,
     startDate?: string,
   ) => {
-    const newTasks = await addTask(
+    const task = await addTask(
       desc,
       startDate,
       date,
-      group,
+      groupId,
       details,
       notificationMinutes,
       null,
     );
-    setTasks(newTasks);
+    handleTaskChange(task);
+  };
+
+  const handleTaskChange = (task: Task) => {
+    setTasks((prev) => upsertById(prev, task));
   };
 
   const handleDeleteTask = async (id: number) => {
-    const newTasks = await deleteTask(id);
-    setTasks(newTasks);
+    const changes = await deleteTask(id);
+    setTasks((prev) => applyTaskChanges(prev, changes));
   };
 
   const handleCompleteTask = async (id: number) => {
-    const newTasks = await completeTask(id);
-    setTasks(newTasks);
+    handleTaskChange(await completeTask(id));
   };
 
   const handleUpdateTask = async (task: Task) => {
-    const newTasks = await updateTask(task);
-    setTasks(newTasks);
+    handleTaskChange(await updateTask(task));
     setEditingTask(null);
   };
 
   const handleAddGroup = async (name: string) => {
-    const newGroups = await createGroups(name);
+    try {
+      const newGroups = await createGroups(name);
+      setGroups(newGroups);
+    } catch (err) {
+      alert("グループの作成に失敗しました: " + (err as any)?.toString?.());
+    }
+  };
+
+  const handleArchiveGroup = async (id: string) => {
+    const newGroups = await setGroupArchived(id, true);
     setGroups(newGroups);
+    if (currentGroup === id) {
+      setCurrentGroup("__ALL__");
+    }
   };
 
-  const handleDeleteGroup = async (name: string) => {
-    const newGroups = await deleteGroups(name);
+  const handleDeleteGroup = async (id: string) => {
+    // Tasks whose group was cleared arrive as task-updated events
+    const newGroups = await deleteGroups(id);
     setGroups(newGroups);
-    // Also refresh tasks as some might have been updated (group cleared)
-    const loadedTasks = await getTasks();
-    setTasks(loadedTasks);
 
-    if (currentGroup === name) {
+    if (currentGroup === id) {
       setCurrentGroup("__ALL__");
     }
   };
 
-  const handleRenameGroup = async (oldName: string, newName: string) => {
+  const handleRenameGroup = async (id: string, newName: string) => {
     try {
-      const res = await renameGroups(oldName, newName);
-      // The backend returns a tuple [groups, tasks]
-      if (Array.isArray(res)) {
-        const newGroups = res[0] as string[];
-        const newTasks = res[1] as Task[];
-        if (newGroups) setGroups(newGroups);
-        if (newTasks) setTasks(newTasks);
-        if (currentGroup === oldName) setCurrentGroup(newName);
-      }
+      // Tasks reference groups by id, so only the group list changes
+      const newGroups = await renameGroups(id, newName);
+      setGroups(newGroups);
     } catch (err) {
       alert("グループ名の変更に失敗しました: " + (err as any)?.toString?.());
     }
@@ -133,8 +157,8 @@ function App() {
       // New task
       await handleAddTask(
         task.description,
-        task.due_date,
-        task.group,
+        task.due_date ?? "",
+        task.group_id,
         task.details,
         task.notification_minutes,
       );
@@ -156,6 +180,7 @@ function App() {
           onAddGroup={handleAddGroup}
           onDeleteGroup={handleDeleteGroup}
           onRenameGroup={handleRenameGroup}
+          onArchiveGroup={handleArchiveGroup}
           onOpenSettings={() => setShowSettings(true)}
         />
       </div>
@@ -173,7 +198,7 @@ function App() {
                 id: 0,
                 description: "",
                 due_date: date,
-                group: "",
+                group_id: null,
                 details: "",
                 completed: false,
                 subtasks: [],
@@ -185,7 +210,11 @@ function App() {
         ) : currentGroup === "__READING_MEMOS__" ? (
           <ReadingMemoView />
         ) : currentGroup === "__GANTT__" ? (
-          <GanttView tasks={tasks} onTaskUpdate={handleUpdateTask} />
+          <GanttView
+            tasks={tasks}
+            groups={groups}
+            onTaskUpdate={handleUpdateTask}
+          />
         ) : (
           <>
             <div className="input-section">
@@ -195,11 +224,12 @@ function App() {
             <div className="task-list-container">
               <TaskList
                 tasks={tasks}
+                groups={groups}
                 currentGroup={currentGroup}
                 onDelete={handleDeleteTask}
                 onComplete={handleCompleteTask}
                 onEdit={setEditingTask}
-                onTasksUpdate={setTasks}
+                onTaskChange={handleTaskChange}
               />
             </div>
           </>
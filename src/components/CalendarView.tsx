@@ -8,11 +8,11 @@ import {
   getCalendarEvents,
   updateCalendarEvents,
 } from "../tauri/calender_api";
-import { CalendarEvent } from "../types";
+import { CalendarEvent, TaskGroup } from "../types";
 
 interface CalendarViewProps {
   tasks: Task[];
-  groups: string[];
+  groups: TaskGroup[];
   onEdit: (task: Task) => void;
   onAddTask: (date: string) => void; // Callback to open Task dialog from App.tsx
 }
@@ -80,15 +80,31 @@ const CalendarView: React.FC<CalendarViewProps> = ({
 
   const handleEventSave = async (eventData: any) => {
     try {
-      let updatedEvents;
       if (eventData.id) {
         // Update
-        updatedEvents = await updateCalendarEvents(eventData);
+        const current = events.find((e) => e.id === eventData.id);
+        const updated = await updateCalendarEvents(
+          eventData.id,
+          {
+            title: eventData.title,
+            description: eventData.description,
+            start_datetime: eventData.startDatetime,
+            end_datetime: eventData.endDatetime,
+            all_day: eventData.allDay,
+            color: eventData.color ?? null,
+            recurrence_rule: eventData.recurrenceRule,
+            reminder_minutes: eventData.reminderMinutes,
+          },
+          current?.updated_at ?? null,
+        );
+        setEvents((prev) =>
+          prev.map((e) => (e.id === updated.id ? updated : e)),
+        );
       } else {
         // Create
-        updatedEvents = await createCalendarEvents(eventData);
+        const created = await createCalendarEvents(eventData);
+        setEvents((prev) => [...prev, created]);
       }
-      setEvents(updatedEvents);
       setShowEventDialog(false);
       setSelectedEvent(undefined);
     } catch (error) {
@@ -100,8 +116,8 @@ const CalendarView: React.FC<CalendarViewProps> = ({
   const handleEventDelete = async (id: string) => {
     if (!window.confirm("Delete this event?")) return;
     try {
-      const updatedEvents = await deleteCalendarEvents(id);
-      setEvents(updatedEvents);
+      const deletedId = await deleteCalendarEvents(id);
+      setEvents((prev) => prev.filter((e) => e.id !== deletedId));
       setShowEventDialog(false);
       setSelectedEvent(undefined);
     } catch (error) {
@@ -122,8 +138,8 @@ const CalendarView: React.FC<CalendarViewProps> = ({
 
       // Filter Tasks
       const dayTasks = tasks.filter((t) => {
-        const dateMatch = t.due_date.startsWith(dateStr);
-        const groupMatch = selectedGroup === "" || t.group === selectedGroup;
+        const dateMatch = (t.due_date ?? "").startsWith(dateStr);
+        const groupMatch = selectedGroup === "" || t.group_id === selectedGroup;
         return dateMatch && groupMatch;
       });
 
@@ -210,7 +226,9 @@ const CalendarView: React.FC<CalendarViewProps> = ({
               onChange={setSelectedGroup}
               options={[
                 { value: "", label: "All Groups" },
-                ...groups.map((g) => ({ value: g, label: g })),
+                ...groups
+                  .filter((g) => !g.archived)
+                  .map((g) => ({ value: g.id, label: g.name })),
               ]}
               style={{ width: "100%" }}
             />
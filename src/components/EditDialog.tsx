@@ -1,11 +1,11 @@
 import React, { useState } from "react";
-import { Task } from "./../types";
+import { Task, TaskGroup } from "./../types";
 import CustomDatePicker from "./CustomDatePicker";
 import CustomDropdown from "./CustomDropdown";
 
 interface EditDialogProps {
   task: Task;
-  existingGroups: string[];
+  existingGroups: TaskGroup[];
   allTasks?: Task[]; // Make optional to avoid breaking if not passed immediately, but we passed it
   onSave: (task: Task) => void;
   onCancel: () => void;
@@ -19,9 +19,9 @@ const EditDialog: React.FC<EditDialogProps> = ({
   onCancel,
 }) => {
   const [description, setDescription] = useState(task.description);
-  const [date, setDate] = useState(task.due_date);
+  const [date, setDate] = useState(task.due_date ?? "");
   const [startDate, setStartDate] = useState(task.start_date || "");
-  const [group, setGroup] = useState(task.group);
+  const [group, setGroup] = useState(task.group_id ?? "");
   const [details, setDetails] = useState(task.details);
   const [dependencies, setDependencies] = useState<number[]>(
     task.dependencies || [],
@@ -54,7 +54,7 @@ const EditDialog: React.FC<EditDialogProps> = ({
       description,
       due_date: date,
       start_date: startDate || undefined,
-      group,
+      group_id: group || null,
       details,
       notification_minutes:
         calculatedMinutes > 0 ? calculatedMinutes : undefined,
@@ -62,6 +62,9 @@ const EditDialog: React.FC<EditDialogProps> = ({
     });
   };
 
+  const groupName = (t: Task) =>
+    existingGroups.find((g) => g.id === t.group_id)?.name ?? "";
+
   const toggleDependency = (targetId: number) => {
     setDependencies((prev) => {
       if (prev.includes(targetId)) {
@@ -154,7 +157,9 @@ const EditDialog: React.FC<EditDialogProps> = ({
               onChange={setGroup}
               options={[
                 { value: "", label: "No Group" },
-                ...existingGroups.map((g) => ({ value: g, label: g })),
+                ...existingGroups
+                  .filter((g) => !g.archived || g.id === task.group_id)
+                  .map((g) => ({ value: g.id, label: g.name })),
               ]}
               style={{ width: "100%" }}
             />
@@ -230,7 +235,9 @@ const EditDialog: React.FC<EditDialogProps> = ({
                     <span
                       style={{ fontSize: "12px", color: "var(--text-primary)" }}
                     >
-                      {t.group ? `${t.group}: ${t.description}` : t.description}
+                      {groupName(t)
+                        ? `${groupName(t)}: ${t.description}`
+                        : t.description}
                     </span>
                   </div>
                 ))}
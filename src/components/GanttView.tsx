@@ -1,17 +1,22 @@
 import React, { useState, useEffect } from "react";
 import { ViewMode, Gantt, Task as GanttTask } from "gantt-task-react";
 import "gantt-task-react/dist/index.css";
-import { Task } from "./../types";
+import { Task, TaskGroup } from "./../types";
 import CustomDropdown from "./CustomDropdown";
 
 interface GanttViewProps {
   tasks: Task[];
+  groups?: TaskGroup[];
   onTaskUpdate: (task: Task) => void;
 }
 
 type SortOption = "default" | "startDate" | "dueDate" | "name";
 
-const GanttView: React.FC<GanttViewProps> = ({ tasks, onTaskUpdate }) => {
+const GanttView: React.FC<GanttViewProps> = ({
+  tasks,
+  groups = [],
+  onTaskUpdate,
+}) => {
   const [ganttTasks, setGanttTasks] = useState<GanttTask[]>([]);
   const [viewMode, setViewMode] = useState<ViewMode>(ViewMode.Day);
   const [sortOption, setSortOption] = useState<SortOption>("default");
@@ -26,12 +31,12 @@ const GanttView: React.FC<GanttViewProps> = ({ tasks, onTaskUpdate }) => {
       return !isNaN(s.getTime()) && !isNaN(e.getTime());
     });
 
-    // Build gantt tasks grouped by Task.group. Each group becomes a `project` task,
+    // Build gantt tasks grouped by Task.group_id. Each group becomes a `project` task,
     // and member tasks are regular `task` entries with `project` set to the project's id.
     const groupsMap: Record<string, Task[]> = {};
     const ungrouped: Task[] = [];
     filtered.forEach((t) => {
-      const key = (t.group || "").trim();
+      const key = t.group_id || "";
       if (key) {
         if (!groupsMap[key]) groupsMap[key] = [];
         groupsMap[key].push(t);
@@ -44,7 +49,7 @@ const GanttView: React.FC<GanttViewProps> = ({ tasks, onTaskUpdate }) => {
 
     // Helper to create GanttTask from Task
     const makeTask = (task: Task, projectId?: string): GanttTask => {
-      let endDate = new Date(task.due_date);
+      let endDate = new Date(task.due_date ?? "");
       if (isNaN(endDate.getTime())) endDate = new Date();
 
       let startDate = new Date();
@@ -92,18 +97,19 @@ const GanttView: React.FC<GanttViewProps> = ({ tasks, onTaskUpdate }) => {
     };
 
     // Create project entries for each non-empty group
-    Object.keys(groupsMap).forEach((groupName, idx) => {
-      const members = groupsMap[groupName];
+    Object.keys(groupsMap).forEach((groupId, idx) => {
+      const members = groupsMap[groupId];
+      const groupName = groups.find((g) => g.id === groupId)?.name ?? groupId;
       // compute min start and max end
       const starts = members
-        .map((m) => new Date(m.start_date || m.due_date))
+        .map((m) => new Date(m.start_date || m.due_date || ""))
         .filter((d) => !isNaN(d.getTime()));
       const ends = members
-        .map((m) => new Date(m.due_date))
+        .map((m) => new Date(m.due_date ?? ""))
         .filter((d) => !isNaN(d.getTime()));
       const projStart = new Date(Math.min(...starts.map((d) => d.getTime())));
       const projEnd = new Date(Math.max(...ends.map((d) => d.getTime())));
-      const projectId = `project-${idx}-${groupName}`;
+      const projectId = `project-${idx}-${groupId}`;
 
       // project progress: average of member progresses
       const progValues = members.map((m) => {
@@ -157,7 +163,7 @@ const GanttView: React.FC<GanttViewProps> = ({ tasks, onTaskUpdate }) => {
     } else {
       setGanttTasks([]);
     }
-  }, [tasks, sortOption, collapsedProjects]);
+  }, [tasks, groups, sortOption, collapsedProjects]);
 
   const onDateChange = (task: GanttTask) => {
     const originalTask = tasks.find((t) => t.id.toString() === task.id);
@@ -13,10 +13,10 @@ import { ContextMenuState } from "../types";
 
 interface SubtaskListProps {
   task: Task;
-  onTasksUpdate: (tasks: Task[]) => void;
+  onTaskChange: (task: Task) => void;
 }
 
-const SubtaskList: React.FC<SubtaskListProps> = ({ task, onTasksUpdate }) => {
+const SubtaskList: React.FC<SubtaskListProps> = ({ task, onTaskChange }) => {
   const [newSubtask, setNewSubtask] = useState("");
   const [isAdding, setIsAdding] = useState(false);
   const [editingId, setEditingId] = useState<number | null>(null);
@@ -53,20 +53,17 @@ const SubtaskList: React.FC<SubtaskListProps> = ({ task, onTasksUpdate }) => {
 
   const handleAddSubtask = async () => {
     if (!newSubtask.trim()) return;
-    const newTasks = await addSubtask(task.id, newSubtask.trim());
-    onTasksUpdate(newTasks);
+    onTaskChange(await addSubtask(task.id, newSubtask.trim()));
     setNewSubtask("");
     setIsAdding(false);
   };
 
   const handleToggleSubtask = async (subtaskId: number) => {
-    const newTasks = await toggleSubtask(task.id, subtaskId);
-    onTasksUpdate(newTasks);
+    onTaskChange(await toggleSubtask(task.id, subtaskId));
   };
 
   const handleDeleteSubtask = async (subtaskId: number) => {
-    const newTasks = await deleteSubtask(task.id, subtaskId);
-    onTasksUpdate(newTasks);
+    onTaskChange(await deleteSubtask(task.id, subtaskId));
   };
 
   const startEdit = (subtaskId: number, currentDescription: string) => {
@@ -85,13 +82,13 @@ const SubtaskList: React.FC<SubtaskListProps> = ({ task, onTasksUpdate }) => {
       return;
     }
     try {
-      const newTasks = await updateSubtask(
+      const updated = await updateSubtask(
         task.id,
         subtaskId,
         editDescription.trim(),
         completed,
       );
-      onTasksUpdate(newTasks);
+      onTaskChange(updated);
       cancelEdit();
     } catch (error) {
       console.error("Failed to update subtask:", error);
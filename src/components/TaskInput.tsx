@@ -1,17 +1,18 @@
 import React, { useState } from "react";
 import CustomDatePicker from "./CustomDatePicker";
 import CustomDropdown from "./CustomDropdown";
+import { TaskGroup } from "../types";
 
 interface TaskInputProps {
   onAddTask: (
     desc: string,
     date: string,
-    group: string,
+    groupId: string | null,
     details: string,
     notificationMinutes?: number,
     startDate?: string,
   ) => void;
-  existingGroups: string[];
+  existingGroups: TaskGroup[];
 }
 
 const TaskInput: React.FC<TaskInputProps> = ({ onAddTask, existingGroups }) => {
@@ -39,7 +40,7 @@ const TaskInput: React.FC<TaskInputProps> = ({ onAddTask, existingGroups }) => {
     onAddTask(
       description,
       date,
-      group,
+      group || null,
       details,
       totalMinutes > 0 ? totalMinutes : undefined,
       startDate || undefined,
@@ -108,7 +109,9 @@ const TaskInput: React.FC<TaskInputProps> = ({ onAddTask, existingGroups }) => {
             onChange={setGroup}
             options={[
               { value: "", label: "No Group" },
-              ...existingGroups.map((g) => ({ value: g, label: g })),
+              ...existingGroups
+                .filter((g) => !g.archived)
+                .map((g) => ({ value: g.id, label: g.name })),
             ]}
             style={{ width: "100%" }}
           />
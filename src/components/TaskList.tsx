@@ -1,29 +1,31 @@
 import React from "react";
-import { Task } from "../types";
+import { Task, TaskGroup } from "../types";
 import TaskRow from "./TaskRow";
 
 interface TaskListProps {
   tasks: Task[];
+  groups?: TaskGroup[];
   currentGroup: string;
   onDelete: (id: number) => void;
   onComplete: (id: number) => void;
   onEdit: (task: Task) => void;
-  onTasksUpdate: (tasks: Task[]) => void;
+  onTaskChange: (task: Task) => void;
 }
 
 const TaskList: React.FC<TaskListProps> = ({
   tasks,
+  groups = [],
   currentGroup,
   onDelete,
   onComplete,
   onEdit,
-  onTasksUpdate,
+  onTaskChange,
 }) => {
   const filteredTasks = tasks
     .filter((task) => {
       if (currentGroup === "__ALL__") return true;
-      if (currentGroup === "__NO_GROUP__") return !task.group;
-      return task.group === currentGroup;
+      if (currentGroup === "__NO_GROUP__") return !task.group_id;
+      return task.group_id === currentGroup;
     })
     .sort((a, b) => {
       if (a.completed === b.completed) return 0;
@@ -36,10 +38,11 @@ const TaskList: React.FC<TaskListProps> = ({
         <TaskRow
           key={task.id}
           task={task}
+          groupName={groups.find((g) => g.id === task.group_id)?.name}
           onDelete={onDelete}
           onComplete={onComplete}
           onEdit={onEdit}
-          onTasksUpdate={onTasksUpdate}
+          onTaskChange={onTaskChange}
         />
       ))}
     </div>
@@ -4,18 +4,20 @@ import SubtaskList from "./SubtaskList";
 
 interface TaskRowProps {
   task: Task;
+  groupName?: string;
   onDelete: (id: number) => void;
   onComplete: (id: number) => void;
   onEdit: (task: Task) => void;
-  onTasksUpdate: (tasks: Task[]) => void;
+  onTaskChange: (task: Task) => void;
 }
 
 const TaskRow: React.FC<TaskRowProps> = ({
   task,
+  groupName,
   onDelete,
   onComplete,
   onEdit,
-  onTasksUpdate,
+  onTaskChange,
 }) => {
   const [expanded, setExpanded] = useState(false);
 
@@ -48,11 +50,11 @@ const TaskRow: React.FC<TaskRowProps> = ({
             )}
             <span className="task-expand-icon">{expanded ? "▲" : "▼"}</span>
           </div>
-          {(task.due_date || task.group) && (
+          {(task.due_date || groupName) && (
             <div className="task-meta">
               {task.due_date && `Due: ${task.due_date}`}
-              {task.due_date && task.group && " | "}
-              {task.group && `Group: ${task.group}`}
+              {task.due_date && groupName && " | "}
+              {groupName && `Group: ${groupName}`}
             </div>
           )}
         </div>
@@ -75,7 +77,7 @@ const TaskRow: React.FC<TaskRowProps> = ({
               <div className="task-details-text">{task.details}</div>
             </div>
           )}
-          <SubtaskList task={task} onTasksUpdate={onTasksUpdate} />
+          <SubtaskList task={task} onTaskChange={onTaskChange} />
         </div>
       )}
     </div>
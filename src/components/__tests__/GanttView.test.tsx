@@ -40,7 +40,7 @@ describe("GanttView", () => {
       description: "Test Task 1",
       due_date: "2023-12-31",
       start_date: "2023-12-01",
-      group: "Work",
+      group_id: "work",
       details: "",
       completed: false,
       // notified: false, // Removed as it is not in Task interface
@@ -28,7 +28,7 @@ describe("Sidebar", () => {
       id: 1,
       description: "Task 1",
       due_date: "",
-      group: "Work",
+      group_id: "work",
       details: "",
       completed: false,
       subtasks: [],
@@ -37,7 +37,7 @@ describe("Sidebar", () => {
       id: 2,
       description: "Task 2",
       due_date: "",
-      group: "",
+      group_id: null,
       details: "",
       completed: false,
       subtasks: [],
@@ -52,7 +52,21 @@ describe("Sidebar", () => {
     onOpenSettings: vi.fn(),
   };
 
-  const groups = ["Work", "Personal"];
+  const makeGroup = (id: string, name: string, sortOrder: number) => ({
+    id,
+    name,
+    color: null,
+    icon: null,
+    sort_order: sortOrder,
+    notification_minutes: null,
+    archived: false,
+    parent_id: null,
+  });
+
+  const groups = [
+    makeGroup("work", "Work", 0),
+    makeGroup("personal", "Personal", 1),
+  ];
 
   // beforeEach(() => {
   //     vi.clearAllMocks();
@@ -129,7 +143,26 @@ describe("Sidebar", () => {
     );
 
     fireEvent.click(screen.getByText("Work"));
-    expect(mockHandlers.onSelectGroup).toHaveBeenCalledWith("Work");
+    expect(mockHandlers.onSelectGroup).toHaveBeenCalledWith("work");
+  });
+
+  it("hides archived groups and indents sub-groups", () => {
+    render(
+      <Sidebar
+        tasks={mockTasks}
+        groups={[
+          ...groups,
+          { ...makeGroup("sprint", "Sprint", 2), parent_id: "work" },
+          { ...makeGroup("old", "Old", 3), archived: true },
+        ]}
+        currentGroup="__ALL__"
+        {...mockHandlers}
+      />,
+    );
+
+    expect(screen.queryByText("Old")).not.toBeInTheDocument();
+    const sprint = screen.getByText("Sprint").closest(".sidebar-item");
+    expect(sprint).toHaveStyle({ paddingLeft: "16px" });
   });
 
   it("shows add group input when + is clicked", () => {
@@ -17,13 +17,13 @@ vi.mock("../ui/ContextMenu", () => ({
 }));
 
 describe("SubtaskList", () => {
-  const mockOnTasksUpdate = vi.fn();
+  const mockOnTaskChange = vi.fn();
 
   const taskWithSubtasks = {
     id: 1,
     description: "Main Task",
     due_date: "",
-    group: "",
+    group_id: null,
     details: "",
     completed: false,
     subtasks: [
@@ -36,7 +36,7 @@ describe("SubtaskList", () => {
     id: 2,
     description: "Task without subtasks",
     due_date: "",
-    group: "",
+    group_id: null,
     details: "",
     completed: false,
     subtasks: [],
@@ -48,7 +48,7 @@ describe("SubtaskList", () => {
 
   it("renders subtasks correctly", () => {
     render(
-      <SubtaskList task={taskWithSubtasks} onTasksUpdate={mockOnTasksUpdate} />,
+      <SubtaskList task={taskWithSubtasks} onTaskChange={mockOnTaskChange} />,
     );
 
     expect(screen.getByText("Subtask 1")).toBeInTheDocument();
@@ -57,7 +57,7 @@ describe("SubtaskList", () => {
 
   it("shows progress bar with correct count", () => {
     render(
-      <SubtaskList task={taskWithSubtasks} onTasksUpdate={mockOnTasksUpdate} />,
+      <SubtaskList task={taskWithSubtasks} onTaskChange={mockOnTaskChange} />,
     );
 
     expect(screen.getByText("1/2")).toBeInTheDocument();
@@ -67,7 +67,7 @@ describe("SubtaskList", () => {
     render(
       <SubtaskList
         task={taskWithoutSubtasks}
-        onTasksUpdate={mockOnTasksUpdate}
+        onTaskChange={mockOnTaskChange}
       />,
     );
 
@@ -78,7 +78,7 @@ describe("SubtaskList", () => {
     render(
       <SubtaskList
         task={taskWithoutSubtasks}
-        onTasksUpdate={mockOnTasksUpdate}
+        onTaskChange={mockOnTaskChange}
       />,
     );
 
@@ -91,7 +91,7 @@ describe("SubtaskList", () => {
 
   it("renders checkbox for each subtask", () => {
     render(
-      <SubtaskList task={taskWithSubtasks} onTasksUpdate={mockOnTasksUpdate} />,
+      <SubtaskList task={taskWithSubtasks} onTaskChange={mockOnTaskChange} />,
     );
 
     const checkboxes = screen.getAllByRole("checkbox");
@@ -102,7 +102,7 @@ describe("SubtaskList", () => {
 
   it("shows completed subtask with completed style", () => {
     render(
-      <SubtaskList task={taskWithSubtasks} onTasksUpdate={mockOnTasksUpdate} />,
+      <SubtaskList task={taskWithSubtasks} onTaskChange={mockOnTaskChange} />,
     );
 
     const completedSubtask = screen.getByText("Subtask 2");
@@ -113,7 +113,7 @@ describe("SubtaskList", () => {
     render(
       <SubtaskList
         task={taskWithoutSubtasks}
-        onTasksUpdate={mockOnTasksUpdate}
+        onTaskChange={mockOnTaskChange}
       />,
     );
 
@@ -124,7 +124,7 @@ describe("SubtaskList", () => {
     render(
       <SubtaskList
         task={taskWithoutSubtasks}
-        onTasksUpdate={mockOnTasksUpdate}
+        onTaskChange={mockOnTaskChange}
       />,
     );
 
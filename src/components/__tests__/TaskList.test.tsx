@@ -30,7 +30,7 @@ describe("TaskList", () => {
       id: 1,
       description: "Task 1",
       due_date: "",
-      group: "",
+      group_id: null,
       details: "",
       completed: false,
       subtasks: [],
@@ -39,7 +39,7 @@ describe("TaskList", () => {
       id: 2,
       description: "Task 2",
       due_date: "",
-      group: "",
+      group_id: null,
       details: "",
       completed: true,
       subtasks: [],
@@ -50,7 +50,7 @@ describe("TaskList", () => {
     onDelete: vi.fn(),
     onComplete: vi.fn(),
     onEdit: vi.fn(),
-    onTasksUpdate: vi.fn(),
+    onTaskChange: vi.fn(),
   };
 
   beforeEach(() => {
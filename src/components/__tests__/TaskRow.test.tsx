@@ -12,7 +12,7 @@ describe("TaskRow", () => {
     id: 1,
     description: "Test Task",
     due_date: "2023-01-01",
-    group: "Test Group",
+    group_id: "test-group",
     details: "Some detauls",
     completed: false,
     // notified: false, // Removed as it is not in Task interface
@@ -24,11 +24,13 @@ describe("TaskRow", () => {
     onDelete: vi.fn(),
     onComplete: vi.fn(),
     onEdit: vi.fn(),
-    onTasksUpdate: vi.fn(),
+    onTaskChange: vi.fn(),
   };
 
   it("renders task information correctly", () => {
-    render(<TaskRow task={mockTask} {...mockHandlers} />);
+    render(
+      <TaskRow task={mockTask} groupName="Test Group" {...mockHandlers} />,
+    );
 
     expect(screen.getByText("Test Task")).toBeInTheDocument();
     expect(screen.getByText(/Due: 2023-01-01/)).toBeInTheDocument();
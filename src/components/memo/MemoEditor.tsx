@@ -3,11 +3,17 @@
 // This component provides a markdown editor for memos with auto‑save and tag management.
 
 import React, { useState, useEffect, useCallback } from "react";
-import ReactMarkdown from "react-markdown";
+import ReactMarkdown, { defaultUrlTransform } from "react-markdown";
 import remarkGfm from "remark-gfm";
 import { Prism as SyntaxHighlighter } from "react-syntax-highlighter";
 import { vscDarkPlus } from "react-syntax-highlighter/dist/esm/styles/prism";
-import { Memo, Folder } from "../../types";
+import { Backlink, Memo, Folder } from "../../types";
+import { getBacklinks } from "../../tauri/memo_api";
+import {
+  addAttachmentFromBytes,
+  attachmentUrl,
+  getAttachmentsDir,
+} from "../../tauri/attachment_api";
 import CustomDropdown from "../CustomDropdown";
 
 interface MemoEditorProps {
@@ -38,6 +44,54 @@ const MemoEditor: React.FC<MemoEditorProps> = ({
   const [autoSaveTimer, setAutoSaveTimer] = useState<ReturnType<
     typeof setTimeout
   > | null>(null);
+  const [backlinks, setBacklinks] = useState<Backlink[]>([]);
+  const [attachmentsDir, setAttachmentsDir] = useState<string | null>(null);
+
+  useEffect(() => {
+    getAttachmentsDir()
+      .then(setAttachmentsDir)
+      .catch(() => setAttachmentsDir(null));
+  }, []);
+
+  // Pasted images are stored as attachments and referenced from the content
+  const handlePaste = async (
+    e: React.ClipboardEvent<HTMLTextAreaElement>,
+  ) => {
+    const files = Array.from(e.clipboardData.files);
+    if (files.length === 0) {
+      return;
+    }
+    e.preventDefault();
+    const textarea = e.currentTarget;
+    const { selectionStart, selectionEnd } = textarea;
+    const markdown: string[] = [];
+    for (const file of files) {
+      try {
+        const bytes = new Uint8Array(await file.arrayBuffer());
+        const added = await addAttachmentFromBytes(bytes, file.name, file.type);
+        markdown.push(added.markdown);
+      } catch {
+        // Already logged by the API wrapper; skip the file
+      }
+    }
+    setContent(
+      (current) =>
+        current.slice(0, selectionStart) +
+        markdown.join("\n") +
+        current.slice(selectionEnd),
+    );
+  };
+
+  // Backlinks come from the backend link index; refresh whenever memos change
+  useEffect(() => {
+    if (!memo) {
+      setBacklinks([]);
+      return;
+    }
+    getBacklinks(memo.id)
+      .then(setBacklinks)
+      .catch(() => setBacklinks([]));
+  }, [memo, allMemos]);
 
   useEffect(() => {
     if (memo) {
@@ -121,25 +175,19 @@ const MemoEditor: React.FC<MemoEditorProps> = ({
     setTags(tags.filter((t) => t !== tag));
   };
 
-  // Replace [[memo-title]] with clickable links
+  // Replace [[title]], [[id]] and [[target|alias]] with clickable links
   const renderContentWithLinks = (text: string) => {
-    const linkRegex = /\[\[([^\]]+)\]\]/g;
-    return text.replace(linkRegex, (match, title) => {
-      const linkedMemo = allMemos.find(
-        (m) => m.title.toLowerCase() === title.toLowerCase(),
-      );
-      return linkedMemo ? `[${title}](#memo-${linkedMemo.id})` : match;
+    const linkRegex = /\[\[([^\]|]+)(?:\|([^\]]+))?\]\]/g;
+    return text.replace(linkRegex, (match, target, alias) => {
+      const key = target.trim().toLowerCase();
+      const linkedMemo =
+        allMemos.find((m) => m.id === target.trim()) ??
+        allMemos.find((m) => m.title.toLowerCase() === key);
+      const label = alias ?? target;
+      return linkedMemo ? `[${label}](#memo-${linkedMemo.id})` : match;
     });
   };
 
-  // Find backlinks (memos that link to this one)
-  const getBacklinks = () => {
-    if (!memo) return [];
-    return allMemos.filter(
-      (m) => m.id !== memo.id && m.content.includes(`[[${memo.title}]]`),
-    );
-  };
-
   if (!memo) {
     return (
       <div className="memo-editor empty">
@@ -150,8 +198,6 @@ const MemoEditor: React.FC<MemoEditorProps> = ({
     );
   }
 
-  const backlinks = getBacklinks();
-
   return (
     <div className="memo-editor">
       <div className="memo-editor-header">
@@ -217,6 +263,7 @@ const MemoEditor: React.FC<MemoEditorProps> = ({
           <textarea
             value={content}
             onChange={(e) => setContent(e.target.value)}
+            onPaste={handlePaste}
             placeholder="Write your memo in Markdown... Use [[memo-title]] to link to other memos."
             className="memo-textarea"
           />
@@ -224,6 +271,11 @@ const MemoEditor: React.FC<MemoEditorProps> = ({
         <div className="memo-preview-pane">
           <ReactMarkdown
             remarkPlugins={[remarkGfm]}
+            urlTransform={(url) =>
+              attachmentsDir && url.startsWith("attachment:")
+                ? attachmentUrl(attachmentsDir, url)
+                : defaultUrlTransform(url)
+            }
             components={{
               code({ node, inline, className, children, ...props }: any) {
                 const match = /language-(\w+)/.exec(className || "");
@@ -279,9 +331,18 @@ const MemoEditor: React.FC<MemoEditorProps> = ({
         <div className="memo-backlinks">
           <h4>Linked from:</h4>
           <ul className="memo-backlinks-list">
-            {backlinks.map((link) => (
-              <li key={link.id}>{link.title}</li>
-            ))}
+            {backlinks.map((link) => {
+              const source = allMemos.find((m) => m.id === link.memo_id);
+              return (
+                <li
+                  key={link.memo_id}
+                  title={link.lines.join("\n")}
+                  onClick={() => source && onSelectMemo(source)}
+                >
+                  {link.title}
+                </li>
+              );
+            })}
           </ul>
         </div>
       )}
@@ -3,7 +3,7 @@
 // This component displays a list of memos with search functionality and filtering.
 
 import React from "react";
-import { Memo } from "../../types";
+import { Memo, Snippet } from "../../types";
 
 interface MemoListProps {
   memos: Memo[];
@@ -12,6 +12,8 @@ interface MemoListProps {
   onCreateMemo: () => void;
   searchQuery: string;
   onSearchChange: (query: string) => void;
+  // Search snippets by memo id, shown instead of the plain preview
+  snippets?: Record<string, Snippet>;
 }
 
 const MemoList: React.FC<MemoListProps> = ({
@@ -21,6 +23,7 @@ const MemoList: React.FC<MemoListProps> = ({
   onCreateMemo,
   searchQuery,
   onSearchChange,
+  snippets = {},
 }) => {
   const formatDate = (dateStr: string) => {
     const date = new Date(dateStr);
@@ -34,6 +37,20 @@ const MemoList: React.FC<MemoListProps> = ({
       : stripped;
   };
 
+  // Highlight ranges count characters, not UTF-16 code units
+  const renderSnippet = ({ text, highlights }: Snippet) => {
+    const chars = Array.from(text);
+    const parts: React.ReactNode[] = [];
+    let cursor = 0;
+    highlights.forEach(([start, end], idx) => {
+      parts.push(chars.slice(cursor, start).join(""));
+      parts.push(<mark key={idx}>{chars.slice(start, end).join("")}</mark>);
+      cursor = end;
+    });
+    parts.push(chars.slice(cursor).join(""));
+    return parts;
+  };
+
   return (
     <div className="memo-list">
       <div className="memo-list-header">
@@ -60,7 +77,9 @@ const MemoList: React.FC<MemoListProps> = ({
             >
               <div className="memo-list-item-title">{memo.title}</div>
               <div className="memo-list-item-preview">
-                {getPreview(memo.content)}
+                {snippets[memo.id]
+                  ? renderSnippet(snippets[memo.id])
+                  : getPreview(memo.content)}
               </div>
               <div className="memo-list-item-footer">
                 <div className="memo-list-item-tags">
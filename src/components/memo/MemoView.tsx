@@ -7,14 +7,16 @@ import { invoke } from "@tauri-apps/api/core";
 import FolderTree from "./FolderTree";
 import MemoList from "./MemoList";
 import MemoEditor from "./MemoEditor";
-import type { Memo, Folder } from "../../types";
+import type { Memo, Folder, MemoSearchHit } from "../../types";
 import {
   createMemo,
   deleteMemo,
   getMemos,
+  searchMemos,
   updateMemo,
 } from "../../tauri/memo_api";
 import { getFolders } from "../../tauri/task_api";
+import { listenMemoEvents, removeById, upsertById } from "../../tauri/events";
 
 const MemoView: React.FC = () => {
   const [memos, setMemos] = useState<Memo[]>([]);
@@ -22,12 +24,39 @@ const MemoView: React.FC = () => {
   const [selectedMemo, setSelectedMemo] = useState<Memo | null>(null);
   const [selectedFolder, setSelectedFolder] = useState<string | null>(null);
   const [searchQuery, setSearchQuery] = useState("");
+  // Ranked results from the backend index; null when not searching
+  const [searchHits, setSearchHits] = useState<MemoSearchHit[] | null>(null);
 
   useEffect(() => {
     loadMemos();
     loadFolders();
+    // Memos changed in other windows arrive as memo-* events
+    const unlisten = listenMemoEvents(setMemos);
+    return () => {
+      unlisten.then((fn) => fn());
+    };
   }, []);
 
+  useEffect(() => {
+    const query = searchQuery.trim();
+    if (!query) {
+      setSearchHits(null);
+      return;
+    }
+    let cancelled = false;
+    const timer = setTimeout(() => {
+      searchMemos(query)
+        .then((hits) => {
+          if (!cancelled) setSearchHits(hits);
+        })
+        .catch((error) => console.error("Failed to search memos:", error));
+    }, 150);
+    return () => {
+      cancelled = true;
+      clearTimeout(timer);
+    };
+  }, [searchQuery, memos]);
+
   const loadMemos = async () => {
     try {
       const loaded = await getMemos();
@@ -48,11 +77,9 @@ const MemoView: React.FC = () => {
 
   const handleCreateMemo = async () => {
     try {
-      const newMemos = await createMemo("Untitled", "", selectedFolder, []);
-      setMemos(newMemos);
-      if (newMemos.length > 0) {
-        setSelectedMemo(newMemos[newMemos.length - 1]);
-      }
+      const created = await createMemo("Untitled", "", selectedFolder, []);
+      setMemos((prev) => upsertById(prev, created));
+      setSelectedMemo(created);
     } catch (error) {
       console.error("Failed to create memo:", error);
     }
@@ -67,8 +94,8 @@ const MemoView: React.FC = () => {
         memo.folder_id,
         memo.tags,
       );
-      setMemos(updated);
-      setSelectedMemo(memo);
+      setMemos((prev) => upsertById(prev, updated));
+      setSelectedMemo(updated);
     } catch (error) {
       console.error("Failed to save memo:", error);
     }
@@ -76,8 +103,8 @@ const MemoView: React.FC = () => {
 
   const handleDeleteMemo = async (id: string) => {
     try {
-      const updated = await deleteMemo(id);
-      setMemos(updated);
+      await deleteMemo(id);
+      setMemos((prev) => removeById(prev, id));
       if (selectedMemo?.id === id) {
         setSelectedMemo(null);
       }
@@ -119,17 +146,16 @@ const MemoView: React.FC = () => {
     }
   };
 
-  const filteredMemos = memos.filter((memo) => {
-    const matchesFolder = !selectedFolder || memo.folder_id === selectedFolder;
-    const matchesSearch =
-      !searchQuery ||
-      memo.title.toLowerCase().includes(searchQuery.toLowerCase()) ||
-      memo.content.toLowerCase().includes(searchQuery.toLowerCase()) ||
-      memo.tags.some((tag) =>
-        tag.toLowerCase().includes(searchQuery.toLowerCase()),
-      );
-    return matchesFolder && matchesSearch;
-  });
+  const inSelectedFolder = (memo: Memo) =>
+    !selectedFolder || memo.folder_id === selectedFolder;
+  const filteredMemos = searchHits
+    ? searchHits
+        .flatMap((hit) => memos.filter((memo) => memo.id === hit.id))
+        .filter(inSelectedFolder)
+    : memos.filter(inSelectedFolder);
+  const snippets = Object.fromEntries(
+    (searchHits ?? []).map((hit) => [hit.id, hit.snippet]),
+  );
 
   return (
     <div className="memo-view">
@@ -149,6 +175,7 @@ const MemoView: React.FC = () => {
           onCreateMemo={handleCreateMemo}
           searchQuery={searchQuery}
           onSearchChange={setSearchQuery}
+          snippets={snippets}
         />
       </div>
       <MemoEditor
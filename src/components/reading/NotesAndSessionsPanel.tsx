@@ -5,13 +5,11 @@ import SessionsList from "./SessionsList";
 
 interface NotesAndSessionsPanelProps {
   book: ReadingBook | null;
-  onUpdateBook: () => void;
 }
 
-function NotesAndSessionsPanel({
-  book,
-  onUpdateBook,
-}: NotesAndSessionsPanelProps) {
+// Notes and sessions reach the book through book-note-* / book-session-*
+// events, so the lists only call the API
+function NotesAndSessionsPanel({ book }: NotesAndSessionsPanelProps) {
   const [activeTab, setActiveTab] = useState<"notes" | "sessions">("notes");
 
   if (!book) {
@@ -41,9 +39,9 @@ function NotesAndSessionsPanel({
 
       <div className="panel-content">
         {activeTab === "notes" ? (
-          <NotesList book={book} onUpdateBook={onUpdateBook} />
+          <NotesList book={book} />
         ) : (
-          <SessionsList book={book} onUpdateBook={onUpdateBook} />
+          <SessionsList book={book} />
         )}
       </div>
     </div>
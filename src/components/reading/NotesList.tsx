@@ -10,10 +10,9 @@ import {
 
 interface NotesListProps {
   book: ReadingBook;
-  onUpdateBook: () => void;
 }
 
-function NotesList({ book, onUpdateBook }: NotesListProps) {
+function NotesList({ book }: NotesListProps) {
   const [showEditor, setShowEditor] = useState(false);
   const [editingNote, setEditingNote] = useState<ReadingNote | null>(null);
 
@@ -24,7 +23,6 @@ function NotesList({ book, onUpdateBook }: NotesListProps) {
   ) => {
     try {
       await addReadingNote(book.id, pageNumber, quote, comment);
-      onUpdateBook();
       setShowEditor(false);
     } catch (error) {
       console.error("Failed to add note:", error);
@@ -39,7 +37,6 @@ function NotesList({ book, onUpdateBook }: NotesListProps) {
   ) => {
     try {
       await updateReadingNote(book.id, noteId, pageNumber, quote, comment);
-      onUpdateBook();
       setEditingNote(null);
     } catch (error) {
       console.error("Failed to update note:", error);
@@ -51,7 +48,6 @@ function NotesList({ book, onUpdateBook }: NotesListProps) {
 
     try {
       await deleteReadingNote(book.id, noteId);
-      onUpdateBook();
     } catch (error) {
       console.error("Failed to delete note:", error);
     }
@@ -9,32 +9,31 @@ import {
   getReadingBooks,
   updateReadingBooks,
 } from "../../tauri/reading_api";
+import { listenBookEvents, removeById, upsertById } from "../../tauri/events";
 
 function ReadingMemoView() {
   const [books, setBooks] = useState<ReadingBook[]>([]);
-  const [selectedBook, setSelectedBook] = useState<ReadingBook | null>(null);
+  const [selectedBookId, setSelectedBookId] = useState<string | null>(null);
   const [searchQuery, setSearchQuery] = useState("");
   const [statusFilter, setStatusFilter] = useState<ReadingStatus | "all">(
     "all",
   );
 
+  // 選択中の書籍は一覧から引くため、変更イベントで常に最新になる
+  const selectedBook = books.find((b) => b.id === selectedBookId) ?? null;
+
   useEffect(() => {
     loadBooks();
+    const unlisten = listenBookEvents(setBooks);
+    return () => {
+      unlisten.then((fn) => fn());
+    };
   }, []);
 
   async function loadBooks() {
     try {
-      const currentBookId = selectedBook?.id;
       const loadedBooks = await getReadingBooks();
       setBooks(loadedBooks);
-
-      // 選択中の書籍があれば再選択（最新データに更新）
-      if (currentBookId) {
-        const updatedBook = loadedBooks.find((b) => b.id === currentBookId);
-        if (updatedBook) {
-          setSelectedBook(updatedBook);
-        }
-      }
     } catch (error) {
       console.error("Failed to load reading books:", error);
     }
@@ -60,15 +59,13 @@ function ReadingMemoView() {
 
   const handleCreateBook = async (title: string) => {
     try {
-      const updatedBooks = await createReadingBooks(title);
-      setBooks(updatedBooks);
+      const created = await createReadingBooks(title);
+      setBooks((prev) => upsertById(prev, created));
       // 新しく作成した本を選択
-      if (updatedBooks.length > 0) {
-        setSelectedBook(updatedBooks[updatedBooks.length - 1]);
-        // フィルタをリセットして新規作成した本が表示されるようにする
-        setSearchQuery("");
-        setStatusFilter("all");
-      }
+      setSelectedBookId(created.id);
+      // フィルタをリセットして新規作成した本が表示されるようにする
+      setSearchQuery("");
+      setStatusFilter("all");
     } catch (error) {
       console.error("Failed to create book:", error);
     }
@@ -76,30 +73,28 @@ function ReadingMemoView() {
 
   const handleUpdateBook = async (book: ReadingBook) => {
     try {
-      const updatedBooks = await updateReadingBooks(
+      const updated = await updateReadingBooks(
         book.id,
-        book.title,
-        book.author || null,
-        book.isbn || null,
-        book.publisher || null,
-        book.published_year || null,
-        book.cover_image_url || null,
-        book.genres,
-        book.status,
-        book.start_date || null,
-        book.finish_date || null,
-        book.total_pages || null,
-        book.current_page || null,
-        book.rating || null,
-        book.summary,
-        book.tags,
+        {
+          title: book.title,
+          author: book.author || null,
+          isbn: book.isbn || null,
+          publisher: book.publisher || null,
+          published_year: book.published_year || null,
+          cover_image_url: book.cover_image_url || null,
+          genres: book.genres,
+          status: book.status,
+          start_date: book.start_date || null,
+          finish_date: book.finish_date || null,
+          total_pages: book.total_pages || null,
+          current_page: book.current_page || null,
+          rating: book.rating || null,
+          summary: book.summary,
+          tags: book.tags,
+        },
+        book.updated_at,
       );
-      setBooks(updatedBooks);
-      // 更新された書籍を再選択
-      const updated = updatedBooks.find((b) => b.id === book.id);
-      if (updated) {
-        setSelectedBook(updated);
-      }
+      setBooks((prev) => upsertById(prev, updated));
     } catch (error) {
       console.error("Failed to update book:", error);
     }
@@ -107,10 +102,10 @@ function ReadingMemoView() {
 
   const handleDeleteBook = async (id: string) => {
     try {
-      const updatedBooks = await deleteReadingBooks(id);
-      setBooks(updatedBooks);
-      if (selectedBook?.id === id) {
-        setSelectedBook(null);
+      await deleteReadingBooks(id);
+      setBooks((prev) => removeById(prev, id));
+      if (selectedBookId === id) {
+        setSelectedBookId(null);
       }
     } catch (error) {
       console.error("Failed to delete book:", error);
@@ -122,7 +117,7 @@ function ReadingMemoView() {
       <BookList
         books={filteredBooks}
         selectedBook={selectedBook}
-        onSelectBook={setSelectedBook}
+        onSelectBook={(book) => setSelectedBookId(book.id)}
         onCreateBook={handleCreateBook}
         searchQuery={searchQuery}
         onSearchChange={setSearchQuery}
@@ -134,7 +129,7 @@ function ReadingMemoView() {
         onUpdateBook={handleUpdateBook}
         onDeleteBook={handleDeleteBook}
       />
-      <NotesAndSessionsPanel book={selectedBook} onUpdateBook={loadBooks} />
+      <NotesAndSessionsPanel book={selectedBook} />
     </div>
   );
 }
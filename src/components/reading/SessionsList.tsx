@@ -11,10 +11,9 @@ import {
 
 interface SessionsListProps {
   book: ReadingBook;
-  onUpdateBook: () => void;
 }
 
-function SessionsList({ book, onUpdateBook }: SessionsListProps) {
+function SessionsList({ book }: SessionsListProps) {
   const [showEditor, setShowEditor] = useState(false);
   const [editingSession, setEditingSession] = useState<ReadingSession | null>(
     null,
@@ -38,7 +37,6 @@ function SessionsList({ book, onUpdateBook }: SessionsListProps) {
         durationMinutes,
         memo,
       );
-      onUpdateBook();
       setShowEditor(false);
     } catch (error) {
       console.error("Failed to add session:", error);
@@ -65,7 +63,6 @@ function SessionsList({ book, onUpdateBook }: SessionsListProps) {
         durationMinutes,
         memo,
       );
-      onUpdateBook();
       setEditingSession(null);
     } catch (error) {
       console.error("Failed to update session:", error);
@@ -77,7 +74,6 @@ function SessionsList({ book, onUpdateBook }: SessionsListProps) {
 
     try {
       await deleteReadingSession(book.id, sessionId);
-      onUpdateBook();
     } catch (error) {
       console.error("Failed to delete session:", error);
     }
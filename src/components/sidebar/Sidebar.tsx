@@ -3,20 +3,48 @@
 // This component renders the navigation sidebar with group selection and settings access.
 
 import React from "react";
-import { Task } from "../../types";
+import { Task, TaskGroup } from "../../types";
 import ContextMenu from "../ui/ContextMenu";
 
 interface SidebarProps {
   tasks: Task[];
-  groups: string[];
+  groups: TaskGroup[];
   currentGroup: string;
   onSelectGroup: (group: string) => void;
   onAddGroup: (name: string) => void;
-  onDeleteGroup: (name: string) => void;
-  onRenameGroup: (oldName: string, newName: string) => void;
+  onDeleteGroup: (id: string) => void;
+  onRenameGroup: (id: string, newName: string) => void;
+  onArchiveGroup?: (id: string) => void;
   onOpenSettings: () => void;
 }
 
+// Flatten the group tree (sorted by sort_order) into display rows with depth.
+// Archived groups and everything below them are hidden.
+const visibleGroupRows = (
+  groups: TaskGroup[],
+): { group: TaskGroup; depth: number }[] => {
+  const rows: { group: TaskGroup; depth: number }[] = [];
+  const sorted = [...groups].sort((a, b) => a.sort_order - b.sort_order);
+  const ids = new Set(groups.map((g) => g.id));
+  const seen = new Set<string>();
+  const visit = (parentId: string | null, depth: number) => {
+    sorted
+      .filter((g) =>
+        parentId === null
+          ? !g.parent_id || !ids.has(g.parent_id)
+          : g.parent_id === parentId,
+      )
+      .filter((g) => !g.archived && !seen.has(g.id))
+      .forEach((g) => {
+        seen.add(g.id);
+        rows.push({ group: g, depth });
+        visit(g.id, depth + 1);
+      });
+  };
+  visit(null, 0);
+  return rows;
+};
+
 const Sidebar: React.FC<SidebarProps> = ({
   tasks,
   groups,
@@ -25,16 +53,17 @@ const Sidebar: React.FC<SidebarProps> = ({
   onAddGroup,
   onDeleteGroup,
   onRenameGroup,
+  onArchiveGroup,
   onOpenSettings,
 }) => {
-  const hasNoGroup = tasks.some((t) => !t.group);
+  const hasNoGroup = tasks.some((t) => !t.group_id);
   const [newGroupName, setNewGroupName] = React.useState("");
   const [isAdding, setIsAdding] = React.useState(false);
   const inputRef = React.useRef<HTMLInputElement>(null);
   const [contextMenu, setContextMenu] = React.useState<{
     x: number;
     y: number;
-    group: string | null;
+    group: TaskGroup | null;
   } | null>(null);
 
   React.useEffect(() => {
@@ -136,24 +165,29 @@ const Sidebar: React.FC<SidebarProps> = ({
         </div>
       )}
 
-      {groups.map((group) => (
+      {visibleGroupRows(groups).map(({ group, depth }) => (
         <div
-          key={group}
-          className={`${getItemClass(currentGroup === group)} justify-between`}
-          onClick={() => onSelectGroup(group)}
+          key={group.id}
+          className={`${getItemClass(currentGroup === group.id)} justify-between`}
+          style={depth > 0 ? { paddingLeft: `${depth * 16}px` } : undefined}
+          onClick={() => onSelectGroup(group.id)}
           onContextMenu={(e) => {
             e.preventDefault();
             setContextMenu({ x: e.clientX, y: e.clientY, group });
           }}
         >
           <div className="sidebar-group-content">
-            <span>📁</span> {group}
+            <span style={group.color ? { color: group.color } : undefined}>
+              {group.icon || "📁"}
+            </span>{" "}
+            {group.name}
           </div>
           <div className="sidebar-group-actions">
             <button
               onClick={(e) => {
                 e.stopPropagation();
-                if (confirm(`Delete group "${group}"?`)) onDeleteGroup(group);
+                if (confirm(`Delete group "${group.name}"?`))
+                  onDeleteGroup(group.id);
               }}
               className="sidebar-delete-btn"
               title="Delete"
@@ -173,18 +207,29 @@ const Sidebar: React.FC<SidebarProps> = ({
             {
               label: "Rename",
               onClick: () => {
-                const g = contextMenu.group as string;
-                const newName = prompt(`Rename group "${g}" to:`, g);
-                if (newName && newName.trim() && newName.trim() !== g)
-                  onRenameGroup(g, newName.trim());
+                const g = contextMenu.group as TaskGroup;
+                const newName = prompt(`Rename group "${g.name}" to:`, g.name);
+                if (newName && newName.trim() && newName.trim() !== g.name)
+                  onRenameGroup(g.id, newName.trim());
               },
             },
+            ...(onArchiveGroup
+              ? [
+                  {
+                    label: "Archive",
+                    onClick: () => {
+                      const g = contextMenu.group as TaskGroup;
+                      onArchiveGroup(g.id);
+                    },
+                  },
+                ]
+              : []),
             {
               label: "Delete",
               danger: true,
               onClick: () => {
-                const g = contextMenu.group as string;
-                if (confirm(`Delete group "${g}"?`)) onDeleteGroup(g);
+                const g = contextMenu.group as TaskGroup;
+                if (confirm(`Delete group "${g.name}"?`)) onDeleteGroup(g.id);
               },
             },
           ]}
@@ -1,5 +1,5 @@
 import { invoke } from "@tauri-apps/api/core";
-import type { MailSettings } from "../types";
+import type { GlobalSearchHit, MailSettings } from "../types";
 
 //Mail
 export async function getMailSettings(): Promise<MailSettings> {
@@ -38,3 +38,17 @@ export async function checkNotifications(): Promise<string> {
     throw e;
   }
 }
+
+//Search
+// Searches tasks, subtasks, memos, books, reading notes and calendar events
+export async function globalSearch(
+  query: string,
+  limit?: number,
+): Promise<GlobalSearchHit[]> {
+  try {
+    return await invoke<GlobalSearchHit[]>("global_search", { query, limit });
+  } catch (e) {
+    console.error("globalSearch failed", e);
+    throw e;
+  }
+}
@@ -0,0 +1,81 @@
+import { convertFileSrc, invoke } from "@tauri-apps/api/core";
+import type { AddedAttachment, Attachment, AttachmentGcReport } from "../types";
+
+// Scheme used by memo Markdown to reference stored attachments
+export const ATTACHMENT_SCHEME = "attachment:";
+
+//Attachments
+export async function getAttachmentsDir(): Promise<string> {
+  try {
+    return await invoke<string>("get_attachments_dir");
+  } catch (e) {
+    console.error("getAttachmentsDir failed", e);
+    throw e;
+  }
+}
+
+// Maps "attachment:<hash>.<ext>" to an asset protocol URL; other URLs are
+// returned unchanged
+export function attachmentUrl(dir: string, url: string): string {
+  if (!url.startsWith(ATTACHMENT_SCHEME)) {
+    return url;
+  }
+  const name = url.slice(ATTACHMENT_SCHEME.length);
+  // Stored names are "<hash>.<ext>"; anything else could escape the directory
+  if (!/^[0-9a-f]{64}(\.[0-9a-z]+)?$/.test(name)) {
+    return "";
+  }
+  return convertFileSrc(`${dir}/${name}`);
+}
+
+export async function addAttachmentFromPath(
+  path: string,
+): Promise<AddedAttachment> {
+  try {
+    return await invoke<AddedAttachment>("add_attachment_from_path", {
+      path,
+    });
+  } catch (e) {
+    console.error("addAttachmentFromPath failed", e);
+    throw e;
+  }
+}
+
+// Used for pasted or dropped images; the extension falls back to mimeType
+export async function addAttachmentFromBytes(
+  bytes: Uint8Array,
+  fileName: string,
+  mimeType?: string,
+): Promise<AddedAttachment> {
+  try {
+    return await invoke<AddedAttachment>("add_attachment_from_bytes", {
+      bytes: Array.from(bytes),
+      fileName,
+      mimeType,
+    });
+  } catch (e) {
+    console.error("addAttachmentFromBytes failed", e);
+    throw e;
+  }
+}
+
+export async function getMemoAttachments(
+  memoId: string,
+): Promise<Attachment[]> {
+  try {
+    return await invoke<Attachment[]>("get_memo_attachments", { memoId });
+  } catch (e) {
+    console.error("getMemoAttachments failed", e);
+    throw e;
+  }
+}
+
+// Removes attachments no memo or template references (older than 24 hours)
+export async function collectUnusedAttachments(): Promise<AttachmentGcReport> {
+  try {
+    return await invoke<AttachmentGcReport>("collect_unused_attachments");
+  } catch (e) {
+    console.error("collectUnusedAttachments failed", e);
+    throw e;
+  }
+}
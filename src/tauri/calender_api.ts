@@ -1,5 +1,5 @@
 import { invoke } from "@tauri-apps/api/core";
-import type { CalendarEvent } from "../types";
+import type { CalendarEvent, CalendarEventPatch } from "../types";
 
 //Calender
 export async function getCalendarEvents(): Promise<CalendarEvent[]> {
@@ -11,11 +11,19 @@ export async function getCalendarEvents(): Promise<CalendarEvent[]> {
   }
 }
 
+// Rejects with an error starting with "Conflict:" if the event changed since
+// expectedUpdatedAt was read (null skips the check)
 export async function updateCalendarEvents(
-  eventData: any,
-): Promise<CalendarEvent[]> {
+  id: string,
+  patch: CalendarEventPatch,
+  expectedUpdatedAt: string | null,
+): Promise<CalendarEvent> {
   try {
-    return await invoke<CalendarEvent[]>("update_calendar_event", eventData);
+    return await invoke<CalendarEvent>("update_calendar_event", {
+      id,
+      patch,
+      expectedUpdatedAt,
+    });
   } catch (e) {
     console.error("updateSubtask failed", e);
     throw e;
@@ -24,20 +32,19 @@ export async function updateCalendarEvents(
 
 export async function createCalendarEvents(
   eventData: any,
-): Promise<CalendarEvent[]> {
+): Promise<CalendarEvent> {
   try {
-    return await invoke<CalendarEvent[]>("create_calendar_event", eventData);
+    return await invoke<CalendarEvent>("create_calendar_event", eventData);
   } catch (e) {
     console.error("createCalendarEvents failed", e);
     throw e;
   }
 }
 
-export async function deleteCalendarEvents(
-  id: string,
-): Promise<CalendarEvent[]> {
+// Resolves to the deleted event's id
+export async function deleteCalendarEvents(id: string): Promise<string> {
   try {
-    return await invoke<CalendarEvent[]>("delete_calendar_event", { id });
+    return await invoke<string>("delete_calendar_event", { id });
   } catch (e) {
     console.error("deleteCalendarEvents failed", e);
     throw e;
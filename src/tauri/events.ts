@@ -0,0 +1,153 @@
+import { listen, type UnlistenFn } from "@tauri-apps/api/event";
+import type { Dispatch, SetStateAction } from "react";
+import type {
+  BookItemChange,
+  DeletedPayload,
+  Memo,
+  ReadingBook,
+  ReadingNote,
+  ReadingSession,
+  Task,
+  TaskChanges,
+} from "../types";
+
+// Change events emitted by the backend after every mutation. Each window
+// merges them into its own state, so a second window stays in sync too.
+
+export function upsertById<T extends { id: K }, K>(items: T[], item: T): T[] {
+  const index = items.findIndex((i) => i.id === item.id);
+  if (index === -1) {
+    return [...items, item];
+  }
+  const next = items.slice();
+  next[index] = item;
+  return next;
+}
+
+export function removeById<T extends { id: K }, K>(items: T[], id: K): T[] {
+  return items.filter((i) => i.id !== id);
+}
+
+export function applyTaskChanges(tasks: Task[], changes: TaskChanges): Task[] {
+  let next = tasks;
+  for (const task of [...changes.created, ...changes.updated]) {
+    next = upsertById(next, task);
+  }
+  return next.filter((t) => !changes.deleted.includes(t.id));
+}
+
+async function listenAll(
+  handlers: Record<string, (payload: any) => void>,
+): Promise<UnlistenFn> {
+  const unlisteners = await Promise.all(
+    Object.entries(handlers).map(([name, handler]) =>
+      listen(name, (event) => handler(event.payload)),
+    ),
+  );
+  return () => unlisteners.forEach((unlisten) => unlisten());
+}
+
+export function listenTaskEvents(
+  setTasks: Dispatch<SetStateAction<Task[]>>,
+): Promise<UnlistenFn> {
+  const upsert = (task: Task) => setTasks((prev) => upsertById(prev, task));
+  return listenAll({
+    "task-created": upsert,
+    "task-updated": upsert,
+    "task-deleted": ({ id }: DeletedPayload<number>) =>
+      setTasks((prev) => removeById(prev, id)),
+  });
+}
+
+export function listenMemoEvents(
+  setMemos: Dispatch<SetStateAction<Memo[]>>,
+): Promise<UnlistenFn> {
+  const upsert = (memo: Memo) => setMemos((prev) => upsertById(prev, memo));
+  return listenAll({
+    "memo-created": upsert,
+    "memo-updated": upsert,
+    "memo-deleted": ({ id }: DeletedPayload<string>) =>
+      setMemos((prev) => removeById(prev, id)),
+  });
+}
+
+// Applies a note/session change to the book it belongs to
+function updateBookItems<T>(
+  books: ReadingBook[],
+  change: BookItemChange<T>,
+  update: (book: ReadingBook) => Partial<ReadingBook>,
+): ReadingBook[] {
+  return books.map((book) =>
+    book.id === change.book_id
+      ? { ...book, ...update(book), updated_at: change.book_updated_at }
+      : book,
+  );
+}
+
+export function applyBookEvent(
+  books: ReadingBook[],
+  name: string,
+  payload: any,
+): ReadingBook[] {
+  switch (name) {
+    case "book-created":
+    case "book-updated":
+      return upsertById(books, payload as ReadingBook);
+    case "book-deleted":
+      return removeById(books, (payload as DeletedPayload<string>).id);
+    case "book-note-added":
+    case "book-note-updated": {
+      const change = payload as BookItemChange<ReadingNote>;
+      return updateBookItems(books, change, (book) => ({
+        notes: upsertById(book.notes, change.item),
+      }));
+    }
+    case "book-note-deleted": {
+      const change = payload as BookItemChange<string>;
+      return updateBookItems(books, change, (book) => ({
+        notes: removeById(book.notes, change.item),
+      }));
+    }
+    case "book-session-added":
+    case "book-session-updated": {
+      const change = payload as BookItemChange<ReadingSession>;
+      return updateBookItems(books, change, (book) => ({
+        reading_sessions: upsertById(book.reading_sessions, change.item),
+      }));
+    }
+    case "book-session-deleted": {
+      const change = payload as BookItemChange<string>;
+      return updateBookItems(books, change, (book) => ({
+        reading_sessions: removeById(book.reading_sessions, change.item),
+      }));
+    }
+    default:
+      return books;
+  }
+}
+
+export const BOOK_EVENTS = [
+  "book-created",
+  "book-updated",
+  "book-deleted",
+  "book-note-added",
+  "book-note-updated",
+  "book-note-deleted",
+  "book-session-added",
+  "book-session-updated",
+  "book-session-deleted",
+];
+
+export function listenBookEvents(
+  setBooks: Dispatch<SetStateAction<ReadingBook[]>>,
+): Promise<UnlistenFn> {
+  return listenAll(
+    Object.fromEntries(
+      BOOK_EVENTS.map((name) => [
+        name,
+        (payload: any) =>
+          setBooks((prev) => applyBookEvent(prev, name, payload)),
+      ]),
+    ),
+  );
+}
@@ -0,0 +1,77 @@
+import { invoke } from "@tauri-apps/api/core";
+import type { EntityLink, EntityRef, LinkedEntity, Memo, Task } from "../types";
+
+//Entity links
+// Returns the existing link when the two entities are already linked
+export async function addEntityLink(
+  source: EntityRef,
+  target: EntityRef,
+  label?: string,
+): Promise<EntityLink> {
+  try {
+    return await invoke<EntityLink>("add_entity_link", {
+      source,
+      target,
+      label,
+    });
+  } catch (e) {
+    console.error("addEntityLink failed", e);
+    throw e;
+  }
+}
+
+// Resolves to the id of the removed link
+export async function removeEntityLink(id: string): Promise<string> {
+  try {
+    return await invoke<string>("remove_entity_link", { id });
+  } catch (e) {
+    console.error("removeEntityLink failed", e);
+    throw e;
+  }
+}
+
+export async function getEntityLinks(
+  entity: EntityRef,
+): Promise<LinkedEntity[]> {
+  try {
+    return await invoke<LinkedEntity[]>("get_entity_links", { entity });
+  } catch (e) {
+    console.error("getEntityLinks failed", e);
+    throw e;
+  }
+}
+
+export async function createMemoFromReadingNote(
+  bookId: string,
+  noteId: string,
+  folderId: string | null = null,
+): Promise<Memo> {
+  try {
+    return await invoke<Memo>("create_memo_from_reading_note", {
+      bookId,
+      noteId,
+      folderId,
+    });
+  } catch (e) {
+    console.error("createMemoFromReadingNote failed", e);
+    throw e;
+  }
+}
+
+// line is 0-based
+export async function createTaskFromMemoLine(
+  memoId: string,
+  line: number,
+  groupId: string | null = null,
+): Promise<Task> {
+  try {
+    return await invoke<Task>("create_task_from_memo_line", {
+      memoId,
+      line,
+      groupId,
+    });
+  } catch (e) {
+    console.error("createTaskFromMemoLine failed", e);
+    throw e;
+  }
+}
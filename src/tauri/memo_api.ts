@@ -1,12 +1,76 @@
 import { invoke } from "@tauri-apps/api/core";
-import { Memo } from "../types";
+import type {
+  Backlink,
+  BrokenLink,
+  DailyNoteSettings,
+  ListOptions,
+  Listed,
+  Memo,
+  MemoSearchHit,
+  MemoSummary,
+  MemoSyncStatus,
+  MemoTemplate,
+  Page,
+  SyncSide,
+  VaultExportReport,
+  VaultImportReport,
+} from "../types";
 
 //Memo
 export async function getMemos(): Promise<Memo[]> {
+  return (await listMemos({})).items;
+}
+
+export async function listMemos<O extends ListOptions>(
+  options: O,
+): Promise<Page<Listed<O, Memo, MemoSummary>>> {
+  try {
+    return await invoke<Page<Listed<O, Memo, MemoSummary>>>("get_memos", {
+      options,
+    });
+  } catch (e) {
+    console.error("listMemos failed", e);
+    throw e;
+  }
+}
+
+export async function getMemo(id: string): Promise<Memo | null> {
+  try {
+    return await invoke<Memo | null>("get_memo", { id });
+  } catch (e) {
+    console.error("getMemo failed", e);
+    throw e;
+  }
+}
+
+// Query syntax: words, "phrases", prefix*, tag:name, folder:name
+export async function searchMemos(
+  query: string,
+  limit?: number,
+): Promise<MemoSearchHit[]> {
+  try {
+    return await invoke<MemoSearchHit[]>("search_memos", { query, limit });
+  } catch (e) {
+    console.error("searchMemos failed", e);
+    throw e;
+  }
+}
+
+export async function getBacklinks(id: string): Promise<Backlink[]> {
+  try {
+    return await invoke<Backlink[]>("get_backlinks", { id });
+  } catch (e) {
+    console.error("getBacklinks failed", e);
+    throw e;
+  }
+}
+
+// Lists broken links of one memo, or of every memo when memoId is omitted
+export async function getBrokenLinks(memoId?: string): Promise<BrokenLink[]> {
   try {
-    return await invoke<Memo[]>("get_memos");
+    return await invoke<BrokenLink[]>("get_broken_links", { memoId });
   } catch (e) {
-    console.error("getMemos failed", e);
+    console.error("getBrokenLinks failed", e);
     throw e;
   }
 }
@@ -16,9 +80,9 @@ export async function createMemo(
   content: string,
   folderId: string | null,
   tags: string[],
-): Promise<Memo[]> {
+): Promise<Memo> {
   try {
-    return await invoke<Memo[]>("create_memo", {
+    return await invoke<Memo>("create_memo", {
       title: title,
       content: content,
       folderId: folderId,
@@ -36,10 +100,10 @@ export async function updateMemo(
   content: string,
   folderId: string | null,
   tags: string[],
-): Promise<Memo[]> {
+): Promise<Memo> {
   try {
-    return await invoke<Memo[]>("update_memo", {
-      memoId: memoId,
+    return await invoke<Memo>("update_memo", {
+      id: memoId,
       title: title,
       content: content,
       folderId: folderId,
@@ -51,11 +115,202 @@ export async function updateMemo(
   }
 }
 
-export async function deleteMemo(memoId: string): Promise<Memo[]> {
+// Resolves to the id of the deleted memo
+export async function deleteMemo(memoId: string): Promise<string> {
   try {
-    return await invoke<Memo[]>("delete_memo", { memoId: memoId });
+    return await invoke<string>("delete_memo", { id: memoId });
   } catch (e) {
     console.error("deleteMemo failed", e);
     throw e;
   }
 }
+
+//Memo templates
+export async function getMemoTemplates(): Promise<MemoTemplate[]> {
+  try {
+    return await invoke<MemoTemplate[]>("get_memo_templates");
+  } catch (e) {
+    console.error("getMemoTemplates failed", e);
+    throw e;
+  }
+}
+
+export async function createMemoTemplate(
+  name: string,
+  title: string,
+  content: string,
+  folderId: string | null,
+  tags: string[],
+): Promise<MemoTemplate> {
+  try {
+    return await invoke<MemoTemplate>("create_memo_template", {
+      name,
+      title,
+      content,
+      folderId,
+      tags,
+    });
+  } catch (e) {
+    console.error("createMemoTemplate failed", e);
+    throw e;
+  }
+}
+
+export async function updateMemoTemplate(
+  id: string,
+  name: string,
+  title: string,
+  content: string,
+  folderId: string | null,
+  tags: string[],
+): Promise<MemoTemplate> {
+  try {
+    return await invoke<MemoTemplate>("update_memo_template", {
+      id,
+      name,
+      title,
+      content,
+      folderId,
+      tags,
+    });
+  } catch (e) {
+    console.error("updateMemoTemplate failed", e);
+    throw e;
+  }
+}
+
+// Resolves to the id of the deleted template
+export async function deleteMemoTemplate(id: string): Promise<string> {
+  try {
+    return await invoke<string>("delete_memo_template", { id });
+  } catch (e) {
+    console.error("deleteMemoTemplate failed", e);
+    throw e;
+  }
+}
+
+// title and folderId default to the template's own values
+export async function createMemoFromTemplate(
+  templateId: string,
+  title?: string,
+  folderId?: string,
+): Promise<Memo> {
+  try {
+    return await invoke<Memo>("create_memo_from_template", {
+      templateId,
+      title,
+      folderId,
+    });
+  } catch (e) {
+    console.error("createMemoFromTemplate failed", e);
+    throw e;
+  }
+}
+
+//Daily notes
+export async function getDailyNoteSettings(): Promise<DailyNoteSettings> {
+  try {
+    return await invoke<DailyNoteSettings>("get_daily_note_settings");
+  } catch (e) {
+    console.error("getDailyNoteSettings failed", e);
+    throw e;
+  }
+}
+
+export async function updateDailyNoteSettings(
+  settings: DailyNoteSettings,
+): Promise<DailyNoteSettings> {
+  try {
+    return await invoke<DailyNoteSettings>("update_daily_note_settings", {
+      settings,
+    });
+  } catch (e) {
+    console.error("updateDailyNoteSettings failed", e);
+    throw e;
+  }
+}
+
+// date is "YYYY-MM-DD" and defaults to today
+export async function getOrCreateDailyNote(date?: string): Promise<Memo> {
+  try {
+    return await invoke<Memo>("get_or_create_daily_note", { date });
+  } catch (e) {
+    console.error("getOrCreateDailyNote failed", e);
+    throw e;
+  }
+}
+
+//Markdown export/import
+export async function exportMemosToDirectory(
+  path: string,
+): Promise<VaultExportReport> {
+  try {
+    return await invoke<VaultExportReport>("export_memos_to_directory", {
+      path,
+    });
+  } catch (e) {
+    console.error("exportMemosToDirectory failed", e);
+    throw e;
+  }
+}
+
+// Files whose front matter id matches an existing memo update that memo
+export async function importMemosFromDirectory(
+  path: string,
+): Promise<VaultImportReport> {
+  try {
+    return await invoke<VaultImportReport>("import_memos_from_directory", {
+      path,
+    });
+  } catch (e) {
+    console.error("importMemosFromDirectory failed", e);
+    throw e;
+  }
+}
+
+//Two-way sync with a directory
+export async function getMemoSyncStatus(): Promise<MemoSyncStatus> {
+  try {
+    return await invoke<MemoSyncStatus>("get_memo_sync_status");
+  } catch (e) {
+    console.error("getMemoSyncStatus failed", e);
+    throw e;
+  }
+}
+
+// Memos changed on disk arrive through the usual memo-* events
+export async function startMemoSync(
+  directory: string,
+): Promise<MemoSyncStatus> {
+  try {
+    return await invoke<MemoSyncStatus>("start_memo_sync", { directory });
+  } catch (e) {
+    console.error("startMemoSync failed", e);
+    throw e;
+  }
+}
+
+export async function stopMemoSync(): Promise<MemoSyncStatus> {
+  try {
+    return await invoke<MemoSyncStatus>("stop_memo_sync");
+  } catch (e) {
+    console.error("stopMemoSync failed", e);
+    throw e;
+  }
+}
+
+// keep: "app" overwrites the file, "file" overwrites the memo
+export async function resolveMemoSyncConflict(
+  memoId: string,
+  keep: SyncSide,
+): Promise<MemoSyncStatus> {
+  try {
+    return await invoke<MemoSyncStatus>("resolve_memo_sync_conflict", {
+      memoId,
+      keep,
+    });
+  } catch (e) {
+    console.error("resolveMemoSyncConflict failed", e);
+    throw e;
+  }
+}
@@ -1,79 +1,175 @@
-import { invoke } from "@tauri-apps/api/core";
-import type { ReadingBook, ReadingStatus } from "../types";
+import { convertFileSrc, invoke } from "@tauri-apps/api/core";
+import type {
+  BookCoverCacheReport,
+  BookItemChange,
+  CoverSource,
+  ListOptions,
+  Listed,
+  Page,
+  ReadingBook,
+  ReadingBookSummary,
+  ReadingBookPatch,
+  ReadingNote,
+  ReadingSession,
+} from "../types";
 //Reading Memos
 
 export async function getReadingBooks(): Promise<ReadingBook[]> {
+  return (await listReadingBooks({})).items;
+}
+
+export async function listReadingBooks<O extends ListOptions>(
+  options: O,
+): Promise<Page<Listed<O, ReadingBook, ReadingBookSummary>>> {
+  try {
+    return await invoke<Page<Listed<O, ReadingBook, ReadingBookSummary>>>(
+      "get_reading_books",
+      { options },
+    );
+  } catch (e) {
+    console.error("listReadingBooks failed", e);
+    throw e;
+  }
+}
+
+export async function getReadingBook(id: string): Promise<ReadingBook> {
   try {
-    return await invoke<ReadingBook[]>("get_reading_books");
+    return await invoke<ReadingBook>("get_reading_book", { id });
   } catch (e) {
-    console.error("getReadingBooks failed", e);
+    console.error("getReadingBook failed", e);
     throw e;
   }
 }
 
-export async function createReadingBooks(
-  title: string,
-): Promise<ReadingBook[]> {
+export async function createReadingBooks(title: string): Promise<ReadingBook> {
   try {
-    return await invoke<ReadingBook[]>("create_reading_book", { title });
+    return await invoke<ReadingBook>("create_reading_book", { title });
   } catch (e) {
     console.error("createReadingBooks failed", e);
     throw e;
   }
 }
 
+// Rejects with an error starting with "Conflict:" if the book changed since
+// expectedUpdatedAt was read (null skips the check)
 export async function updateReadingBooks(
   bookId: string,
-  title: string,
-  author: string | null,
-  isbn: string | null,
-  publisher: string | null,
-  publishedYear: number | null,
-  coverImageUrl: string | null,
-  genres: string[],
-  status: ReadingStatus,
-  startDate: string | null,
-  finishDate: string | null,
-  totalPages: number | null,
-  currentPage: number | null,
-  rating: number | null,
-  summary: string,
-  tags: string[],
-): Promise<ReadingBook[]> {
-  try {
-    return await invoke<ReadingBook[]>("update_reading_book", {
+  patch: ReadingBookPatch,
+  expectedUpdatedAt: string | null,
+): Promise<ReadingBook> {
+  try {
+    return await invoke<ReadingBook>("update_reading_book", {
       id: bookId,
-      title: title,
-      author: author || null,
-      isbn: isbn || null,
-      publisher: publisher || null,
-      publishedYear: publishedYear || null,
-      coverImageUrl: coverImageUrl || null,
-      genres: genres,
-      status: status,
-      startDate: startDate || null,
-      finishDate: finishDate || null,
-      totalPages: totalPages || null,
-      currentPage: currentPage || null,
-      rating: rating || null,
-      summary: summary,
-      tags: tags,
+      patch,
+      expectedUpdatedAt,
     });
   } catch (e) {
-    console.error("createReadingBooks failed", e);
+    console.error("updateReadingBooks failed", e);
     throw e;
   }
 }
 
-export async function deleteReadingBooks(id: string) {
+// Resolves to the id of the deleted book
+export async function deleteReadingBooks(id: string): Promise<string> {
   try {
-    return await invoke<ReadingBook[]>("delete_reading_book", { id });
+    return await invoke<string>("delete_reading_book", { id });
   } catch (e) {
     console.error("deleteReadingBooks failed", e);
     throw e;
   }
 }
 
+//Reading Memos => covers
+export async function getBookCoversDir(): Promise<string> {
+  try {
+    return await invoke<string>("get_book_covers_dir");
+  } catch (e) {
+    console.error("getBookCoversDir failed", e);
+    throw e;
+  }
+}
+
+// Prefers the stored copy (thumbnail by default) and falls back to the
+// remote cover_image_url until it has been downloaded
+export function bookCoverUrl(
+  dir: string,
+  book: Pick<ReadingBook, "cover" | "cover_image_url">,
+  thumbnail = true,
+): string | null {
+  const cover = book.cover;
+  const current =
+    cover && (!cover.source_url || cover.source_url === book.cover_image_url);
+  if (cover && current) {
+    const name = thumbnail
+      ? `${cover.hash}_thumb.jpg`
+      : `${cover.hash}.${cover.extension}`;
+    return convertFileSrc(`${dir}/${name}`);
+  }
+  return book.cover_image_url ?? null;
+}
+
+export async function setBookCover(
+  bookId: string,
+  source: CoverSource,
+): Promise<ReadingBook> {
+  try {
+    return await invoke<ReadingBook>("set_book_cover", { bookId, source });
+  } catch (e) {
+    console.error("setBookCover failed", e);
+    throw e;
+  }
+}
+
+export async function removeBookCover(bookId: string): Promise<ReadingBook> {
+  try {
+    return await invoke<ReadingBook>("remove_book_cover", { bookId });
+  } catch (e) {
+    console.error("removeBookCover failed", e);
+    throw e;
+  }
+}
+
+// Downloads remote covers that are not stored yet; call again once online
+export async function cacheBookCovers(): Promise<BookCoverCacheReport> {
+  try {
+    return await invoke<BookCoverCacheReport>("cache_book_covers");
+  } catch (e) {
+    console.error("cacheBookCovers failed", e);
+    throw e;
+  }
+}
+
+//Reading Memos => ISBN
+// Resolves to the ISBN without hyphens; rejects on a bad length or check digit
+export async function validateIsbn(isbn: string): Promise<string> {
+  try {
+    return await invoke<string>("validate_isbn", { isbn });
+  } catch (e) {
+    console.error("validateIsbn failed", e);
+    throw e;
+  }
+}
+
+// Fills author, publisher, published_year, total_pages and the cover from
+// Open Library. Only empty fields are filled unless overwrite is set; isbn
+// defaults to the book's own ISBN
+export async function lookupBookByIsbn(
+  bookId: string,
+  isbn?: string,
+  overwrite?: boolean,
+): Promise<ReadingBook> {
+  try {
+    return await invoke<ReadingBook>("lookup_book_by_isbn", {
+      bookId,
+      isbn,
+      overwrite,
+    });
+  } catch (e) {
+    console.error("lookupBookByIsbn failed", e);
+    throw e;
+  }
+}
+
 //Reading Memos => sessions
 
 export async function addReadingSession(
@@ -86,7 +182,7 @@ export async function addReadingSession(
   memo: string,
 ) {
   try {
-    return await invoke("add_reading_session", {
+    return await invoke<BookItemChange<ReadingSession>>("add_reading_session", {
       bookId: bookId,
       sessionDate: sessionDate,
       startPage: startPage || null,
@@ -112,16 +208,19 @@ export async function updateReadingSession(
   memo: string,
 ) {
   try {
-    return await invoke("update_reading_session", {
-      bookId: bookId,
-      sessionId: sessionId,
-      sessionDate: sessionDate,
-      startPage: startPage || null,
-      endPage: endPage || null,
-      pagesRead,
-      durationMinutes: durationMinutes || null,
-      memo: memo || null,
-    });
+    return await invoke<BookItemChange<ReadingSession>>(
+      "update_reading_session",
+      {
+        bookId: bookId,
+        sessionId: sessionId,
+        sessionDate: sessionDate,
+        startPage: startPage || null,
+        endPage: endPage || null,
+        pagesRead,
+        durationMinutes: durationMinutes || null,
+        memo: memo || null,
+      },
+    );
   } catch (e) {
     console.error("updateReadingSession failed", e);
     throw e;
@@ -130,7 +229,7 @@ export async function updateReadingSession(
 
 export async function deleteReadingSession(bookId: string, sessionId: string) {
   try {
-    return await invoke("delete_reading_session", {
+    return await invoke<BookItemChange<string>>("delete_reading_session", {
       bookId: bookId,
       sessionId,
     });
@@ -148,7 +247,7 @@ export async function addReadingNote(
   comment: string,
 ) {
   try {
-    return await invoke("add_reading_note", {
+    return await invoke<BookItemChange<ReadingNote>>("add_reading_note", {
       bookId: bookId,
       pageNumber: pageNumber || null,
       quote: quote || null,
@@ -168,7 +267,7 @@ export async function updateReadingNote(
   comment: string,
 ) {
   try {
-    return await invoke("update_reading_note", {
+    return await invoke<BookItemChange<ReadingNote>>("update_reading_note", {
       bookId: bookId,
       noteId,
       pageNumber: pageNumber || null,
@@ -183,7 +282,7 @@ export async function updateReadingNote(
 
 export async function deleteReadingNote(bookId: string, noteId: string) {
   try {
-    return await invoke("delete_reading_note", {
+    return await invoke<BookItemChange<string>>("delete_reading_note", {
       bookId: bookId,
       noteId,
     });
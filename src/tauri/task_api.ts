@@ -1,11 +1,48 @@
 import { invoke } from "@tauri-apps/api/core";
-import type { Task } from "../types";
+import type {
+  Board,
+  BulkTaskPatch,
+  DeleteFolderOptions,
+  Folder,
+  FolderTree,
+  GroupTimeReport,
+  ListOptions,
+  Listed,
+  Page,
+  ProjectSchedule,
+  SavedView,
+  Task,
+  TaskChanges,
+  TaskGroup,
+  TaskPatch,
+  TaskPriority,
+  TaskSummary,
+  TimeEntry,
+  WorkflowState,
+} from "../types";
 //Task
 export async function getTasks(): Promise<Task[]> {
+  return (await listTasks({})).items;
+}
+
+export async function listTasks<O extends ListOptions>(
+  options: O,
+): Promise<Page<Listed<O, Task, TaskSummary>>> {
   try {
-    return await invoke<Task[]>("get_tasks");
+    return await invoke<Page<Listed<O, Task, TaskSummary>>>("get_tasks", {
+      options,
+    });
   } catch (e) {
-    console.error("getTasks failed", e);
+    console.error("listTasks failed", e);
+    throw e;
+  }
+}
+
+export async function getTask(id: number): Promise<Task> {
+  try {
+    return await invoke<Task>("get_task", { id });
+  } catch (e) {
+    console.error("getTask failed", e);
     throw e;
   }
 }
@@ -14,20 +51,26 @@ export async function addTask(
   description: string,
   startDate: string | undefined,
   dueDate: string,
-  group: string,
+  groupId: string | null,
   details: string,
   notificationMinutes: number | undefined,
   dependencies: null,
-): Promise<Task[]> {
+  priority?: TaskPriority | null,
+  estimatedMinutes?: number | null,
+  tags?: string[],
+): Promise<Task> {
   try {
-    return await invoke<Task[]>("add_task", {
+    return await invoke<Task>("add_task", {
       description: description,
       startDate: startDate,
       dueDate: dueDate,
-      group: group,
+      groupId: groupId || null,
       details: details,
       notificationMinutes: notificationMinutes,
       dependencies: dependencies,
+      priority: priority ?? null,
+      estimatedMinutes: estimatedMinutes ?? null,
+      tags: tags ?? [],
     });
   } catch (e) {
     console.error("addTask failed", e);
@@ -35,76 +78,283 @@ export async function addTask(
   }
 }
 
-export async function updateTask(task: Task): Promise<Task[]> {
+export async function quickAddTask(text: string): Promise<Task> {
   try {
-    return await invoke<Task[]>("update_task", {
-      id: task.id,
+    return await invoke<Task>("quick_add_task", { text });
+  } catch (e) {
+    console.error("quickAddTask failed", e);
+    throw e;
+  }
+}
+
+export async function updateTask(task: Task): Promise<Task> {
+  return patchTask(
+    task.id,
+    {
       description: task.description,
-      startDate: task.start_date || null,
-      dueDate: task.due_date,
-      group: task.group,
+      start_date: task.start_date || null,
+      due_date: task.due_date || null,
+      group_id: task.group_id || null,
       details: task.details,
-      notificationMinutes: task.notification_minutes || null,
+      notification_minutes: task.notification_minutes || null,
       dependencies: task.dependencies || null,
+      priority: task.priority ?? null,
+      estimated_minutes: task.estimated_minutes ?? null,
+      tags: task.tags ?? [],
+    },
+    task.updated_at ?? null,
+  );
+}
+
+// Rejects with an error starting with "Conflict:" if the task changed since
+// expectedUpdatedAt was read (null skips the check)
+export async function patchTask(
+  id: number,
+  patch: TaskPatch,
+  expectedUpdatedAt: string | null,
+): Promise<Task> {
+  try {
+    return await invoke<Task>("update_task", {
+      id,
+      patch,
+      expectedUpdatedAt,
     });
   } catch (e) {
-    console.error("updateTask failed", e);
+    console.error("patchTask failed", e);
     throw e;
   }
 }
 
-export async function deleteTask(id: number): Promise<Task[]> {
+export async function deleteTask(id: number): Promise<TaskChanges> {
   try {
-    return await invoke<Task[]>("delete_task", { id });
+    return await invoke<TaskChanges>("delete_task", { id });
   } catch (e) {
     console.error("deleteTask failed", e);
     throw e;
   }
 }
 
-export async function completeTask(id: number): Promise<Task[]> {
+export async function reorderTasks(
+  taskId: number,
+  afterId: number | null,
+): Promise<Task> {
+  try {
+    return await invoke<Task>("reorder_tasks", { taskId, afterId });
+  } catch (e) {
+    console.error("reorderTasks failed", e);
+    throw e;
+  }
+}
+
+export async function completeTask(id: number): Promise<Task> {
   try {
-    return await invoke<Task[]>("complete_task", { id });
+    return await invoke<Task>("complete_task", { id });
   } catch (e) {
     console.error("completeTask failed", e);
     throw e;
   }
 }
 
+export async function bulkUpdateTasks(
+  ids: number[],
+  patch: BulkTaskPatch,
+): Promise<TaskChanges> {
+  try {
+    return await invoke<TaskChanges>("bulk_update_tasks", { ids, patch });
+  } catch (e) {
+    console.error("bulkUpdateTasks failed", e);
+    throw e;
+  }
+}
+
+export async function undoTaskChange(): Promise<TaskChanges> {
+  try {
+    return await invoke<TaskChanges>("undo_task_change");
+  } catch (e) {
+    console.error("undoTaskChange failed", e);
+    throw e;
+  }
+}
+
+//Task => Query
+export async function getTaskTags(): Promise<string[]> {
+  try {
+    return await invoke<string[]>("get_task_tags");
+  } catch (e) {
+    console.error("getTaskTags failed", e);
+    throw e;
+  }
+}
+
+export async function queryTasks(query: string): Promise<Task[]> {
+  try {
+    return await invoke<Task[]>("query_tasks", { query });
+  } catch (e) {
+    console.error("queryTasks failed", e);
+    throw e;
+  }
+}
+
+export async function getSavedViews(): Promise<SavedView[]> {
+  try {
+    return await invoke<SavedView[]>("get_saved_views");
+  } catch (e) {
+    console.error("getSavedViews failed", e);
+    throw e;
+  }
+}
+
+export async function createSavedView(
+  name: string,
+  query: string,
+): Promise<SavedView[]> {
+  try {
+    return await invoke<SavedView[]>("create_saved_view", { name, query });
+  } catch (e) {
+    console.error("createSavedView failed", e);
+    throw e;
+  }
+}
+
+export async function updateSavedView(
+  id: string,
+  name: string,
+  query: string,
+): Promise<SavedView[]> {
+  try {
+    return await invoke<SavedView[]>("update_saved_view", { id, name, query });
+  } catch (e) {
+    console.error("updateSavedView failed", e);
+    throw e;
+  }
+}
+
+export async function deleteSavedView(id: string): Promise<SavedView[]> {
+  try {
+    return await invoke<SavedView[]>("delete_saved_view", { id });
+  } catch (e) {
+    console.error("deleteSavedView failed", e);
+    throw e;
+  }
+}
+
+//Task => Workflow
+export async function moveTaskState(
+  taskId: number,
+  stateId: string,
+): Promise<Task> {
+  try {
+    return await invoke<Task>("move_task_state", { taskId, stateId });
+  } catch (e) {
+    console.error("moveTaskState failed", e);
+    throw e;
+  }
+}
+
+export async function getBoard(groupId: string | null): Promise<Board> {
+  try {
+    return await invoke<Board>("get_board", { groupId });
+  } catch (e) {
+    console.error("getBoard failed", e);
+    throw e;
+  }
+}
+
+export async function setGroupWorkflow(
+  groupId: string,
+  states: WorkflowState[],
+): Promise<TaskGroup[]> {
+  try {
+    return await invoke<TaskGroup[]>("set_group_workflow", { groupId, states });
+  } catch (e) {
+    console.error("setGroupWorkflow failed", e);
+    throw e;
+  }
+}
+
 //Task => Group
 
-export async function getGroups(): Promise<string[]> {
+export async function getGroups(): Promise<TaskGroup[]> {
   try {
-    return await invoke<string[]>("get_groups");
+    return await invoke<TaskGroup[]>("get_groups");
   } catch (e) {
     console.error("getGroups failed", e);
     throw e;
   }
 }
 
-export async function createGroups(name: string): Promise<string[]> {
+export async function createGroups(
+  name: string,
+  parentId?: string | null,
+): Promise<TaskGroup[]> {
   try {
-    return await invoke<string[]>("create_group", { name });
+    return await invoke<TaskGroup[]>("create_group", {
+      name,
+      parentId: parentId ?? null,
+      color: null,
+      icon: null,
+      notificationMinutes: null,
+    });
   } catch (e) {
     console.error("createGroups failed", e);
     throw e;
   }
 }
 
-export async function deleteGroups(name: string): Promise<string[]> {
+export async function updateGroup(group: TaskGroup): Promise<TaskGroup[]> {
+  try {
+    return await invoke<TaskGroup[]>("update_group", {
+      id: group.id,
+      name: group.name,
+      parentId: group.parent_id,
+      color: group.color,
+      icon: group.icon,
+      notificationMinutes: group.notification_minutes,
+    });
+  } catch (e) {
+    console.error("updateGroup failed", e);
+    throw e;
+  }
+}
+
+export async function deleteGroups(id: string): Promise<TaskGroup[]> {
   try {
-    return await invoke<string[]>("delete_group", { name });
+    return await invoke<TaskGroup[]>("delete_group", { id });
   } catch (e) {
     console.error("deleteGroups failed", e);
     throw e;
   }
 }
 
-export async function renameGroups(oldName: string, newName: string) {
+export async function renameGroups(
+  id: string,
+  newName: string,
+): Promise<TaskGroup[]> {
   try {
-    return await invoke<any>("rename_group", { oldName, newName });
+    return await invoke<TaskGroup[]>("rename_group", { id, newName });
   } catch (e) {
-    console.error("deleteGroups failed", e);
+    console.error("renameGroups failed", e);
+    throw e;
+  }
+}
+
+export async function setGroupArchived(
+  id: string,
+  archived: boolean,
+): Promise<TaskGroup[]> {
+  try {
+    return await invoke<TaskGroup[]>("set_group_archived", { id, archived });
+  } catch (e) {
+    console.error("setGroupArchived failed", e);
+    throw e;
+  }
+}
+
+export async function reorderGroups(ids: string[]): Promise<TaskGroup[]> {
+  try {
+    return await invoke<TaskGroup[]>("reorder_groups", { ids });
+  } catch (e) {
+    console.error("reorderGroups failed", e);
     throw e;
   }
 }
@@ -113,11 +363,13 @@ export async function renameGroups(oldName: string, newName: string) {
 export async function addSubtask(
   taskid: number,
   description: string,
-): Promise<Task[]> {
+  parentSubtaskId: number | null = null,
+): Promise<Task> {
   try {
-    return await invoke<Task[]>("add_subtask", {
+    return await invoke<Task>("add_subtask", {
       taskId: taskid,
       description: description.trim(),
+      parentSubtaskId,
     });
   } catch (e) {
     console.error("addSubtask failed", e);
@@ -128,9 +380,9 @@ export async function addSubtask(
 export async function toggleSubtask(
   taskid: number,
   subTaskId: number,
-): Promise<Task[]> {
+): Promise<Task> {
   try {
-    return await invoke<Task[]>("toggle_subtask", {
+    return await invoke<Task>("toggle_subtask", {
       taskId: taskid,
       subtaskId: subTaskId,
     });
@@ -143,9 +395,9 @@ export async function toggleSubtask(
 export async function deleteSubtask(
   taskid: number,
   subTaskId: number,
-): Promise<Task[]> {
+): Promise<Task> {
   try {
-    return await invoke<Task[]>("delete_subtask", {
+    return await invoke<Task>("delete_subtask", {
       taskId: taskid,
       subtaskId: subTaskId,
     });
@@ -160,9 +412,9 @@ export async function updateSubtask(
   subtaskId: number,
   description: string,
   completed: boolean,
-): Promise<Task[]> {
+): Promise<Task> {
   try {
-    return await invoke<Task[]>("update_subtask", {
+    return await invoke<Task>("update_subtask", {
       taskId,
       subtaskId,
       description,
@@ -174,19 +426,220 @@ export async function updateSubtask(
   }
 }
 
+export async function reorderSubtasks(
+  taskId: number,
+  subtaskId: number,
+  targetTaskId: number,
+  afterSubtaskId: number | null,
+  targetParentSubtaskId: number | null = null,
+): Promise<TaskChanges> {
+  try {
+    return await invoke<TaskChanges>("reorder_subtasks", {
+      taskId,
+      subtaskId,
+      targetTaskId,
+      targetParentSubtaskId,
+      afterSubtaskId,
+    });
+  } catch (e) {
+    console.error("reorderSubtasks failed", e);
+    throw e;
+  }
+}
+
+export async function updateSubtaskDetails(
+  taskId: number,
+  subtaskId: number,
+  dueDate: string | null,
+  notes: string,
+): Promise<Task> {
+  try {
+    return await invoke<Task>("update_subtask_details", {
+      taskId,
+      subtaskId,
+      dueDate,
+      notes,
+    });
+  } catch (e) {
+    console.error("updateSubtaskDetails failed", e);
+    throw e;
+  }
+}
+
+export async function promoteSubtask(
+  taskId: number,
+  subtaskId: number,
+): Promise<TaskChanges> {
+  try {
+    return await invoke<TaskChanges>("promote_subtask", { taskId, subtaskId });
+  } catch (e) {
+    console.error("promoteSubtask failed", e);
+    throw e;
+  }
+}
+
+// Rejects if the task has tags, dependencies or other data a subtask cannot
+// keep, unless force is set to discard it
+export async function demoteTask(
+  taskId: number,
+  parentTaskId: number,
+  parentSubtaskId: number | null = null,
+  force = false,
+): Promise<TaskChanges> {
+  try {
+    return await invoke<TaskChanges>("demote_task", {
+      taskId,
+      parentTaskId,
+      parentSubtaskId,
+      force,
+    });
+  } catch (e) {
+    console.error("demoteTask failed", e);
+    throw e;
+  }
+}
+
+export async function setSubtaskRollup(
+  taskId: number,
+  enabled: boolean,
+): Promise<Task> {
+  try {
+    return await invoke<Task>("set_subtask_rollup", { taskId, enabled });
+  } catch (e) {
+    console.error("setSubtaskRollup failed", e);
+    throw e;
+  }
+}
+
+//Task => Dependencies
+export async function getBlockedTasks(): Promise<Task[]> {
+  try {
+    return await invoke<Task[]>("get_blocked_tasks");
+  } catch (e) {
+    console.error("getBlockedTasks failed", e);
+    throw e;
+  }
+}
+
+export async function getReadyTasks(): Promise<Task[]> {
+  try {
+    return await invoke<Task[]>("get_ready_tasks");
+  } catch (e) {
+    console.error("getReadyTasks failed", e);
+    throw e;
+  }
+}
+
+export async function getTaskTopologicalOrder(): Promise<number[]> {
+  try {
+    return await invoke<number[]>("get_task_topological_order");
+  } catch (e) {
+    console.error("getTaskTopologicalOrder failed", e);
+    throw e;
+  }
+}
+
+//Task => Schedule
+export async function getTaskSchedule(): Promise<ProjectSchedule> {
+  try {
+    return await invoke<ProjectSchedule>("get_task_schedule");
+  } catch (e) {
+    console.error("getTaskSchedule failed", e);
+    throw e;
+  }
+}
+
+export async function autoScheduleTasks(): Promise<TaskChanges> {
+  try {
+    return await invoke<TaskChanges>("auto_schedule_tasks");
+  } catch (e) {
+    console.error("autoScheduleTasks failed", e);
+    throw e;
+  }
+}
+
+//Task => Time tracking
+export async function startTaskTimer(taskId: number): Promise<TaskChanges> {
+  try {
+    return await invoke<TaskChanges>("start_task_timer", { taskId });
+  } catch (e) {
+    console.error("startTaskTimer failed", e);
+    throw e;
+  }
+}
+
+export async function stopTaskTimer(
+  taskId: number,
+  memo?: string,
+): Promise<Task> {
+  try {
+    return await invoke<Task>("stop_task_timer", {
+      taskId,
+      memo: memo ?? null,
+    });
+  } catch (e) {
+    console.error("stopTaskTimer failed", e);
+    throw e;
+  }
+}
+
+export async function getTimeEntries(taskId: number): Promise<TimeEntry[]> {
+  try {
+    return await invoke<TimeEntry[]>("get_time_entries", { taskId });
+  } catch (e) {
+    console.error("getTimeEntries failed", e);
+    throw e;
+  }
+}
+
+export async function getTimeReport(): Promise<GroupTimeReport[]> {
+  try {
+    return await invoke<GroupTimeReport[]>("get_time_report");
+  } catch (e) {
+    console.error("getTimeReport failed", e);
+    throw e;
+  }
+}
+
 //Task => Folder
-export async function getFolders(): Promise<any[]> {
+export async function getFolders(): Promise<Folder[]> {
   try {
-    return await invoke<any[]>("get_folders");
+    return await invoke<Folder[]>("get_folders");
   } catch (e) {
     console.error("getFolders failed", e);
     throw e;
   }
 }
 
-export async function deleteFolder(id: string): Promise<any[]> {
+export async function getFolderTree(): Promise<FolderTree> {
+  try {
+    return await invoke<FolderTree>("get_folder_tree");
+  } catch (e) {
+    console.error("getFolderTree failed", e);
+    throw e;
+  }
+}
+
+// parentId null moves the folder to the top level
+export async function moveFolder(
+  id: string,
+  parentId: string | null,
+): Promise<Folder[]> {
+  try {
+    return await invoke<Folder[]>("move_folder", { id, parentId });
+  } catch (e) {
+    console.error("moveFolder failed", e);
+    throw e;
+  }
+}
+
+// Without options, subfolders move up to the parent and memos become unfiled
+export async function deleteFolder(
+  id: string,
+  options?: DeleteFolderOptions,
+): Promise<Folder[]> {
   try {
-    return await invoke<any[]>("delete_folder", { id });
+    return await invoke<Folder[]>("delete_folder", { id, options });
   } catch (e) {
     console.error("deleteFolder failed", e);
     throw e;
@@ -26,20 +26,131 @@ export interface ContextMenuState {
 export interface Task {
   id: number;
   description: string;
-  start_date?: string;
-  due_date: string;
-  group: string;
+  start_date?: string | null;
+  due_date: string | null;
+  group_id: string | null;
   details: string;
   completed: boolean;
   notification_minutes?: number;
   subtasks: Subtask[];
   dependencies?: number[];
+  priority?: TaskPriority | null;
+  estimated_minutes?: number | null;
+  time_entries?: TimeEntry[];
+  tags?: string[];
+  state?: string | null;
+  state_history?: StateTransition[];
+  sort_key?: string;
+  rollup_subtasks?: boolean;
+  updated_at?: string | null;
+}
+
+// Fields omitted from a patch are left unchanged; null clears a value
+export interface TaskPatch {
+  description?: string;
+  start_date?: string | null;
+  due_date?: string | null;
+  group_id?: string | null;
+  details?: string;
+  notification_minutes?: number | null;
+  dependencies?: number[] | null;
+  priority?: TaskPriority | null;
+  estimated_minutes?: number | null;
+  tags?: string[];
+}
+
+export interface BulkTaskPatch {
+  completed?: boolean;
+  delete?: boolean;
+  group_id?: string | null;
+  shift_due_days?: number;
+  add_tags?: string[];
+}
+
+export interface WorkflowState {
+  id: string;
+  name: string;
+  terminal: boolean;
+}
+
+export interface StateTransition {
+  from: string | null;
+  to: string;
+  at: string;
+}
+
+export interface BoardColumn {
+  state: WorkflowState;
+  tasks: Task[];
+}
+
+export interface Board {
+  group_id: string | null;
+  columns: BoardColumn[];
+}
+
+export interface TaskGroup {
+  id: string;
+  name: string;
+  color: string | null;
+  icon: string | null;
+  sort_order: number;
+  notification_minutes: number | null;
+  archived: boolean;
+  parent_id: string | null;
+  workflow?: WorkflowState[];
+}
+
+export interface SavedView {
+  id: string;
+  name: string;
+  query: string;
+}
+
+export type TaskPriority = "low" | "medium" | "high" | "urgent";
+
+export interface TimeEntry {
+  id: string;
+  started_at: string;
+  ended_at: string | null;
+  memo: string | null;
+}
+
+export interface GroupTimeReport {
+  group_id: string | null;
+  group: string;
+  task_count: number;
+  estimated_minutes: number;
+  actual_minutes: number;
+  unestimated_minutes: number;
 }
 
 export interface Subtask {
   id: number;
   description: string;
   completed: boolean;
+  due_date?: string | null;
+  notes?: string;
+  subtasks?: Subtask[];
+  sort_key?: string;
+}
+
+//Schedule
+export interface TaskSchedule {
+  task_id: number;
+  earliest_start: string;
+  earliest_finish: string;
+  latest_start: string;
+  latest_finish: string;
+  slack_days: number;
+  critical: boolean;
+}
+
+export interface ProjectSchedule {
+  tasks: TaskSchedule[];
+  critical_path: number[];
+  project_start: string | null;
+  project_end: string | null;
 }
 
 //Dashboard
@@ -73,6 +184,17 @@ export interface CalendarEvent {
   updated_at: string;
 }
 
+export interface CalendarEventPatch {
+  title?: string;
+  description?: string;
+  start_datetime?: string;
+  end_datetime?: string | null;
+  all_day?: boolean;
+  color?: string | null;
+  recurrence_rule?: string | null;
+  reminder_minutes?: number | null;
+}
+
 //Memos
 export interface Memo {
   id: string;
@@ -90,6 +212,29 @@ export interface Folder {
   parent_id: string | null;
 }
 
+export interface FolderNode {
+  id: string;
+  name: string;
+  parent_id: string | null;
+  // Memos directly in this folder
+  memo_count: number;
+  // Memos in this folder and all subfolders
+  total_memo_count: number;
+  children: FolderNode[];
+}
+
+export interface FolderTree {
+  roots: FolderNode[];
+  unfiled_memo_count: number;
+}
+
+export interface DeleteFolderOptions {
+  // Delete subfolders too instead of moving them up to the parent
+  recursive?: boolean;
+  // Move memos to the parent folder instead of leaving them unfiled
+  move_memos_to_parent?: boolean;
+}
+
 //Reading Memos
 // export interface ReadingBook {
 //   id: string;
@@ -122,6 +267,28 @@ export interface ReadingSession {
   memo?: string;
 }
 
+// A cover image stored in the data directory
+export interface BookCover {
+  hash: string;
+  extension: string;
+  width: number;
+  height: number;
+  // Set when the image was downloaded from cover_image_url
+  source_url: string | null;
+  updated_at: string;
+}
+
+export type CoverSource =
+  | { kind: "file"; path: string }
+  | { kind: "bytes"; bytes: number[] }
+  | { kind: "url"; url: string };
+
+export interface BookCoverCacheReport {
+  cached: string[];
+  // [book id, error]
+  failed: [string, string][];
+}
+
 export interface ReadingBook {
   id: string;
   title: string;
@@ -130,6 +297,7 @@ export interface ReadingBook {
   publisher?: string;
   published_year?: number;
   cover_image_url?: string;
+  cover?: BookCover | null;
   genres: string[];
   status: ReadingStatus;
   start_date?: string;
@@ -146,4 +314,253 @@ export interface ReadingBook {
   updated_at: string;
 }
 
+export interface ReadingBookPatch {
+  title?: string;
+  author?: string | null;
+  isbn?: string | null;
+  publisher?: string | null;
+  published_year?: number | null;
+  cover_image_url?: string | null;
+  genres?: string[];
+  status?: ReadingStatus;
+  start_date?: string | null;
+  finish_date?: string | null;
+  total_pages?: number | null;
+  current_page?: number | null;
+  rating?: number | null;
+  summary?: string;
+  tags?: string[];
+}
+
+// 一覧取得の条件と結果（src-tauri/src/listing.rs）
+export interface ListOptions {
+  offset?: number;
+  limit?: number;
+  cursor?: string;
+  // Field name, prefixed with "-" for descending order
+  sort?: string;
+  summary?: boolean;
+}
+
+export interface Page<T> {
+  items: T[];
+  total: number;
+  next_cursor: string | null;
+}
+
+export interface TaskSummary {
+  id: number;
+  description: string;
+  start_date?: string | null;
+  due_date?: string | null;
+  group_id: string | null;
+  completed: boolean;
+  priority?: TaskPriority | null;
+  tags: string[];
+  state?: string | null;
+  sort_key: string;
+  subtask_count: number;
+  updated_at?: string | null;
+}
+
+export interface MemoSummary {
+  id: string;
+  title: string;
+  excerpt: string;
+  folder_id: string | null;
+  tags: string[];
+  created_at: string;
+  updated_at: string;
+}
+
+// Character ranges [start, end) within the highlighted text
+export type HighlightRange = [number, number];
+
+export interface Snippet {
+  text: string;
+  highlights: HighlightRange[];
+}
+
+export interface MemoSearchHit {
+  id: string;
+  title: string;
+  title_highlights: HighlightRange[];
+  snippet: Snippet;
+  folder_id: string | null;
+  tags: string[];
+  updated_at: string;
+  score: number;
+}
+
+export interface Backlink {
+  memo_id: string;
+  title: string;
+  // Lines of the linking memo that contain the link
+  lines: string[];
+}
+
+export interface BrokenLink {
+  memo_id: string;
+  title: string;
+  target: string;
+}
+
+// Placeholders: {{date}}, {{time}}, {{datetime}} (optionally {{date:%Y/%m/%d}})
+// and {{title}}
+export interface MemoTemplate {
+  id: string;
+  name: string;
+  title: string;
+  content: string;
+  folder_id: string | null;
+  tags: string[];
+  created_at: string;
+  updated_at: string;
+}
+
+export interface DailyNoteSettings {
+  folder_id: string | null;
+  template_id: string | null;
+  // chrono strftime format, e.g. "%Y-%m-%d"
+  title_format: string;
+}
+
+export interface VaultExportReport {
+  memo_count: number;
+  folder_count: number;
+}
+
+export interface VaultImportReport {
+  created: string[];
+  updated: string[];
+  created_folders: string[];
+  // Paths of files that could not be read
+  skipped: string[];
+}
+
+// A memo whose app copy and file both changed since the last sync
+export interface SyncConflict {
+  memo_id: string;
+  // Relative to the sync directory, "/"-separated
+  path: string;
+  detected_at: string;
+}
+
+export interface MemoSyncStatus {
+  enabled: boolean;
+  directory: string | null;
+  watching: boolean;
+  conflicts: SyncConflict[];
+  last_error: string | null;
+}
+
+export type SyncSide = "app" | "file";
+
+export interface Attachment {
+  // SHA-256 of the content, also the stored file name
+  hash: string;
+  file_name: string;
+  extension: string;
+  mime_type: string;
+  size: number;
+  created_at: string;
+}
+
+export interface AddedAttachment {
+  attachment: Attachment;
+  // Ready to insert into a memo, e.g. "![shot.png](attachment:<hash>.png)"
+  markdown: string;
+}
+
+export interface AttachmentGcReport {
+  removed: string[];
+  freed_bytes: number;
+}
+
+export type EntityKind =
+  | "task"
+  | "memo"
+  | "book"
+  | "reading_note"
+  | "calendar_event";
+
+// Task ids are sent as strings
+export interface EntityRef {
+  kind: EntityKind;
+  id: string;
+}
+
+export interface EntityLink {
+  id: string;
+  source: EntityRef;
+  target: EntityRef;
+  label: string | null;
+  created_at: string;
+}
+
+// The other end of a link, as seen from the entity that was queried
+export interface LinkedEntity {
+  link_id: string;
+  entity: EntityRef;
+  title: string;
+  label: string | null;
+  created_at: string;
+}
+
+// Which entity a global search hit opens
+export type SearchTarget =
+  | { kind: "task"; task_id: number }
+  | { kind: "subtask"; task_id: number; subtask_id: number }
+  | { kind: "memo"; memo_id: string }
+  | { kind: "book"; book_id: string }
+  | { kind: "reading_note"; book_id: string; note_id: string }
+  | { kind: "calendar_event"; event_id: string };
+
+export type GlobalSearchHit = SearchTarget & {
+  title: string;
+  title_highlights: HighlightRange[];
+  // Parent task, book author or event start
+  context: string | null;
+  snippet: Snippet | null;
+  score: number;
+};
+
+export interface ReadingBookSummary {
+  id: string;
+  title: string;
+  author?: string;
+  cover_image_url?: string;
+  cover?: BookCover | null;
+  status: ReadingStatus;
+  progress_percent?: number;
+  rating?: number;
+  tags: string[];
+  note_count: number;
+  session_count: number;
+  created_at: string;
+  updated_at: string;
+}
+
+// summary: true returns the lightweight projection
+export type Listed<O extends ListOptions, F, S> = O extends { summary: true }
+  ? S
+  : F;
+
+// 変更通知イベント（src-tauri/src/events.rs）
+export interface TaskChanges {
+  created: Task[];
+  updated: Task[];
+  deleted: number[];
+}
+
+export interface BookItemChange<T> {
+  book_id: string;
+  book_updated_at: string;
+  item: T;
+}
+
+export interface DeletedPayload<K> {
+  id: K;
+}
+
 export default {};